@@ -0,0 +1,343 @@
+//! `build.rs` - checks, builds and runs targets of the current project.
+//!
+//! Targets are the library (`lib`) and binaries (`bins`) of the manifest.
+//! Analyzed programs are cached in `target/lib/<name>.json` and
+//! `target/bin/<name>.json`, see [`ry_driver::cache`], so unchanged targets
+//! are not compiled again. Bytecode of every file is cached in
+//! `target/objects/`, so the files, that haven't changed, are not compiled
+//! again, even if the rest of the target has.
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use codespan_reporting::files::SimpleFiles;
+use ry_driver::{
+    cache::{build, Build, Program, OBJECTS_DIRECTORY},
+    check, emit_errors, load_program,
+};
+use ry_interpreter::{bytecode, vm::Vm};
+use ry_manifest::{Manifest, ModuleRoots};
+use ry_report::{Reporter, ReporterState};
+
+use crate::{error::Failure, manifest::Member};
+
+/// Directory for build artifacts, relative to the workspace root.
+pub const TARGET_DIRECTORY: &str = "target";
+
+struct Target {
+    /// `"lib"` or `"bin"`.
+    kind: &'static str,
+    name: String,
+    entry: PathBuf,
+}
+
+impl Target {
+    fn artifact(&self, target_directory: &Path) -> PathBuf {
+        target_directory
+            .join(self.kind)
+            .join(format!("{}.json", self.name))
+    }
+}
+
+fn targets(root: &Path, manifest: &Manifest) -> Vec<Target> {
+    let lib = manifest.lib.iter().map(|lib| Target {
+        kind: "lib",
+        name: manifest.name.clone(),
+        entry: root.join(lib),
+    });
+    let bins = manifest.bins.iter().map(|(name, bin)| Target {
+        kind: "bin",
+        name: name.clone(),
+        entry: root.join(bin),
+    });
+
+    lib.chain(bins).collect()
+}
+
+fn status(action: &str, manifest: &Manifest, target: &Target) {
+    eprintln!(
+        "{action:>12} {} v{} ({} `{}`)",
+        manifest.name, manifest.version, target.kind, target.name
+    );
+}
+
+/// Builds the target (or takes it from the cache) and compiles it to
+/// bytecode.
+fn build_target(
+    manifest: &Manifest,
+    target_directory: &Path,
+    roots: &ModuleRoots,
+    target: &Target,
+    reporter: &ReporterState,
+) -> Result<(Program, Vec<bytecode::Module>), Failure> {
+    let program = analyze_target(manifest, target_directory, roots, target, reporter)?;
+
+    match program.compile(&target_directory.join(OBJECTS_DIRECTORY)) {
+        Ok(modules) => Ok((program, modules)),
+        Err(e) => Err(Failure::Error(format!(
+            "cannot write object files of {} `{}`: {e}.",
+            target.kind, target.name
+        ))),
+    }
+}
+
+fn analyze_target(
+    manifest: &Manifest,
+    target_directory: &Path,
+    roots: &ModuleRoots,
+    target: &Target,
+    reporter: &ReporterState,
+) -> Result<Program, Failure> {
+    let result = build(&target.entry, roots, &target.artifact(target_directory)).map_err(|e| {
+        Failure::Error(format!(
+            "cannot build {} `{}`: {e}.",
+            target.kind, target.name
+        ))
+    })?;
+
+    match result {
+        Build::Fresh(program) => {
+            status("Fresh", manifest, target);
+            Ok(program)
+        }
+        Build::Compiled(program) => {
+            status("Compiling", manifest, target);
+            Ok(program)
+        }
+        Build::Failed(program, errors) => {
+            status("Compiling", manifest, target);
+            emit_errors(&program, &errors, reporter);
+
+            Err(Failure::Error(format!(
+                "could not compile {} `{}` due to the previous errors.",
+                target.kind, target.name
+            )))
+        }
+    }
+}
+
+/// `lapis check`: reports errors of all targets without writing artifacts.
+pub fn check_project(
+    member: &Member,
+    roots: &ModuleRoots,
+    reporter: &ReporterState,
+) -> Result<(), Failure> {
+    let manifest = &member.manifest;
+    let mut has_errors = false;
+
+    for target in targets(&member.root, manifest) {
+        status("Checking", manifest, &target);
+
+        let Ok(mut program) = load_program(&target.entry, roots) else {
+            reporter.emit_global_error(&format!("cannot read `{}`.", target.entry.display()));
+            has_errors = true;
+            continue;
+        };
+
+        let errors = check(&mut program);
+        has_errors |= emit_errors(&program, &errors, reporter);
+    }
+
+    if has_errors {
+        return Err(Failure::Error(
+            "could not check the project due to the previous errors.".to_owned(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// `lapis build`: builds all targets. Artifacts are written into
+/// `target_directory`, which is shared by the workspace members.
+pub fn build_project(
+    member: &Member,
+    target_directory: &Path,
+    roots: &ModuleRoots,
+    reporter: &ReporterState,
+) -> Result<(), Failure> {
+    let targets = targets(&member.root, &member.manifest);
+
+    if targets.is_empty() {
+        return Err(Failure::Error(
+            "the project has no targets to build.".to_owned(),
+        ));
+    }
+
+    for target in targets {
+        build_target(&member.manifest, target_directory, roots, &target, reporter)?;
+    }
+
+    Ok(())
+}
+
+/// `lapis run`: builds the binary and executes it. If `bin` is not given,
+/// the project must have a single binary. Output of the program is written
+/// into `output`.
+pub fn run_project(
+    member: &Member,
+    target_directory: &Path,
+    roots: &ModuleRoots,
+    bin: Option<&str>,
+    output: &mut dyn Write,
+    reporter: &ReporterState,
+) -> Result<(), Failure> {
+    let mut bins = targets(&member.root, &member.manifest)
+        .into_iter()
+        .filter(|target| target.kind == "bin")
+        .collect::<Vec<_>>();
+
+    let target = match bin {
+        Some(bin) => match bins.iter().position(|target| target.name == bin) {
+            Some(position) => bins.swap_remove(position),
+            None => {
+                return Err(Failure::Error(format!("no binary target named `{bin}`.")));
+            }
+        },
+        None if bins.len() == 1 => bins.remove(0),
+        None if bins.is_empty() => {
+            return Err(Failure::Error(
+                "the project has no binary targets to run.".to_owned(),
+            ));
+        }
+        None => {
+            let names = bins
+                .iter()
+                .map(|target| format!("`{}`", target.name))
+                .collect::<Vec<_>>();
+
+            return Err(Failure::Error(format!(
+                "could not determine which binary to run, use `--bin` to specify one of: {}.",
+                names.join(", ")
+            )));
+        }
+    };
+
+    let (program, compiled) =
+        build_target(&member.manifest, target_directory, roots, &target, reporter)?;
+
+    eprintln!("{:>12} `{}`", "Running", target.entry.display());
+
+    // The virtual machine doesn't take native stack for calls of
+    // the program, unlike the interpreter.
+    let result = Vm::new(&compiled, output).run().map(|_| ());
+    let _ = output.flush();
+
+    if let Err((module, error)) = result {
+        let mut files = SimpleFiles::new();
+        let file_ids = program
+            .files
+            .iter()
+            .map(|file| files.add(file.name.as_str(), file.contents.as_str()))
+            .collect::<Vec<_>>();
+
+        error.emit_diagnostic(reporter, &files, file_ids[module]);
+        return Err(Failure::Panicked);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_directory;
+
+    #[test]
+    fn commands_test() {
+        let temp = temp_directory(&[
+            ("bin/hello.ry", "fun main() { println(\"hello\"); }\n"),
+            ("bin/panics.ry", "fun main() { panic(\"boom\"); }\n"),
+            ("bin/invalid.ry", "const A = 1 / 0;\nfun main() {}\n"),
+        ]);
+        let root = temp.path();
+
+        let reporter = ReporterState::default();
+        let target_directory = root.join(TARGET_DIRECTORY);
+        let mut member = Member {
+            root: root.to_owned(),
+            manifest: Manifest::new("demo"),
+        };
+        member.manifest.bins.clear();
+
+        let run = |member: &Member, bin: Option<&str>| {
+            let roots = ModuleRoots::new(&member.root, &member.manifest);
+            let mut output = vec![];
+
+            run_project(
+                member,
+                &target_directory,
+                &roots,
+                bin,
+                &mut output,
+                &reporter,
+            )
+            .map(|_| String::from_utf8(output).unwrap())
+        };
+
+        let roots = ModuleRoots::new(root, &member.manifest);
+        assert_eq!(
+            build_project(&member, &target_directory, &roots, &reporter),
+            Err(Failure::Error(
+                "the project has no targets to build.".to_owned()
+            ))
+        );
+        assert_eq!(
+            run(&member, None),
+            Err(Failure::Error(
+                "the project has no binary targets to run.".to_owned()
+            ))
+        );
+
+        for bin in ["hello", "panics"] {
+            member
+                .manifest
+                .bins
+                .insert(bin.to_owned(), format!("bin/{bin}.ry").into());
+        }
+
+        let roots = ModuleRoots::new(root, &member.manifest);
+        assert_eq!(check_project(&member, &roots, &reporter), Ok(()));
+        assert_eq!(
+            build_project(&member, &target_directory, &roots, &reporter),
+            Ok(())
+        );
+        assert!(target_directory.join("bin").join("hello.json").is_file());
+
+        assert_eq!(run(&member, Some("hello")), Ok("hello\n".to_owned()));
+        assert_eq!(run(&member, Some("panics")), Err(Failure::Panicked));
+        assert_eq!(
+            run(&member, Some("missing")),
+            Err(Failure::Error(
+                "no binary target named `missing`.".to_owned()
+            ))
+        );
+        assert_eq!(
+            run(&member, None),
+            Err(Failure::Error(
+                "could not determine which binary to run, use `--bin` to specify one of: \
+                 `hello`, `panics`."
+                    .to_owned()
+            ))
+        );
+
+        member
+            .manifest
+            .bins
+            .insert("invalid".to_owned(), "bin/invalid.ry".into());
+
+        let roots = ModuleRoots::new(root, &member.manifest);
+        assert_eq!(
+            check_project(&member, &roots, &reporter),
+            Err(Failure::Error(
+                "could not check the project due to the previous errors.".to_owned()
+            ))
+        );
+        assert_eq!(
+            build_project(&member, &target_directory, &roots, &reporter),
+            Err(Failure::Error(
+                "could not compile bin `invalid` due to the previous errors.".to_owned()
+            ))
+        );
+    }
+}
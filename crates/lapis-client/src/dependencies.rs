@@ -0,0 +1,215 @@
+//! `dependencies.rs` - resolves, adds and searches dependencies of
+//! the current project.
+//!
+//! Dependencies of all workspace members are resolved together. Versions
+//! are taken from `lapis.lock`, if they still satisfy the manifests. The
+//! lockfile is updated, when the resolution changes. Registry packages are
+//! downloaded into `target/packages` of the workspace.
+use std::{collections::HashMap, fs, path::Path};
+
+use ry_manifest::{
+    add_dependency, is_valid_name, Dependency, Manifest, ModuleRoots, MANIFEST_FILE,
+};
+use semver::VersionReq;
+
+use crate::{
+    error::Failure,
+    lockfile::{self, LOCKFILE},
+    manifest::Workspace,
+    registry::{self, fetch, Registry, REGISTRY_ENV},
+    resolver::{resolve, Source},
+};
+
+/// Registry configured by the environment, see [`registry::from_env`].
+pub fn open_registry() -> Result<Box<dyn Registry>, Failure> {
+    registry::from_env().ok_or_else(|| {
+        Failure::Error(format!(
+            "cannot find the registry, set `{REGISTRY_ENV}` environment variable."
+        ))
+    })
+}
+
+/// Resolves dependencies of all members of the workspace together, updates
+/// the shared lockfile and returns module roots of all packages. If
+/// `update` is set, versions from the lockfile are ignored.
+pub fn resolve_dependencies(workspace: &Workspace, update: bool) -> Result<ModuleRoots, Failure> {
+    let lockfile_path = workspace.root.join(LOCKFILE);
+    let lockfile = fs::read_to_string(&lockfile_path).ok();
+
+    let preferred = match &lockfile {
+        Some(source) if !update => lockfile::parse(source).map_err(|reason| {
+            Failure::Error(format!(
+                "invalid lockfile `{}`: {reason}, remove it or run `lapis update`.",
+                lockfile_path.display()
+            ))
+        })?,
+        _ => HashMap::new(),
+    };
+
+    let registry = open_registry()?;
+
+    let members = workspace
+        .members
+        .iter()
+        .map(|member| (member.root.as_path(), &member.manifest))
+        .collect::<Vec<_>>();
+
+    let resolution = resolve(&members, registry.as_ref(), &preferred)
+        .map_err(|error| Failure::Error(error.to_string()))?;
+
+    let json = lockfile::to_json(&resolution);
+
+    if lockfile.as_deref() != Some(json.as_str()) {
+        fs::write(&lockfile_path, json).map_err(|e| {
+            Failure::Error(format!("cannot write `{}`: {e}.", lockfile_path.display()))
+        })?;
+    }
+
+    let cache = workspace.target_directory().join("packages");
+    let mut roots = ModuleRoots::default();
+
+    for member in &workspace.members {
+        roots.merge(ModuleRoots::new(&member.root, &member.manifest));
+    }
+
+    for (name, package) in &resolution {
+        if workspace
+            .members
+            .iter()
+            .any(|member| member.manifest.name == *name)
+        {
+            continue;
+        }
+
+        let directory = match &package.source {
+            Source::Path(directory) => Ok(directory.clone()),
+            Source::Registry => {
+                fetch(registry.as_ref(), &cache, name, &package.version).map_err(|e| e.to_string())
+            }
+        };
+
+        let package_manifest = directory.and_then(|directory| {
+            let source = fs::read_to_string(directory.join(MANIFEST_FILE))
+                .map_err(|e| format!("cannot read its manifest: {e}"))?;
+            let manifest =
+                Manifest::parse(&source).map_err(|_| "its manifest is invalid".to_owned())?;
+
+            Ok((directory, manifest))
+        });
+
+        match package_manifest {
+            Ok((directory, package_manifest)) => {
+                roots.add_package(name, &directory, &package_manifest)
+            }
+            Err(reason) => {
+                return Err(Failure::Error(format!(
+                    "cannot use package `{name}`: {reason}."
+                )));
+            }
+        }
+    }
+
+    Ok(roots)
+}
+
+/// `lapis add`: adds the registry package to dependencies of the project.
+/// `spec` is `<name>` (the latest version is required) or `<name>@<req>`.
+pub fn add(root: &Path, manifest: &Manifest, spec: &str) -> Result<(), Failure> {
+    let (name, requirement) = match spec.split_once('@') {
+        Some((name, requirement)) => match VersionReq::parse(requirement) {
+            Ok(requirement) => (name, Some(requirement)),
+            Err(e) => {
+                return Err(Failure::Error(format!(
+                    "invalid version requirement `{requirement}`: {e}."
+                )));
+            }
+        },
+        None => (spec, None),
+    };
+
+    if !is_valid_name(name) {
+        return Err(Failure::Error(format!("invalid package name `{name}`.")));
+    }
+
+    if name == manifest.name {
+        return Err(Failure::Error(format!(
+            "package `{name}` cannot depend on itself."
+        )));
+    }
+
+    let registry = open_registry()?;
+
+    let versions = registry
+        .versions(name)
+        .map_err(|e| Failure::Error(format!("{e}.")))?;
+
+    // Pre-releases are picked only if explicitly requested.
+    let Some(latest) = versions
+        .iter()
+        .rev()
+        .find(|version| match &requirement {
+            Some(requirement) => requirement.matches(version),
+            None => version.pre.is_empty(),
+        })
+        .or(versions.last())
+    else {
+        return Err(Failure::Error(format!(
+            "no package named `{name}` found in registry."
+        )));
+    };
+
+    let requirement = match requirement {
+        Some(requirement) if requirement.matches(latest) => requirement,
+        Some(requirement) => {
+            return Err(Failure::Error(format!(
+                "no version of `{name}` matches `{requirement}`, the latest is {latest}."
+            )));
+        }
+        None => VersionReq::parse(&format!("^{latest}")).expect("caret requirement is valid"),
+    };
+
+    let path = root.join(MANIFEST_FILE);
+    let dependency = Dependency {
+        version: Some(requirement.clone()),
+        path: None,
+    };
+
+    let source = fs::read_to_string(&path)
+        .ok()
+        .and_then(|source| add_dependency(&source, name, &dependency));
+
+    let Some(source) = source else {
+        return Err(Failure::Error(format!(
+            "cannot update `{}`.",
+            path.display()
+        )));
+    };
+
+    fs::write(&path, source)
+        .map_err(|e| Failure::Error(format!("cannot write `{}`: {e}.", path.display())))?;
+
+    eprintln!("{:>12} {name} {requirement} to dependencies", "Adding");
+
+    Ok(())
+}
+
+/// `lapis search`: prints packages, which name or description matches
+/// the query.
+pub fn search(query: &str) -> Result<(), Failure> {
+    let results = open_registry()?
+        .search(query)
+        .map_err(|e| Failure::Error(format!("{e}.")))?;
+
+    if results.is_empty() {
+        eprintln!("no packages found matching `{query}`");
+    }
+
+    for result in results {
+        match result.description {
+            Some(description) => println!("{} v{} - {description}", result.name, result.version),
+            None => println!("{} v{}", result.name, result.version),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+//! `error.rs` - failures of lapis commands.
+//!
+//! Commands return [`Failure`] instead of terminating the process, so that
+//! they can be tested. It is reported and turned into the exit code only
+//! in `main`.
+use std::process;
+
+use ry_report::ReporterState;
+
+/// Exit code of the program, that panicked.
+const PANIC_EXIT_CODE: i32 = 101;
+
+/// Reason, why the command failed.
+#[derive(Debug, PartialEq)]
+pub enum Failure {
+    /// Error, that is not reported yet.
+    Error(String),
+    /// Errors are already reported, for example, several manifest errors
+    /// at once or failed tests.
+    Reported,
+    /// Program run by `lapis run` panicked, its error is already reported.
+    Panicked,
+}
+
+impl Failure {
+    /// Reports the error, if it is not reported yet, and terminates
+    /// the process.
+    pub fn exit(self, reporter: &ReporterState) -> ! {
+        match self {
+            Self::Error(message) => {
+                reporter.emit_global_error(&message);
+                process::exit(1)
+            }
+            Self::Reported => process::exit(1),
+            Self::Panicked => process::exit(PANIC_EXIT_CODE),
+        }
+    }
+}
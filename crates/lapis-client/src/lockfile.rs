@@ -0,0 +1,149 @@
+//! `lockfile.rs` - reads and writes `lapis.lock`.
+//!
+//! The lockfile records versions of all packages picked by the resolver,
+//! so subsequent builds use the same versions, until the manifest changes
+//! or `lapis update` is run:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "packages": [
+//!     {
+//!       "name": "json",
+//!       "version": "1.2.0",
+//!       "source": "registry",
+//!       "dependencies": []
+//!     }
+//!   ]
+//! }
+//! ```
+use std::collections::HashMap;
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::resolver::{Resolution, Source};
+
+/// Name of the lockfile in the root of the project.
+pub const LOCKFILE: &str = "lapis.lock";
+
+/// Version of the lockfile format.
+const FORMAT_VERSION: u64 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Lockfile {
+    version: u64,
+    packages: Vec<LockedPackage>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: Version,
+    /// `"path"` or `"registry"`.
+    source: String,
+    dependencies: Vec<String>,
+}
+
+/// Serializes the resolution into pretty-printed JSON. Packages are sorted
+/// by name, so the lockfile doesn't change, if versions are the same.
+pub fn to_json(resolution: &Resolution) -> String {
+    let packages = resolution
+        .iter()
+        .map(|(name, package)| LockedPackage {
+            name: name.clone(),
+            version: package.version.clone(),
+            source: match package.source {
+                Source::Path(_) => "path",
+                Source::Registry => "registry",
+            }
+            .to_owned(),
+            dependencies: package.dependencies.clone(),
+        })
+        .collect();
+
+    let mut json = serde_json::to_string_pretty(&Lockfile {
+        version: FORMAT_VERSION,
+        packages,
+    })
+    .expect("lockfile is always serializable");
+    json.push('\n');
+    json
+}
+
+/// Parses the lockfile and returns locked versions of registry packages.
+/// Returns the reason, if the lockfile is invalid.
+pub fn parse(source: &str) -> Result<HashMap<String, Version>, String> {
+    let lockfile = serde_json::from_str::<Lockfile>(source).map_err(|e| e.to_string())?;
+
+    if lockfile.version != FORMAT_VERSION {
+        return Err(format!(
+            "unsupported format version {}, expected {FORMAT_VERSION}",
+            lockfile.version
+        ));
+    }
+
+    Ok(lockfile
+        .packages
+        .into_iter()
+        .filter(|package| package.source == "registry")
+        .map(|package| (package.name, package.version))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolver::ResolvedPackage;
+
+    #[test]
+    fn lockfile_test() {
+        let package = |version: Version, source, dependencies: &[&str]| ResolvedPackage {
+            version,
+            source,
+            dependencies: dependencies.iter().map(ToString::to_string).collect(),
+        };
+        let resolution = Resolution::from([
+            (
+                "app".to_owned(),
+                package(Version::new(0, 1, 0), Source::Path("app".into()), &["json"]),
+            ),
+            (
+                "json".to_owned(),
+                package(Version::new(1, 2, 0), Source::Registry, &[]),
+            ),
+        ]);
+
+        let json = to_json(&resolution);
+        assert_eq!(
+            json,
+            r#"{
+  "version": 1,
+  "packages": [
+    {
+      "name": "app",
+      "version": "0.1.0",
+      "source": "path",
+      "dependencies": [
+        "json"
+      ]
+    },
+    {
+      "name": "json",
+      "version": "1.2.0",
+      "source": "registry",
+      "dependencies": []
+    }
+  ]
+}
+"#
+        );
+        assert_eq!(
+            parse(&json),
+            Ok(HashMap::from([("json".to_owned(), Version::new(1, 2, 0))]))
+        );
+
+        assert!(parse(r#"{"version": 2, "packages": []}"#).is_err());
+        assert!(parse(r#"{"version": 1, "packages": [{"name": "json"}]}"#).is_err());
+    }
+}
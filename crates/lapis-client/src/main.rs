@@ -1,33 +1,249 @@
+mod build;
+mod dependencies;
+mod error;
+mod lockfile;
+mod manifest;
+mod package;
 mod project_template;
+mod publish;
+mod registry;
+mod resolver;
+mod test_runner;
+#[cfg(test)]
+mod test_utils;
 
-use clap::{arg, Command};
+use std::{env, io, path::PathBuf};
+
+use clap::{arg, ArgGroup, ArgMatches, Command};
+use error::Failure;
+use project_template::Template;
 use ry_report::ReporterState;
 
+/// Template arguments shared by `new` and `init`.
+fn template_args(command: Command) -> Command {
+    command
+        .arg(arg!(--bin "use a binary template (default)"))
+        .arg(arg!(--lib "use a library template"))
+        .arg(arg!(--template <DIR> "copy the project from a template directory"))
+        .group(ArgGroup::new("kind").args(["bin", "lib", "template"]))
+}
+
+/// Package selection arguments: `-p` and (if `all` is set) `--workspace`.
+fn package_args(command: Command, all: bool) -> Command {
+    let command = command.arg(arg!(-p --package <NAME> "workspace member to use"));
+
+    if all {
+        command
+            .arg(arg!(--workspace "use all workspace members"))
+            .group(ArgGroup::new("packages").args(["package", "workspace"]))
+    } else {
+        command
+    }
+}
+
+fn selected_members<'w>(
+    workspace: &'w manifest::Workspace,
+    matches: &ArgMatches,
+) -> Result<Vec<&'w manifest::Member>, Failure> {
+    workspace.select(
+        matches.get_one::<String>("package").map(String::as_str),
+        matches.get_flag("workspace"),
+    )
+}
+
+fn template(matches: &ArgMatches) -> Template {
+    if let Some(directory) = matches.get_one::<String>("template") {
+        Template::Directory(PathBuf::from(directory))
+    } else if matches.get_flag("lib") {
+        Template::Lib
+    } else {
+        Template::Bin
+    }
+}
+
 fn cli() -> Command {
     Command::new("lapis")
         .about("Lapis - official Ry package manager client")
         .subcommand_required(true)
         .arg_required_else_help(true)
-        .subcommand(
+        .subcommand(template_args(
             Command::new("new")
                 .about("Create new lapis project")
                 .arg(arg!(<NAME> "project name"))
                 .arg_required_else_help(true),
+        ))
+        .subcommand(template_args(
+            Command::new("init")
+                .about("Create new lapis project in the current directory")
+                .arg(arg!(--name <NAME> "project name, directory name is used by default")),
+        ))
+        .subcommand(package_args(
+            Command::new("check").about("Report errors in all targets of the current project"),
+            true,
+        ))
+        .subcommand(package_args(
+            Command::new("build")
+                .about("Compile all targets of the current project, unchanged ones are reused"),
+            true,
+        ))
+        .subcommand(package_args(
+            Command::new("run")
+                .about("Build and run a binary of the current project")
+                .arg(arg!(--bin <NAME> "name of the binary to run")),
+            false,
+        ))
+        .subcommand(package_args(
+            Command::new("test")
+                .about("Run `@test` functions in `test/` and in the library")
+                .arg(arg!([FILTER] "run only tests, which names contain this text"))
+                .arg(arg!(--json "print results as JSON")),
+            true,
+        ))
+        .subcommand(
+            Command::new("update")
+                .about("Resolve dependencies again ignoring the lockfile and update it"),
+        )
+        .subcommand(
+            Command::new("add")
+                .about("Add a registry package to dependencies of the current project")
+                .arg(arg!(<PACKAGE> "package name, optionally with requirement: `name@^1.2`"))
+                .arg_required_else_help(true),
+        )
+        .subcommand(
+            Command::new("search")
+                .about("Search the registry for packages by name and description")
+                .arg(arg!(<QUERY> "text to search for"))
+                .arg_required_else_help(true),
+        )
+        .subcommand(
+            Command::new("publish")
+                .about("Package the current project and upload it to the registry")
+                .arg(arg!(--token <TOKEN> "registry token, `LAPIS_TOKEN` is used by default")),
+        )
+        .subcommand(
+            Command::new("metadata").about(
+                "Validate manifest of the current project and print it with defaults filled in",
+            ),
         )
 }
 
-#[allow(clippy::single_match)]
 fn main() {
     let reporter = ReporterState::default();
 
     let matches = cli().get_matches();
 
+    if let Err(failure) = run(&matches, &reporter) {
+        failure.exit(&reporter);
+    }
+}
+
+/// Runs the command. Errors are reported and terminate the process only in
+/// [`main`].
+fn run(matches: &ArgMatches, reporter: &ReporterState) -> Result<(), Failure> {
     match matches.subcommand() {
         Some(("new", sub_matches)) => {
             let name = sub_matches.get_one::<String>("NAME").unwrap();
 
-            project_template::create_lapis_project(name, &reporter);
+            project_template::create_project(name.as_ref(), name, &template(sub_matches))
+                .map_err(|e| Failure::Error(format!("{e}.")))?;
+
+            eprintln!("{:>12} `{name}` package", "Created");
+        }
+        Some(("init", sub_matches)) => {
+            let directory = env::current_dir().map_err(|_| {
+                Failure::Error("cannot access the current directory.".to_owned())
+            })?;
+            let name = sub_matches.get_one::<String>("name").cloned().unwrap_or_else(|| {
+                directory
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            });
+
+            project_template::init_project(&directory, &name, &template(sub_matches))
+                .map_err(|e| Failure::Error(format!("{e}.")))?;
+
+            eprintln!("{:>12} `{name}` package", "Created");
+        }
+        Some(("check", sub_matches)) => {
+            let workspace = manifest::load_workspace(reporter)?;
+            let roots = dependencies::resolve_dependencies(&workspace, false)?;
+
+            for member in selected_members(&workspace, sub_matches)? {
+                build::check_project(member, &roots, reporter)?;
+            }
+        }
+        Some(("build", sub_matches)) => {
+            let workspace = manifest::load_workspace(reporter)?;
+            let roots = dependencies::resolve_dependencies(&workspace, false)?;
+
+            for member in selected_members(&workspace, sub_matches)? {
+                build::build_project(member, &workspace.target_directory(), &roots, reporter)?;
+            }
+        }
+        Some(("run", sub_matches)) => {
+            let workspace = manifest::load_workspace(reporter)?;
+            let roots = dependencies::resolve_dependencies(&workspace, false)?;
+            let package = sub_matches.get_one::<String>("package");
+            let bin = sub_matches.get_one::<String>("bin");
+
+            build::run_project(
+                workspace.select_runnable(package.map(String::as_str))?,
+                &workspace.target_directory(),
+                &roots,
+                bin.map(String::as_str),
+                &mut io::stdout().lock(),
+                reporter,
+            )?;
+        }
+        Some(("test", sub_matches)) => {
+            let workspace = manifest::load_workspace(reporter)?;
+            let roots = dependencies::resolve_dependencies(&workspace, false)?;
+            let filter = sub_matches.get_one::<String>("FILTER");
+
+            test_runner::test_project(
+                &selected_members(&workspace, sub_matches)?,
+                &roots,
+                filter.map(String::as_str),
+                sub_matches.get_flag("json"),
+                reporter,
+            )?;
+        }
+        Some(("update", _)) => {
+            let workspace = manifest::load_workspace(reporter)?;
+
+            dependencies::resolve_dependencies(&workspace, true)?;
+        }
+        Some(("add", sub_matches)) => {
+            let workspace = manifest::load_workspace(reporter)?;
+            let member = workspace.current_member()?;
+            let package = sub_matches.get_one::<String>("PACKAGE").unwrap();
+
+            dependencies::add(&member.root, &member.manifest, package)?;
+
+            // Lockfile is updated with the new dependency.
+            let workspace = manifest::load_workspace(reporter)?;
+            dependencies::resolve_dependencies(&workspace, false)?;
+        }
+        Some(("search", sub_matches)) => {
+            let query = sub_matches.get_one::<String>("QUERY").unwrap();
+
+            dependencies::search(query)?;
+        }
+        Some(("publish", sub_matches)) => {
+            let workspace = manifest::load_workspace(reporter)?;
+            let member = workspace.current_member()?;
+            let token = sub_matches.get_one::<String>("token");
+
+            publish::publish_project(&member.root, &member.manifest, token.map(String::as_str))?;
+        }
+        Some(("metadata", _)) => {
+            let workspace = manifest::load_workspace(reporter)?;
+
+            print!("{}", workspace.current_member()?.manifest.to_json());
         }
         _ => {}
     }
+
+    Ok(())
 }
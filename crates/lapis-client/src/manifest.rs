@@ -0,0 +1,344 @@
+//! `manifest.rs` - loads the workspace of the current project.
+//!
+//! Project outside of any workspace is treated as a workspace with
+//! the single member, so commands handle both cases the same way.
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use codespan_reporting::files::SimpleFiles;
+use ry_manifest::{
+    error::ManifestError,
+    find_root,
+    workspace::{is_workspace, WorkspaceManifest},
+    Manifest, MANIFEST_FILE,
+};
+use ry_report::{Reporter, ReporterState};
+
+use crate::{build::TARGET_DIRECTORY, error::Failure};
+
+/// Package of the workspace.
+pub struct Member {
+    /// Root directory of the package.
+    pub root: PathBuf,
+    pub manifest: Manifest,
+}
+
+/// Packages sharing the lockfile and the `target` directory.
+pub struct Workspace {
+    /// Directory with the lockfile and `target`.
+    pub root: PathBuf,
+    pub members: Vec<Member>,
+    /// Member, that the current directory belongs to. It is `None` in
+    /// the root of the workspace.
+    pub current: Option<usize>,
+}
+
+impl Workspace {
+    pub fn target_directory(&self) -> PathBuf {
+        self.root.join(TARGET_DIRECTORY)
+    }
+
+    /// Members, that the command is run for: all of them if `all` is set,
+    /// `package` if it is given, the current member otherwise. In the root
+    /// of the workspace all members are selected by default.
+    pub fn select(&self, package: Option<&str>, all: bool) -> Result<Vec<&Member>, Failure> {
+        if let (Some(package), false) = (package, all) {
+            let Some(member) = self
+                .members
+                .iter()
+                .find(|member| member.manifest.name == package)
+            else {
+                return Err(Failure::Error(format!(
+                    "package `{package}` is not a member of the workspace."
+                )));
+            };
+
+            return Ok(vec![member]);
+        }
+
+        Ok(match self.current {
+            Some(current) if !all => vec![&self.members[current]],
+            _ => self.members.iter().collect(),
+        })
+    }
+
+    /// Selects the member to run, see [`Workspace::select`]. In the root of
+    /// the workspace it is the only member with binaries.
+    pub fn select_runnable(&self, package: Option<&str>) -> Result<&Member, Failure> {
+        let mut members = self.select(package, false)?;
+
+        if members.len() > 1 {
+            members.retain(|member| !member.manifest.bins.is_empty());
+        }
+
+        if let [member] = members[..] {
+            return Ok(member);
+        }
+
+        if members.is_empty() {
+            return Err(Failure::Error(
+                "the workspace has no binary targets to run.".to_owned(),
+            ));
+        }
+
+        let names = members
+            .iter()
+            .map(|member| format!("`{}`", member.manifest.name))
+            .collect::<Vec<_>>();
+
+        Err(Failure::Error(format!(
+            "could not determine which package to use, use `-p` to specify one of: {}.",
+            names.join(", ")
+        )))
+    }
+
+    /// Member, that the current directory belongs to.
+    pub fn current_member(&self) -> Result<&Member, Failure> {
+        match self.current {
+            Some(current) => Ok(&self.members[current]),
+            None => Err(Failure::Error(
+                "this command must be run in the directory of a workspace member.".to_owned(),
+            )),
+        }
+    }
+}
+
+fn read_manifest(root: &Path) -> Result<String, Failure> {
+    let path = root.join(MANIFEST_FILE);
+
+    fs::read_to_string(&path)
+        .map_err(|_| Failure::Error(format!("cannot read `{}`.", path.display())))
+}
+
+/// Reports errors of the manifest.
+fn manifest_errors(
+    root: &Path,
+    source: &str,
+    errors: Vec<ManifestError>,
+    reporter: &ReporterState,
+) -> Failure {
+    let name = root.join(MANIFEST_FILE).display().to_string();
+
+    let mut files = SimpleFiles::new();
+    let file_id = files.add(name.as_str(), source);
+
+    for error in errors {
+        error.emit_diagnostic(reporter, &files, file_id);
+    }
+
+    Failure::Error("could not load manifest due to the previous errors.".to_owned())
+}
+
+fn load_member(root: &Path, reporter: &ReporterState) -> Result<Member, Failure> {
+    let source = read_manifest(root)?;
+
+    match Manifest::load(root, &source) {
+        Ok(manifest) => Ok(Member {
+            root: root.to_owned(),
+            manifest,
+        }),
+        Err(errors) => Err(manifest_errors(root, &source, errors, reporter)),
+    }
+}
+
+/// Loads all members of the workspace in `root` and checks, that their
+/// names and names of binaries don't clash.
+fn load_members(
+    root: &Path,
+    source: &str,
+    reporter: &ReporterState,
+) -> Result<Vec<Member>, Failure> {
+    let workspace = WorkspaceManifest::load(root, source)
+        .map_err(|errors| manifest_errors(root, source, errors, reporter))?;
+
+    let members = workspace
+        .members
+        .iter()
+        .map(|member| load_member(&root.join(member), reporter))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut packages = HashMap::new();
+    let mut bins = HashMap::new();
+    let mut has_errors = false;
+
+    for member in &members {
+        let name = &member.manifest.name;
+
+        if let Some(other) = packages.insert(name, &member.root) {
+            reporter.emit_global_error(&format!(
+                "two workspace members are named `{name}`: `{}` and `{}`.",
+                other.display(),
+                member.root.display()
+            ));
+            has_errors = true;
+        }
+
+        // Binaries share the `target` directory.
+        for bin in member.manifest.bins.keys() {
+            if let Some(other) = bins.insert(bin, name) {
+                reporter.emit_global_error(&format!(
+                    "binary `{bin}` is defined by workspace members `{other}` and `{name}`."
+                ));
+                has_errors = true;
+            }
+        }
+    }
+
+    if has_errors {
+        return Err(Failure::Reported);
+    }
+
+    Ok(members)
+}
+
+/// Finds the workspace, that the current directory belongs to, and loads
+/// manifests of its members.
+pub fn load_workspace(reporter: &ReporterState) -> Result<Workspace, Failure> {
+    let current_dir = env::current_dir()
+        .map_err(|_| Failure::Error("cannot get current directory.".to_owned()))?;
+
+    load_workspace_of(&current_dir, reporter)
+}
+
+/// Finds the workspace, that `directory` belongs to, and loads manifests of
+/// its members.
+fn load_workspace_of(directory: &Path, reporter: &ReporterState) -> Result<Workspace, Failure> {
+    let Some(root) = find_root(directory) else {
+        return Err(Failure::Error(format!(
+            "could not find `{MANIFEST_FILE}` in the current directory or any parent directory."
+        )));
+    };
+
+    let source = read_manifest(root)?;
+
+    if is_workspace(&source) {
+        return Ok(Workspace {
+            root: root.to_owned(),
+            members: load_members(root, &source, reporter)?,
+            current: None,
+        });
+    }
+
+    let member = load_member(root, reporter)?;
+
+    // The closest workspace above the package, that lists it as a member.
+    let workspace = root
+        .ancestors()
+        .skip(1)
+        .filter_map(|directory| {
+            let source = fs::read_to_string(directory.join(MANIFEST_FILE)).ok()?;
+            is_workspace(&source).then_some((directory, source))
+        })
+        .next()
+        .filter(|(directory, source)| {
+            WorkspaceManifest::load(directory, source)
+                .is_ok_and(|workspace| workspace.contains(directory, root))
+        });
+
+    Ok(match workspace {
+        Some((directory, source)) => {
+            let members = load_members(directory, &source, reporter)?;
+            let current = members
+                .iter()
+                .position(|other| other.manifest.name == member.manifest.name);
+
+            Workspace {
+                root: directory.to_owned(),
+                members,
+                current,
+            }
+        }
+        None => Workspace {
+            root: root.to_owned(),
+            members: vec![member],
+            current: Some(0),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_directory;
+
+    #[test]
+    fn workspace_test() {
+        let temp = temp_directory(&[
+            (
+                MANIFEST_FILE,
+                r#"{"workspace": {"members": ["app", "util"]}}"#,
+            ),
+            (
+                "app/.lapis.json",
+                r#"{"name": "app", "version": "0.1.0", "bin": {"app": "main.ry"}}"#,
+            ),
+            ("app/main.ry", "fun main() {}\n"),
+            (
+                "util/.lapis.json",
+                r#"{"name": "util", "version": "0.1.0", "lib": "lib.ry"}"#,
+            ),
+            ("util/lib.ry", ""),
+        ]);
+        let root = temp.path();
+
+        let reporter = ReporterState::default();
+        let names = |members: Vec<&Member>| {
+            members
+                .into_iter()
+                .map(|member| member.manifest.name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let workspace = load_workspace_of(root, &reporter).unwrap();
+        assert_eq!(workspace.current, None);
+        assert_eq!(
+            names(workspace.select(None, false).unwrap()),
+            ["app", "util"]
+        );
+        assert_eq!(
+            names(workspace.select(Some("util"), false).unwrap()),
+            ["util"]
+        );
+        assert_eq!(
+            workspace.select(Some("missing"), false).err(),
+            Some(Failure::Error(
+                "package `missing` is not a member of the workspace.".to_owned()
+            ))
+        );
+        assert_eq!(
+            workspace.select_runnable(None).unwrap().manifest.name,
+            "app"
+        );
+        assert_eq!(
+            workspace.current_member().err(),
+            Some(Failure::Error(
+                "this command must be run in the directory of a workspace member.".to_owned()
+            ))
+        );
+
+        let workspace = load_workspace_of(&root.join("util"), &reporter).unwrap();
+        assert_eq!(workspace.root, root);
+        assert_eq!(workspace.current_member().unwrap().manifest.name, "util");
+
+        fs::write(
+            root.join("util").join(MANIFEST_FILE),
+            r#"{"name": "app", "version": "0.1.0", "lib": "lib.ry"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            load_workspace_of(root, &reporter).err(),
+            Some(Failure::Reported)
+        );
+
+        fs::write(root.join("util").join(MANIFEST_FILE), "{").unwrap();
+        assert_eq!(
+            load_workspace_of(root, &reporter).err(),
+            Some(Failure::Error(
+                "could not load manifest due to the previous errors.".to_owned()
+            ))
+        );
+    }
+}
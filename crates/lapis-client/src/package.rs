@@ -0,0 +1,160 @@
+//! `package.rs` - packs projects into archives for publishing.
+//!
+//! Archive is a `.tar.gz` with all files of the project, except the build
+//! directory, the lockfile and hidden files (but not the manifest). Files
+//! are added in a fixed order with zeroed timestamps, so packing the same
+//! sources always gives the same archive and checksum.
+use std::{fs, io, path::Path};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use ry_manifest::{Manifest, MANIFEST_FILE};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use crate::{build::TARGET_DIRECTORY, lockfile::LOCKFILE};
+
+/// Package ready for publishing.
+pub struct Package {
+    pub manifest: Manifest,
+    pub archive: Vec<u8>,
+    /// SHA-256 checksum of the archive in hex.
+    pub checksum: String,
+}
+
+/// SHA-256 checksum of the data in hex.
+pub fn checksum(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Whether the file (path relative to the project root) is packed.
+fn is_packed(path: &Path) -> bool {
+    if path == Path::new(MANIFEST_FILE) {
+        return true;
+    }
+
+    path != Path::new(TARGET_DIRECTORY)
+        && path != Path::new(LOCKFILE)
+        && !path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// Checks, that the project can be published, and packs it. Returns
+/// the reason, if it can't be.
+pub fn package(root: &Path, manifest: &Manifest) -> Result<Package, String> {
+    if manifest.lib.is_none() {
+        return Err("only libraries can be published, but the project has no `lib`".to_owned());
+    }
+
+    if let Some((name, _)) = manifest
+        .dependencies
+        .iter()
+        .find(|(_, dependency)| dependency.path.is_some())
+    {
+        return Err(format!(
+            "dependency `{name}` is a local package, only registry dependencies can be published"
+        ));
+    }
+
+    let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+
+    let entries = WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry
+                .path()
+                .strip_prefix(root)
+                .is_ok_and(|path| path.as_os_str().is_empty() || is_packed(path))
+        });
+
+    let pack = |builder: &mut tar::Builder<_>| -> io::Result<()> {
+        for entry in entries {
+            let entry = entry?;
+
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let path = entry
+                .path()
+                .strip_prefix(root)
+                .expect("walked paths are inside the root");
+            let contents = fs::read(entry.path())?;
+
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(0);
+
+            builder.append_data(&mut header, path, contents.as_slice())?;
+        }
+
+        Ok(())
+    };
+
+    let archive = pack(&mut builder)
+        .and_then(|_| builder.into_inner())
+        .and_then(GzEncoder::finish)
+        .map_err(|e| format!("cannot pack the project: {e}"))?;
+
+    Ok(Package {
+        manifest: manifest.clone(),
+        checksum: checksum(&archive),
+        archive,
+    })
+}
+
+/// Unpacks the archive into the directory. Entries outside of it are
+/// skipped.
+pub fn unpack(archive: &[u8], directory: &Path) -> io::Result<()> {
+    fs::create_dir_all(directory)?;
+
+    let mut archive = tar::Archive::new(GzDecoder::new(archive));
+
+    for entry in archive.entries()? {
+        entry?.unpack_in(directory)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_directory;
+
+    #[test]
+    fn package_test() {
+        let temp = temp_directory(&[
+            ("log/.lapis.json", ""),
+            ("log/lib/lib.ry", ""),
+            ("log/lib/util.ry", ""),
+            ("log/lapis.lock", ""),
+            ("log/target/lib/log.json", ""),
+            ("log/.git/HEAD", ""),
+        ]);
+        let root = temp.path();
+
+        let project = root.join("log");
+        let mut manifest = Manifest::new("log");
+        manifest.bins.clear();
+
+        assert!(package(&project, &manifest).is_err());
+
+        manifest.lib = Some("lib/lib.ry".into());
+        let package = package(&project, &manifest).unwrap();
+        assert_eq!(package.checksum, checksum(&package.archive));
+
+        unpack(&package.archive, &root.join("unpacked")).unwrap();
+
+        let files = WalkDir::new(root.join("unpacked"))
+            .sort_by_file_name()
+            .into_iter()
+            .map(|entry| entry.unwrap())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(files, [MANIFEST_FILE, "lib.ry", "util.ry"]);
+    }
+}
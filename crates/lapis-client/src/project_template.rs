@@ -1,54 +1,322 @@
-use std::{process::exit, fs::{self, File}, path::Path, env, io::Write};
-use ry_report::ReporterState;
-
-fn check_project_name(name: &str, reporter: &ReporterState) {
-    name.chars().for_each(|c| {
-        if !(c.is_ascii_digit() || c.is_ascii_lowercase() || c == '_') {
-            reporter.emit_global_error(&format!("project name must consist only of ascii lowercase, ascii digit or `_` characters! but somewhy i see `{c}`."));
-            exit(1);
-        }
-    })
-}
-
-fn create_file(name: &str, reporter: &ReporterState) -> File {
-	match File::create(name) {
-		Ok(file) => file,
-		Err(_) => {
-			reporter.emit_global_error(
-		  	  &format!("cannot create file `{name}`."));
-				exit(1);
-		}
-	}
-}
-
-pub fn create_lapis_project(name: &str, reporter: &ReporterState) {
-	check_project_name(name, reporter);
-
-	if Path::new(name).exists() {
-		reporter.emit_global_error(
-		    "folder with this name of this project already exists.");
-		exit(1);
-	}
-
-	fs::create_dir_all(name).unwrap_or_else(|_| {
-    reporter.emit_global_error(
-        &format!("cannot create project directory `{name}`."));
-    exit(1);
-	});
-
-	env::set_current_dir(name).unwrap_or_else(|_| {
-    reporter.emit_global_error(
-        "cannot go into project dir.");
-    exit(1);
-	});
-
-	let mut lapis_config = create_file(".lapis.json", reporter);
-	write!(lapis_config, "{{\n  \"project_name\": \"{name}\"\n}}\n").unwrap();
-
-	["test", "bin", "lib"].iter().for_each(|f| {
-		fs::create_dir_all(f).unwrap();
-	});
-
-	let mut main_file = create_file("bin/main.ry", reporter);
-	write!(main_file, "pub fun main() {{\n\n}}").unwrap();
-}
\ No newline at end of file
+//! `project_template.rs` - creates projects from templates.
+//!
+//! Built-in templates are a binary (`bin/main.ry`) and a library
+//! (`lib/lib.ry` with a test). User-defined template is a directory, that
+//! is copied into the project: `{{name}}` in text files is replaced with
+//! the name of the project. If the template has no manifest, it is
+//! generated for the targets found in the template.
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use ry_manifest::{is_valid_name, Manifest, MANIFEST_FILE};
+use walkdir::WalkDir;
+
+use crate::test_runner::TEST_DIRECTORY;
+
+/// Placeholder for the project name in user-defined templates.
+const NAME_PLACEHOLDER: &str = "{{name}}";
+
+const GITIGNORE: &str = "/target\n";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Template {
+    Bin,
+    Lib,
+    /// User-defined template in the directory.
+    Directory(PathBuf),
+}
+
+#[derive(Debug)]
+pub enum TemplateError {
+    /// [`String`] can't be used as a project name.
+    InvalidName(String),
+
+    /// Directory of the new project already exists.
+    AlreadyExists(PathBuf),
+
+    /// Directory already contains the manifest.
+    AlreadyInitialized(PathBuf),
+
+    /// Directory of the user-defined template doesn't exist.
+    TemplateNotFound(PathBuf),
+
+    /// File or directory can't be read or written.
+    Io(PathBuf, io::Error),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidName(name) => write!(
+                f,
+                "invalid project name `{name}`: it must start with ascii lowercase letter or `_` \
+                 and consist only of ascii lowercase letters, digits and `_`"
+            ),
+            Self::AlreadyExists(path) => write!(f, "`{}` already exists", path.display()),
+            Self::AlreadyInitialized(path) => {
+                write!(f, "`{}` already contains `{MANIFEST_FILE}`", path.display())
+            }
+            Self::TemplateNotFound(path) => {
+                write!(f, "template directory `{}` not found", path.display())
+            }
+            Self::Io(path, error) => write!(f, "cannot access `{}`: {error}", path.display()),
+        }
+    }
+}
+
+/// Files and directories of the project, paths are relative to its root.
+#[derive(Debug, Default)]
+struct Scaffold {
+    directories: Vec<PathBuf>,
+    files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+impl Scaffold {
+    fn file(&mut self, path: &str, contents: impl Into<Vec<u8>>) {
+        self.files.push((PathBuf::from(path), contents.into()));
+    }
+
+    fn has_file(&self, path: &str) -> bool {
+        self.files.iter().any(|(file, _)| file == Path::new(path))
+    }
+
+    /// Writes the scaffold into the directory. Existing files are kept.
+    fn write(&self, directory: &Path) -> Result<(), TemplateError> {
+        let io_error = |path: &Path| {
+            let path = path.to_owned();
+            move |error| TemplateError::Io(path, error)
+        };
+
+        for path in &self.directories {
+            let path = directory.join(path);
+            fs::create_dir_all(&path).map_err(io_error(&path))?;
+        }
+
+        for (path, contents) in &self.files {
+            let path = directory.join(path);
+
+            if path.exists() {
+                continue;
+            }
+
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(io_error(parent))?;
+            }
+
+            fs::write(&path, contents).map_err(io_error(&path))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn builtin_scaffold(name: &str, template: &Template) -> Scaffold {
+    let mut scaffold = Scaffold {
+        directories: vec![PathBuf::from(TEST_DIRECTORY)],
+        ..Scaffold::default()
+    };
+    let mut manifest = Manifest::new(name);
+
+    if *template == Template::Lib {
+        manifest.bins.clear();
+        manifest.lib = Some(PathBuf::from("lib/lib.ry"));
+
+        scaffold.file(
+            "lib/lib.ry",
+            "pub fun add(a i32, b i32) i32 {\n    a + b\n}\n\n\
+             @test\nfun add_works() {\n    assert_eq(add(2, 2), 4);\n}\n",
+        );
+    } else {
+        scaffold.file(
+            "bin/main.ry",
+            "pub fun main() {\n    println(\"Hello, world!\");\n}\n",
+        );
+    }
+
+    scaffold.file(MANIFEST_FILE, manifest.to_json());
+    scaffold.file(".gitignore", GITIGNORE);
+    scaffold
+}
+
+fn directory_scaffold(name: &str, template: &Path) -> Result<Scaffold, TemplateError> {
+    if !template.is_dir() {
+        return Err(TemplateError::TemplateNotFound(template.to_owned()));
+    }
+
+    let mut scaffold = Scaffold::default();
+
+    let entries = WalkDir::new(template)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git");
+
+    for entry in entries {
+        let entry = entry.map_err(|e| TemplateError::Io(template.to_owned(), e.into()))?;
+        let path = entry
+            .path()
+            .strip_prefix(template)
+            .expect("walked paths are inside the template")
+            .to_owned();
+
+        if entry.file_type().is_dir() {
+            scaffold.directories.push(path);
+            continue;
+        }
+
+        let contents = fs::read(entry.path()).map_err(|e| TemplateError::Io(path.clone(), e))?;
+
+        // Binary files are copied as is.
+        let contents = match String::from_utf8(contents) {
+            Ok(text) => text.replace(NAME_PLACEHOLDER, name).into_bytes(),
+            Err(e) => e.into_bytes(),
+        };
+
+        scaffold.files.push((path, contents));
+    }
+
+    if !scaffold.has_file(MANIFEST_FILE) {
+        let mut manifest = Manifest::new(name);
+
+        if !scaffold.has_file("bin/main.ry") {
+            manifest.bins.clear();
+        }
+
+        if scaffold.has_file("lib/lib.ry") {
+            manifest.lib = Some(PathBuf::from("lib/lib.ry"));
+        }
+
+        scaffold.file(MANIFEST_FILE, manifest.to_json());
+    }
+
+    Ok(scaffold)
+}
+
+fn scaffold(name: &str, template: &Template) -> Result<Scaffold, TemplateError> {
+    if !is_valid_name(name) {
+        return Err(TemplateError::InvalidName(name.to_owned()));
+    }
+
+    match template {
+        Template::Directory(directory) => directory_scaffold(name, directory),
+        _ => Ok(builtin_scaffold(name, template)),
+    }
+}
+
+/// `lapis new`: creates the project in the new `directory`.
+pub fn create_project(
+    directory: &Path,
+    name: &str,
+    template: &Template,
+) -> Result<(), TemplateError> {
+    if directory.exists() {
+        return Err(TemplateError::AlreadyExists(directory.to_owned()));
+    }
+
+    let scaffold = scaffold(name, template)?;
+
+    fs::create_dir_all(directory).map_err(|e| TemplateError::Io(directory.to_owned(), e))?;
+    scaffold.write(directory)
+}
+
+/// `lapis init`: turns the existing `directory` into the project. Files
+/// of the template, that already exist, are kept.
+pub fn init_project(
+    directory: &Path,
+    name: &str,
+    template: &Template,
+) -> Result<(), TemplateError> {
+    if directory.join(MANIFEST_FILE).exists() {
+        return Err(TemplateError::AlreadyInitialized(directory.to_owned()));
+    }
+
+    scaffold(name, template)?.write(directory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_directory;
+
+    fn read(path: PathBuf) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn create_project_test() {
+        let temp = temp_directory(&[]);
+        let root = temp.path();
+
+        create_project(&root.join("app"), "app", &Template::Bin).unwrap();
+        let manifest = Manifest::load(&root.join("app"), &read(root.join("app/.lapis.json")));
+        assert_eq!(
+            manifest.unwrap().bins,
+            [("app".to_owned(), PathBuf::from("bin/main.ry"))].into()
+        );
+        assert!(root.join("app/test").is_dir());
+
+        create_project(&root.join("math"), "math", &Template::Lib).unwrap();
+        let manifest = Manifest::load(&root.join("math"), &read(root.join("math/.lapis.json")));
+        assert_eq!(manifest.unwrap().lib, Some(PathBuf::from("lib/lib.ry")));
+
+        assert!(matches!(
+            create_project(&root.join("app"), "app", &Template::Bin),
+            Err(TemplateError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            create_project(&root.join("App"), "App", &Template::Bin),
+            Err(TemplateError::InvalidName(_))
+        ));
+
+        // User-defined template.
+        let template = root.join("template");
+        fs::create_dir_all(template.join("lib")).unwrap();
+        fs::create_dir_all(template.join(".git")).unwrap();
+        fs::write(template.join("lib/lib.ry"), "/// {{name}} library\n").unwrap();
+        fs::write(template.join(".git/HEAD"), "").unwrap();
+
+        let directory = Template::Directory(template.clone());
+        create_project(&root.join("json"), "json", &directory).unwrap();
+        assert_eq!(read(root.join("json/lib/lib.ry")), "/// json library\n");
+        assert!(!root.join("json/.git").exists());
+
+        let manifest =
+            Manifest::load(&root.join("json"), &read(root.join("json/.lapis.json"))).unwrap();
+        assert_eq!(manifest.lib, Some(PathBuf::from("lib/lib.ry")));
+        assert!(manifest.bins.is_empty());
+
+        assert!(matches!(
+            create_project(
+                &root.join("xml"),
+                "xml",
+                &Template::Directory(root.join("missing"))
+            ),
+            Err(TemplateError::TemplateNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn init_project_test() {
+        let temp = temp_directory(&[("bin/main.ry", "pub fun main() {}\n")]);
+        let root = temp.path();
+
+        init_project(root, "tool", &Template::Bin).unwrap();
+
+        // Existing files are kept.
+        assert_eq!(read(root.join("bin/main.ry")), "pub fun main() {}\n");
+        assert_eq!(read(root.join(".gitignore")), GITIGNORE);
+        assert_eq!(
+            Manifest::parse(&read(root.join(MANIFEST_FILE)))
+                .unwrap()
+                .name,
+            "tool"
+        );
+
+        assert!(matches!(
+            init_project(root, "tool", &Template::Bin),
+            Err(TemplateError::AlreadyInitialized(_))
+        ));
+    }
+}
@@ -0,0 +1,46 @@
+//! `publish.rs` - publishes the current project into the registry.
+use std::{env, path::Path};
+
+use ry_manifest::Manifest;
+
+use crate::{dependencies::open_registry, error::Failure, package::package, registry::TOKEN_ENV};
+
+/// `lapis publish`: packs the project and uploads it with the checksum and
+/// the manifest. `token` defaults to [`TOKEN_ENV`].
+pub fn publish_project(
+    root: &Path,
+    manifest: &Manifest,
+    token: Option<&str>,
+) -> Result<(), Failure> {
+    eprintln!(
+        "{:>12} {} v{}",
+        "Packaging", manifest.name, manifest.version
+    );
+
+    let package = package(root, manifest)
+        .map_err(|reason| Failure::Error(format!("cannot publish {}: {reason}.", manifest.name)))?;
+
+    let token = token
+        .map(ToOwned::to_owned)
+        .or_else(|| env::var(TOKEN_ENV).ok());
+
+    eprintln!(
+        "{:>12} {} v{} ({} bytes, sha256 {})",
+        "Uploading",
+        manifest.name,
+        manifest.version,
+        package.archive.len(),
+        package.checksum
+    );
+
+    open_registry()?
+        .publish(&package, token.as_deref())
+        .map_err(|e| Failure::Error(format!("{e}.")))?;
+
+    eprintln!(
+        "{:>12} {} v{}",
+        "Published", manifest.name, manifest.version
+    );
+
+    Ok(())
+}
@@ -0,0 +1,638 @@
+//! `registry.rs` - registries of published packages.
+//!
+//! [`Registry`] has two implementations:
+//!
+//! - [`DirectoryRegistry`] keeps every published version of a package in
+//!   `<registry>/<name>/<version>/`: the manifest (`.lapis.json`),
+//!   the archive (`package.tar.gz`) and its SHA-256 checksum
+//!   (`package.sha256`). It works offline, for example, as a local mirror
+//!   or in tests.
+//! - [`HttpRegistry`] talks to the registry over HTTP. The protocol below
+//!   is a proposal: `lapis-server` doesn't implement it yet, it only serves
+//!   `/api` and `/jwt`.
+//!
+//!   | Request | Response |
+//!   | --- | --- |
+//!   | `GET /api/packages/<name>` | `{"versions": [{"version", "checksum", "manifest"}]}` |
+//!   | `GET /api/packages/<name>/<version>/download` | the archive |
+//!   | `PUT /api/packages/<name>/<version>` | `{"manifest", "checksum", "archive"}` (hex) |
+//!   | `GET /api/search?q=<query>` | `{"packages": [{"name", "version", "description"}]}` |
+//!
+//!   Publishing requires the token in the `Token` header. Unknown packages
+//!   and versions are answered with `404`, rejected tokens with `401` and
+//!   versions, that are already published, with `409`.
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    env, fmt, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use ry_manifest::{Manifest, MANIFEST_FILE};
+use semver::Version;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::package::{self, Package};
+
+/// Environment variable with the path or URL of the registry.
+pub const REGISTRY_ENV: &str = "LAPIS_REGISTRY";
+
+/// Environment variable with the token used for publishing.
+pub const TOKEN_ENV: &str = "LAPIS_TOKEN";
+
+const ARCHIVE_FILE: &str = "package.tar.gz";
+const CHECKSUM_FILE: &str = "package.sha256";
+
+#[derive(Debug, PartialEq)]
+pub enum RegistryError {
+    /// Version of the package (1-st [`String`]) is not published.
+    NotFound(String, Version),
+
+    /// Version of the package (1-st [`String`]) is already published.
+    AlreadyPublished(String, Version),
+
+    /// Checksum of the downloaded archive of the package (1-st [`String`])
+    /// doesn't match the published one.
+    ChecksumMismatch(String, Version),
+
+    /// Registry rejected the token (or it is missing).
+    Unauthorized,
+
+    /// I/O or network failure, or invalid response of the registry.
+    Other(String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(name, version) => {
+                write!(f, "{name} v{version} is not published in the registry")
+            }
+            Self::AlreadyPublished(name, version) => {
+                write!(f, "{name} v{version} is already published")
+            }
+            Self::ChecksumMismatch(name, version) => write!(
+                f,
+                "checksum of the downloaded archive of {name} v{version} doesn't match"
+            ),
+            Self::Unauthorized => write!(
+                f,
+                "registry rejected the token, pass a valid one with `--token` or `{TOKEN_ENV}`"
+            ),
+            Self::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<io::Error> for RegistryError {
+    fn from(error: io::Error) -> Self {
+        Self::Other(error.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SearchResult {
+    pub name: String,
+    /// The latest published version.
+    pub version: Version,
+    pub description: Option<String>,
+}
+
+pub trait Registry {
+    /// Published versions of the package in ascending order (empty, if
+    /// the package is unknown).
+    fn versions(&self, name: &str) -> Result<Vec<Version>, RegistryError>;
+
+    /// Manifest of the published version.
+    fn manifest(&self, name: &str, version: &Version) -> Result<Manifest, RegistryError>;
+
+    /// SHA-256 checksum of the archive of the published version.
+    fn checksum(&self, name: &str, version: &Version) -> Result<String, RegistryError>;
+
+    /// Archive (`.tar.gz`) of the published version.
+    fn download(&self, name: &str, version: &Version) -> Result<Vec<u8>, RegistryError>;
+
+    fn publish(&self, package: &Package, token: Option<&str>) -> Result<(), RegistryError>;
+
+    /// Packages, which name or description contains the query (ignoring
+    /// case), sorted by name.
+    fn search(&self, query: &str) -> Result<Vec<SearchResult>, RegistryError>;
+}
+
+/// Registry from [`REGISTRY_ENV`] (URL or path to the directory), or
+/// `~/.lapis/registry` by default.
+pub fn from_env() -> Option<Box<dyn Registry>> {
+    let location = match env::var(REGISTRY_ENV) {
+        Ok(location) => location,
+        Err(_) => {
+            let home = env::var_os("HOME")?;
+            return Some(Box::new(DirectoryRegistry::new(
+                Path::new(&home).join(".lapis").join("registry"),
+            )));
+        }
+    };
+
+    if location.starts_with("http://") || location.starts_with("https://") {
+        Some(Box::new(HttpRegistry::new(&location)))
+    } else {
+        Some(Box::new(DirectoryRegistry::new(location)))
+    }
+}
+
+/// Downloads the published version of the package, verifies its checksum
+/// and unpacks it into `<cache>/<name>/<version>`, unless it is already
+/// there. Returns the directory of the package.
+pub fn fetch(
+    registry: &dyn Registry,
+    cache: &Path,
+    name: &str,
+    version: &Version,
+) -> Result<PathBuf, RegistryError> {
+    let directory = cache.join(name).join(version.to_string());
+
+    if directory.join(MANIFEST_FILE).is_file() {
+        return Ok(directory);
+    }
+
+    let archive = registry.download(name, version)?;
+
+    if package::checksum(&archive) != registry.checksum(name, version)? {
+        return Err(RegistryError::ChecksumMismatch(
+            name.to_owned(),
+            version.clone(),
+        ));
+    }
+
+    // Unpacked into the temporary directory first, so the interrupted
+    // download is not mistaken for the complete one.
+    let partial = cache.join(name).join(format!(".{version}.partial"));
+    let _ = fs::remove_dir_all(&partial);
+
+    package::unpack(&archive, &partial)?;
+    fs::rename(&partial, &directory)?;
+
+    Ok(directory)
+}
+
+pub struct DirectoryRegistry {
+    root: PathBuf,
+}
+
+impl DirectoryRegistry {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn package(&self, name: &str, version: &Version) -> PathBuf {
+        self.root.join(name).join(version.to_string())
+    }
+
+    fn read(&self, name: &str, version: &Version, file: &str) -> Result<Vec<u8>, RegistryError> {
+        fs::read(self.package(name, version).join(file)).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => RegistryError::NotFound(name.to_owned(), version.clone()),
+            _ => e.into(),
+        })
+    }
+}
+
+impl Registry for DirectoryRegistry {
+    /// Directories, which are not named as versions, are ignored.
+    fn versions(&self, name: &str) -> Result<Vec<Version>, RegistryError> {
+        let Ok(entries) = fs::read_dir(self.root.join(name)) else {
+            return Ok(vec![]);
+        };
+
+        let mut versions = entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+
+                if !entry.path().join(MANIFEST_FILE).is_file() {
+                    return None;
+                }
+
+                Version::parse(entry.file_name().to_str()?).ok()
+            })
+            .collect::<Vec<_>>();
+
+        versions.sort();
+        Ok(versions)
+    }
+
+    fn manifest(&self, name: &str, version: &Version) -> Result<Manifest, RegistryError> {
+        let source =
+            String::from_utf8_lossy(&self.read(name, version, MANIFEST_FILE)?).into_owned();
+
+        parse_manifest(name, version, &source)
+    }
+
+    fn checksum(&self, name: &str, version: &Version) -> Result<String, RegistryError> {
+        let checksum = self.read(name, version, CHECKSUM_FILE)?;
+
+        Ok(String::from_utf8_lossy(&checksum).trim().to_owned())
+    }
+
+    fn download(&self, name: &str, version: &Version) -> Result<Vec<u8>, RegistryError> {
+        self.read(name, version, ARCHIVE_FILE)
+    }
+
+    /// Token is not needed.
+    fn publish(&self, package: &Package, _: Option<&str>) -> Result<(), RegistryError> {
+        let name = &package.manifest.name;
+        let version = &package.manifest.version;
+        let directory = self.package(name, version);
+
+        if directory.exists() {
+            return Err(RegistryError::AlreadyPublished(
+                name.clone(),
+                version.clone(),
+            ));
+        }
+
+        // The manifest is written last, versions without it are ignored.
+        fs::create_dir_all(&directory)?;
+        fs::write(directory.join(ARCHIVE_FILE), &package.archive)?;
+        fs::write(
+            directory.join(CHECKSUM_FILE),
+            format!("{}\n", package.checksum),
+        )?;
+        fs::write(directory.join(MANIFEST_FILE), package.manifest.to_json())?;
+
+        Ok(())
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<SearchResult>, RegistryError> {
+        let Ok(entries) = fs::read_dir(&self.root) else {
+            return Ok(vec![]);
+        };
+
+        let query = query.to_lowercase();
+        let mut results = vec![];
+
+        for entry in entries {
+            let Ok(name) = entry?.file_name().into_string() else {
+                continue;
+            };
+
+            let Some(version) = self.versions(&name)?.pop() else {
+                continue;
+            };
+
+            let manifest = self.manifest(&name, &version)?;
+
+            let matches = name.contains(&query)
+                || manifest
+                    .description
+                    .as_ref()
+                    .is_some_and(|description| description.to_lowercase().contains(&query));
+
+            if matches {
+                results.push(SearchResult {
+                    name,
+                    version,
+                    description: manifest.description,
+                });
+            }
+        }
+
+        results.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(results)
+    }
+}
+
+#[derive(Deserialize)]
+struct IndexEntry {
+    version: Version,
+    checksum: String,
+    manifest: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct Index {
+    versions: Vec<IndexEntry>,
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    packages: Vec<SearchResult>,
+}
+
+pub struct HttpRegistry {
+    url: String,
+    /// Versions of packages, that were already requested.
+    index: RefCell<HashMap<String, Vec<IndexEntry>>>,
+}
+
+impl HttpRegistry {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_owned(),
+            index: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn index(&self, name: &str) -> Result<(), RegistryError> {
+        if self.index.borrow().contains_key(name) {
+            return Ok(());
+        }
+
+        let versions = match ureq::get(&format!("{}/api/packages/{name}", self.url)).call() {
+            Ok(response) => {
+                response
+                    .into_json::<Index>()
+                    .map_err(|e| {
+                        RegistryError::Other(format!("invalid response of the registry: {e}"))
+                    })?
+                    .versions
+            }
+            Err(ureq::Error::Status(404, _)) => vec![],
+            Err(e) => return Err(request_error(e)),
+        };
+
+        self.index.borrow_mut().insert(name.to_owned(), versions);
+        Ok(())
+    }
+
+    /// Calls the function with the index entry of the published version.
+    fn entry<T>(
+        &self,
+        name: &str,
+        version: &Version,
+        f: impl FnOnce(&IndexEntry) -> Result<T, RegistryError>,
+    ) -> Result<T, RegistryError> {
+        self.index(name)?;
+
+        let index = self.index.borrow();
+        let entry = index[name]
+            .iter()
+            .find(|entry| entry.version == *version)
+            .ok_or_else(|| RegistryError::NotFound(name.to_owned(), version.clone()))?;
+
+        f(entry)
+    }
+}
+
+impl Registry for HttpRegistry {
+    fn versions(&self, name: &str) -> Result<Vec<Version>, RegistryError> {
+        self.index(name)?;
+
+        let mut versions = self.index.borrow()[name]
+            .iter()
+            .map(|entry| entry.version.clone())
+            .collect::<Vec<_>>();
+
+        versions.sort();
+        Ok(versions)
+    }
+
+    fn manifest(&self, name: &str, version: &Version) -> Result<Manifest, RegistryError> {
+        self.entry(name, version, |entry| {
+            parse_manifest(name, version, &entry.manifest.to_string())
+        })
+    }
+
+    fn checksum(&self, name: &str, version: &Version) -> Result<String, RegistryError> {
+        self.entry(name, version, |entry| Ok(entry.checksum.clone()))
+    }
+
+    fn download(&self, name: &str, version: &Version) -> Result<Vec<u8>, RegistryError> {
+        let url = format!("{}/api/packages/{name}/{version}/download", self.url);
+
+        match ureq::get(&url).call() {
+            Ok(response) => {
+                let mut archive = vec![];
+                response.into_reader().read_to_end(&mut archive)?;
+                Ok(archive)
+            }
+            Err(ureq::Error::Status(404, _)) => {
+                Err(RegistryError::NotFound(name.to_owned(), version.clone()))
+            }
+            Err(e) => Err(request_error(e)),
+        }
+    }
+
+    fn publish(&self, package: &Package, token: Option<&str>) -> Result<(), RegistryError> {
+        let name = &package.manifest.name;
+        let version = &package.manifest.version;
+
+        let Some(token) = token else {
+            return Err(RegistryError::Unauthorized);
+        };
+
+        let manifest = serde_json::from_str::<serde_json::Value>(&package.manifest.to_json())
+            .expect("manifest is serialized into valid JSON");
+
+        let result = ureq::put(&format!("{}/api/packages/{name}/{version}", self.url))
+            .set("Token", token)
+            .send_json(json!({
+                "manifest": manifest,
+                "checksum": package.checksum,
+                "archive": hex::encode(&package.archive),
+            }));
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(401, _)) => Err(RegistryError::Unauthorized),
+            Err(ureq::Error::Status(409, _)) => Err(RegistryError::AlreadyPublished(
+                name.clone(),
+                version.clone(),
+            )),
+            Err(e) => Err(request_error(e)),
+        }
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<SearchResult>, RegistryError> {
+        ureq::get(&format!("{}/api/search", self.url))
+            .query("q", query)
+            .call()
+            .map_err(request_error)?
+            .into_json::<SearchResponse>()
+            .map(|response| response.packages)
+            .map_err(|e| RegistryError::Other(format!("invalid response of the registry: {e}")))
+    }
+}
+
+fn request_error(error: ureq::Error) -> RegistryError {
+    RegistryError::Other(format!("request to the registry failed: {error}"))
+}
+
+/// Parses the manifest of the published version and checks, that it
+/// describes this version.
+fn parse_manifest(name: &str, version: &Version, source: &str) -> Result<Manifest, RegistryError> {
+    let manifest = Manifest::parse(source)
+        .map_err(|_| RegistryError::Other(format!("manifest of {name} v{version} is invalid")))?;
+
+    if manifest.name != name || manifest.version != *version {
+        return Err(RegistryError::Other(format!(
+            "manifest of {name} v{version} describes {} v{}",
+            manifest.name, manifest.version
+        )));
+    }
+
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use super::*;
+    use crate::test_utils::temp_directory;
+
+    /// Starts the server, that answers requests with the given statuses
+    /// (one request per connection) and an empty body. Returns its URL and
+    /// the thread, which returns request lines (`"GET /path"`).
+    fn stub_server(statuses: Vec<u16>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let mut requests = vec![];
+
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                requests.push(line.rsplit_once(' ').unwrap().0.to_owned());
+
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+
+                    if header.trim().is_empty() {
+                        break;
+                    }
+
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )
+                .unwrap();
+            }
+
+            requests
+        });
+
+        (url, server)
+    }
+
+    #[test]
+    fn http_registry_errors_test() {
+        let temp = temp_directory(&[("json/lib/lib.ry", "pub fun parse() {}\n")]);
+        let project = temp.path().join("json");
+
+        let mut manifest = Manifest::new("json");
+        manifest.lib = Some(PathBuf::from("lib/lib.ry"));
+        manifest.bins.clear();
+        fs::write(project.join(MANIFEST_FILE), manifest.to_json()).unwrap();
+
+        let package = package::package(&project, &manifest).unwrap();
+        let version = Version::new(0, 1, 0);
+
+        let (url, server) = stub_server(vec![404, 404, 401, 409]);
+        let registry = HttpRegistry::new(&url);
+
+        assert_eq!(registry.versions("json"), Ok(vec![]));
+        assert_eq!(
+            registry.download("json", &version),
+            Err(RegistryError::NotFound("json".to_owned(), version.clone()))
+        );
+        assert_eq!(
+            registry.publish(&package, Some("invalid")),
+            Err(RegistryError::Unauthorized)
+        );
+        assert_eq!(
+            registry.publish(&package, Some("token")),
+            Err(RegistryError::AlreadyPublished("json".to_owned(), version))
+        );
+
+        assert_eq!(
+            server.join().unwrap(),
+            [
+                "GET /api/packages/json",
+                "GET /api/packages/json/0.1.0/download",
+                "PUT /api/packages/json/0.1.0",
+                "PUT /api/packages/json/0.1.0",
+            ]
+        );
+    }
+
+    #[test]
+    fn directory_registry_test() {
+        let temp = temp_directory(&[("json/lib/lib.ry", "pub fun parse() {}\n")]);
+        let root = temp.path();
+
+        let project = root.join("json");
+
+        let mut manifest = Manifest::new("json");
+        manifest.description = Some("JSON Parser".to_owned());
+        manifest.lib = Some(PathBuf::from("lib/lib.ry"));
+        manifest.bins.clear();
+        fs::write(project.join(MANIFEST_FILE), manifest.to_json()).unwrap();
+
+        let registry = DirectoryRegistry::new(root.join("registry"));
+        assert_eq!(registry.versions("json"), Ok(vec![]));
+
+        let package = package::package(&project, &manifest).unwrap();
+        registry.publish(&package, None).unwrap();
+        assert_eq!(
+            registry.publish(&package, None),
+            Err(RegistryError::AlreadyPublished(
+                "json".to_owned(),
+                Version::new(0, 1, 0)
+            ))
+        );
+
+        let version = Version::new(0, 1, 0);
+        assert_eq!(registry.versions("json"), Ok(vec![version.clone()]));
+        let published = registry.manifest("json", &version).unwrap();
+        assert_eq!(published.lib, manifest.lib);
+        assert_eq!(published.description, manifest.description);
+        assert_eq!(
+            registry.search("parser"),
+            Ok(vec![SearchResult {
+                name: "json".to_owned(),
+                version: version.clone(),
+                description: Some("JSON Parser".to_owned()),
+            }])
+        );
+        assert_eq!(registry.search("xml"), Ok(vec![]));
+
+        let cache = root.join("cache");
+        let directory = fetch(&registry, &cache, "json", &version).unwrap();
+        assert_eq!(
+            fs::read_to_string(directory.join("lib/lib.ry")).unwrap(),
+            "pub fun parse() {}\n"
+        );
+
+        // Corrupted archive is rejected.
+        fs::remove_dir_all(&cache).unwrap();
+        fs::write(
+            root.join("registry/json/0.1.0").join(ARCHIVE_FILE),
+            b"corrupted",
+        )
+        .unwrap();
+        assert_eq!(
+            fetch(&registry, &cache, "json", &version),
+            Err(RegistryError::ChecksumMismatch("json".to_owned(), version))
+        );
+    }
+}
@@ -0,0 +1,547 @@
+//! `resolver.rs` - picks versions of dependencies of the project.
+//!
+//! Every package is used in a single version, which must satisfy
+//! requirements of all packages depending on it. Path dependencies (and
+//! their own path dependencies) are used as is, versions of registry
+//! packages are searched with backtracking: the newest matching version is
+//! tried first, unless the lockfile prefers another one.
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use ry_manifest::{Manifest, MANIFEST_FILE};
+use semver::{Version, VersionReq};
+
+use crate::registry::Registry;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    /// Local package (the project itself or its path dependency) in
+    /// the directory.
+    Path(PathBuf),
+    Registry,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedPackage {
+    pub version: Version,
+    pub source: Source,
+    /// Names of the dependencies.
+    pub dependencies: Vec<String>,
+}
+
+/// Packages of the project by name, including the project itself (or
+/// all members of the workspace).
+pub type Resolution = BTreeMap<String, ResolvedPackage>;
+
+#[derive(Debug, PartialEq)]
+pub enum ResolveError {
+    /// No version of the package [`String`] satisfies all requirements.
+    /// [`Vec<(String, VersionReq)>`] are the requirements with packages,
+    /// which declare them, [`Vec<Version>`] are available versions.
+    Conflict(String, Vec<(String, VersionReq)>, Vec<Version>),
+
+    /// Package (1-st [`String`]) required by another one (2-nd [`String`])
+    /// is not published in the registry.
+    PackageNotFound(String, String),
+
+    /// Manifest of the package (1-st [`String`]) can't be used. 2-nd
+    /// [`String`] is the reason.
+    InvalidPackage(String, String),
+
+    /// Registry package (1-st [`String`]) depends on the local package
+    /// (2-nd [`String`]).
+    PathDependencyInRegistry(String, String),
+
+    /// Registry can't be accessed, [`String`] is the reason.
+    Registry(String),
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Conflict(package, requirements, available) => {
+                write!(f, "failed to select a version of `{package}`:")?;
+
+                for (required_by, requirement) in requirements {
+                    write!(f, "\n  {required_by} requires `{package} {requirement}`")?;
+                }
+
+                if available.is_empty() {
+                    write!(f, "\nno versions of `{package}` are published")
+                } else {
+                    let available = available
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>();
+
+                    write!(
+                        f,
+                        "\nno version satisfies all of them, available versions: {}",
+                        available.join(", ")
+                    )
+                }
+            }
+            Self::PackageNotFound(package, required_by) => write!(
+                f,
+                "no package named `{package}` found in registry (required by {required_by})"
+            ),
+            Self::InvalidPackage(package, reason) => {
+                write!(f, "cannot use package `{package}`: {reason}")
+            }
+            Self::PathDependencyInRegistry(package, dependency) => write!(
+                f,
+                "registry package {package} depends on local package `{dependency}`"
+            ),
+            Self::Registry(reason) => f.write_str(reason),
+        }
+    }
+}
+
+/// Requirement of a package for the version of its dependency.
+struct Requirement {
+    package: String,
+    requirement: VersionReq,
+    /// Name and version of the dependent package.
+    required_by: String,
+}
+
+struct Resolver<'r> {
+    registry: &'r dyn Registry,
+    /// Versions, which are tried first (from the lockfile).
+    preferred: &'r HashMap<String, Version>,
+    /// First conflict found, it is reported if there is no solution.
+    conflict: Option<ResolveError>,
+    versions: HashMap<String, Vec<Version>>,
+    manifests: HashMap<(String, Version), Manifest>,
+}
+
+/// Resolves dependencies of the workspace `members` (directories with
+/// manifests) together. `preferred` versions of registry packages are
+/// used, if they satisfy requirements.
+pub fn resolve(
+    members: &[(&Path, &Manifest)],
+    registry: &dyn Registry,
+    preferred: &HashMap<String, Version>,
+) -> Result<Resolution, ResolveError> {
+    let mut resolution = Resolution::new();
+    let mut requirements = vec![];
+
+    for (directory, manifest) in members {
+        // Member may be already added as a path dependency of another one.
+        if !resolution.contains_key(&manifest.name) {
+            add_local(directory, manifest, &mut resolution, &mut requirements)?;
+        }
+    }
+
+    let mut resolver = Resolver {
+        registry,
+        preferred,
+        conflict: None,
+        versions: HashMap::new(),
+        manifests: HashMap::new(),
+    };
+
+    if resolver.search(&mut resolution, &mut requirements)? {
+        Ok(resolution)
+    } else {
+        Err(resolver
+            .conflict
+            .expect("conflict is recorded, when there is no solution"))
+    }
+}
+
+fn describe(name: &str, version: &Version) -> String {
+    format!("{name} v{version}")
+}
+
+/// Adds the local package with its path dependencies (recursively) and
+/// collects their requirements of registry packages.
+fn add_local(
+    directory: &Path,
+    manifest: &Manifest,
+    resolution: &mut Resolution,
+    requirements: &mut Vec<Requirement>,
+) -> Result<(), ResolveError> {
+    resolution.insert(
+        manifest.name.clone(),
+        ResolvedPackage {
+            version: manifest.version.clone(),
+            source: Source::Path(directory.to_owned()),
+            dependencies: manifest.dependencies.keys().cloned().collect(),
+        },
+    );
+
+    for (name, dependency) in &manifest.dependencies {
+        match (&dependency.path, &dependency.version) {
+            (Some(path), _) => {
+                if resolution.contains_key(name) {
+                    continue;
+                }
+
+                let directory = directory.join(path);
+                let manifest_path = directory.join(MANIFEST_FILE);
+
+                let manifest = fs::read_to_string(&manifest_path)
+                    .map_err(|e| format!("cannot read `{}`: {e}", manifest_path.display()))
+                    .and_then(|source| {
+                        Manifest::load(&directory, &source).map_err(|_| {
+                            format!("`{}` is not a valid manifest", manifest_path.display())
+                        })
+                    })
+                    .map_err(|reason| ResolveError::InvalidPackage(name.clone(), reason))?;
+
+                add_local(&directory, &manifest, resolution, requirements)?;
+            }
+            (None, Some(requirement)) => requirements.push(Requirement {
+                package: name.clone(),
+                requirement: requirement.clone(),
+                required_by: describe(&manifest.name, &manifest.version),
+            }),
+            (None, None) => unreachable!("manifest validation rejects empty dependencies"),
+        }
+    }
+
+    Ok(())
+}
+
+impl Resolver<'_> {
+    fn versions(&mut self, name: &str) -> Result<&[Version], ResolveError> {
+        if !self.versions.contains_key(name) {
+            let versions = self
+                .registry
+                .versions(name)
+                .map_err(|e| ResolveError::Registry(e.to_string()))?;
+
+            self.versions.insert(name.to_owned(), versions);
+        }
+
+        Ok(&self.versions[name])
+    }
+
+    fn manifest(&mut self, name: &str, version: &Version) -> Result<&Manifest, ResolveError> {
+        let key = (name.to_owned(), version.clone());
+
+        if !self.manifests.contains_key(&key) {
+            let manifest = self.registry.manifest(name, version).map_err(|e| {
+                ResolveError::InvalidPackage(describe(name, version), e.to_string())
+            })?;
+
+            self.manifests.insert(key.clone(), manifest);
+        }
+
+        Ok(&self.manifests[&key])
+    }
+
+    fn record_conflict(&mut self, package: &str, requirements: &[Requirement]) {
+        if self.conflict.is_some() {
+            return;
+        }
+
+        let requirements = requirements
+            .iter()
+            .filter(|requirement| requirement.package == package)
+            .map(|requirement| {
+                (
+                    requirement.required_by.clone(),
+                    requirement.requirement.clone(),
+                )
+            })
+            .collect();
+        let available = self.versions.get(package).cloned().unwrap_or_default();
+
+        self.conflict = Some(ResolveError::Conflict(
+            package.to_owned(),
+            requirements,
+            available,
+        ));
+    }
+
+    /// Picks versions of packages, which are required, but not resolved
+    /// yet. Returns `false` if there is no solution.
+    fn search(
+        &mut self,
+        resolution: &mut Resolution,
+        requirements: &mut Vec<Requirement>,
+    ) -> Result<bool, ResolveError> {
+        for requirement in requirements.iter() {
+            if let Some(package) = resolution.get(&requirement.package) {
+                if !requirement.requirement.matches(&package.version) {
+                    let package = requirement.package.clone();
+                    self.record_conflict(&package, requirements);
+                    return Ok(false);
+                }
+            }
+        }
+
+        let Some(next) = requirements
+            .iter()
+            .find(|requirement| !resolution.contains_key(&requirement.package))
+        else {
+            return Ok(true);
+        };
+
+        let name = next.package.clone();
+
+        if self.versions(&name)?.is_empty() {
+            return Err(ResolveError::PackageNotFound(
+                name,
+                next.required_by.clone(),
+            ));
+        }
+
+        let mut candidates = self
+            .versions(&name)?
+            .iter()
+            .rev()
+            .filter(|version| {
+                requirements
+                    .iter()
+                    .filter(|requirement| requirement.package == name)
+                    .all(|requirement| requirement.requirement.matches(version))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if let Some(preferred) = self.preferred.get(&name) {
+            if let Some(position) = candidates.iter().position(|version| version == preferred) {
+                let preferred = candidates.remove(position);
+                candidates.insert(0, preferred);
+            }
+        }
+
+        if candidates.is_empty() {
+            self.record_conflict(&name, requirements);
+            return Ok(false);
+        }
+
+        for version in candidates {
+            let manifest = self.manifest(&name, &version)?;
+
+            if let Some((dependency, _)) = manifest
+                .dependencies
+                .iter()
+                .find(|(_, dependency)| dependency.path.is_some())
+            {
+                return Err(ResolveError::PathDependencyInRegistry(
+                    describe(&name, &version),
+                    dependency.clone(),
+                ));
+            }
+
+            let dependencies = manifest
+                .dependencies
+                .iter()
+                .map(|(dependency, requirement)| Requirement {
+                    package: dependency.clone(),
+                    requirement: requirement
+                        .version
+                        .clone()
+                        .expect("registry dependencies have versions"),
+                    required_by: describe(&name, &version),
+                })
+                .collect::<Vec<_>>();
+
+            resolution.insert(
+                name.clone(),
+                ResolvedPackage {
+                    version: version.clone(),
+                    source: Source::Registry,
+                    dependencies: dependencies
+                        .iter()
+                        .map(|requirement| requirement.package.clone())
+                        .collect(),
+                },
+            );
+
+            let len = requirements.len();
+            requirements.extend(dependencies);
+
+            if self.search(resolution, requirements)? {
+                return Ok(true);
+            }
+
+            requirements.truncate(len);
+            resolution.remove(&name);
+        }
+
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::registry::DirectoryRegistry;
+    use crate::test_utils::temp_directory;
+
+    /// Name, version and dependencies with requirements.
+    type Package<'a> = (&'a str, &'a str, &'a [(&'a str, &'a str)]);
+
+    /// Creates the registry with the given packages in a temporary
+    /// directory, which is returned too.
+    fn registry(packages: &[Package<'_>]) -> (TempDir, DirectoryRegistry) {
+        let temp = temp_directory(&[]);
+        let root = temp.path();
+
+        for (package, version, dependencies) in packages {
+            let mut manifest = Manifest::new(package);
+            manifest.version = Version::parse(version).unwrap();
+            manifest.bins.clear();
+
+            for (dependency, requirement) in *dependencies {
+                manifest.dependencies.insert(
+                    dependency.to_string(),
+                    ry_manifest::Dependency {
+                        version: Some(VersionReq::parse(requirement).unwrap()),
+                        path: None,
+                    },
+                );
+            }
+
+            let directory = root.join(package).join(version);
+            fs::create_dir_all(&directory).unwrap();
+            fs::write(directory.join(MANIFEST_FILE), manifest.to_json()).unwrap();
+        }
+
+        let registry = DirectoryRegistry::new(root);
+
+        (temp, registry)
+    }
+
+    fn project(dependencies: &[(&str, &str)]) -> Manifest {
+        let mut manifest = Manifest::new("app");
+
+        for (dependency, requirement) in dependencies {
+            manifest.dependencies.insert(
+                dependency.to_string(),
+                ry_manifest::Dependency {
+                    version: Some(VersionReq::parse(requirement).unwrap()),
+                    path: None,
+                },
+            );
+        }
+
+        manifest
+    }
+
+    fn versions(resolution: &Resolution) -> Vec<(&str, String)> {
+        resolution
+            .iter()
+            .map(|(name, package)| (name.as_str(), package.version.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn resolve_test() {
+        let (_temp, registry) = registry(&[
+            ("json", "1.0.0", &[]),
+            ("json", "1.2.0", &[]),
+            ("json", "2.0.0", &[]),
+            ("http", "0.1.0", &[("json", "^1.0")]),
+            // The newest `http` is incompatible with `json ^1.1`.
+            ("http", "0.2.0", &[("json", "^2.0")]),
+            ("log", "0.3.0", &[]),
+        ]);
+        let directory = Path::new("app");
+        let manifest = project(&[("http", "*"), ("json", "^1.1")]);
+
+        let resolution = resolve(&[(directory, &manifest)], &registry, &HashMap::new()).unwrap();
+        assert_eq!(
+            versions(&resolution),
+            [
+                ("app", "0.1.0".to_owned()),
+                ("http", "0.1.0".to_owned()),
+                ("json", "1.2.0".to_owned())
+            ]
+        );
+        assert_eq!(resolution["http"].dependencies, ["json"]);
+        assert_eq!(resolution["app"].source, Source::Path(directory.to_owned()));
+
+        // Locked version is kept, although there is a newer one.
+        let manifest = project(&[("json", "^1.0")]);
+        let preferred = HashMap::from([("json".to_owned(), Version::new(1, 0, 0))]);
+        let resolution = resolve(&[(directory, &manifest)], &registry, &preferred).unwrap();
+        assert_eq!(resolution["json"].version, Version::new(1, 0, 0));
+
+        // Locked version, which doesn't match the requirement, is ignored.
+        let manifest = project(&[("json", "^2.0")]);
+        let resolution = resolve(&[(directory, &manifest)], &registry, &preferred).unwrap();
+        assert_eq!(resolution["json"].version, Version::new(2, 0, 0));
+
+        // Workspace members share versions of their dependencies.
+        let mut cli = project(&[("http", "*")]);
+        cli.name = "cli".to_owned();
+        let manifest = project(&[("json", "^1.0")]);
+        let resolution = resolve(
+            &[(directory, &manifest), (Path::new("cli"), &cli)],
+            &registry,
+            &HashMap::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            versions(&resolution),
+            [
+                ("app", "0.1.0".to_owned()),
+                ("cli", "0.1.0".to_owned()),
+                ("http", "0.1.0".to_owned()),
+                ("json", "1.2.0".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_test() {
+        let (_temp, registry) = registry(&[
+            ("json", "1.0.0", &[]),
+            ("json", "2.0.0", &[]),
+            ("http", "0.1.0", &[("json", "^2.0"), ("tls", "^1.0")]),
+        ]);
+        let directory = Path::new("app");
+        let resolve = |dependencies: &[(&str, &str)]| {
+            resolve(
+                &[(directory, &project(dependencies))],
+                &registry,
+                &HashMap::new(),
+            )
+            .unwrap_err()
+        };
+
+        let error = resolve(&[("json", "^1.0"), ("http", "0.1")]);
+        assert_eq!(
+            error,
+            ResolveError::Conflict(
+                "json".to_owned(),
+                vec![
+                    ("app v0.1.0".to_owned(), VersionReq::parse("^1.0").unwrap()),
+                    ("http v0.1.0".to_owned(), VersionReq::parse("^2.0").unwrap()),
+                ],
+                vec![Version::new(1, 0, 0), Version::new(2, 0, 0)]
+            )
+        );
+        assert_eq!(
+            error.to_string(),
+            "failed to select a version of `json`:
+  app v0.1.0 requires `json ^1.0`
+  http v0.1.0 requires `json ^2.0`
+no version satisfies all of them, available versions: 1.0.0, 2.0.0"
+        );
+
+        assert_eq!(
+            resolve(&[("json", ">=3")]),
+            ResolveError::Conflict(
+                "json".to_owned(),
+                vec![("app v0.1.0".to_owned(), VersionReq::parse(">=3").unwrap())],
+                vec![Version::new(1, 0, 0), Version::new(2, 0, 0)]
+            )
+        );
+        assert_eq!(
+            resolve(&[("http", "*")]),
+            ResolveError::PackageNotFound("tls".to_owned(), "http v0.1.0".to_owned())
+        );
+    }
+}
@@ -0,0 +1,422 @@
+//! `test_runner.rs` - discovers and runs tests of the current project.
+//!
+//! Tests are functions marked with `@test` attribute in `test/*.ry` and
+//! in files of the library. Every test runs in a fresh interpreter (with
+//! globals initialized again), its output is captured and shown only if
+//! it fails. Test fails, if it panics, for example, because of failed
+//! `assert` or `assert_eq`.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use codespan_reporting::files::{Files, SimpleFiles};
+use ry_ast::TopLevelStatement;
+use ry_driver::{check, emit_errors, load_program, modules, SourceFile};
+use ry_interpreter::{with_program_stack, Interpreter};
+use ry_manifest::{Manifest, ModuleRoots};
+use ry_report::{Reporter, ReporterState};
+use serde::Serialize;
+
+use crate::{error::Failure, manifest::Member};
+
+/// Directory with tests, relative to the project root.
+pub const TEST_DIRECTORY: &str = "test";
+
+/// Attribute, that marks test functions.
+const TEST_ATTRIBUTE: &str = "test";
+
+/// Test function found in the program.
+#[derive(Debug, Clone, PartialEq)]
+struct Test {
+    /// Path of the file (relative to the project root, without extension)
+    /// and the function name: `test/math::adds`.
+    name: String,
+    function: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Passed,
+    Failed,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct TestResult {
+    /// Workspace member, that the test belongs to.
+    package: String,
+    name: String,
+    status: Status,
+    /// Error message with location, if the test failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    /// Captured output.
+    output: String,
+}
+
+#[derive(Serialize)]
+struct Report<'r> {
+    passed: usize,
+    failed: usize,
+    filtered_out: usize,
+    tests: &'r [TestResult],
+}
+
+/// Name of the file relative to the project root without extension, with
+/// `/` as a separator.
+fn module_name(root: &Path, file: &str) -> String {
+    let path = Path::new(file);
+    let path = path.strip_prefix(root).unwrap_or(path).with_extension("");
+
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Finds test functions in the files of the program, which are inside
+/// `directory`. Returns tests and names of invalid test functions (with
+/// parameters).
+fn discover(program: &[SourceFile], root: &Path, directory: &Path) -> (Vec<Test>, Vec<String>) {
+    let mut tests = vec![];
+    let mut invalid = vec![];
+
+    for file in program {
+        if !Path::new(&file.name).starts_with(directory) {
+            continue;
+        }
+
+        let Ok(ast) = &file.ast else {
+            continue;
+        };
+
+        for (_, statement) in &ast.top_level_statements {
+            let TopLevelStatement::FunctionDecl(function) = statement else {
+                continue;
+            };
+
+            let is_test = function
+                .def
+                .attributes
+                .iter()
+                .any(|attribute| attribute.value == TEST_ATTRIBUTE);
+
+            if !is_test {
+                continue;
+            }
+
+            let name = format!(
+                "{}::{}",
+                module_name(root, &file.name),
+                function.def.name.value
+            );
+
+            if function.def.params.is_empty() {
+                tests.push(Test {
+                    name,
+                    function: function.def.name.value.clone(),
+                });
+            } else {
+                invalid.push(name);
+            }
+        }
+    }
+
+    (tests, invalid)
+}
+
+/// Runs every test in a fresh interpreter.
+fn run_tests(package: &str, program: &[SourceFile], tests: &[Test]) -> Vec<TestResult> {
+    let modules = modules(program).expect("tests are run only for parsed programs");
+
+    let mut files = SimpleFiles::new();
+    let file_ids = program
+        .iter()
+        .map(|file| files.add(file.name.as_str(), file.contents.as_str()))
+        .collect::<Vec<_>>();
+
+    tests
+        .iter()
+        .map(|test| {
+            let mut output = vec![];
+            let mut interpreter = Interpreter::new(&modules, &mut output);
+
+            let result = interpreter
+                .initialize()
+                .and_then(|_| interpreter.call(&test.function, vec![]));
+
+            let message = result.err().map(|(module, error)| {
+                let diagnostic = error.build_diagnostic(file_ids[module]);
+
+                let location = diagnostic.labels.first().and_then(|label| {
+                    let location = files.location(label.file_id, label.range.start).ok()?;
+
+                    Some(format!(
+                        "{}:{}:{}",
+                        program[module].name, location.line_number, location.column_number
+                    ))
+                });
+
+                match location {
+                    Some(location) => format!("{location}: {}", diagnostic.message),
+                    None => diagnostic.message,
+                }
+            });
+
+            TestResult {
+                package: package.to_owned(),
+                name: test.name.clone(),
+                status: if message.is_none() {
+                    Status::Passed
+                } else {
+                    Status::Failed
+                },
+                message,
+                output: String::from_utf8_lossy(&output).into_owned(),
+            }
+        })
+        .collect()
+}
+
+/// Entry points of test programs: the library (tests are searched in all
+/// its files) and every file in `test/` (tests are searched only in
+/// the file itself), with directories containing tests.
+fn test_programs(root: &Path, manifest: &Manifest) -> Vec<(PathBuf, PathBuf)> {
+    let mut programs = vec![];
+
+    if let Some(lib) = &manifest.lib {
+        let entry = root.join(lib);
+        let directory = entry.parent().unwrap_or(root).to_owned();
+        programs.push((entry, directory));
+    }
+
+    let mut files = fs::read_dir(root.join(TEST_DIRECTORY))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == "ry"))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    files.sort();
+
+    programs.extend(files.into_iter().map(|file| (file.clone(), file)));
+    programs
+}
+
+/// Prints failures and the summary of the member's tests.
+fn print_summary(results: &[TestResult], filtered_out: usize) {
+    let failed = results
+        .iter()
+        .filter(|result| result.status == Status::Failed)
+        .collect::<Vec<_>>();
+
+    if !failed.is_empty() {
+        println!("\nfailures:");
+
+        for result in &failed {
+            println!("\n---- {} ----", result.name);
+            print!("{}", result.output);
+
+            if let Some(message) = &result.message {
+                println!("{message}");
+            }
+        }
+    }
+
+    println!(
+        "\ntest result: {}. {} passed; {} failed; {filtered_out} filtered out\n",
+        if failed.is_empty() { "ok" } else { "FAILED" },
+        results.len() - failed.len(),
+        failed.len()
+    );
+}
+
+/// `lapis test`: runs tests of the workspace members, which names contain
+/// `filter`. Prints results as JSON, if `json` is set. Exits with an error,
+/// if some test failed.
+pub fn test_project(
+    members: &[&Member],
+    roots: &ModuleRoots,
+    filter: Option<&str>,
+    json: bool,
+    reporter: &ReporterState,
+) -> Result<(), Failure> {
+    let mut packages = vec![];
+    let mut has_errors = false;
+
+    for member in members {
+        let mut programs = vec![];
+
+        for (entry, directory) in test_programs(&member.root, &member.manifest) {
+            let Ok(mut program) = load_program(&entry, roots) else {
+                reporter.emit_global_error(&format!("cannot read `{}`.", entry.display()));
+                has_errors = true;
+                continue;
+            };
+
+            let errors = check(&mut program);
+            has_errors |= emit_errors(&program, &errors, reporter);
+
+            let (tests, invalid) = discover(&program, &member.root, &directory);
+
+            for name in invalid {
+                reporter.emit_global_error(&format!("test `{name}` must not have parameters."));
+                has_errors = true;
+            }
+
+            programs.push((program, tests));
+        }
+
+        packages.push((member, programs));
+    }
+
+    if has_errors {
+        return Err(Failure::Error(
+            "could not compile tests due to the previous errors.".to_owned(),
+        ));
+    }
+
+    let mut filtered_out = vec![];
+
+    for (_, programs) in &mut packages {
+        let mut count = 0;
+
+        for (_, tests) in programs {
+            let total = tests.len();
+            tests.retain(|test| filter.is_none_or(|filter| test.name.contains(filter)));
+            count += total - tests.len();
+        }
+
+        filtered_out.push(count);
+    }
+
+    let results = with_program_stack(|| {
+        let mut results = vec![];
+
+        for ((member, programs), filtered_out) in packages.iter().zip(&filtered_out) {
+            let total = programs.iter().map(|(_, tests)| tests.len()).sum::<usize>();
+            let start = results.len();
+
+            if !json {
+                eprintln!(
+                    "{:>12} {} v{}",
+                    "Testing", member.manifest.name, member.manifest.version
+                );
+                println!(
+                    "\nrunning {total} test{}",
+                    if total == 1 { "" } else { "s" }
+                );
+            }
+
+            for (program, tests) in programs {
+                for result in run_tests(&member.manifest.name, program, tests) {
+                    if !json {
+                        let status = match result.status {
+                            Status::Passed => "ok",
+                            Status::Failed => "FAILED",
+                        };
+
+                        println!("test {} ... {status}", result.name);
+                    }
+
+                    results.push(result);
+                }
+            }
+
+            if !json {
+                print_summary(&results[start..], *filtered_out);
+            }
+        }
+
+        results
+    });
+
+    let failed = results
+        .iter()
+        .filter(|result| result.status == Status::Failed)
+        .count();
+
+    if json {
+        let report = Report {
+            passed: results.len() - failed,
+            failed,
+            filtered_out: filtered_out.iter().sum(),
+            tests: &results,
+        };
+
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("report is always serializable")
+        );
+    }
+
+    if failed > 0 {
+        return Err(Failure::Reported);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_directory;
+
+    #[test]
+    fn runner_test() {
+        let temp = temp_directory(&[
+            (
+                "lib/lib.ry",
+                "import \"util\";\n\
+                 var calls = 0;\n\
+                 pub fun double(x i32) i32 { calls += 1; x * 2 }\n\
+                 @test fun doubles() { assert_eq(double(2), 4); assert_eq(calls, 1); }\n",
+            ),
+            (
+                "lib/util.ry",
+                "@test fun fails() { println(\"output\"); assert(false, \"%d\", 1); }\n\
+                 @test fun invalid(a i32) {}\n",
+            ),
+        ]);
+        let root = temp.path();
+
+        let mut manifest = Manifest::new("demo");
+        manifest.lib = Some("lib/lib.ry".into());
+
+        let programs = test_programs(root, &manifest);
+        assert_eq!(programs, [(root.join("lib/lib.ry"), root.join("lib"))]);
+
+        let roots = ModuleRoots::new(root, &manifest);
+        let mut program = load_program(&programs[0].0, &roots).unwrap();
+        assert!(check(&mut program).is_empty());
+
+        let (tests, invalid) = discover(&program, root, &programs[0].1);
+        assert_eq!(invalid, ["lib/util::invalid"]);
+
+        // Globals are initialized again for every test.
+        let tests = [&tests[..], &tests[..1]].concat();
+        let results = run_tests("demo", &program, &tests);
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| (result.name.as_str(), &result.status))
+                .collect::<Vec<_>>(),
+            [
+                ("lib/lib::doubles", &Status::Passed),
+                ("lib/util::fails", &Status::Failed),
+                ("lib/lib::doubles", &Status::Passed),
+            ]
+        );
+        assert_eq!(results[1].output, "output\n");
+        assert_eq!(
+            results[1].message.as_deref(),
+            Some(&*format!(
+                "{}:1:40: program panicked: 1",
+                root.join("lib/util.ry").display()
+            ))
+        );
+    }
+}
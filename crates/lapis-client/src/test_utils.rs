@@ -0,0 +1,18 @@
+//! `test_utils.rs` - helpers shared by tests of the package manager.
+use std::fs;
+
+use tempfile::TempDir;
+
+/// Creates a temporary directory with the given files (paths are relative
+/// to the directory). It is removed, when the result is dropped.
+pub(crate) fn temp_directory(files: &[(&str, &str)]) -> TempDir {
+    let directory = tempfile::tempdir().unwrap();
+
+    for (path, contents) in files {
+        let path = directory.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    directory
+}
@@ -0,0 +1,226 @@
+//! `graph.rs` - builds call graphs and import graphs of Ry programs.
+//!
+//! Unlike [`GraphvizTranslatorState`], that shows the whole AST of a single
+//! file, these graphs are meant to give an overview of larger codebases.
+//! Both can be emitted in DOT format and as JSON adjacency lists.
+//!
+//! [`GraphvizTranslatorState`]: crate::GraphvizTranslatorState
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    path::Path,
+};
+
+use ry_ast::{visit::*, *};
+use serde::Serialize;
+
+use crate::escape;
+
+/// Directed graph, serialized as adjacency lists: `{"node": ["neighbour"]}`.
+#[derive(Debug, Default, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Graph {
+    pub adjacency: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Graph {
+    pub fn add_node(&mut self, node: &str) {
+        self.adjacency.entry(node.to_owned()).or_default();
+    }
+
+    pub fn add_edge(&mut self, from: &str, to: &str) {
+        self.add_node(to);
+        self.adjacency
+            .entry(from.to_owned())
+            .or_default()
+            .insert(to.to_owned());
+    }
+
+    /// Writes graph in DOT format into the `sink`.
+    pub fn to_dot(&self, name: &str, sink: &mut impl io::Write) -> io::Result<()> {
+        writeln!(sink, "digraph {name} {{")?;
+        writeln!(sink, "\tnode [shape=box, fontname=\"monospace\"];")?;
+
+        for (node, neighbours) in &self.adjacency {
+            writeln!(sink, "\t\"{}\";", escape(node))?;
+
+            for neighbour in neighbours {
+                writeln!(sink, "\t\"{}\" -> \"{}\";", escape(node), escape(neighbour))?;
+            }
+        }
+
+        writeln!(sink, "}}")
+    }
+}
+
+/// Builds graph of calls between functions of the program. Methods are
+/// named `Type::method` and default methods of traits `Trait::method`
+/// (methods without bodies are skipped). Only calls of static names are
+/// taken into account, because resolving method calls requires type
+/// information.
+pub fn call_graph(ast: &ProgramUnit) -> Graph {
+    let mut builder = CallGraphBuilder::default();
+    builder.visit_program_unit(ast);
+    builder.graph
+}
+
+#[derive(Default)]
+struct CallGraphBuilder {
+    graph: Graph,
+    /// Name of the function, which body is being visited.
+    current_function: Option<String>,
+    /// Name of the type, which implementation is being visited, or of
+    /// the trait, which declaration is being visited.
+    current_type: Option<String>,
+}
+
+impl Visitor for CallGraphBuilder {
+    fn visit_function_decl(&mut self, function_decl: &FunctionDecl) {
+        self.graph.add_node(&function_decl.def.name.value);
+        self.current_function = Some(function_decl.def.name.value.clone());
+
+        walk_function_decl(self, function_decl);
+
+        self.current_function = None;
+    }
+
+    fn visit_trait_decl(&mut self, trait_decl: &TraitDecl) {
+        self.current_type = Some(trait_decl.name.value.clone());
+
+        walk_trait_decl(self, trait_decl);
+
+        self.current_type = None;
+    }
+
+    fn visit_impl(&mut self, r#impl: &Impl) {
+        if let RawType::Primary(name, _) = &*r#impl.r#type.value {
+            self.current_type = Some(name.value.clone());
+        }
+
+        walk_impl(self, r#impl);
+
+        self.current_type = None;
+    }
+
+    fn visit_trait_method(&mut self, method: &TraitMethod) {
+        if method.body.is_none() {
+            return;
+        }
+
+        let name = match &self.current_type {
+            Some(r#type) => format!("{type}::{}", method.name.value),
+            None => method.name.value.clone(),
+        };
+
+        self.graph.add_node(&name);
+        self.current_function = Some(name);
+
+        walk_trait_method(self, method);
+
+        self.current_function = None;
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        if let RawExpression::Call(_, caller, _) = &*expression.value {
+            if let (Some(function), RawExpression::StaticName(callee)) =
+                (&self.current_function, &*caller.value)
+            {
+                self.graph.add_edge(function, callee);
+            }
+        }
+
+        walk_expression(self, expression);
+    }
+}
+
+/// Builds graph of imports between files, starting from the `entry` file.
+///
+/// `load` parses the file with given path. Files, that cannot be loaded
+/// (`load` returned `None`), are still added into the graph, but their
+/// imports are not followed.
+pub fn import_graph<F>(entry: &Path, mut load: F) -> Graph
+where
+    F: FnMut(&Path) -> Option<ProgramUnit>,
+{
+    let mut graph = Graph::default();
+    let mut visited = BTreeSet::new();
+    let mut queue = vec![normalize_path(entry)];
+
+    while let Some(path) = queue.pop() {
+        let node = path.display().to_string();
+
+        if !visited.insert(node.clone()) {
+            continue;
+        }
+
+        graph.add_node(&node);
+
+        let Some(ast) = load(&path) else {
+            continue;
+        };
+
+        let directory = path.parent().unwrap_or(Path::new(""));
+
+        for import in &ast.imports {
+            let imported = import.resolve(directory);
+
+            graph.add_edge(&node, &imported.display().to_string());
+            queue.push(imported);
+        }
+    }
+
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::Path};
+
+    use crate::graph::{call_graph, import_graph};
+    use ry_parser::Parser;
+
+    #[test]
+    fn call_graph_test() {
+        let source = "fun a() { b(); c(b()); }\nfun b() {}\nimpl S { fun d() { a(); } }\n\
+                      trait T { fun e(); fun f() { b(); } }";
+        let graph = call_graph(&Parser::new(source).parse().unwrap());
+
+        assert_eq!(
+            serde_json::to_string(&graph).unwrap(),
+            r#"{"S::d":["a"],"T::f":["b"],"a":["b","c"],"b":[],"c":[]}"#
+        );
+    }
+
+    #[test]
+    fn import_graph_test() {
+        let files = HashMap::from([
+            ("src/main.ry", "import \"lib\";\nimport \"./util/io.ry\";"),
+            ("src/lib.ry", "import \"util/io\";"),
+            ("src/util/io.ry", "import \"../lib\";\nimport \"missing\";"),
+        ]);
+
+        let graph = import_graph(Path::new("src/main.ry"), |path| {
+            files
+                .get(path.to_str().unwrap())
+                .map(|source| Parser::new(source).parse().unwrap())
+        });
+
+        assert_eq!(
+            serde_json::to_string(&graph).unwrap(),
+            r#"{"src/lib.ry":["src/util/io.ry"],"src/main.ry":["src/lib.ry","src/util/io.ry"],"src/util/io.ry":["src/lib.ry","src/util/missing.ry"],"src/util/missing.ry":[]}"#
+        );
+    }
+
+    #[test]
+    fn dot_test() {
+        let graph = call_graph(&Parser::new("fun a() { b(); }").parse().unwrap());
+        let mut output = vec![];
+
+        graph.to_dot("calls", &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "digraph calls {\n\tnode [shape=box, fontname=\"monospace\"];\n\t\"a\";\n\t\"a\" -> \"b\";\n\t\"b\";\n}\n"
+        );
+    }
+}
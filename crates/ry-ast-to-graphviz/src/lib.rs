@@ -1,8 +1,64 @@
-use ry_ast::{location::WithSpan, *};
-use std::ops::Deref;
+//! `lib.rs` - translates AST into graph in graphviz DOT format.
+//!
+//! Every import and top level declaration is placed into its own cluster
+//! and node ids are local to the cluster, so change in one declaration
+//! doesn't affect ids of nodes in the other ones. Nodes are styled by their
+//! category (declarations, statements, expressions, types) and roles of
+//! children (`condition`, `then`, `else`, ...) are shown as edge labels.
+pub mod graph;
+
+use std::{collections::HashMap, fmt::Write as _, io};
+
+use ry_ast::{
+    location::{LineIndex, Span, WithSpan},
+    visit::*,
+    *,
+};
 
 pub struct GraphvizTranslatorState {
+    output: String,
+    /// Prefix of ids of nodes in the current cluster.
+    cluster_prefix: String,
     current_node_index: u32,
+    /// Stack of nodes, that newly created nodes are connected to.
+    parents: Vec<u32>,
+    /// Label of the edge to the next created node.
+    role: Option<&'static str>,
+    span_labels: Option<SpanLabels>,
+}
+
+/// How spans of nodes are shown in their labels.
+enum SpanLabels {
+    /// `@start..end` byte offsets.
+    Offsets,
+    /// `line:column..line:column` positions in the source text.
+    Lines(LineIndex, String),
+}
+
+/// Category of the node, that defines its style.
+#[derive(Clone, Copy)]
+enum NodeKind {
+    Declaration,
+    Statement,
+    Expression,
+    Type,
+    /// Node grouping children of the same kind, for example: `Params`.
+    Group,
+    /// Names, literal values and operators.
+    Leaf,
+}
+
+impl NodeKind {
+    fn attributes(self) -> &'static str {
+        match self {
+            NodeKind::Declaration => "shape=box, style=\"filled,bold\", fillcolor=lightblue",
+            NodeKind::Statement => "shape=box, style=\"filled,rounded\", fillcolor=lightyellow",
+            NodeKind::Expression => "shape=ellipse, style=filled, fillcolor=palegreen",
+            NodeKind::Type => "shape=hexagon, style=filled, fillcolor=plum",
+            NodeKind::Group => "shape=folder, style=filled, fillcolor=lightgray",
+            NodeKind::Leaf => "shape=plaintext",
+        }
+    }
 }
 
 impl Default for GraphvizTranslatorState {
@@ -14,660 +70,741 @@ impl Default for GraphvizTranslatorState {
 impl GraphvizTranslatorState {
     pub fn new() -> Self {
         Self {
+            output: String::new(),
+            cluster_prefix: String::new(),
             current_node_index: 0,
+            parents: vec![],
+            role: None,
+            span_labels: None,
         }
     }
 
-    pub fn ast_to_graphviz(&mut self, ast: &ProgramUnit) {
-        println!("digraph {{");
-        for import in &ast.imports {
-            self.create_import_node(&import);
-        }
-        for stmt in &ast.top_level_statements {
-            self.create_top_level_stmt_node(&stmt.1);
-        }
-        println!("}}");
+    /// Adds `@start..end` span labels to nodes.
+    pub fn with_span_labels(mut self) -> Self {
+        self.span_labels = Some(SpanLabels::Offsets);
+        self
     }
 
-    fn create_import_node(&mut self, import: &Import) -> u32 {
-        let import_node = self.add_node("Import");
-        let filename_node = self.add_node(&import.filename.value);
-        self.add_node_connections(&[import_node, filename_node]);
-        import_node
+    /// Adds `line:column..line:column` span labels to nodes, positions are
+    /// computed using the source text, that the AST was parsed from.
+    pub fn with_line_labels(mut self, source: &str) -> Self {
+        self.span_labels = Some(SpanLabels::Lines(LineIndex::new(source), source.to_owned()));
+        self
     }
 
-    fn create_top_level_stmt_node(&mut self, stmt: &TopLevelStatement) -> u32 {
-        match stmt {
-            TopLevelStatement::FunctionDecl(f) => {
-                let root = self.add_node("FunDecl");
-                let name_node_root = self.add_node("Name");
-                let name_node = self.add_node(&f.def.name.value);
-
-                self.add_node_connections(&[root, name_node_root, name_node]);
-
-                if f.def.public.is_some() {
-                    let public_node = self.add_node("Public");
-                    self.add_node_connections(&[root, public_node]);
-                }
-
-                if !f.def.generic_annotations.is_empty() {
-                    let generics_node =
-                        self.create_generic_annotations_node(&f.def.generic_annotations);
-                    self.add_node_connections(&[root, generics_node]);
-                }
+    /// Writes graph of the AST into the `sink`.
+    pub fn ast_to_graphviz(
+        &mut self,
+        ast: &ProgramUnit,
+        sink: &mut impl io::Write,
+    ) -> io::Result<()> {
+        self.output.clear();
+        self.output
+            .push_str("digraph {\n\tnode [fontname=\"monospace\"];\n");
+        self.visit_program_unit(ast);
+        self.output.push_str("}\n");
+
+        sink.write_all(self.output.as_bytes())
+    }
 
-                if !f.def.params.is_empty() {
-                    let params_node = self.create_params_node(&f.def.params);
-                    self.add_node_connections(&[root, params_node]);
-                }
+    /// Opens new cluster, nodes created inside of `f` belong to it.
+    fn with_cluster<F>(&mut self, id: &str, label: &str, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        self.cluster_prefix = id.to_owned();
+        self.current_node_index = 0;
+
+        writeln!(
+            self.output,
+            "\tsubgraph cluster_{id} {{\n\t\tlabel=\"{}\";",
+            escape(label)
+        )
+        .unwrap();
+        f(self);
+        self.output.push_str("\t}\n");
+    }
 
-                let statements_block_node = self.create_statements_block_node(&f.stmts);
-                self.add_node_connections(&[root, statements_block_node]);
+    fn node_id(&self, index: u32) -> String {
+        format!("{}_{index}", self.cluster_prefix)
+    }
 
-                if let Some(t) = &f.def.return_type {
-                    let return_type_node_root = self.add_node("ReturnType");
-                    let return_type_node = self.create_type_node(t.value.deref());
-                    self.add_node_connections(&[root, return_type_node_root, return_type_node]);
-                }
+    fn span_label(&self, span: &Span) -> String {
+        match &self.span_labels {
+            Some(SpanLabels::Offsets) => format!("\n@{}..{}", span.range.start, span.range.end),
+            Some(SpanLabels::Lines(index, source)) => {
+                let (start_line, start_column) = index.line_column(source, span.range.start);
+                let (end_line, end_column) = index.line_column(source, span.range.end);
 
-                root
+                format!("\n{start_line}:{start_column}..{end_line}:{end_column}")
             }
-            TopLevelStatement::StructDecl(sd) => {
-                let root = self.add_node("StructDecl");
-
-                let name_node_root = self.add_node("Name");
-                let name_node = self.add_node(&sd.name.value);
+            None => String::new(),
+        }
+    }
 
-                self.add_node_connections(&[root, name_node_root, name_node]);
+    fn add_node(&mut self, label: &str, kind: NodeKind, span: Option<&Span>) -> u32 {
+        self.current_node_index += 1;
 
-                if sd.public.is_some() {
-                    let public_node = self.add_node("Public");
-                    self.add_node_connections(&[root, public_node]);
-                }
+        let label = match span {
+            Some(span) => format!("{label}{}", self.span_label(span)),
+            None => label.to_owned(),
+        };
 
-                if !sd.generic_annotations.is_empty() {
-                    let generics_node =
-                        self.create_generic_annotations_node(&sd.generic_annotations);
-                    self.add_node_connections(&[root, generics_node]);
-                }
+        writeln!(
+            self.output,
+            "\t\t{} [label=\"{}\", {}];",
+            self.node_id(self.current_node_index),
+            escape(&label),
+            kind.attributes()
+        )
+        .unwrap();
 
-                if !sd.members.is_empty() {
-                    let members_node = self.add_node("Members");
+        self.current_node_index
+    }
 
-                    for member in &sd.members {
-                        let member = &member.1;
-                        let member_node = self.add_node("Member");
+    fn add_node_connection(&mut self, from: u32, to: u32, role: Option<&str>) {
+        let attributes = match role {
+            Some(role) => format!(" [label=\"{}\"]", escape(role)),
+            None => String::new(),
+        };
+
+        writeln!(
+            self.output,
+            "\t\t{} -> {}{attributes};",
+            self.node_id(from),
+            self.node_id(to)
+        )
+        .unwrap();
+    }
 
-                        self.add_node_connections(&[members_node, member_node]);
+    /// Adds node and connects current parent node to it.
+    fn add_child(&mut self, label: &str, kind: NodeKind, span: Option<&Span>) -> u32 {
+        let node = self.add_node(label, kind, span);
+        let role = self.role.take();
 
-                        if member.public.is_some() {
-                            let public_node = self.add_node("Public");
-                            self.add_node_connections(&[member_node, public_node]);
-                        }
+        if let Some(&parent) = self.parents.last() {
+            self.add_node_connection(parent, node, role);
+        }
 
-                        let name_node_root = self.add_node("Name");
-                        let name_node = self.add_node(&member.name.value);
+        node
+    }
 
-                        self.add_node_connections(&[member_node, name_node_root, name_node]);
+    /// Adds node and makes it parent for all nodes created inside of `f`.
+    fn with_child<F>(&mut self, label: &str, kind: NodeKind, span: Option<&Span>, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let node = self.add_child(label, kind, span);
 
-                        let type_node_root = self.add_node("Type");
-                        let type_node = self.create_type_node(member.r#type.value.deref());
+        self.parents.push(node);
+        f(self);
+        self.parents.pop();
+    }
 
-                        self.add_node_connections(&[member_node, type_node_root, type_node]);
-                    }
+    /// Adds group node, see [`NodeKind::Group`].
+    fn with_group<F>(&mut self, label: &str, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        self.with_child(label, NodeKind::Group, None, f);
+    }
 
-                    self.add_node_connections(&[root, members_node]);
-                }
+    /// Labels the edge to the first node created inside of `f` with `role`.
+    fn with_role<F>(&mut self, role: &'static str, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        self.role = Some(role);
+        f(self);
+        self.role = None;
+    }
 
-                root
-            }
-            TopLevelStatement::TraitDecl(i) => {
-                let root = self.add_node("TraitDecl");
+    /// Adds leaf node connected to the parent with `role` edge.
+    fn add_leaf(&mut self, role: &'static str, value: &str, span: Option<&Span>) {
+        self.with_role(role, |s| {
+            s.add_child(value, NodeKind::Leaf, span);
+        });
+    }
 
-                let name_node_root = self.add_node("Name");
-                let name_node = self.add_node(&i.name.value);
+    fn add_name_leaf(&mut self, name: &WithSpan<String>) {
+        self.add_leaf("name", &name.value, Some(&name.span));
+    }
 
-                self.add_node_connections(&[root, name_node_root, name_node]);
+    fn add_public_node(&mut self, public: &Option<Span>) {
+        if let Some(span) = public {
+            self.add_child("pub", NodeKind::Leaf, Some(span));
+        }
+    }
 
-                if i.public.is_some() {
-                    let public_node = self.add_node("Public");
-                    self.add_node_connections(&[root, public_node]);
+    fn add_generic_annotations_node(&mut self, annotations: &GenericAnnotations) {
+        if !annotations.is_empty() {
+            self.with_group("Generics", |s| {
+                for annotation in annotations {
+                    s.visit_generic_annotation(annotation);
                 }
+            });
+        }
+    }
 
-                if !i.generic_annotations.is_empty() {
-                    let generics_node =
-                        self.create_generic_annotations_node(&i.generic_annotations);
-                    self.add_node_connections(&[root, generics_node]);
+    fn add_associated_types_node(&mut self, associated_types: &[(String, AssociatedType)]) {
+        if !associated_types.is_empty() {
+            self.with_group("Types", |s| {
+                for (_, associated_type) in associated_types {
+                    s.visit_associated_type(associated_type);
                 }
+            });
+        }
+    }
 
-                if !i.methods.is_empty() {
-                    let methods_node = self.add_node("Methods");
-
-                    for method in &i.methods {
-                        let method = &method.1;
-                        let method_node = self.add_node("Method");
-
-                        let name_node_root = self.add_node("Name");
-                        let name_node = self.add_node(&method.name.value);
-
-                        self.add_node_connections(&[
-                            methods_node,
-                            method_node,
-                            name_node_root,
-                            name_node,
-                        ]);
-
-                        if !method.generic_annotations.is_empty() {
-                            let generics_node =
-                                self.create_generic_annotations_node(&method.generic_annotations);
-
-                            self.add_node_connections(&[method_node, generics_node]);
-                        }
-
-                        if !method.params.is_empty() {
-                            let params_node = self.create_params_node(&method.params);
-                            self.add_node_connections(&[method_node, params_node]);
-                        }
-
-                        if method.return_type.is_some() {
-                            let return_type_node_root = self.add_node("ReturnType");
-                            let return_type_node = self.create_type_node(
-                                method.return_type.as_ref().unwrap().value.deref(),
-                            );
-
-                            self.add_node_connections(&[
-                                method_node,
-                                return_type_node_root,
-                                return_type_node,
-                            ]);
-                        }
-                    }
-
-                    self.add_node_connections(&[root, methods_node]);
+    fn add_where_clause_node(&mut self, where_clause: &WhereClause) {
+        if !where_clause.is_empty() {
+            self.with_group("Where", |s| {
+                for item in where_clause {
+                    s.visit_where_clause_item(item);
                 }
+            });
+        }
+    }
 
-                root
-            }
-            TopLevelStatement::EnumDecl(e) => {
-                let root = self.add_node("EnumDecl");
-                let name_node_root = self.add_node("Name");
-                let name_node = self.add_node(&e.name.value);
+    fn add_params_node(&mut self, params: &[FunctionParam]) {
+        if !params.is_empty() {
+            self.with_group("Params", |s| {
+                for param in params {
+                    s.visit_function_param(param);
+                }
+            });
+        }
+    }
 
-                self.add_node_connections(&[root, name_node_root, name_node]);
+    fn add_return_type_node(&mut self, return_type: &Option<Type>) {
+        if let Some(return_type) = return_type {
+            self.with_role("return type", |s| s.visit_type(return_type));
+        }
+    }
 
-                if e.public.is_some() {
-                    let public_node = self.add_node("Public");
-                    self.add_node_connections(&[root, public_node]);
+    fn add_methods_node(&mut self, methods: &[(String, TraitMethod)]) {
+        if !methods.is_empty() {
+            self.with_group("Methods", |s| {
+                for (_, method) in methods {
+                    s.visit_trait_method(method);
                 }
+            });
+        }
+    }
 
-                if !e.variants.is_empty() {
-                    let variants_node_root = self.add_node("Variants");
-
-                    for variant in &e.variants {
-                        let variant_node_root = self.add_node("Variant");
-                        let variant_node = self.add_node(&variant.value);
+    fn add_fields_node(&mut self, fields: &HashMap<String, (Span, WithSpan<Expression>)>) {
+        let mut fields = fields.iter().collect::<Vec<_>>();
+        fields.sort_by_key(|(name, _)| *name);
 
-                        self.add_node_connections(&[
-                            variants_node_root,
-                            variant_node_root,
-                            variant_node,
-                        ]);
-                    }
+        self.with_group("Fields", |s| {
+            for (name, (name_span, value)) in fields {
+                s.with_child("Field", NodeKind::Expression, Some(&value.span), |s| {
+                    s.add_leaf("name", name, Some(name_span));
+                    s.with_role("value", |s| s.visit_expression(&value.value));
+                });
+            }
+        });
+    }
 
-                    self.add_node_connections(&[root, variants_node_root]);
+    fn add_types_node(&mut self, label: &str, types: &[Type]) {
+        if !types.is_empty() {
+            self.with_group(label, |s| {
+                for r#type in types {
+                    s.visit_type(r#type);
                 }
-
-                root
-            }
-            _ => todo!(),
+            });
         }
     }
 
-    fn create_statements_block_node(&mut self, statements: &Vec<Statement>) -> u32 {
-        let root = self.add_node("StatementsBlock");
+    /// Adds expression with the value, for example: `Int 3`.
+    fn add_literal(&mut self, kind: &str, value: &str, span: &Span) {
+        self.add_child(&format!("{kind} {value}"), NodeKind::Expression, Some(span));
+    }
+}
 
-        for statement in statements {
-            let statement_node_root = self.add_node("Statement");
-            let statement_node = self.create_statement_node(statement);
+/// Escapes string to be used inside of quoted DOT label.
+pub(crate) fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
 
-            self.add_node_connections(&[root, statement_node_root, statement_node]);
+impl Visitor for GraphvizTranslatorState {
+    fn visit_program_unit(&mut self, program_unit: &ProgramUnit) {
+        if !program_unit.imports.is_empty() {
+            self.with_cluster("imports", "imports", |s| {
+                for import in &program_unit.imports {
+                    s.visit_import(import);
+                }
+            });
         }
 
-        root
+        for (i, (_, statement)) in program_unit.top_level_statements.iter().enumerate() {
+            let label = match statement {
+                TopLevelStatement::FunctionDecl(f) => format!("fun {}", f.def.name.value),
+                TopLevelStatement::StructDecl(s) => format!("struct {}", s.name.value),
+                TopLevelStatement::TraitDecl(t) => format!("trait {}", t.name.value),
+                TopLevelStatement::EnumDecl(e) => format!("enum {}", e.name.value),
+                TopLevelStatement::Impl(_) => "impl".to_owned(),
+                TopLevelStatement::ConstDecl(c) => format!("const {}", c.name.value),
+                TopLevelStatement::VarDecl(v) => format!("var {}", v.name.value),
+                TopLevelStatement::TypeAlias(a) => format!("type {}", a.name.value),
+                TopLevelStatement::Newtype(n) => format!("newtype {}", n.name.value),
+            };
+
+            self.with_cluster(&format!("d{i}"), &label, |s| {
+                s.visit_top_level_statement(statement)
+            });
+        }
     }
 
-    fn create_statement_node(&mut self, statement: &Statement) -> u32 {
-        match statement {
-            Statement::Return(e) => {
-                let node = self.add_node("ReturnStatement");
-                let expr_node_root = self.add_node("Expression");
-                let expr_node = self.create_expression_node(e.value.deref());
-
-                self.add_node_connections(&[node, expr_node_root, expr_node]);
+    fn visit_import(&mut self, import: &Import) {
+        self.with_child("Import", NodeKind::Declaration, None, |s| {
+            s.add_leaf("file", &import.filename.value, Some(&import.filename.span));
+        });
+    }
 
-                node
-            }
-            Statement::ExpressionWithoutSemicolon(e) => {
-                let node = self.add_node("ExpressionStatementWithoutSemicolon");
-                let expr_node = self.create_expression_node(e.value.deref());
+    fn visit_function_decl(&mut self, function_decl: &FunctionDecl) {
+        self.with_child("FunDecl", NodeKind::Declaration, None, |s| {
+            s.add_name_leaf(&function_decl.def.name);
+            s.add_public_node(&function_decl.def.public);
+            s.add_generic_annotations_node(&function_decl.def.generic_annotations);
+            s.add_params_node(&function_decl.def.params);
+            s.add_return_type_node(&function_decl.def.return_type);
+            s.add_where_clause_node(&function_decl.def.where_clause);
+            s.with_role("body", |s| s.visit_statements_block(&function_decl.stmts));
+        });
+    }
 
-                self.add_node_connections(&[node, expr_node]);
+    fn visit_struct_decl(&mut self, struct_decl: &StructDecl) {
+        self.with_child("StructDecl", NodeKind::Declaration, None, |s| {
+            s.add_name_leaf(&struct_decl.name);
+            s.add_public_node(&struct_decl.public);
+            s.add_generic_annotations_node(&struct_decl.generic_annotations);
 
-                node
+            if !struct_decl.members.is_empty() {
+                s.with_group("Members", |s| {
+                    for (_, member) in &struct_decl.members {
+                        s.visit_struct_member(member);
+                    }
+                });
             }
-            Statement::Defer(d) => {
-                let node = self.add_node("DeferStatement");
-                let expr_node_root = self.add_node("Expression");
-                let expr_node = self.create_expression_node(d.value.deref());
-
-                self.add_node_connections(&[node, expr_node_root, expr_node]);
+        });
+    }
 
-                node
-            }
-            Statement::Expression(e) => {
-                let node = self.add_node("ExpressionStatement");
-                let expr_node_root = self.add_node("Expression");
-                let expr_node = self.create_expression_node(e.value.deref());
+    fn visit_struct_member(&mut self, member: &StructMemberDef) {
+        self.with_child("Member", NodeKind::Declaration, None, |s| {
+            s.add_public_node(&member.public);
+            s.add_name_leaf(&member.name);
+            s.with_role("type", |s| walk_struct_member(s, member));
+        });
+    }
 
-                self.add_node_connections(&[node, expr_node_root, expr_node]);
+    fn visit_trait_decl(&mut self, trait_decl: &TraitDecl) {
+        self.with_child("TraitDecl", NodeKind::Declaration, None, |s| {
+            s.add_name_leaf(&trait_decl.name);
+            s.add_public_node(&trait_decl.public);
+            s.add_generic_annotations_node(&trait_decl.generic_annotations);
 
-                node
+            for supertrait in &trait_decl.supertraits {
+                s.with_role("supertrait", |s| s.visit_type(supertrait));
             }
-            Statement::Var(name, r#type, value) => {
-                let node = self.add_node("VarStatement");
-
-                let name_node_root = self.add_node("Name");
-                let name_node = self.add_node(&name.value);
 
-                self.add_node_connections(&[node, name_node_root, name_node]);
+            s.add_associated_types_node(&trait_decl.associated_types);
+            s.add_methods_node(&trait_decl.methods);
+        });
+    }
 
-                if let Some(t) = r#type {
-                    let type_node_root = self.add_node("Type");
-                    let type_node = self.create_type_node(t.value.deref());
+    fn visit_impl(&mut self, r#impl: &Impl) {
+        self.with_child("Impl", NodeKind::Declaration, None, |s| {
+            s.add_generic_annotations_node(&r#impl.global_generic_annotations);
+            s.with_role("type", |s| s.visit_type(&r#impl.r#type));
 
-                    self.add_node_connections(&[node, type_node_root, type_node]);
-                }
+            if let Some(r#trait) = &r#impl.r#trait {
+                s.with_role("trait", |s| s.visit_type(r#trait));
+            }
 
-                let value_node_root = self.add_node("Value");
-                let value_node = self.create_expression_node(value.value.deref());
+            s.add_where_clause_node(&r#impl.where_clause);
+            s.add_associated_types_node(&r#impl.associated_types);
+            s.add_methods_node(&r#impl.methods);
+        });
+    }
 
-                self.add_node_connections(&[node, value_node_root, value_node]);
+    fn visit_trait_method(&mut self, method: &TraitMethod) {
+        self.with_child("Method", NodeKind::Declaration, None, |s| {
+            s.add_name_leaf(&method.name);
+            s.add_generic_annotations_node(&method.generic_annotations);
+            s.add_params_node(&method.params);
+            s.add_return_type_node(&method.return_type);
+            s.add_where_clause_node(&method.where_clause);
 
-                node
+            if let Some(body) = &method.body {
+                s.with_role("body", |s| s.visit_statements_block(body));
             }
-        }
+        });
     }
 
-    fn create_params_node(&mut self, params: &Vec<FunctionParam>) -> u32 {
-        let params_node = self.add_node("Params");
-
-        for param in params {
-            let param_node = self.add_node("Param");
-            let param_name_node_root = self.add_node("Name");
-            let param_name_node = self.add_node(&param.name.value);
+    fn visit_enum_decl(&mut self, enum_decl: &EnumDecl) {
+        self.with_child("EnumDecl", NodeKind::Declaration, None, |s| {
+            s.add_name_leaf(&enum_decl.name);
+            s.add_public_node(&enum_decl.public);
 
-            self.add_node_connections(&[
-                params_node,
-                param_node,
-                param_name_node_root,
-                param_name_node,
-            ]);
+            if !enum_decl.variants.is_empty() {
+                s.with_group("Variants", |s| {
+                    for variant in &enum_decl.variants {
+                        s.add_child(&variant.value, NodeKind::Leaf, Some(&variant.span));
+                    }
+                });
+            }
+        });
+    }
 
-            if let Some(value) = &param.default_value {
-                let value_node = self.add_node("Default value");
-                let expr_node = self.create_expression_node(value.value.deref());
+    fn visit_const_decl(&mut self, const_decl: &ConstDecl) {
+        self.with_child("ConstDecl", NodeKind::Declaration, None, |s| {
+            s.add_name_leaf(&const_decl.name);
+            s.add_public_node(&const_decl.public);
 
-                self.add_node_connections(&[param_node, value_node, expr_node]);
+            if let Some(r#type) = &const_decl.r#type {
+                s.with_role("type", |s| s.visit_type(r#type));
             }
 
-            let type_node_root = self.add_node("Type");
-            let type_node = self.create_type_node(param.r#type.value.deref());
+            s.with_role("value", |s| s.visit_expression(&const_decl.value));
+        });
+    }
 
-            self.add_node_connections(&[param_node, type_node_root, type_node]);
-        }
+    fn visit_var_decl(&mut self, var_decl: &VarDecl) {
+        self.with_child("VarDecl", NodeKind::Declaration, None, |s| {
+            s.add_name_leaf(&var_decl.name);
+            s.add_public_node(&var_decl.public);
 
-        params_node
+            if let Some(r#type) = &var_decl.r#type {
+                s.with_role("type", |s| s.visit_type(r#type));
+            }
+
+            s.with_role("value", |s| s.visit_expression(&var_decl.value));
+        });
     }
 
-    fn create_generic_annotations_node(
-        &mut self,
-        annotations: &Vec<(WithSpan<String>, Option<Type>)>,
-    ) -> u32 {
-        let generics_node = self.add_node("Generics");
-
-        for generic in annotations {
-            let generic_node_root = self.add_node("Generic");
-            let generic_node = self.add_node(&generic.0.value);
-            self.add_node_connections(&[generics_node, generic_node_root, generic_node]);
-        }
+    fn visit_type_alias(&mut self, alias: &TypeAlias) {
+        self.with_child("TypeAlias", NodeKind::Declaration, None, |s| {
+            s.add_name_leaf(&alias.name);
+            s.add_public_node(&alias.public);
+            s.add_generic_annotations_node(&alias.generic_annotations);
+            s.with_role("value", |s| s.visit_type(&alias.value));
+        });
+    }
 
-        generics_node
+    fn visit_newtype(&mut self, newtype: &Newtype) {
+        self.with_child("Newtype", NodeKind::Declaration, None, |s| {
+            s.add_name_leaf(&newtype.name);
+            s.add_public_node(&newtype.public);
+            s.add_generic_annotations_node(&newtype.generic_annotations);
+            s.with_role("value", |s| s.visit_type(&newtype.value));
+        });
     }
 
-    fn create_expression_node(&mut self, expression: &RawExpression) -> u32 {
-        match expression {
-            RawExpression::Int(i) => {
-                let root = self.add_node("Int");
-                let node = self.add_node(&i.to_string());
+    fn visit_generic_annotation(&mut self, annotation: &GenericAnnotation) {
+        self.with_child(
+            &annotation.name.value,
+            NodeKind::Type,
+            Some(&annotation.name.span),
+            |s| {
+                for constraint in &annotation.constraints {
+                    s.with_role("constraint", |s| s.visit_type(constraint));
+                }
+            },
+        );
+    }
 
-                self.add_node_connections(&[root, node]);
+    fn visit_associated_type(&mut self, associated_type: &AssociatedType) {
+        self.with_child("AssocType", NodeKind::Declaration, None, |s| {
+            s.add_name_leaf(&associated_type.name);
 
-                root
+            for constraint in &associated_type.constraints {
+                s.with_role("constraint", |s| s.visit_type(constraint));
             }
-            RawExpression::Float(f) => {
-                let root = self.add_node("Float");
-                let node = self.add_node(&f.to_string());
-
-                self.add_node_connections(&[root, node]);
 
-                root
+            if let Some(value) = &associated_type.value {
+                s.with_role("value", |s| s.visit_type(value));
             }
-            RawExpression::Imag(f) => {
-                let root = self.add_node("Imag");
-                let node = self.add_node(&f.to_string());
+        });
+    }
 
-                self.add_node_connections(&[root, node]);
+    fn visit_where_clause_item(&mut self, item: &WhereClauseItem) {
+        self.with_child("WhereItem", NodeKind::Declaration, None, |s| {
+            s.with_role("type", |s| s.visit_type(&item.r#type));
 
-                root
+            for constraint in &item.constraints {
+                s.with_role("constraint", |s| s.visit_type(constraint));
             }
-            RawExpression::Char(c) => {
-                let root = self.add_node("Char");
-                let node = self.add_node(&c.to_string());
+        });
+    }
 
-                self.add_node_connections(&[root, node]);
+    fn visit_function_param(&mut self, param: &FunctionParam) {
+        self.with_child("Param", NodeKind::Declaration, None, |s| {
+            s.add_name_leaf(&param.name);
+            s.with_role("type", |s| s.visit_type(&param.r#type));
 
-                root
+            if let Some(value) = &param.default_value {
+                s.with_role("default", |s| s.visit_expression(value));
             }
-            RawExpression::String(str) => {
-                let root = self.add_node("String");
-                let node = self.add_node(&str.to_string());
-
-                self.add_node_connections(&[root, node]);
+        });
+    }
 
-                root
+    fn visit_statements_block(&mut self, block: &[Statement]) {
+        self.with_group("StatementsBlock", |s| {
+            for statement in block {
+                s.visit_statement(statement);
             }
-            RawExpression::Bool(b) => {
-                let root = self.add_node("Bool");
-                let node = self.add_node(&b.to_string());
+        });
+    }
 
-                self.add_node_connections(&[root, node]);
+    fn visit_statement(&mut self, statement: &Statement) {
+        let (label, expression) = match statement {
+            Statement::Return(e) => ("ReturnStatement", e),
+            Statement::ExpressionWithoutSemicolon(e) => ("ExpressionStatementWithoutSemicolon", e),
+            Statement::Defer(e) => ("DeferStatement", e),
+            Statement::Expression(e) => ("ExpressionStatement", e),
+            Statement::Var(name, r#type, value) => {
+                return self.with_child("VarStatement", NodeKind::Statement, None, |s| {
+                    s.add_name_leaf(name);
+
+                    if let Some(r#type) = r#type {
+                        s.with_role("type", |s| s.visit_type(r#type));
+                    }
 
-                root
+                    s.with_role("value", |s| s.visit_expression(value));
+                });
             }
-            RawExpression::List(l) => {
-                let root = self.add_node("ListExpr");
+        };
 
-                for expr in l {
-                    let elem = self.add_node("Elem");
-                    let expr_node = self.create_expression_node(expr.value.deref());
+        self.with_child(label, NodeKind::Statement, None, |s| {
+            s.visit_expression(expression);
+        });
+    }
 
-                    self.add_node_connections(&[root, elem, expr_node]);
-                }
+    fn visit_expression(&mut self, expression: &Expression) {
+        let span = Some(&expression.span);
 
-                root
+        match &*expression.value {
+            RawExpression::Int(i, suffix) => {
+                let value = format!("{i}{}", suffix.map(|s| s.to_string()).unwrap_or_default());
+                self.add_literal("Int", &value, &expression.span)
             }
-            RawExpression::StaticName(name) => {
-                let root = self.add_node("StaticName");
-                let node = self.add_node(name);
-
-                self.add_node_connections(&[root, node]);
-
-                root
+            RawExpression::Float(f, suffix) => {
+                let value = format!("{f}{}", suffix.map(|s| s.to_string()).unwrap_or_default());
+                self.add_literal("Float", &value, &expression.span)
             }
-            RawExpression::Binary(lhs, op, rhs) => {
-                let root = self.add_node("BinaryExpr");
-                let op_node_root = self.add_node("Op");
-                let op_node = self.add_node(&op.value.to_string());
-
-                self.add_node_connections(&[root, op_node_root, op_node]);
-
-                let lhs_node_root = self.add_node("LHS");
-                let lhs_node = self.create_expression_node(lhs.value.deref());
-
-                let rhs_node_root = self.add_node("RHS");
-                let rhs_node = self.create_expression_node(rhs.value.deref());
-
-                self.add_node_connections(&[root, lhs_node_root, lhs_node]);
-                self.add_node_connections(&[root, rhs_node_root, rhs_node]);
-
-                root
+            RawExpression::Imag(i) => self.add_literal("Imag", &i.to_string(), &expression.span),
+            RawExpression::Char(c) => self.add_literal("Char", &format!("{c:?}"), &expression.span),
+            RawExpression::String(s) => {
+                self.add_literal("String", &format!("{s:?}"), &expression.span)
             }
-            RawExpression::Call(generics, caller, params) => {
-                let root = self.add_node("Call");
-
-                let caller_node_root = self.add_node("Caller");
-                let caller_node = self.create_expression_node(caller.value.deref());
-
-                self.add_node_connections(&[root, caller_node_root, caller_node]);
-
-                if !params.is_empty() {
-                    let params_node_root = self.add_node("Params");
-
-                    for param in params {
-                        let param_node_root = self.add_node("Param");
-                        let param_node = self.create_expression_node(param.value.deref());
-
-                        self.add_node_connections(&[params_node_root, param_node_root, param_node]);
+            RawExpression::Bool(b) => self.add_literal("Bool", &b.to_string(), &expression.span),
+            RawExpression::StaticName(n) => self.add_literal("StaticName", n, &expression.span),
+            RawExpression::List(elements) => {
+                self.with_child("ListExpr", NodeKind::Expression, span, |s| {
+                    for element in elements {
+                        s.with_role("element", |s| s.visit_expression(element));
                     }
+                })
+            }
+            RawExpression::Binary(left, op, right) => {
+                self.with_child("BinaryExpr", NodeKind::Expression, span, |s| {
+                    s.add_leaf("op", &op.value.to_string(), Some(&op.span));
+                    s.with_role("lhs", |s| s.visit_expression(left));
+                    s.with_role("rhs", |s| s.visit_expression(right));
+                })
+            }
+            RawExpression::Call(generics, caller, arguments) => {
+                self.with_child("Call", NodeKind::Expression, span, |s| {
+                    s.with_role("callee", |s| s.visit_expression(caller));
 
-                    self.add_node_connections(&[root, params_node_root]);
-                }
-
-                if !generics.is_empty() {
-                    let generics_node_root = self.add_node("Generics");
-
-                    for generic in generics {
-                        let generic_node_root = self.add_node("Generic");
-                        let generic_node = self.create_type_node(generic.value.deref());
-
-                        self.add_node_connections(&[
-                            generics_node_root,
-                            generic_node_root,
-                            generic_node,
-                        ]);
+                    for argument in arguments {
+                        s.with_role("argument", |s| s.visit_expression(argument));
                     }
 
-                    self.add_node_connections(&[root, generics_node_root]);
-                }
-
-                root
+                    s.add_types_node("Generics", generics);
+                })
             }
-            RawExpression::Index(lhs, rhs) => {
-                let root = self.add_node("IndexExpr");
-
-                let lhs_node = self.create_expression_node(lhs.value.deref());
-
-                let rhs_node_root = self.add_node("Index");
-                let rhs_node = self.create_expression_node(rhs.value.deref());
-
-                self.add_node_connections(&[root, lhs_node]);
-                self.add_node_connections(&[root, rhs_node_root, rhs_node]);
-
-                root
+            RawExpression::Index(left, index) => {
+                self.with_child("IndexExpr", NodeKind::Expression, span, |s| {
+                    s.visit_expression(left);
+                    s.with_role("index", |s| s.visit_expression(index));
+                })
             }
-            RawExpression::Property(lhs, rhs) => {
-                let root = self.add_node("PropertyAccess");
-
-                let lhs_node = self.create_expression_node(lhs.value.deref());
-
-                let rhs_node_root = self.add_node("Property");
-                let rhs_node = self.add_node(&rhs.value);
-
-                self.add_node_connections(&[root, lhs_node]);
-                self.add_node_connections(&[root, rhs_node_root, rhs_node]);
-
-                root
+            RawExpression::Property(left, name) => {
+                self.with_child("PropertyAccess", NodeKind::Expression, span, |s| {
+                    s.visit_expression(left);
+                    s.add_leaf("property", &name.value, Some(&name.span));
+                })
             }
-            RawExpression::PrefixOrPostfix(t, e) => {
-                let root = self.add_node("PrefixOrPostfix");
-
-                let op_node = self.add_node(&t.value.to_string());
-                let expr_node = self.create_expression_node(e.value.deref());
-
-                self.add_node_connections(&[root, expr_node]);
-                self.add_node_connections(&[root, op_node]);
-
-                root
+            RawExpression::PrefixOrPostfix(op, inner) => {
+                self.with_child("PrefixOrPostfix", NodeKind::Expression, span, |s| {
+                    s.visit_expression(inner);
+                    s.add_leaf("op", &op.value.to_string(), Some(&op.span));
+                })
             }
-            RawExpression::If(r#if, elseifs, r#else) => {
-                let root = self.add_node("IfExpr");
-
-                let if_node = self.add_node("If");
-                let if_condition_node_root = self.add_node("Condition");
-                let if_condition_node = self.create_expression_node(r#if.0.value.deref());
-                let if_statements_block = self.create_statements_block_node(&r#if.1);
-
-                self.add_node_connections(&[
-                    root,
-                    if_node,
-                    if_condition_node_root,
-                    if_condition_node,
-                ]);
-                self.add_node_connections(&[if_node, if_statements_block]);
-
-                if !elseifs.is_empty() {
-                    let elseifs_root = self.add_node("IfElseChain");
-
-                    for elseif in elseifs {
-                        let elseif_node = self.add_node("ElseIf");
-                        let elseif_condition_node_root = self.add_node("Condition");
-                        let elseif_condition_node =
-                            self.create_expression_node(elseif.0.value.deref());
-                        let elseif_statements_block = self.create_statements_block_node(&elseif.1);
-
-                        self.add_node_connections(&[
-                            elseifs_root,
-                            elseif_node,
-                            elseif_condition_node_root,
-                            elseif_condition_node,
-                        ]);
-                        self.add_node_connections(&[elseif_node, elseif_statements_block]);
+            RawExpression::If((condition, block), else_ifs, r#else) => {
+                self.with_child("IfExpr", NodeKind::Expression, span, |s| {
+                    s.with_role("condition", |s| s.visit_expression(condition));
+                    s.with_role("then", |s| s.visit_statements_block(block));
+
+                    for (condition, block) in else_ifs {
+                        s.with_role("else if", |s| {
+                            s.with_child("ElseIf", NodeKind::Expression, None, |s| {
+                                s.with_role("condition", |s| s.visit_expression(condition));
+                                s.with_role("then", |s| s.visit_statements_block(block));
+                            })
+                        });
                     }
 
-                    self.add_node_connections(&[root, elseifs_root]);
-                }
-
-                if r#else.is_some() {
-                    let else_node = self.add_node("Else");
-                    let else_statements_block =
-                        self.create_statements_block_node(r#else.as_ref().unwrap());
-
-                    self.add_node_connections(&[root, else_node, else_statements_block]);
-                }
-
-                root
+                    if let Some(block) = r#else {
+                        s.with_role("else", |s| s.visit_statements_block(block));
+                    }
+                })
             }
-            RawExpression::While(condition, statements_block) => {
-                let root = self.add_node("WhileExpr");
-
-                let condition_node_root = self.add_node("Condition");
-                let condition_node = self.create_expression_node(condition.value.deref());
-
-                let statements_block_node = self.create_statements_block_node(statements_block);
-
-                self.add_node_connections(&[root, condition_node_root, condition_node]);
-                self.add_node_connections(&[root, statements_block_node]);
-
-                root
+            RawExpression::While(condition, block) => {
+                self.with_child("WhileExpr", NodeKind::Expression, span, |s| {
+                    s.with_role("condition", |s| s.visit_expression(condition));
+                    s.with_role("body", |s| s.visit_statements_block(block));
+                })
             }
             RawExpression::As(left, r#type) => {
-                let root = self.add_node("AsExpr");
-
-                let left_node_root = self.add_node("Left");
-                let left_node = self.create_expression_node(left.value.deref());
-
-                let right_node_root = self.add_node("Type");
-                let right_node = self.create_type_node(r#type.value.deref());
-
-                self.add_node_connections(&[root, left_node_root, left_node]);
-                self.add_node_connections(&[root, right_node_root, right_node]);
-
-                root
+                self.with_child("AsExpr", NodeKind::Expression, span, |s| {
+                    s.with_role("value", |s| s.visit_expression(left));
+                    s.with_role("type", |s| s.visit_type(r#type));
+                })
+            }
+            RawExpression::Struct(name, fields) => {
+                self.with_child("StructExpr", NodeKind::Expression, span, |s| {
+                    s.add_name_leaf(name);
+                    s.add_fields_node(fields);
+                })
+            }
+            RawExpression::Map(fields) => {
+                self.with_child("MapExpr", NodeKind::Expression, span, |s| {
+                    s.add_fields_node(fields);
+                })
             }
-            _ => todo!(),
         }
     }
 
-    fn create_type_node(&mut self, r#type: &RawType) -> u32 {
-        match r#type {
-            RawType::Array(a) => {
-                let root = self.add_node("ArrayType");
-                let node = self.create_type_node(a.value.deref());
+    fn visit_type(&mut self, r#type: &Type) {
+        let span = Some(&r#type.span);
 
-                self.add_node_connections(&[root, node]);
+        match &*r#type.value {
+            RawType::Array(inner, size) => {
+                self.with_child("ArrayType", NodeKind::Type, span, |s| {
+                    s.visit_type(inner);
 
-                root
+                    if let Some(size) = size {
+                        s.with_role("size", |s| s.visit_expression(size));
+                    }
+                })
             }
-            RawType::Pointer(p) => {
-                let root = self.add_node("PointerType");
-                let node = self.create_type_node(p.value.deref());
-
-                self.add_node_connections(&[root, node]);
-
-                root
+            RawType::Pointer(_) => self.with_child("PointerType", NodeKind::Type, span, |s| {
+                walk_type(s, r#type)
+            }),
+            RawType::Option(_) => {
+                self.with_child("OptionType", NodeKind::Type, span, |s| walk_type(s, r#type))
             }
-            RawType::Primary(p, generics) => {
-                let root = self.add_node("PrimaryType");
-                let node = self.add_node(&p.value);
-
-                if !generics.is_empty() {
-                    let generics_node_root = self.add_node("Generics");
-
-                    for generic in generics {
-                        let generic_node_root = self.add_node("Generic");
-                        let generic_node = self.create_type_node(generic.value.deref());
+            RawType::Primary(name, generics) => {
+                self.with_child(&name.value, NodeKind::Type, span, |s| {
+                    s.add_types_node("Generics", generics);
+                })
+            }
+            RawType::Generic(name) => {
+                self.add_child(&name.value, NodeKind::Type, span);
+            }
+        }
+    }
+}
 
-                        self.add_node_connections(&[
-                            generics_node_root,
-                            generic_node_root,
-                            generic_node,
-                        ]);
-                    }
+#[cfg(test)]
+mod tests {
+    use crate::GraphvizTranslatorState;
+    use ry_parser::Parser;
 
-                    self.add_node_connections(&[root, generics_node_root]);
-                }
+    fn graph(source: &str, translator: GraphvizTranslatorState) -> String {
+        let ast = Parser::new(source).parse().unwrap();
+        let mut output = vec![];
 
-                self.add_node_connections(&[root, node]);
+        let mut translator = translator;
+        translator.ast_to_graphviz(&ast, &mut output).unwrap();
 
-                root
-            }
-            RawType::Generic(g) => {
-                let root = self.add_node("GenericType");
-                let node = self.add_node(&g.value);
+        String::from_utf8(output).unwrap()
+    }
 
-                self.add_node_connections(&[root, node]);
+    #[test]
+    fn clusters_test() {
+        let graph = graph(
+            "import \"std\";\nfun a() {}\nstruct B {}\npub const C = 1;\nvar d u8 = C;\ntype E<T> = [T];\n\
+             type F u8;",
+            GraphvizTranslatorState::new(),
+        );
 
-                root
-            }
-            RawType::Option(t) => {
-                let root = self.add_node("OptionType");
-                let node = self.create_type_node(t.value.deref());
+        assert!(graph.starts_with("digraph {\n"));
+        assert!(graph.contains("subgraph cluster_imports {"));
+        assert!(graph.contains("subgraph cluster_d0 {\n\t\tlabel=\"fun a\";"));
+        assert!(graph.contains("subgraph cluster_d1 {\n\t\tlabel=\"struct B\";"));
+        assert!(graph.contains("subgraph cluster_d2 {\n\t\tlabel=\"const C\";"));
+        assert!(graph.contains("subgraph cluster_d3 {\n\t\tlabel=\"var d\";"));
+        assert!(graph.contains("\t\td3_1 [label=\"VarDecl\""));
+        assert!(graph.contains("subgraph cluster_d4 {\n\t\tlabel=\"type E\";"));
+        assert!(graph.contains("subgraph cluster_d5 {\n\t\tlabel=\"newtype F\";"));
+        assert!(graph.contains("\t\td5_1 [label=\"Newtype\""));
+        assert!(graph.contains("\t\td5_1 -> d5_3 [label=\"value\"];"));
+        // Ids are local to the cluster.
+        assert!(graph.contains("\t\td1_1 [label=\"StructDecl\""));
+    }
 
-                self.add_node_connections(&[root, node]);
+    #[test]
+    fn edge_roles_test() {
+        let graph = graph(
+            "fun a() { if b { 1; } else { 2; } }",
+            GraphvizTranslatorState::new(),
+        );
 
-                root
-            }
+        for role in ["condition", "then", "else", "body", "name"] {
+            assert!(
+                graph.contains(&format!("[label=\"{role}\"];")),
+                "no `{role}` edge in:\n{graph}"
+            );
         }
     }
 
-    fn add_node(&mut self, label: &str) -> u32 {
-        self.current_node_index += 1;
+    #[test]
+    fn constraints_test() {
+        let constraints = graph(
+            "fun a<T B + C>() where [T] D {}",
+            GraphvizTranslatorState::new(),
+        );
 
-        println!("\tnode{} [label=\"{}\"];", self.current_node_index, label);
+        assert_eq!(constraints.matches("[label=\"constraint\"];").count(), 3);
+        assert!(constraints.contains("[label=\"Where\""));
 
-        self.current_node_index
+        let traits = graph(
+            "trait A B + C { type D E; }",
+            GraphvizTranslatorState::new(),
+        );
+
+        assert_eq!(traits.matches("[label=\"supertrait\"];").count(), 2);
+        assert!(traits.contains("[label=\"AssocType\""));
     }
 
-    fn add_node_connections(&mut self, connections: &[u32]) {
-        print!("\t");
-        print!(
-            "{}",
-            &connections
-                .iter()
-                .map(|x| format!("node{x}"))
-                .collect::<Vec<String>>()
-                .join(" -> "),
+    #[test]
+    fn span_labels_test() {
+        let source = "fun a() {\n    \"\\\"quoted\\\"\";\n}";
+
+        let offsets = graph(source, GraphvizTranslatorState::new().with_span_labels());
+        assert!(offsets.contains("[label=\"String \\\"\\\\\\\"quoted\\\\\\\"\\\"\\n@14..26\""));
+
+        let lines = graph(
+            source,
+            GraphvizTranslatorState::new().with_line_labels(source),
         );
-        println!(";");
+        assert!(lines.contains("\\n2:5..2:17\""));
     }
 }
@@ -1,15 +1,23 @@
 //! `lib.rs` - defines AST nodes and additional stuff.
 pub mod location;
 pub mod precedence;
+pub mod sexp;
 pub mod token;
+pub mod visit;
+pub mod visit_mut;
 
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Component, Path, PathBuf},
+};
 
 use location::{Span, WithSpan};
-use token::Token;
+use serde::{Deserialize, Serialize};
+use token::{NumberSuffix, Token};
 
 /// Represents Ry source file.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct ProgramUnit {
     /// Global source file docstring
     pub docstring: String,
@@ -24,18 +32,59 @@ pub struct ProgramUnit {
 /// import "test.ry";
 ///        --------- `filename`
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Import {
     pub filename: WithSpan<String>,
 }
 
-#[derive(Debug, PartialEq)]
+impl Import {
+    /// Resolves path of the imported file relatively to the `directory` of
+    /// the importing file, `.ry` extension can be omitted.
+    pub fn resolve(&self, directory: &Path) -> PathBuf {
+        let mut path = directory.join(&self.filename.value);
+
+        if path.extension().is_none() {
+            path.set_extension("ry");
+        }
+
+        normalize_path(&path)
+    }
+}
+
+/// Lexically removes `.` and `..` components, so that the same file
+/// imported through different relative paths has the same path.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum TopLevelStatement {
     FunctionDecl(FunctionDecl),
     StructDecl(StructDecl),
     TraitDecl(TraitDecl),
     Impl(Impl),
     EnumDecl(EnumDecl),
+    ConstDecl(ConstDecl),
+    VarDecl(VarDecl),
+    TypeAlias(TypeAlias),
+    Newtype(Newtype),
 }
 
 /// Function declaration top level statement
@@ -48,31 +97,93 @@ pub enum TopLevelStatement {
 ///   |   --- `stmts`
 /// 7 | }
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct FunctionDecl {
     pub def: FunctionDef,
     pub stmts: Vec<Statement>,
 }
 
-pub type GenericAnnotation = (WithSpan<String>, Option<Type>);
+/// Generic parameter with its constraints
+///
+/// ```ry
+/// T Number + ToString
+/// - ----------------- `constraints`
+/// |
+/// `name`
+/// ```
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct GenericAnnotation {
+    pub name: WithSpan<String>,
+    pub constraints: Vec<Type>,
+}
+
 pub type GenericAnnotations = Vec<GenericAnnotation>;
 
+/// Item of the `where` clause: type with its constraints
+///
+/// ```ry
+/// where T Number + ToString, [T] Into<string>
+///       - -----------------  --- ------------ `constraints`
+///       |                 |    |
+///       `type`  `constraints`  `type`
+/// ```
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct WhereClauseItem {
+    pub r#type: Type,
+    pub constraints: Vec<Type>,
+}
+
+pub type WhereClause = Vec<WhereClauseItem>;
+
+impl fmt::Display for GenericAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name.value)?;
+        write_constraints(f, &self.constraints)
+    }
+}
+
+impl fmt::Display for WhereClauseItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.r#type.value)?;
+        write_constraints(f, &self.constraints)
+    }
+}
+
+/// Writes constraints in the form of ` A + B`.
+fn write_constraints(f: &mut fmt::Formatter<'_>, constraints: &[Type]) -> fmt::Result {
+    for (i, constraint) in constraints.iter().enumerate() {
+        write!(
+            f,
+            "{}{}",
+            if i == 0 { " " } else { " + " },
+            constraint.value
+        )?;
+    }
+
+    Ok(())
+}
+
 /// Function definition
 ///
 /// ```ry
-/// pub fun test<T Number, M, A>(a T, b T) T
-/// ---     ---- --------------- --------  - `return_type`
-/// |          | |                      |
-/// `public`   | `generic_annotations`  |
+/// @test
+/// ----- `attributes`
+/// pub fun test<T Number, M, A>(a T, b T) T where M Into<T>
+/// ---     ---- --------------- --------  - ---------------- `where_clause`
+/// |          | |                      |  |
+/// `public`   | `generic_annotations`  |  `return_type`
 ///        `name`                      `params`
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct FunctionDef {
+    /// Names of the attributes (without `@`).
+    pub attributes: Vec<WithSpan<String>>,
     pub public: Option<Span>,
     pub generic_annotations: GenericAnnotations,
     pub name: WithSpan<String>,
     pub params: Vec<FunctionParam>,
     pub return_type: Option<Type>,
+    pub where_clause: WhereClause,
 }
 
 /// Struct declaration top level statement
@@ -91,7 +202,7 @@ pub struct FunctionDef {
 /// 5 |   ...
 /// 6 | }
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct StructDecl {
     pub public: Option<Span>,
     pub generic_annotations: GenericAnnotations,
@@ -102,58 +213,83 @@ pub struct StructDecl {
 /// Trait implementation top level statement
 ///
 /// ```ry
-/// 1 | impl<A, B> Into<Tuple<A, B>> for Tuple<B, A> {
-///   |     ------ -----------------     ----------- `type`
-///   |     |                      |
-///   |     |                 `trait`
+/// 1 | impl<A, B> Into<Tuple<A, B>> for Tuple<B, A> where A Clone {
+///   |     ------ -----------------     ----------- ------------- `where_clause`
+///   |     |                      |               |
+///   |     |                 `trait`         `type`
 ///   |     `global_generic_annotations`
 /// 2 |   ...
 ///   |   --- `methods`
 /// 3 | }
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Impl {
     pub global_generic_annotations: GenericAnnotations,
     pub r#type: Type,
     pub r#trait: Option<Type>,
+    pub where_clause: WhereClause,
+    pub associated_types: Vec<(String, AssociatedType)>,
     pub methods: Vec<(String, TraitMethod)>,
 }
 
 /// Trait declaration top level statement
 ///
 /// ```ry
-/// 1 | pub trait Into<T> {
-///   | ---       ---- - `generic_annotations`
-///   | |            |
-///   | `pub`    `name`
-/// 2 |   ...
+/// 1 | pub trait Into<T> Clone + ToString {
+///   | ---       ---- - ---------------- `supertraits`
+///   | |            | |
+///   | `pub`   `name` `generic_annotations`
+/// 2 |   type Output;
+///   |   ------------ `associated_types`
+/// 3 |   ...
 ///   |   --- `methods`
-/// 3 | }
+/// 4 | }
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct TraitDecl {
     pub public: Option<Span>,
     pub name: WithSpan<String>,
     pub generic_annotations: GenericAnnotations,
+    pub supertraits: Vec<Type>,
+    pub associated_types: Vec<(String, AssociatedType)>,
     pub methods: Vec<(String, TraitMethod)>,
 }
 
+/// Associated type of trait or trait implementation
+///
+/// ```ry
+/// type Item ToString = string;
+///      ---- --------   ------ `value`
+///      |           |
+///      `name`      `constraints`
+/// ```
+///
+/// Traits declare associated types (with optional constraints and default
+/// value), implementations give them values.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct AssociatedType {
+    pub name: WithSpan<String>,
+    pub constraints: Vec<Type>,
+    pub value: Option<Type>,
+}
+
 /// Trait method
 ///
 /// ```ry
-/// pub fun into<T>(self Self) T { ... }
-/// ---     ---- -  ---------  -   --- `body`
-/// |          | |          |  |
-/// |          | |   `params` `return_type`
+/// pub fun into<T>(self Self) T where T Clone { ... }
+/// ---     ---- -  ---------  - ------------- --- `body`
+/// |          | |          |  |             |
+/// |          | |   `params` `return_type`  `where_clause`
 /// `public`   | `generic_annotations`
 ///        `name`
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct TraitMethod {
     pub name: WithSpan<String>,
     pub generic_annotations: GenericAnnotations,
     pub params: Vec<FunctionParam>,
     pub return_type: Option<Type>,
+    pub where_clause: WhereClause,
     pub body: Option<StatementsBlock>,
 }
 
@@ -171,13 +307,134 @@ pub struct TraitMethod {
 ///   |   -----   /
 /// 4 | }
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct EnumDecl {
     pub public: Option<Span>,
     pub name: WithSpan<String>,
     pub variants: Vec<WithSpan<String>>,
 }
 
+/// Constant declaration top level statement
+///
+/// ```ry
+/// pub const SIZE usize = 2 * 8;
+/// ---       ---- -----   ----- `value`
+/// |            |     |
+/// `public`  `name`  `type`
+/// ```
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ConstDecl {
+    pub public: Option<Span>,
+    pub name: WithSpan<String>,
+    pub r#type: Option<Type>,
+    pub value: Expression,
+}
+
+/// Global variable declaration top level statement. Global variables are
+/// initialized in order of declaration, after globals of imported files.
+///
+/// ```ry
+/// pub var counter u64 = 0;
+/// ---     ------- ---   - `value`
+/// |             |   |
+/// `public` `name`  `type`
+/// ```
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct VarDecl {
+    pub public: Option<Span>,
+    pub name: WithSpan<String>,
+    pub r#type: Option<Type>,
+    pub value: Expression,
+}
+
+/// Type alias top level statement. Alias is interchangeable with the type
+/// it stands for, generic parameters are substituted on use.
+///
+/// ```ry
+/// pub type Table<T> = Map<string, [T?]>;
+/// ---      ----- -   ----------------- `value`
+/// |        |     |
+/// |        |     `generic_annotations`
+/// `public` `name`
+/// ```
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TypeAlias {
+    pub public: Option<Span>,
+    pub name: WithSpan<String>,
+    pub generic_annotations: GenericAnnotations,
+    pub value: Type,
+}
+
+impl fmt::Display for TypeAlias {
+    /// Signature of the alias, for example: `pub type Table<T> = Map<string, [T?]>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.public.is_some() {
+            write!(f, "pub ")?;
+        }
+
+        write!(f, "type {}", self.name.value)?;
+
+        if !self.generic_annotations.is_empty() {
+            let parameters = self
+                .generic_annotations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+
+            write!(f, "<{}>", parameters.join(", "))?;
+        }
+
+        write!(f, " = {}", self.value.value)
+    }
+}
+
+/// Newtype top level statement. Unlike alias, newtype is a distinct type:
+/// values of the wrapped type are not accepted in place of it. Value is
+/// created by calling the newtype like a function, `Meters(1.5)`, and
+/// the wrapped value is accessed as the field [`Newtype::FIELD`].
+///
+/// ```ry
+/// pub type Meters f64;
+/// ---      ------ --- `value`
+/// |        |
+/// `public` `name`
+/// ```
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Newtype {
+    pub public: Option<Span>,
+    pub name: WithSpan<String>,
+    pub generic_annotations: GenericAnnotations,
+    pub value: Type,
+}
+
+impl Newtype {
+    /// Name of the field with the wrapped value.
+    pub const FIELD: &'static str = "value";
+}
+
+impl fmt::Display for Newtype {
+    /// Signature of the newtype, for example: `pub type Meters f64`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.public.is_some() {
+            write!(f, "pub ")?;
+        }
+
+        write!(f, "type {}", self.name.value)?;
+
+        if !self.generic_annotations.is_empty() {
+            let parameters = self
+                .generic_annotations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+
+            write!(f, "<{}>", parameters.join(", "))?;
+        }
+
+        write!(f, " {}", self.value.value)
+    }
+}
+
 /// ```ry
 /// pub a [i32];
 /// --- - ----- `type`
@@ -185,7 +442,7 @@ pub struct EnumDecl {
 /// |   `name`
 /// `public`
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct StructMemberDef {
     pub public: Option<Span>,
     pub name: WithSpan<String>,
@@ -200,7 +457,7 @@ pub struct StructMemberDef {
 ///              | `type`
 ///              `name`
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct FunctionParam {
     pub name: WithSpan<String>,
     pub r#type: Type,
@@ -209,18 +466,58 @@ pub struct FunctionParam {
 
 pub type Type = WithSpan<Box<RawType>>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum RawType {
-    Array(Type),
+    /// `[T]` or `[T; N]`, where `N` is constant expression.
+    Array(Type, Option<Expression>),
     Pointer(Type),
     Primary(WithSpan<String>, Vec<Type>),
     Generic(WithSpan<String>),
     Option(Type),
 }
 
+impl fmt::Display for RawType {
+    /// Formats type the way it is written in the source code. Array size
+    /// is shown only when it is an integer literal, otherwise it's `_`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Array(inner, None) => write!(f, "[{}]", inner.value),
+            Self::Array(inner, Some(size)) => match &*size.value {
+                RawExpression::Int(size, suffix) => {
+                    write!(f, "[{}; {size}", inner.value)?;
+
+                    if let Some(suffix) = suffix {
+                        write!(f, "{suffix}")?;
+                    }
+
+                    write!(f, "]")
+                }
+                _ => write!(f, "[{}; _]", inner.value),
+            },
+            Self::Pointer(inner) => write!(f, "*{}", inner.value),
+            Self::Primary(name, generics) => {
+                write!(f, "{}", name.value)?;
+
+                if !generics.is_empty() {
+                    let generics = generics
+                        .iter()
+                        .map(|generic| generic.value.to_string())
+                        .collect::<Vec<_>>();
+
+                    write!(f, "<{}>", generics.join(", "))?;
+                }
+
+                Ok(())
+            }
+            Self::Generic(name) => write!(f, "{}", name.value),
+            Self::Option(inner) => write!(f, "{}?", inner.value),
+        }
+    }
+}
+
 pub type StatementsBlock = Vec<Statement>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Statement {
     Expression(Expression),
     ExpressionWithoutSemicolon(Expression),
@@ -240,11 +537,11 @@ impl Statement {
 
 pub type Expression = WithSpan<Box<RawExpression>>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum RawExpression {
     String(String),
-    Int(u64),
-    Float(f64),
+    Int(u128, Option<NumberSuffix>),
+    Float(f64, Option<NumberSuffix>),
     Imag(f64),
     Bool(bool),
     Char(char),
@@ -4,8 +4,13 @@
 //! AST node.
 use std::ops::Range;
 
+use serde::{Deserialize, Serialize};
+
 /// Represents code block location in source text.
-#[derive(Clone, Debug, PartialEq, Default)]
+///
+/// Serialized as `[start, end]` pair to keep serialized AST compact.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(from = "(usize, usize)", into = "(usize, usize)")]
 pub struct Span {
     pub range: Range<usize>,
 }
@@ -32,8 +37,20 @@ impl From<Range<usize>> for Span {
     }
 }
 
+impl From<(usize, usize)> for Span {
+    fn from((start, end): (usize, usize)) -> Self {
+        Span::new(start, end)
+    }
+}
+
+impl From<Span> for (usize, usize) {
+    fn from(span: Span) -> Self {
+        (span.range.start, span.range.end)
+    }
+}
+
 /// Represents thing located in some [`Span`].
-#[derive(Debug, PartialEq, Clone, Default)]
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
 pub struct WithSpan<T> {
     pub value: T,
     pub span: Span,
@@ -50,3 +67,53 @@ impl<T> From<(T, Span)> for WithSpan<T> {
         WithSpan::new(val.0, val.1)
     }
 }
+
+/// Converts byte offsets into 1-based line and column (in characters) numbers.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        Self {
+            line_starts: std::iter::once(0)
+                .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+                .collect(),
+        }
+    }
+
+    /// Returns line and column of the `offset` in the `source`, that the
+    /// index was built from. Offset inside of a character refers to
+    /// the character.
+    pub fn line_column(&self, source: &str, offset: usize) -> (usize, usize) {
+        let mut offset = offset.min(source.len());
+
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+
+        (line, source[line_start..offset].chars().count() + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::location::LineIndex;
+
+    #[test]
+    fn line_column_test() {
+        let source = "a\nпривет b\n";
+        let index = LineIndex::new(source);
+
+        assert_eq!(index.line_column(source, 0), (1, 1));
+        assert_eq!(index.line_column(source, 2), (2, 1));
+        assert_eq!(index.line_column(source, 15), (2, 8));
+        // Inside of `р`.
+        assert_eq!(index.line_column(source, 5), (2, 2));
+        assert_eq!(index.line_column(source, source.len()), (3, 1));
+    }
+}
@@ -0,0 +1,540 @@
+//! `sexp.rs` - defines serializer of AST (or any other [`Serialize`] value)
+//! into S-expressions.
+//!
+//! Structs and enum variants are printed as lists headed by their name,
+//! struct fields are printed as `:field value` pairs, [`WithSpan`] values
+//! are printed as the wrapped value with `@start..end` suffix attached to
+//! its head:
+//!
+//! ```
+//! use ry_ast::{location::{Span, WithSpan}, sexp::to_sexp, RawExpression};
+//!
+//! let expression = WithSpan::new(Box::new(RawExpression::Int(3, None)), Span::new(0, 1));
+//! assert_eq!(to_sexp(&expression).unwrap(), "(Int@0..1 3 nil)");
+//! ```
+//!
+//! [`WithSpan`]: crate::location::WithSpan
+use std::fmt::{self, Display};
+
+use serde::{ser, Serialize};
+
+/// Maximum width of a line, after which lists are broken into several lines.
+const MAX_WIDTH: usize = 80;
+
+/// Serializes value into pretty printed S-expression.
+pub fn to_sexp<T: Serialize + ?Sized>(value: &T) -> Result<String, SexpError> {
+    let mut output = String::new();
+    value
+        .serialize(SexpSerializer)?
+        .pretty_print(0, &mut output);
+    Ok(output)
+}
+
+/// Represents error that can occur during serialization into S-expression.
+#[derive(Debug, PartialEq)]
+pub struct SexpError(String);
+
+impl Display for SexpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for SexpError {}
+
+impl ser::Error for SexpError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Sexp {
+    Atom(String),
+    List(Vec<Sexp>),
+    /// Struct field: `:name value`.
+    Field(&'static str, Box<Sexp>),
+}
+
+impl Sexp {
+    fn width(&self) -> usize {
+        match self {
+            Sexp::Atom(atom) => atom.len(),
+            Sexp::List(items) => {
+                items.iter().map(Sexp::width).sum::<usize>() + items.len().max(1) + 1
+            }
+            Sexp::Field(name, value) => name.len() + value.width() + 2,
+        }
+    }
+
+    fn print(&self, output: &mut String) {
+        match self {
+            Sexp::Atom(atom) => output.push_str(atom),
+            Sexp::List(items) => {
+                output.push('(');
+
+                for (i, item) in items.iter().enumerate() {
+                    if i != 0 {
+                        output.push(' ');
+                    }
+
+                    item.print(output);
+                }
+
+                output.push(')');
+            }
+            Sexp::Field(name, value) => {
+                output.push_str(&format!(":{name} "));
+                value.print(output);
+            }
+        }
+    }
+
+    fn pretty_print(&self, indent: usize, output: &mut String) {
+        match self {
+            Sexp::List(items) if !items.is_empty() && indent + self.width() > MAX_WIDTH => {
+                // Arguments of the named list are indented, elements of
+                // the plain list are aligned with the first one.
+                let items_indent = match items[0] {
+                    Sexp::Atom(_) => indent + 2,
+                    _ => indent + 1,
+                };
+
+                output.push('(');
+                items[0].pretty_print(indent + 1, output);
+
+                for item in &items[1..] {
+                    output.push('\n');
+                    output.push_str(&" ".repeat(items_indent));
+                    item.pretty_print(items_indent, output);
+                }
+
+                output.push(')');
+            }
+            Sexp::Field(name, value) if indent + self.width() > MAX_WIDTH => {
+                output.push_str(&format!(":{name} "));
+                value.pretty_print(indent + name.len() + 2, output);
+            }
+            _ => self.print(output),
+        }
+    }
+
+    /// Attaches span suffix to the atom or to the head of the list.
+    fn with_span(self, span: Sexp) -> Sexp {
+        let span = match span {
+            Sexp::List(items) if items.len() == 2 => format!("@{}..{}", items[0], items[1]),
+            _ => return Sexp::List(vec![self, span]),
+        };
+
+        match self {
+            Sexp::Atom(atom) => Sexp::Atom(atom + &span),
+            Sexp::Field(..) => unreachable!(),
+            Sexp::List(mut items) => match items.first_mut() {
+                Some(Sexp::Atom(head)) if !head.starts_with('"') => {
+                    head.push_str(&span);
+                    Sexp::List(items)
+                }
+                _ => {
+                    items.insert(0, Sexp::Atom(span));
+                    Sexp::List(items)
+                }
+            },
+        }
+    }
+}
+
+impl Display for Sexp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut output = String::new();
+        self.print(&mut output);
+        f.write_str(&output)
+    }
+}
+
+fn atom(value: impl ToString) -> Sexp {
+    Sexp::Atom(value.to_string())
+}
+
+fn quoted(value: &str) -> Sexp {
+    Sexp::Atom(format!("{value:?}"))
+}
+
+struct SexpSerializer;
+
+impl ser::Serializer for SexpSerializer {
+    type Ok = Sexp;
+    type Error = SexpError;
+
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = ListSerializer;
+    type SerializeMap = ListSerializer;
+    type SerializeStruct = ListSerializer;
+    type SerializeStructVariant = ListSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<Sexp, SexpError> {
+        Ok(atom(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Sexp, SexpError> {
+        Ok(atom(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Sexp, SexpError> {
+        Ok(atom(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Sexp, SexpError> {
+        Ok(atom(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Sexp, SexpError> {
+        Ok(atom(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Sexp, SexpError> {
+        Ok(atom(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Sexp, SexpError> {
+        Ok(atom(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Sexp, SexpError> {
+        Ok(atom(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Sexp, SexpError> {
+        Ok(atom(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Sexp, SexpError> {
+        Ok(atom(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Sexp, SexpError> {
+        Ok(atom(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Sexp, SexpError> {
+        Ok(atom(format!("{v:?}")))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Sexp, SexpError> {
+        Ok(atom(format!("{v:?}")))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Sexp, SexpError> {
+        Ok(atom(format!("{v:?}")))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Sexp, SexpError> {
+        Ok(quoted(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Sexp, SexpError> {
+        Ok(Sexp::List(v.iter().map(atom).collect()))
+    }
+
+    fn serialize_none(self) -> Result<Sexp, SexpError> {
+        Ok(atom("nil"))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Sexp, SexpError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Sexp, SexpError> {
+        Ok(Sexp::List(vec![]))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Sexp, SexpError> {
+        Ok(atom(name))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Sexp, SexpError> {
+        Ok(atom(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Sexp, SexpError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Sexp, SexpError> {
+        Ok(Sexp::List(vec![atom(variant), value.serialize(self)?]))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<ListSerializer, SexpError> {
+        Ok(ListSerializer::new(None))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<ListSerializer, SexpError> {
+        Ok(ListSerializer::new(None))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<ListSerializer, SexpError> {
+        Ok(ListSerializer::new(Some(name)))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<ListSerializer, SexpError> {
+        Ok(ListSerializer::new(Some(variant)))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<ListSerializer, SexpError> {
+        Ok(ListSerializer::new(None))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<ListSerializer, SexpError> {
+        Ok(ListSerializer::new(Some(name)))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<ListSerializer, SexpError> {
+        Ok(ListSerializer::new(Some(variant)))
+    }
+}
+
+struct ListSerializer {
+    name: Option<&'static str>,
+    items: Vec<Sexp>,
+    /// Pending key of the map entry.
+    key: Option<Sexp>,
+}
+
+impl ListSerializer {
+    fn new(name: Option<&'static str>) -> Self {
+        Self {
+            name,
+            items: name.map(atom).into_iter().collect(),
+            key: None,
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SexpError> {
+        self.items.push(value.serialize(SexpSerializer)?);
+        Ok(())
+    }
+
+    fn push_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SexpError> {
+        self.items
+            .push(Sexp::Field(key, Box::new(value.serialize(SexpSerializer)?)));
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<Sexp, SexpError> {
+        if self.name == Some("WithSpan") {
+            // (WithSpan :value <value> :span <span>)
+            if let [_, Sexp::Field("value", _), Sexp::Field("span", _)] = &self.items[..] {
+                let (Some(Sexp::Field(_, span)), Some(Sexp::Field(_, value))) =
+                    (self.items.pop(), self.items.pop())
+                else {
+                    unreachable!()
+                };
+
+                return Ok(value.with_span(*span));
+            }
+        }
+
+        Ok(Sexp::List(self.items))
+    }
+}
+
+impl ser::SerializeSeq for ListSerializer {
+    type Ok = Sexp;
+    type Error = SexpError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SexpError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Sexp, SexpError> {
+        self.end()
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+    type Ok = Sexp;
+    type Error = SexpError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SexpError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Sexp, SexpError> {
+        self.end()
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+    type Ok = Sexp;
+    type Error = SexpError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SexpError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Sexp, SexpError> {
+        self.end()
+    }
+}
+
+impl ser::SerializeTupleVariant for ListSerializer {
+    type Ok = Sexp;
+    type Error = SexpError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SexpError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Sexp, SexpError> {
+        self.end()
+    }
+}
+
+impl ser::SerializeMap for ListSerializer {
+    type Ok = Sexp;
+    type Error = SexpError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SexpError> {
+        self.key = Some(key.serialize(SexpSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SexpError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| SexpError("map value without a key".to_owned()))?;
+
+        self.items
+            .push(Sexp::List(vec![key, value.serialize(SexpSerializer)?]));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Sexp, SexpError> {
+        self.end()
+    }
+}
+
+impl ser::SerializeStruct for ListSerializer {
+    type Ok = Sexp;
+    type Error = SexpError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SexpError> {
+        self.push_field(key, value)
+    }
+
+    fn end(self) -> Result<Sexp, SexpError> {
+        self.end()
+    }
+}
+
+impl ser::SerializeStructVariant for ListSerializer {
+    type Ok = Sexp;
+    type Error = SexpError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SexpError> {
+        self.push_field(key, value)
+    }
+
+    fn end(self) -> Result<Sexp, SexpError> {
+        self.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        location::{Span, WithSpan},
+        sexp::to_sexp,
+        token::RawToken,
+        RawExpression,
+    };
+
+    fn expression(raw: RawExpression, start: usize, end: usize) -> crate::Expression {
+        WithSpan::new(Box::new(raw), Span::new(start, end))
+    }
+
+    #[test]
+    fn binary_expression_test() {
+        let binary = expression(
+            RawExpression::Binary(
+                expression(RawExpression::Int(1, None), 0, 1),
+                WithSpan::new(RawToken::Plus, Span::new(2, 3)),
+                expression(RawExpression::String("a".to_owned()), 4, 7),
+            ),
+            0,
+            7,
+        );
+
+        assert_eq!(
+            to_sexp(&binary).unwrap(),
+            "(Binary@0..7 (Int@0..1 1 nil) Plus@2..3 (String@4..7 \"a\"))"
+        );
+    }
+
+    #[test]
+    fn long_lists_are_broken_test() {
+        let list = expression(
+            RawExpression::List(
+                (0..30)
+                    .map(|i| expression(RawExpression::Int(i, None), i as usize, i as usize + 1))
+                    .collect(),
+            ),
+            0,
+            30,
+        );
+
+        let sexp = to_sexp(&list).unwrap();
+
+        assert!(sexp.starts_with("(List@0..30\n  ("));
+        assert!(sexp.lines().all(|line| line.len() <= 80));
+    }
+}
@@ -5,6 +5,7 @@ use std::mem::discriminant;
 
 use derive_more::Display;
 use phf::phf_map;
+use serde::{Deserialize, Serialize};
 
 use num_traits::ToPrimitive;
 
@@ -12,7 +13,7 @@ use crate::location::WithSpan;
 use crate::precedence::Precedence;
 
 /// Represents error that lexer can fail with.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Display)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Display, Serialize, Deserialize)]
 pub enum LexerError {
     #[display(fmt = "unexpected character `{_0}`")]
     UnexpectedChar(char),
@@ -62,10 +63,91 @@ pub enum LexerError {
     NumberParserError,
     #[display(fmt = "underscore must seperate successive digits")]
     UnderscoreMustSeperateSuccessiveDigits,
+    #[display(fmt = "unknown number literal suffix")]
+    UnknownNumberSuffix,
+    #[display(fmt = "integer suffix on float literal")]
+    IntegerSuffixOnFloatLiteral,
+}
+
+/// Type suffix of the number literal, for example: `u8` in `255u8`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Display, Serialize, Deserialize)]
+pub enum NumberSuffix {
+    #[display(fmt = "i8")]
+    I8,
+    #[display(fmt = "i16")]
+    I16,
+    #[display(fmt = "i32")]
+    I32,
+    #[display(fmt = "i64")]
+    I64,
+    #[display(fmt = "i128")]
+    I128,
+    #[display(fmt = "u8")]
+    U8,
+    #[display(fmt = "u16")]
+    U16,
+    #[display(fmt = "u32")]
+    U32,
+    #[display(fmt = "u64")]
+    U64,
+    #[display(fmt = "u128")]
+    U128,
+    #[display(fmt = "f32")]
+    F32,
+    #[display(fmt = "f64")]
+    F64,
+}
+
+impl NumberSuffix {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "i8" => Self::I8,
+            "i16" => Self::I16,
+            "i32" => Self::I32,
+            "i64" => Self::I64,
+            "i128" => Self::I128,
+            "u8" => Self::U8,
+            "u16" => Self::U16,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "u128" => Self::U128,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            _ => return None,
+        })
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, Self::F32 | Self::F64)
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(
+            self,
+            Self::I8 | Self::I16 | Self::I32 | Self::I64 | Self::I128
+        )
+    }
+
+    /// Maximum value of the integer type, `None` for float types.
+    pub fn max_int(self) -> Option<u128> {
+        Some(match self {
+            Self::I8 => i8::MAX as u128,
+            Self::I16 => i16::MAX as u128,
+            Self::I32 => i32::MAX as u128,
+            Self::I64 => i64::MAX as u128,
+            Self::I128 => i128::MAX as u128,
+            Self::U8 => u8::MAX as u128,
+            Self::U16 => u16::MAX as u128,
+            Self::U32 => u32::MAX as u128,
+            Self::U64 => u64::MAX as u128,
+            Self::U128 => u128::MAX,
+            Self::F32 | Self::F64 => return None,
+        })
+    }
 }
 
 /// Wether the number is integer, float or imaginary literal.
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
 pub enum NumberKind {
     Invalid,
     Int,
@@ -74,16 +156,16 @@ pub enum NumberKind {
 }
 
 /// Represents token without a specific location in source text.
-#[derive(Clone, Debug, PartialEq, Display, Default)]
+#[derive(Clone, Debug, PartialEq, Display, Default, Serialize, Deserialize)]
 pub enum RawToken {
     #[display(fmt = "identifier")]
     Identifier(String),
     #[display(fmt = "string literal")]
     String(String),
     #[display(fmt = "integer literal")]
-    Int(u64),
+    Int(u128, Option<NumberSuffix>),
     #[display(fmt = "float literal")]
-    Float(f64),
+    Float(f64, Option<NumberSuffix>),
     #[display(fmt = "imaginary number literal")]
     Imag(f64),
     #[display(fmt = "character literal")]
@@ -139,6 +221,12 @@ pub enum RawToken {
     As,
     #[display(fmt = "`for`")]
     For,
+    #[display(fmt = "`const`")]
+    Const,
+    #[display(fmt = "`type`")]
+    Type,
+    #[display(fmt = "`where`")]
+    Where,
 
     #[display(fmt = "`?`")]
     QuestionMark,
@@ -234,6 +322,10 @@ pub enum RawToken {
     #[display(fmt = "comment")]
     Comment(String),
 
+    /// Emitted only by lexer with trivia enabled.
+    #[display(fmt = "whitespace")]
+    Whitespace,
+
     #[display(fmt = "end of file")]
     EndOfFile,
 
@@ -298,11 +390,31 @@ impl RawToken {
         }
     }
 
+    /// Value carried by the token (identifier name, literal value, comment
+    /// text or error message) written in the source-like form.
+    pub fn payload(&self) -> Option<String> {
+        match self {
+            Self::Identifier(i) => Some(i.to_owned()),
+            Self::String(s) | Self::Comment(s) => Some(format!("{s:?}")),
+            Self::Int(i, suffix) => Some(format!("{i}{}", display_suffix(suffix))),
+            Self::Float(f, suffix) => Some(format!("{f}{}", display_suffix(suffix))),
+            Self::Imag(i) => Some(format!("{i}i")),
+            Self::Char(c) => Some(format!("{c:?}")),
+            Self::Bool(b) => Some(b.to_string()),
+            Self::Invalid(e) => Some(e.to_string()),
+            _ => None,
+        }
+    }
+
     pub fn is(&self, raw: &Self) -> bool {
         discriminant(self) == discriminant(raw)
     }
 }
 
+fn display_suffix(suffix: &Option<NumberSuffix>) -> String {
+    suffix.map(|s| s.to_string()).unwrap_or_default()
+}
+
 pub type Token = WithSpan<RawToken>;
 
 /// List of reserved Ry names: keywords, boolean literals & etc..
@@ -325,4 +437,7 @@ pub static RESERVED: phf::Map<&'static str, RawToken> = phf_map! {
     "var" => RawToken::Var,
     "as" => RawToken::As,
     "for" => RawToken::For,
+    "const" => RawToken::Const,
+    "type" => RawToken::Type,
+    "where" => RawToken::Where,
 };
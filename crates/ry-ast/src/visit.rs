@@ -0,0 +1,397 @@
+//! `visit.rs` - defines [`Visitor`] trait for traversing AST by reference.
+//!
+//! Each `visit_*` method by default calls corresponding `walk_*` function,
+//! that visits all children of the node. When overriding a method, call
+//! `walk_*` function inside it to continue traversal into the children:
+//!
+//! ```
+//! use ry_ast::{visit::*, Expression, RawExpression};
+//!
+//! #[derive(Default)]
+//! struct IntCounter {
+//!     count: usize,
+//! }
+//!
+//! impl Visitor for IntCounter {
+//!     fn visit_expression(&mut self, expression: &Expression) {
+//!         if let RawExpression::Int(..) = *expression.value {
+//!             self.count += 1;
+//!         }
+//!
+//!         walk_expression(self, expression);
+//!     }
+//! }
+//! ```
+use crate::*;
+
+pub trait Visitor: Sized {
+    fn visit_program_unit(&mut self, program_unit: &ProgramUnit) {
+        walk_program_unit(self, program_unit);
+    }
+
+    fn visit_import(&mut self, _import: &Import) {}
+
+    fn visit_top_level_statement(&mut self, statement: &TopLevelStatement) {
+        walk_top_level_statement(self, statement);
+    }
+
+    fn visit_function_decl(&mut self, function_decl: &FunctionDecl) {
+        walk_function_decl(self, function_decl);
+    }
+
+    fn visit_struct_decl(&mut self, struct_decl: &StructDecl) {
+        walk_struct_decl(self, struct_decl);
+    }
+
+    fn visit_struct_member(&mut self, member: &StructMemberDef) {
+        walk_struct_member(self, member);
+    }
+
+    fn visit_trait_decl(&mut self, trait_decl: &TraitDecl) {
+        walk_trait_decl(self, trait_decl);
+    }
+
+    fn visit_impl(&mut self, r#impl: &Impl) {
+        walk_impl(self, r#impl);
+    }
+
+    fn visit_trait_method(&mut self, method: &TraitMethod) {
+        walk_trait_method(self, method);
+    }
+
+    fn visit_enum_decl(&mut self, _enum_decl: &EnumDecl) {}
+
+    fn visit_const_decl(&mut self, const_decl: &ConstDecl) {
+        walk_const_decl(self, const_decl);
+    }
+
+    fn visit_var_decl(&mut self, var_decl: &VarDecl) {
+        walk_var_decl(self, var_decl);
+    }
+
+    fn visit_type_alias(&mut self, alias: &TypeAlias) {
+        walk_type_alias(self, alias);
+    }
+
+    fn visit_newtype(&mut self, newtype: &Newtype) {
+        walk_newtype(self, newtype);
+    }
+
+    fn visit_generic_annotation(&mut self, annotation: &GenericAnnotation) {
+        walk_generic_annotation(self, annotation);
+    }
+
+    fn visit_associated_type(&mut self, associated_type: &AssociatedType) {
+        walk_associated_type(self, associated_type);
+    }
+
+    fn visit_where_clause_item(&mut self, item: &WhereClauseItem) {
+        walk_where_clause_item(self, item);
+    }
+
+    fn visit_function_param(&mut self, param: &FunctionParam) {
+        walk_function_param(self, param);
+    }
+
+    fn visit_statements_block(&mut self, block: &[Statement]) {
+        walk_statements_block(self, block);
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression);
+    }
+
+    fn visit_type(&mut self, r#type: &Type) {
+        walk_type(self, r#type);
+    }
+}
+
+pub fn walk_program_unit<V: Visitor>(visitor: &mut V, program_unit: &ProgramUnit) {
+    for import in &program_unit.imports {
+        visitor.visit_import(import);
+    }
+
+    for (_, statement) in &program_unit.top_level_statements {
+        visitor.visit_top_level_statement(statement);
+    }
+}
+
+pub fn walk_top_level_statement<V: Visitor>(visitor: &mut V, statement: &TopLevelStatement) {
+    match statement {
+        TopLevelStatement::FunctionDecl(f) => visitor.visit_function_decl(f),
+        TopLevelStatement::StructDecl(s) => visitor.visit_struct_decl(s),
+        TopLevelStatement::TraitDecl(t) => visitor.visit_trait_decl(t),
+        TopLevelStatement::Impl(i) => visitor.visit_impl(i),
+        TopLevelStatement::EnumDecl(e) => visitor.visit_enum_decl(e),
+        TopLevelStatement::ConstDecl(c) => visitor.visit_const_decl(c),
+        TopLevelStatement::VarDecl(v) => visitor.visit_var_decl(v),
+        TopLevelStatement::TypeAlias(a) => visitor.visit_type_alias(a),
+        TopLevelStatement::Newtype(n) => visitor.visit_newtype(n),
+    }
+}
+
+pub fn walk_function_decl<V: Visitor>(visitor: &mut V, function_decl: &FunctionDecl) {
+    walk_generic_annotations(visitor, &function_decl.def.generic_annotations);
+
+    for param in &function_decl.def.params {
+        visitor.visit_function_param(param);
+    }
+
+    if let Some(return_type) = &function_decl.def.return_type {
+        visitor.visit_type(return_type);
+    }
+
+    walk_where_clause(visitor, &function_decl.def.where_clause);
+
+    visitor.visit_statements_block(&function_decl.stmts);
+}
+
+pub fn walk_struct_decl<V: Visitor>(visitor: &mut V, struct_decl: &StructDecl) {
+    walk_generic_annotations(visitor, &struct_decl.generic_annotations);
+
+    for (_, member) in &struct_decl.members {
+        visitor.visit_struct_member(member);
+    }
+}
+
+pub fn walk_const_decl<V: Visitor>(visitor: &mut V, const_decl: &ConstDecl) {
+    if let Some(r#type) = &const_decl.r#type {
+        visitor.visit_type(r#type);
+    }
+
+    visitor.visit_expression(&const_decl.value);
+}
+
+pub fn walk_var_decl<V: Visitor>(visitor: &mut V, var_decl: &VarDecl) {
+    if let Some(r#type) = &var_decl.r#type {
+        visitor.visit_type(r#type);
+    }
+
+    visitor.visit_expression(&var_decl.value);
+}
+
+pub fn walk_type_alias<V: Visitor>(visitor: &mut V, alias: &TypeAlias) {
+    walk_generic_annotations(visitor, &alias.generic_annotations);
+
+    visitor.visit_type(&alias.value);
+}
+
+pub fn walk_newtype<V: Visitor>(visitor: &mut V, newtype: &Newtype) {
+    walk_generic_annotations(visitor, &newtype.generic_annotations);
+
+    visitor.visit_type(&newtype.value);
+}
+
+pub fn walk_struct_member<V: Visitor>(visitor: &mut V, member: &StructMemberDef) {
+    visitor.visit_type(&member.r#type);
+}
+
+pub fn walk_trait_decl<V: Visitor>(visitor: &mut V, trait_decl: &TraitDecl) {
+    walk_generic_annotations(visitor, &trait_decl.generic_annotations);
+
+    for supertrait in &trait_decl.supertraits {
+        visitor.visit_type(supertrait);
+    }
+
+    for (_, associated_type) in &trait_decl.associated_types {
+        visitor.visit_associated_type(associated_type);
+    }
+
+    for (_, method) in &trait_decl.methods {
+        visitor.visit_trait_method(method);
+    }
+}
+
+pub fn walk_impl<V: Visitor>(visitor: &mut V, r#impl: &Impl) {
+    walk_generic_annotations(visitor, &r#impl.global_generic_annotations);
+
+    visitor.visit_type(&r#impl.r#type);
+
+    if let Some(r#trait) = &r#impl.r#trait {
+        visitor.visit_type(r#trait);
+    }
+
+    walk_where_clause(visitor, &r#impl.where_clause);
+
+    for (_, associated_type) in &r#impl.associated_types {
+        visitor.visit_associated_type(associated_type);
+    }
+
+    for (_, method) in &r#impl.methods {
+        visitor.visit_trait_method(method);
+    }
+}
+
+pub fn walk_trait_method<V: Visitor>(visitor: &mut V, method: &TraitMethod) {
+    walk_generic_annotations(visitor, &method.generic_annotations);
+
+    for param in &method.params {
+        visitor.visit_function_param(param);
+    }
+
+    if let Some(return_type) = &method.return_type {
+        visitor.visit_type(return_type);
+    }
+
+    walk_where_clause(visitor, &method.where_clause);
+
+    if let Some(body) = &method.body {
+        visitor.visit_statements_block(body);
+    }
+}
+
+fn walk_generic_annotations<V: Visitor>(visitor: &mut V, annotations: &GenericAnnotations) {
+    for annotation in annotations {
+        visitor.visit_generic_annotation(annotation);
+    }
+}
+
+pub fn walk_generic_annotation<V: Visitor>(visitor: &mut V, annotation: &GenericAnnotation) {
+    for constraint in &annotation.constraints {
+        visitor.visit_type(constraint);
+    }
+}
+
+pub fn walk_associated_type<V: Visitor>(visitor: &mut V, associated_type: &AssociatedType) {
+    for constraint in &associated_type.constraints {
+        visitor.visit_type(constraint);
+    }
+
+    if let Some(value) = &associated_type.value {
+        visitor.visit_type(value);
+    }
+}
+
+fn walk_where_clause<V: Visitor>(visitor: &mut V, where_clause: &WhereClause) {
+    for item in where_clause {
+        visitor.visit_where_clause_item(item);
+    }
+}
+
+pub fn walk_where_clause_item<V: Visitor>(visitor: &mut V, item: &WhereClauseItem) {
+    visitor.visit_type(&item.r#type);
+
+    for constraint in &item.constraints {
+        visitor.visit_type(constraint);
+    }
+}
+
+pub fn walk_function_param<V: Visitor>(visitor: &mut V, param: &FunctionParam) {
+    visitor.visit_type(&param.r#type);
+
+    if let Some(default_value) = &param.default_value {
+        visitor.visit_expression(default_value);
+    }
+}
+
+pub fn walk_statements_block<V: Visitor>(visitor: &mut V, block: &[Statement]) {
+    for statement in block {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_statement<V: Visitor>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::Expression(e)
+        | Statement::ExpressionWithoutSemicolon(e)
+        | Statement::Return(e)
+        | Statement::Defer(e) => visitor.visit_expression(e),
+        Statement::Var(_, r#type, value) => {
+            if let Some(r#type) = r#type {
+                visitor.visit_type(r#type);
+            }
+
+            visitor.visit_expression(value);
+        }
+    }
+}
+
+pub fn walk_expression<V: Visitor>(visitor: &mut V, expression: &Expression) {
+    match &*expression.value {
+        RawExpression::String(_)
+        | RawExpression::Int(..)
+        | RawExpression::Float(..)
+        | RawExpression::Imag(_)
+        | RawExpression::Bool(_)
+        | RawExpression::Char(_)
+        | RawExpression::StaticName(_) => {}
+        RawExpression::List(elements) => {
+            for element in elements {
+                visitor.visit_expression(element);
+            }
+        }
+        RawExpression::Binary(left, _, right) => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        }
+        RawExpression::As(left, r#type) => {
+            visitor.visit_expression(left);
+            visitor.visit_type(r#type);
+        }
+        RawExpression::PrefixOrPostfix(_, inner) | RawExpression::Property(inner, _) => {
+            visitor.visit_expression(inner);
+        }
+        RawExpression::Struct(_, fields) | RawExpression::Map(fields) => {
+            for (_, value) in fields.values() {
+                visitor.visit_expression(&value.value);
+            }
+        }
+        RawExpression::Call(generics, caller, arguments) => {
+            visitor.visit_expression(caller);
+
+            for generic in generics {
+                visitor.visit_type(generic);
+            }
+
+            for argument in arguments {
+                visitor.visit_expression(argument);
+            }
+        }
+        RawExpression::Index(left, index) => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(index);
+        }
+        RawExpression::If((condition, block), else_ifs, r#else) => {
+            visitor.visit_expression(condition);
+            visitor.visit_statements_block(block);
+
+            for (condition, block) in else_ifs {
+                visitor.visit_expression(condition);
+                visitor.visit_statements_block(block);
+            }
+
+            if let Some(block) = r#else {
+                visitor.visit_statements_block(block);
+            }
+        }
+        RawExpression::While(condition, block) => {
+            visitor.visit_expression(condition);
+            visitor.visit_statements_block(block);
+        }
+    }
+}
+
+pub fn walk_type<V: Visitor>(visitor: &mut V, r#type: &Type) {
+    match &*r#type.value {
+        RawType::Array(inner, size) => {
+            visitor.visit_type(inner);
+
+            if let Some(size) = size {
+                visitor.visit_expression(size);
+            }
+        }
+        RawType::Pointer(inner) | RawType::Option(inner) => {
+            visitor.visit_type(inner);
+        }
+        RawType::Primary(_, generics) => {
+            for generic in generics {
+                visitor.visit_type(generic);
+            }
+        }
+        RawType::Generic(_) => {}
+    }
+}
@@ -0,0 +1,456 @@
+//! `visit_mut.rs` - defines [`VisitorMut`] trait for traversing AST by mutable
+//! reference, so that passes can rewrite nodes in place (for example, fold
+//! constant expressions).
+//!
+//! Works the same way as [`Visitor`](crate::visit::Visitor): each
+//! `visit_*_mut` method by default calls corresponding `walk_*_mut` function.
+use crate::*;
+
+pub trait VisitorMut: Sized {
+    fn visit_program_unit_mut(&mut self, program_unit: &mut ProgramUnit) {
+        walk_program_unit_mut(self, program_unit);
+    }
+
+    fn visit_import_mut(&mut self, _import: &mut Import) {}
+
+    fn visit_top_level_statement_mut(&mut self, statement: &mut TopLevelStatement) {
+        walk_top_level_statement_mut(self, statement);
+    }
+
+    fn visit_function_decl_mut(&mut self, function_decl: &mut FunctionDecl) {
+        walk_function_decl_mut(self, function_decl);
+    }
+
+    fn visit_struct_decl_mut(&mut self, struct_decl: &mut StructDecl) {
+        walk_struct_decl_mut(self, struct_decl);
+    }
+
+    fn visit_struct_member_mut(&mut self, member: &mut StructMemberDef) {
+        walk_struct_member_mut(self, member);
+    }
+
+    fn visit_trait_decl_mut(&mut self, trait_decl: &mut TraitDecl) {
+        walk_trait_decl_mut(self, trait_decl);
+    }
+
+    fn visit_impl_mut(&mut self, r#impl: &mut Impl) {
+        walk_impl_mut(self, r#impl);
+    }
+
+    fn visit_trait_method_mut(&mut self, method: &mut TraitMethod) {
+        walk_trait_method_mut(self, method);
+    }
+
+    fn visit_enum_decl_mut(&mut self, _enum_decl: &mut EnumDecl) {}
+
+    fn visit_const_decl_mut(&mut self, const_decl: &mut ConstDecl) {
+        walk_const_decl_mut(self, const_decl);
+    }
+
+    fn visit_var_decl_mut(&mut self, var_decl: &mut VarDecl) {
+        walk_var_decl_mut(self, var_decl);
+    }
+
+    fn visit_type_alias_mut(&mut self, alias: &mut TypeAlias) {
+        walk_type_alias_mut(self, alias);
+    }
+
+    fn visit_newtype_mut(&mut self, newtype: &mut Newtype) {
+        walk_newtype_mut(self, newtype);
+    }
+
+    fn visit_generic_annotation_mut(&mut self, annotation: &mut GenericAnnotation) {
+        walk_generic_annotation_mut(self, annotation);
+    }
+
+    fn visit_associated_type_mut(&mut self, associated_type: &mut AssociatedType) {
+        walk_associated_type_mut(self, associated_type);
+    }
+
+    fn visit_where_clause_item_mut(&mut self, item: &mut WhereClauseItem) {
+        walk_where_clause_item_mut(self, item);
+    }
+
+    fn visit_function_param_mut(&mut self, param: &mut FunctionParam) {
+        walk_function_param_mut(self, param);
+    }
+
+    fn visit_statements_block_mut(&mut self, block: &mut StatementsBlock) {
+        walk_statements_block_mut(self, block);
+    }
+
+    fn visit_statement_mut(&mut self, statement: &mut Statement) {
+        walk_statement_mut(self, statement);
+    }
+
+    fn visit_expression_mut(&mut self, expression: &mut Expression) {
+        walk_expression_mut(self, expression);
+    }
+
+    fn visit_type_mut(&mut self, r#type: &mut Type) {
+        walk_type_mut(self, r#type);
+    }
+}
+
+pub fn walk_program_unit_mut<V: VisitorMut>(visitor: &mut V, program_unit: &mut ProgramUnit) {
+    for import in &mut program_unit.imports {
+        visitor.visit_import_mut(import);
+    }
+
+    for (_, statement) in &mut program_unit.top_level_statements {
+        visitor.visit_top_level_statement_mut(statement);
+    }
+}
+
+pub fn walk_top_level_statement_mut<V: VisitorMut>(
+    visitor: &mut V,
+    statement: &mut TopLevelStatement,
+) {
+    match statement {
+        TopLevelStatement::FunctionDecl(f) => visitor.visit_function_decl_mut(f),
+        TopLevelStatement::StructDecl(s) => visitor.visit_struct_decl_mut(s),
+        TopLevelStatement::TraitDecl(t) => visitor.visit_trait_decl_mut(t),
+        TopLevelStatement::Impl(i) => visitor.visit_impl_mut(i),
+        TopLevelStatement::EnumDecl(e) => visitor.visit_enum_decl_mut(e),
+        TopLevelStatement::ConstDecl(c) => visitor.visit_const_decl_mut(c),
+        TopLevelStatement::VarDecl(v) => visitor.visit_var_decl_mut(v),
+        TopLevelStatement::TypeAlias(a) => visitor.visit_type_alias_mut(a),
+        TopLevelStatement::Newtype(n) => visitor.visit_newtype_mut(n),
+    }
+}
+
+pub fn walk_function_decl_mut<V: VisitorMut>(visitor: &mut V, function_decl: &mut FunctionDecl) {
+    walk_generic_annotations_mut(visitor, &mut function_decl.def.generic_annotations);
+
+    for param in &mut function_decl.def.params {
+        visitor.visit_function_param_mut(param);
+    }
+
+    if let Some(return_type) = &mut function_decl.def.return_type {
+        visitor.visit_type_mut(return_type);
+    }
+
+    walk_where_clause_mut(visitor, &mut function_decl.def.where_clause);
+
+    visitor.visit_statements_block_mut(&mut function_decl.stmts);
+}
+
+pub fn walk_struct_decl_mut<V: VisitorMut>(visitor: &mut V, struct_decl: &mut StructDecl) {
+    walk_generic_annotations_mut(visitor, &mut struct_decl.generic_annotations);
+
+    for (_, member) in &mut struct_decl.members {
+        visitor.visit_struct_member_mut(member);
+    }
+}
+
+pub fn walk_const_decl_mut<V: VisitorMut>(visitor: &mut V, const_decl: &mut ConstDecl) {
+    if let Some(r#type) = &mut const_decl.r#type {
+        visitor.visit_type_mut(r#type);
+    }
+
+    visitor.visit_expression_mut(&mut const_decl.value);
+}
+
+pub fn walk_var_decl_mut<V: VisitorMut>(visitor: &mut V, var_decl: &mut VarDecl) {
+    if let Some(r#type) = &mut var_decl.r#type {
+        visitor.visit_type_mut(r#type);
+    }
+
+    visitor.visit_expression_mut(&mut var_decl.value);
+}
+
+pub fn walk_type_alias_mut<V: VisitorMut>(visitor: &mut V, alias: &mut TypeAlias) {
+    walk_generic_annotations_mut(visitor, &mut alias.generic_annotations);
+
+    visitor.visit_type_mut(&mut alias.value);
+}
+
+pub fn walk_newtype_mut<V: VisitorMut>(visitor: &mut V, newtype: &mut Newtype) {
+    walk_generic_annotations_mut(visitor, &mut newtype.generic_annotations);
+
+    visitor.visit_type_mut(&mut newtype.value);
+}
+
+pub fn walk_struct_member_mut<V: VisitorMut>(visitor: &mut V, member: &mut StructMemberDef) {
+    visitor.visit_type_mut(&mut member.r#type);
+}
+
+pub fn walk_trait_decl_mut<V: VisitorMut>(visitor: &mut V, trait_decl: &mut TraitDecl) {
+    walk_generic_annotations_mut(visitor, &mut trait_decl.generic_annotations);
+
+    for supertrait in &mut trait_decl.supertraits {
+        visitor.visit_type_mut(supertrait);
+    }
+
+    for (_, associated_type) in &mut trait_decl.associated_types {
+        visitor.visit_associated_type_mut(associated_type);
+    }
+
+    for (_, method) in &mut trait_decl.methods {
+        visitor.visit_trait_method_mut(method);
+    }
+}
+
+pub fn walk_impl_mut<V: VisitorMut>(visitor: &mut V, r#impl: &mut Impl) {
+    walk_generic_annotations_mut(visitor, &mut r#impl.global_generic_annotations);
+
+    visitor.visit_type_mut(&mut r#impl.r#type);
+
+    if let Some(r#trait) = &mut r#impl.r#trait {
+        visitor.visit_type_mut(r#trait);
+    }
+
+    walk_where_clause_mut(visitor, &mut r#impl.where_clause);
+
+    for (_, associated_type) in &mut r#impl.associated_types {
+        visitor.visit_associated_type_mut(associated_type);
+    }
+
+    for (_, method) in &mut r#impl.methods {
+        visitor.visit_trait_method_mut(method);
+    }
+}
+
+pub fn walk_trait_method_mut<V: VisitorMut>(visitor: &mut V, method: &mut TraitMethod) {
+    walk_generic_annotations_mut(visitor, &mut method.generic_annotations);
+
+    for param in &mut method.params {
+        visitor.visit_function_param_mut(param);
+    }
+
+    if let Some(return_type) = &mut method.return_type {
+        visitor.visit_type_mut(return_type);
+    }
+
+    walk_where_clause_mut(visitor, &mut method.where_clause);
+
+    if let Some(body) = &mut method.body {
+        visitor.visit_statements_block_mut(body);
+    }
+}
+
+fn walk_generic_annotations_mut<V: VisitorMut>(
+    visitor: &mut V,
+    annotations: &mut GenericAnnotations,
+) {
+    for annotation in annotations {
+        visitor.visit_generic_annotation_mut(annotation);
+    }
+}
+
+pub fn walk_generic_annotation_mut<V: VisitorMut>(
+    visitor: &mut V,
+    annotation: &mut GenericAnnotation,
+) {
+    for constraint in &mut annotation.constraints {
+        visitor.visit_type_mut(constraint);
+    }
+}
+
+pub fn walk_associated_type_mut<V: VisitorMut>(
+    visitor: &mut V,
+    associated_type: &mut AssociatedType,
+) {
+    for constraint in &mut associated_type.constraints {
+        visitor.visit_type_mut(constraint);
+    }
+
+    if let Some(value) = &mut associated_type.value {
+        visitor.visit_type_mut(value);
+    }
+}
+
+fn walk_where_clause_mut<V: VisitorMut>(visitor: &mut V, where_clause: &mut WhereClause) {
+    for item in where_clause {
+        visitor.visit_where_clause_item_mut(item);
+    }
+}
+
+pub fn walk_where_clause_item_mut<V: VisitorMut>(visitor: &mut V, item: &mut WhereClauseItem) {
+    visitor.visit_type_mut(&mut item.r#type);
+
+    for constraint in &mut item.constraints {
+        visitor.visit_type_mut(constraint);
+    }
+}
+
+pub fn walk_function_param_mut<V: VisitorMut>(visitor: &mut V, param: &mut FunctionParam) {
+    visitor.visit_type_mut(&mut param.r#type);
+
+    if let Some(default_value) = &mut param.default_value {
+        visitor.visit_expression_mut(default_value);
+    }
+}
+
+pub fn walk_statements_block_mut<V: VisitorMut>(visitor: &mut V, block: &mut StatementsBlock) {
+    for statement in block {
+        visitor.visit_statement_mut(statement);
+    }
+}
+
+pub fn walk_statement_mut<V: VisitorMut>(visitor: &mut V, statement: &mut Statement) {
+    match statement {
+        Statement::Expression(e)
+        | Statement::ExpressionWithoutSemicolon(e)
+        | Statement::Return(e)
+        | Statement::Defer(e) => visitor.visit_expression_mut(e),
+        Statement::Var(_, r#type, value) => {
+            if let Some(r#type) = r#type {
+                visitor.visit_type_mut(r#type);
+            }
+
+            visitor.visit_expression_mut(value);
+        }
+    }
+}
+
+pub fn walk_expression_mut<V: VisitorMut>(visitor: &mut V, expression: &mut Expression) {
+    match &mut *expression.value {
+        RawExpression::String(_)
+        | RawExpression::Int(..)
+        | RawExpression::Float(..)
+        | RawExpression::Imag(_)
+        | RawExpression::Bool(_)
+        | RawExpression::Char(_)
+        | RawExpression::StaticName(_) => {}
+        RawExpression::List(elements) => {
+            for element in elements {
+                visitor.visit_expression_mut(element);
+            }
+        }
+        RawExpression::Binary(left, _, right) => {
+            visitor.visit_expression_mut(left);
+            visitor.visit_expression_mut(right);
+        }
+        RawExpression::As(left, r#type) => {
+            visitor.visit_expression_mut(left);
+            visitor.visit_type_mut(r#type);
+        }
+        RawExpression::PrefixOrPostfix(_, inner) | RawExpression::Property(inner, _) => {
+            visitor.visit_expression_mut(inner);
+        }
+        RawExpression::Struct(_, fields) | RawExpression::Map(fields) => {
+            for (_, value) in fields.values_mut() {
+                visitor.visit_expression_mut(&mut value.value);
+            }
+        }
+        RawExpression::Call(generics, caller, arguments) => {
+            visitor.visit_expression_mut(caller);
+
+            for generic in generics {
+                visitor.visit_type_mut(generic);
+            }
+
+            for argument in arguments {
+                visitor.visit_expression_mut(argument);
+            }
+        }
+        RawExpression::Index(left, index) => {
+            visitor.visit_expression_mut(left);
+            visitor.visit_expression_mut(index);
+        }
+        RawExpression::If((condition, block), else_ifs, r#else) => {
+            visitor.visit_expression_mut(condition);
+            visitor.visit_statements_block_mut(block);
+
+            for (condition, block) in else_ifs {
+                visitor.visit_expression_mut(condition);
+                visitor.visit_statements_block_mut(block);
+            }
+
+            if let Some(block) = r#else {
+                visitor.visit_statements_block_mut(block);
+            }
+        }
+        RawExpression::While(condition, block) => {
+            visitor.visit_expression_mut(condition);
+            visitor.visit_statements_block_mut(block);
+        }
+    }
+}
+
+pub fn walk_type_mut<V: VisitorMut>(visitor: &mut V, r#type: &mut Type) {
+    match &mut *r#type.value {
+        RawType::Array(inner, size) => {
+            visitor.visit_type_mut(inner);
+
+            if let Some(size) = size {
+                visitor.visit_expression_mut(size);
+            }
+        }
+        RawType::Pointer(inner) | RawType::Option(inner) => {
+            visitor.visit_type_mut(inner);
+        }
+        RawType::Primary(_, generics) => {
+            for generic in generics {
+                visitor.visit_type_mut(generic);
+            }
+        }
+        RawType::Generic(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{location::WithSpan, token::RawToken, visit::*, visit_mut::*};
+
+    fn int(value: u128) -> Expression {
+        WithSpan::new(
+            Box::new(RawExpression::Int(value, None)),
+            Default::default(),
+        )
+    }
+
+    fn binary(left: Expression, right: Expression) -> Expression {
+        WithSpan::new(
+            Box::new(RawExpression::Binary(
+                left,
+                WithSpan::new(RawToken::Plus, Default::default()),
+                right,
+            )),
+            Default::default(),
+        )
+    }
+
+    struct Doubler;
+
+    impl VisitorMut for Doubler {
+        fn visit_expression_mut(&mut self, expression: &mut Expression) {
+            if let RawExpression::Int(value, _) = &mut *expression.value {
+                *value *= 2;
+            }
+
+            walk_expression_mut(self, expression);
+        }
+    }
+
+    #[derive(Default)]
+    struct Summator {
+        sum: u128,
+    }
+
+    impl Visitor for Summator {
+        fn visit_expression(&mut self, expression: &Expression) {
+            if let RawExpression::Int(value, _) = *expression.value {
+                self.sum += value;
+            }
+
+            walk_expression(self, expression);
+        }
+    }
+
+    #[test]
+    fn visitor_mut_test() {
+        let mut statements = vec![
+            Statement::Expression(binary(int(1), binary(int(2), int(3)))),
+            Statement::Var(WithSpan::default(), None, int(4)),
+        ];
+
+        Doubler.visit_statements_block_mut(&mut statements);
+
+        let mut summator = Summator::default();
+        summator.visit_statements_block(&statements);
+
+        assert_eq!(summator.sum, 20);
+    }
+}
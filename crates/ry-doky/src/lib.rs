@@ -0,0 +1,126 @@
+//! `lib.rs` - renders documentation of the source file in Markdown.
+//!
+//! Documentation consists of the module docstring and of public top level
+//! items in order of declaration, each with its own docstring. Type aliases
+//! and newtypes are shown with their signatures; aliases, that refer to other
+//! aliases, also show the type they finally stand for.
+use ry_ast::{ProgramUnit, TopLevelStatement};
+use ry_static_analyzer::types::TypeAliases;
+
+/// Renders documentation of the file `name`.
+pub fn document(name: &str, ast: &ProgramUnit) -> String {
+    let aliases = TypeAliases::new(ast);
+
+    let mut output = format!("# {name}\n");
+
+    if !ast.docstring.is_empty() {
+        output.push_str(&format!("\n{}\n", ast.docstring));
+    }
+
+    for (docstring, statement) in &ast.top_level_statements {
+        let Some((kind, name)) = item(statement) else {
+            continue;
+        };
+
+        output.push_str(&format!("\n## {kind} `{name}`\n"));
+
+        match statement {
+            TopLevelStatement::TypeAlias(alias) => {
+                output.push_str(&format!("\n```ry\n{alias}\n```\n"));
+
+                if let Ok(resolved) = aliases.resolve(&alias.value) {
+                    if resolved.value != alias.value.value {
+                        output.push_str(&format!("\nExpands to `{}`.\n", resolved.value));
+                    }
+                }
+            }
+            TopLevelStatement::Newtype(newtype) => {
+                output.push_str(&format!("\n```ry\n{newtype}\n```\n"));
+            }
+            _ => {}
+        }
+
+        if !docstring.is_empty() {
+            output.push_str(&format!("\n{docstring}\n"));
+        }
+    }
+
+    output
+}
+
+/// Kind and name of the public item, `None` for private items and
+/// implementations.
+fn item(statement: &TopLevelStatement) -> Option<(&'static str, &str)> {
+    let (kind, public, name) = match statement {
+        TopLevelStatement::FunctionDecl(function) => {
+            ("function", &function.def.public, &function.def.name)
+        }
+        TopLevelStatement::StructDecl(r#struct) => ("struct", &r#struct.public, &r#struct.name),
+        TopLevelStatement::TraitDecl(r#trait) => ("trait", &r#trait.public, &r#trait.name),
+        TopLevelStatement::EnumDecl(r#enum) => ("enum", &r#enum.public, &r#enum.name),
+        TopLevelStatement::ConstDecl(r#const) => ("constant", &r#const.public, &r#const.name),
+        TopLevelStatement::VarDecl(var) => ("variable", &var.public, &var.name),
+        TopLevelStatement::TypeAlias(alias) => ("type alias", &alias.public, &alias.name),
+        TopLevelStatement::Newtype(newtype) => ("newtype", &newtype.public, &newtype.name),
+        TopLevelStatement::Impl(_) => return None,
+    };
+
+    public.as_ref().map(|_| (kind, name.value.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use ry_parser::Parser;
+
+    use super::*;
+
+    fn document_source(source: &str) -> String {
+        document("test.ry", &Parser::new(source).parse().unwrap())
+    }
+
+    #[test]
+    fn aliases_test() {
+        assert_eq!(
+            document_source(
+                "//! Lengths.\n\
+                 \n\
+                 /// Length in meters.\n\
+                 pub type Meters f64;\n\
+                 /// Table of lengths.\n\
+                 pub type Table<T> = Map<string, [T?]>;\n\
+                 pub type Lengths = Table<Meters>;\n\
+                 type Private = i32;\n\
+                 pub fun f() {}\n"
+            ),
+            "# test.ry\n\
+             \n\
+             Lengths.\n\
+             \n\
+             ## newtype `Meters`\n\
+             \n\
+             ```ry\n\
+             pub type Meters f64\n\
+             ```\n\
+             \n\
+             Length in meters.\n\
+             \n\
+             ## type alias `Table`\n\
+             \n\
+             ```ry\n\
+             pub type Table<T> = Map<string, [T?]>\n\
+             ```\n\
+             \n\
+             Table of lengths.\n\
+             \n\
+             ## type alias `Lengths`\n\
+             \n\
+             ```ry\n\
+             pub type Lengths = Table<Meters>\n\
+             ```\n\
+             \n\
+             Expands to `Map<string, [Meters?]>`.\n\
+             \n\
+             ## function `f`\n"
+        );
+    }
+}
@@ -0,0 +1,295 @@
+//! `cache.rs` - caches analyzed programs in the `target/` directory.
+//!
+//! Artifact is a JSON file with the analyzed (constant folded) AST of every
+//! file of the program together with the source code it was built from.
+//! The artifact is fresh, while sources of all the files and paths of their
+//! imports stay the same, then parsing and analysis are skipped.
+//!
+//! Every file is also compiled to bytecode into its own object file (see
+//! [`ry_interpreter::object`]) in the `objects/` directory. Object file is
+//! reused, while the source code of the file stays the same, even if other
+//! files of the program have changed.
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use ry_ast::{normalize_path, ProgramUnit};
+use ry_interpreter::{
+    bytecode,
+    compiler::compile_module,
+    object::{self, Object},
+};
+use ry_manifest::ModuleRoots;
+use ry_static_analyzer::{error::AnalyzerError, globals::Module};
+use serde::{Deserialize, Serialize};
+
+use crate::{check, load_program, resolve_imports, SourceFile};
+
+/// Version of the compiler, artifacts of other versions are not reused.
+const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Directory for object files, relative to the directory of the artifact.
+pub const OBJECTS_DIRECTORY: &str = "objects";
+
+/// Program without errors, ready to be executed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Program {
+    compiler: String,
+    /// Files of the program, the first one is the entry point.
+    pub files: Vec<CompiledFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompiledFile {
+    pub name: String,
+    pub contents: String,
+    pub imports: Vec<usize>,
+    pub ast: ProgramUnit,
+}
+
+/// Result of [`build`].
+pub enum Build {
+    /// Cached program is up to date.
+    Fresh(Program),
+    /// Program was compiled and saved.
+    Compiled(Program),
+    /// Program has errors: parse errors are in the files, analysis errors
+    /// are returned together with indices of files.
+    Failed(Vec<SourceFile>, Vec<(usize, AnalyzerError)>),
+}
+
+impl Program {
+    /// Converts program without errors.
+    fn new(program: Vec<SourceFile>) -> Self {
+        Self {
+            compiler: COMPILER_VERSION.to_owned(),
+            files: program
+                .into_iter()
+                .map(|file| CompiledFile {
+                    name: file.name,
+                    contents: file.contents,
+                    imports: file.imports,
+                    ast: file.ast.expect("program must not have parse errors"),
+                })
+                .collect(),
+        }
+    }
+
+    /// Reads the artifact. Returns `None` if it doesn't exist, is corrupted
+    /// or is produced by another version of the compiler.
+    pub fn load(path: &Path) -> Option<Self> {
+        let program: Self = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+
+        (program.compiler == COMPILER_VERSION).then_some(program)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    /// Whether the program would be loaded from `entry` the same way:
+    /// the files have the same contents and import the same files.
+    fn is_fresh(&self, entry: &Path, roots: &ModuleRoots) -> bool {
+        let entry = normalize_path(entry).display().to_string();
+
+        if self.files.first().map(|file| &file.name) != Some(&entry) {
+            return false;
+        }
+
+        let names = self
+            .files
+            .iter()
+            .map(|file| file.name.as_str())
+            .collect::<HashSet<_>>();
+
+        self.files.iter().all(|file| {
+            if fs::read_to_string(&file.name).ok().as_ref() != Some(&file.contents) {
+                return false;
+            }
+
+            // Files, that can't be read anymore, are not imported (and
+            // the program is checked again).
+            let imported = resolve_imports(&file.name, &file.ast, roots)
+                .into_iter()
+                .filter(|path| {
+                    names.contains(path.display().to_string().as_str()) || path.is_file()
+                })
+                .collect::<Vec<_>>();
+
+            imported
+                == file
+                    .imports
+                    .iter()
+                    .map(|&i| PathBuf::from(&self.files[i].name))
+                    .collect::<Vec<_>>()
+        })
+    }
+
+    pub fn modules(&self) -> Vec<Module<'_>> {
+        self.files
+            .iter()
+            .map(|file| Module {
+                ast: &file.ast,
+                imports: file.imports.clone(),
+            })
+            .collect()
+    }
+
+    /// Compiles files of the program to bytecode. Object files in
+    /// `directory`, compiled from the same source code, are loaded instead
+    /// of compiling the files again, others are replaced.
+    pub fn compile(&self, directory: &Path) -> io::Result<Vec<bytecode::Module>> {
+        fs::create_dir_all(directory)?;
+
+        self.files
+            .iter()
+            .map(|file| {
+                let path = directory.join(object_name(&file.name));
+
+                if let Some(object) = fs::read(&path)
+                    .ok()
+                    .and_then(|bytes| Object::from_bytes(&bytes).ok())
+                    .filter(|object| object.is_compiled_from(&file.contents))
+                {
+                    // Indices of imported files depend on the whole program.
+                    return Ok(bytecode::Module {
+                        imports: file.imports.clone(),
+                        ..object.module
+                    });
+                }
+
+                let object = Object::new(
+                    &file.contents,
+                    compile_module(&file.ast, file.imports.clone()),
+                );
+                fs::write(&path, object.to_bytes())?;
+
+                Ok(object.module)
+            })
+            .collect()
+    }
+}
+
+/// Name of the object file for the source file `name`: files with the same
+/// name from different directories get different objects.
+fn object_name(name: &str) -> String {
+    let stem = Path::new(name)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let hash = object::hash(name.as_bytes())
+        .iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+
+    format!("{stem}-{hash}.rbo")
+}
+
+/// Compiles the program starting from `entry`, or reuses the artifact at
+/// `artifact`, if it is fresh. Compiled program is saved to `artifact`.
+/// Fails only if the entry point or the artifact can't be accessed.
+pub fn build(entry: &Path, roots: &ModuleRoots, artifact: &Path) -> io::Result<Build> {
+    if let Some(program) = Program::load(artifact) {
+        if program.is_fresh(entry, roots) {
+            return Ok(Build::Fresh(program));
+        }
+    }
+
+    let mut program = load_program(entry, roots)?;
+    let errors = check(&mut program);
+
+    if !errors.is_empty() || program.iter().any(|file| file.ast.is_err()) {
+        return Ok(Build::Failed(program, errors));
+    }
+
+    let program = Program::new(program);
+    program.save(artifact)?;
+
+    Ok(Build::Compiled(program))
+}
+
+#[cfg(test)]
+mod tests {
+    use ry_ast::{RawExpression, TopLevelStatement};
+
+    use super::*;
+    use crate::test_utils::temp_directory;
+
+    #[test]
+    fn build_test() {
+        let temp = temp_directory(&[]);
+        let root = temp.path();
+
+        let entry = root.join("main.ry");
+        let artifact = root.join("target").join("main.json");
+        let roots = ModuleRoots::default();
+        let build = || build(&entry, &roots, &artifact).unwrap();
+
+        fs::write(&entry, "import \"util\";\nconst A = 2 * 3;\n").unwrap();
+        assert!(matches!(
+            build(),
+            Build::Failed(_, errors)
+                if matches!(errors.as_slice(), [(0, AnalyzerError::UnresolvedImport(..))])
+        ));
+
+        fs::write(root.join("util.ry"), "fun f() {}\n").unwrap();
+
+        let Build::Compiled(program) = build() else {
+            panic!("program must be compiled");
+        };
+        assert_eq!(program.files.len(), 2);
+
+        // Constants are folded in the artifact.
+        let TopLevelStatement::ConstDecl(decl) = &program.files[0].ast.top_level_statements[0].1
+        else {
+            unreachable!()
+        };
+        assert_eq!(*decl.value.value, RawExpression::Int(6, None));
+
+        assert!(matches!(build(), Build::Fresh(_)));
+
+        fs::write(root.join("util.ry"), "fun f( {}\n").unwrap();
+        assert!(matches!(build(), Build::Failed(_, errors) if errors.is_empty()));
+
+        fs::write(root.join("util.ry"), "const B = 1 / 0;\n").unwrap();
+        assert!(matches!(build(), Build::Failed(_, errors) if errors.len() == 1));
+
+        // Failed build doesn't overwrite the artifact.
+        fs::write(root.join("util.ry"), "fun f() {}\n").unwrap();
+        assert!(matches!(build(), Build::Fresh(_)));
+
+        fs::write(&artifact, "{").unwrap();
+        let Build::Compiled(program) = build() else {
+            panic!("program must be compiled");
+        };
+
+        let objects = root.join("target").join(OBJECTS_DIRECTORY);
+        let modules = program.compile(&objects).unwrap();
+        assert_eq!(modules[0].imports, vec![1]);
+
+        let object = objects.join(object_name(&program.files[1].name));
+        let compiled = fs::read(&object).unwrap();
+        assert_eq!(program.compile(&objects).unwrap(), modules);
+        assert_eq!(fs::read(&object).unwrap(), compiled);
+
+        // Damaged object is compiled again.
+        fs::write(&object, &compiled[..compiled.len() / 2]).unwrap();
+        assert_eq!(program.compile(&objects).unwrap(), modules);
+        assert_eq!(fs::read(&object).unwrap(), compiled);
+
+        // Object of the changed file is replaced.
+        fs::write(root.join("util.ry"), "fun g() {}\n").unwrap();
+        let Build::Compiled(program) = build() else {
+            panic!("program must be compiled");
+        };
+        assert_ne!(program.compile(&objects).unwrap(), modules);
+        assert_ne!(fs::read(&object).unwrap(), compiled);
+    }
+}
@@ -0,0 +1,257 @@
+//! `lib.rs` - compilation pipeline shared by `ry` and `lapis`: loads
+//! the program (the entry file and all files it imports), parses and
+//! analyzes it, see [`check`], and caches analyzed programs, see [`cache`].
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use codespan_reporting::files::SimpleFiles;
+use ry_ast::{location::WithSpan, normalize_path, ProgramUnit};
+use ry_manifest::ModuleRoots;
+use ry_parser::{error::ParserError, Parser};
+use ry_report::{Reporter, ReporterState};
+use ry_static_analyzer::{
+    analyze,
+    error::AnalyzerError,
+    globals::{check_initialization_order, Module},
+};
+
+pub mod cache;
+#[cfg(test)]
+mod test_utils;
+
+/// Source file of the program.
+pub struct SourceFile {
+    pub name: String,
+    pub contents: String,
+    pub ast: Result<ProgramUnit, ParserError>,
+    /// Indices of the imported files (that could be read).
+    pub imports: Vec<usize>,
+    /// Imports, which files can't be read, with their resolved paths.
+    pub unresolved_imports: Vec<(WithSpan<String>, PathBuf)>,
+}
+
+fn read(path: &Path) -> io::Result<SourceFile> {
+    let contents = fs::read_to_string(path)?;
+
+    Ok(SourceFile {
+        name: path.display().to_string(),
+        ast: Parser::new(&contents).parse(),
+        contents,
+        imports: vec![],
+        unresolved_imports: vec![],
+    })
+}
+
+/// Reads and parses the entry file and all files it imports (transitively).
+/// Imports starting with the name of the module root are resolved inside of
+/// it, see [`ModuleRoots`]. Imported files, that can't be read, are kept in
+/// [`SourceFile::unresolved_imports`] and reported by [`check`].
+pub fn load_program(entry: &Path, roots: &ModuleRoots) -> io::Result<Vec<SourceFile>> {
+    let entry = normalize_path(entry);
+
+    Ok(load_imports(vec![read(&entry)?], roots))
+}
+
+/// Reads and parses files imported by the given ones (transitively) and
+/// appends them to the program, like [`load_program`] does. Given files
+/// may be not on disk (for example, inputs of the REPL), imports in them
+/// are resolved relatively to the current directory. Existing imports of
+/// the files are kept.
+pub fn load_imports(mut program: Vec<SourceFile>, roots: &ModuleRoots) -> Vec<SourceFile> {
+    let mut indices = program
+        .iter()
+        .enumerate()
+        .map(|(i, file)| (PathBuf::from(&file.name), i))
+        .collect::<HashMap<_, _>>();
+    let mut i = 0;
+
+    while i < program.len() {
+        let imported = match &program[i].ast {
+            Ok(ast) => ast
+                .imports
+                .iter()
+                .map(|import| import.filename.clone())
+                .zip(resolve_imports(&program[i].name, ast, roots))
+                .collect(),
+            Err(_) => vec![],
+        };
+
+        for (import, path) in imported {
+            let index = match indices.get(&path) {
+                Some(&index) => index,
+                None => {
+                    let Ok(file) = read(&path) else {
+                        program[i].unresolved_imports.push((import, path));
+                        continue;
+                    };
+
+                    program.push(file);
+                    indices.insert(path, program.len() - 1);
+                    program.len() - 1
+                }
+            };
+
+            program[i].imports.push(index);
+        }
+
+        i += 1;
+    }
+
+    program
+}
+
+/// Paths of files imported by the file `name`.
+fn resolve_imports(name: &str, ast: &ProgramUnit, roots: &ModuleRoots) -> Vec<PathBuf> {
+    let directory = Path::new(name).parent().unwrap_or(Path::new(""));
+
+    ast.imports
+        .iter()
+        .map(|import| match roots.resolve(&import.filename.value) {
+            Some(path) => normalize_path(&path),
+            None => import.resolve(directory),
+        })
+        .collect()
+}
+
+/// Reports unresolved imports, analyzes (and folds constants of) every
+/// parsed file of the program. Initialization order of globals is checked
+/// only if all files are parsed.
+/// Returns errors with indices of files, where they were found (parse
+/// errors stay in [`SourceFile::ast`]).
+pub fn check(program: &mut [SourceFile]) -> Vec<(usize, AnalyzerError)> {
+    let mut errors = vec![];
+
+    for (i, file) in program.iter_mut().enumerate() {
+        errors.extend(file.unresolved_imports.iter().map(|(import, path)| {
+            (
+                i,
+                AnalyzerError::UnresolvedImport(
+                    import.span.clone(),
+                    import.value.clone(),
+                    path.display().to_string(),
+                ),
+            )
+        }));
+
+        if let Ok(ast) = &mut file.ast {
+            errors.extend(analyze(ast).into_iter().map(|e| (i, e)));
+        }
+    }
+
+    // Initialization order can only be checked for the whole program.
+    if let Some(modules) = modules(program) {
+        errors.extend(check_initialization_order(&modules));
+    }
+
+    errors
+}
+
+/// Reports parse errors of the program, then the analysis `errors`.
+/// Returns whether there were any errors.
+pub fn emit_errors(
+    program: &[SourceFile],
+    errors: &[(usize, AnalyzerError)],
+    reporter: &ReporterState,
+) -> bool {
+    let mut files = SimpleFiles::new();
+    let file_ids = program
+        .iter()
+        .map(|file| files.add(file.name.as_str(), file.contents.as_str()))
+        .collect::<Vec<_>>();
+
+    let mut has_errors = false;
+
+    for (file, &file_id) in program.iter().zip(&file_ids) {
+        if let Err(e) = &file.ast {
+            e.emit_diagnostic(reporter, &files, file_id);
+            has_errors = true;
+        }
+    }
+
+    for (i, e) in errors {
+        e.emit_diagnostic(reporter, &files, file_ids[*i]);
+        has_errors = true;
+    }
+
+    has_errors
+}
+
+/// Modules of the program, if all its files are parsed.
+pub fn modules(program: &[SourceFile]) -> Option<Vec<Module<'_>>> {
+    program
+        .iter()
+        .map(|file| {
+            Some(Module {
+                ast: file.ast.as_ref().ok()?,
+                imports: file.imports.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_directory;
+
+    #[test]
+    fn check_test() {
+        let temp = temp_directory(&[
+            (
+                "main.ry",
+                "import \"util\";\nimport \"missing\";\nvar a = c;\nvar c = b;\n",
+            ),
+            ("util.ry", "import \"lib/x\";\nvar b = 1 / 0;\n"),
+            ("lib/x.ry", "import \"../util\";\nfun f( {}\n"),
+        ]);
+        let root = temp.path();
+
+        let mut program = load_program(&root.join("main.ry"), &ModuleRoots::default()).unwrap();
+
+        let names = program
+            .iter()
+            .map(|file| Path::new(&file.name).strip_prefix(root).unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                Path::new("main.ry"),
+                Path::new("util.ry"),
+                Path::new("lib/x.ry")
+            ]
+        );
+        assert_eq!(
+            program
+                .iter()
+                .map(|file| file.imports.clone())
+                .collect::<Vec<_>>(),
+            [vec![1], vec![2], vec![]]
+        );
+
+        let errors = check(&mut program);
+        assert!(program[2].ast.is_err());
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                (0, AnalyzerError::UnresolvedImport(_, name, path)),
+                (1, AnalyzerError::DivisionByZero(..))
+            ] if name == "missing" && path.ends_with("missing.ry")
+        ));
+
+        fs::write(root.join("lib/x.ry"), "import \"../util\";\n").unwrap();
+
+        let mut program = load_program(&root.join("main.ry"), &ModuleRoots::default()).unwrap();
+        let errors = check(&mut program);
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                (0, AnalyzerError::UnresolvedImport(..)),
+                (1, AnalyzerError::DivisionByZero(..)),
+                (0, AnalyzerError::GlobalUsedBeforeInitialization(..))
+            ]
+        ));
+    }
+}
@@ -0,0 +1,68 @@
+//! `examples.rs` - compares the interpreter and the virtual machine on
+//! programs from the `examples` directory. Examples, that can't be run
+//! (they don't parse, have errors, don't define `main` or fail at
+//! runtime), are skipped.
+use std::{fs, io, path::Path};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ry_ast::ProgramUnit;
+use ry_interpreter::{compiler::compile, vm::Vm, Interpreter};
+use ry_parser::Parser;
+use ry_static_analyzer::{analyze, globals::Module};
+
+const EXAMPLES_DIRECTORY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples");
+
+/// Parses and analyzes the example, returns `None`, if it can't be run.
+fn load(path: &Path) -> Option<ProgramUnit> {
+    let source = fs::read_to_string(path).ok()?;
+    let mut ast = Parser::new(&source).parse().ok()?;
+
+    if !analyze(&mut ast).is_empty() {
+        return None;
+    }
+
+    let modules = [Module {
+        ast: &ast,
+        imports: vec![],
+    }];
+
+    Interpreter::new(&modules, &mut io::sink()).run().ok()?;
+
+    Some(ast)
+}
+
+fn examples(c: &mut Criterion) {
+    let mut paths = fs::read_dir(EXAMPLES_DIRECTORY)
+        .expect("cannot read examples directory")
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ry"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        let Some(ast) = load(&path) else {
+            continue;
+        };
+
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        let modules = [Module {
+            ast: &ast,
+            imports: vec![],
+        }];
+        let compiled = compile(&modules);
+
+        let mut group = c.benchmark_group(name);
+
+        group.bench_function("interpreter", |b| {
+            b.iter(|| Interpreter::new(&modules, &mut io::sink()).run())
+        });
+        group.bench_function("vm", |b| {
+            b.iter(|| Vm::new(&compiled, &mut io::sink()).run())
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, examples);
+criterion_main!(benches);
@@ -0,0 +1,218 @@
+//! `builtins.rs` - functions available in every program without imports.
+use std::io::Write;
+
+use ry_ast::location::Span;
+use ry_static_analyzer::eval::{self, Int};
+
+use crate::{error::RuntimeError, value::Value};
+
+/// Names of builtin functions. Declarations of the program shadow them.
+pub const BUILTINS: &[&str] = &[
+    "print",
+    "println",
+    "some",
+    "len",
+    "panic",
+    "assert",
+    "assert_eq",
+];
+
+/// Calls builtin function `name` (one of [`BUILTINS`]).
+pub(crate) fn call(
+    name: &str,
+    mut args: Vec<Value>,
+    span: &Span,
+    output: &mut dyn Write,
+) -> Result<Value, RuntimeError> {
+    let arity = |expected: usize, args: &[Value]| {
+        if args.len() == expected {
+            Ok(())
+        } else {
+            Err(RuntimeError::ArgumentCount(
+                span.clone(),
+                name.to_owned(),
+                expected,
+                args.len(),
+            ))
+        }
+    };
+
+    match name {
+        "print" | "println" => {
+            let mut text = if args.is_empty() {
+                String::new()
+            } else {
+                let format = args.remove(0);
+                format_args(format, &args, span)?
+            };
+
+            if name == "println" {
+                text.push('\n');
+            }
+
+            output.write_all(text.as_bytes()).map_err(|e| {
+                RuntimeError::InvalidOperation(span.clone(), format!("cannot write output: {e}"))
+            })?;
+
+            Ok(Value::Unit)
+        }
+        "some" => {
+            arity(1, &args)?;
+            Ok(Value::Option(Some(Box::new(args.remove(0)))))
+        }
+        "len" => {
+            arity(1, &args)?;
+
+            let len = match &args[0] {
+                Value::List(items) => items.borrow().len(),
+                Value::Primitive(eval::Value::String(string)) => string.chars().count(),
+                value => {
+                    return Err(RuntimeError::InvalidOperation(
+                        span.clone(),
+                        format!("cannot get length of `{}`", value.type_name()),
+                    ))
+                }
+            };
+
+            Ok(Value::Primitive(eval::Value::Int(
+                Int::from(len as u128),
+                None,
+            )))
+        }
+        "panic" => {
+            let message = match args.first() {
+                Some(format) => format_args(format.clone(), &args[1..], span)?,
+                None => "explicit panic".to_owned(),
+            };
+
+            Err(RuntimeError::Panic(span.clone(), message))
+        }
+        // `assert(condition)` or `assert(condition, format, args...)`.
+        "assert" => {
+            if args.is_empty() {
+                return Err(RuntimeError::ArgumentCount(
+                    span.clone(),
+                    name.to_owned(),
+                    1,
+                    0,
+                ));
+            }
+
+            match &args[0] {
+                Value::Primitive(eval::Value::Bool(true)) => Ok(Value::Unit),
+                Value::Primitive(eval::Value::Bool(false)) => {
+                    let message = match args.get(1) {
+                        Some(format) => format_args(format.clone(), &args[2..], span)?,
+                        None => "assertion failed".to_owned(),
+                    };
+
+                    Err(RuntimeError::Panic(span.clone(), message))
+                }
+                value => Err(RuntimeError::InvalidOperation(
+                    span.clone(),
+                    format!(
+                        "condition of `assert` must be `bool`, found `{}`",
+                        value.type_name()
+                    ),
+                )),
+            }
+        }
+        // `assert_eq(left, right)` or `assert_eq(left, right, format, args...)`.
+        "assert_eq" => {
+            if args.len() < 2 {
+                return Err(RuntimeError::ArgumentCount(
+                    span.clone(),
+                    name.to_owned(),
+                    2,
+                    args.len(),
+                ));
+            }
+
+            if args[0] == args[1] {
+                return Ok(Value::Unit);
+            }
+
+            let mut message = "assertion `left == right` failed".to_owned();
+
+            if let Some(format) = args.get(2) {
+                message.push_str(": ");
+                message.push_str(&format_args(format.clone(), &args[3..], span)?);
+            }
+
+            Err(RuntimeError::Panic(
+                span.clone(),
+                format!(
+                    "{message}\n  left: {}\n right: {}",
+                    args[0].quoted(),
+                    args[1].quoted()
+                ),
+            ))
+        }
+        _ => unreachable!("`{name}` is not a builtin"),
+    }
+}
+
+/// Formats the arguments printf-style: `%s` and `%v` accept any value,
+/// `%d` - integers, `%f` - floats, `%c` - chars, `%%` is `%` itself.
+/// Format, which is not a string, is printed as is.
+fn format_args(format: Value, args: &[Value], span: &Span) -> Result<String, RuntimeError> {
+    let Value::Primitive(eval::Value::String(format)) = format else {
+        return if args.is_empty() {
+            Ok(format.to_string())
+        } else {
+            Err(RuntimeError::InvalidOperation(
+                span.clone(),
+                format!("format must be a string, found `{}`", format.type_name()),
+            ))
+        };
+    };
+
+    let invalid = |message: String| Err(RuntimeError::InvalidOperation(span.clone(), message));
+
+    let mut result = String::new();
+    let mut args = args.iter();
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+
+        let Some(directive) = chars.next() else {
+            return invalid("format ends with `%`".to_owned());
+        };
+
+        if directive == '%' {
+            result.push('%');
+            continue;
+        }
+
+        let Some(arg) = args.next() else {
+            return invalid(format!("missing argument for `%{directive}`"));
+        };
+
+        let matches = match directive {
+            's' | 'v' => true,
+            'd' => matches!(arg, Value::Primitive(eval::Value::Int(..))),
+            'f' => matches!(arg, Value::Primitive(eval::Value::Float(..))),
+            'c' => matches!(arg, Value::Primitive(eval::Value::Char(_))),
+            _ => return invalid(format!("unknown format directive `%{directive}`")),
+        };
+
+        if !matches {
+            return invalid(format!(
+                "`%{directive}` doesn't accept `{}`",
+                arg.type_name()
+            ));
+        }
+
+        result.push_str(&arg.to_string());
+    }
+
+    if args.next().is_some() {
+        return invalid("too many arguments for the format".to_owned());
+    }
+
+    Ok(result)
+}
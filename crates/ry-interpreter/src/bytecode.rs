@@ -0,0 +1,369 @@
+//! `bytecode.rs` - instructions of the virtual machine and compiled modules.
+//!
+//! Every module (source file) is compiled separately by
+//! [`crate::compiler`]. Local variables live in numbered slots of the call
+//! frame, other names are kept in the symbol table of the module: all
+//! modules share a single namespace, so globals, functions and enum
+//! variants are resolved only when the program is loaded into
+//! [`crate::vm::Vm`]. Every instruction has a span of the expression it was
+//! compiled from, which is used in runtime errors.
+use std::fmt::{self, Write};
+
+use ry_ast::{
+    location::{LineIndex, Span},
+    token::RawToken,
+    Type, TypeAlias,
+};
+use ry_static_analyzer::eval;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    /// Pushes the constant [`u32`] of the module.
+    Constant(u32),
+    /// Pushes `()`.
+    Unit,
+    Pop,
+    /// Pushes the copy of the value on the top of the stack.
+    Dup,
+    /// Pushes the value of the local variable in the slot [`u32`].
+    LoadLocal(u32),
+    /// Pops the value into the local variable in the slot [`u32`].
+    StoreLocal(u32),
+    /// Pushes the value of the symbol [`u32`]: global variable, constant,
+    /// function, enum variant or `none`.
+    LoadName(u32),
+    /// Pops the value into the global variable (symbol [`u32`]).
+    StoreName(u32),
+    /// Pops [`u32`] items and pushes the list of them.
+    List(u32),
+    /// Pops values of the fields of the struct literal (layout [`u32`]) and
+    /// pushes the struct.
+    Struct(u32),
+    /// Replaces the struct with the value of its field (symbol [`u32`]).
+    GetField(u32),
+    /// Pops the struct, then the value of its field (symbol [`u32`]).
+    SetField(u32),
+    /// Pops the index and the list (or string), pushes the item.
+    GetIndex,
+    /// Pops the index, the list and the value of the item.
+    SetIndex,
+    /// Pops the right operand, then the left one, pushes the result.
+    Binary(RawToken),
+    /// Applies prefix `-`, `!` or `~` to the value on the top of the stack.
+    Unary(RawToken),
+    /// `as` cast into the type [`u32`].
+    Cast(u32),
+    /// Converts the value into the declared type [`u32`]: untyped number
+    /// literals get the type.
+    Coerce(u32),
+    /// Continues execution from the instruction [`u32`].
+    Jump(u32),
+    /// Pops the condition and jumps, if it is `false`.
+    JumpIfFalse(u32),
+    /// Pops the condition and jumps, if it is `true`.
+    JumpIfTrue(u32),
+    /// Jumps to the instruction (2-nd [`u32`]), if the argument for
+    /// the parameter (1-st [`u32`]) is given, so its default value is not
+    /// evaluated.
+    JumpIfArgument(u32, u32),
+    /// Left operand of `?:`: unwraps `some` (and other values, that are not
+    /// options) and jumps, pops `none`.
+    JumpIfSome(u32),
+    /// `?`: unwraps `some`, returns `none` from the function.
+    Try,
+    /// `!!`: unwraps `some`, stops the program on `none`.
+    Unwrap,
+    /// Calls the value below [`u32`] arguments.
+    Call(u32),
+    /// Calls function, method or builtin (symbol, 1-st [`u32`]) with
+    /// the arguments (2-nd [`u32`]).
+    CallName(u32, u32),
+    /// Calls method (symbol, 1-st [`u32`]) of the value below the arguments
+    /// (2-nd [`u32`]).
+    CallMethod(u32, u32),
+    /// Schedules the deferred expression [`u32`] of the function.
+    Defer(u32),
+    /// Pops the value of the deferred expression and continues returning
+    /// from the function.
+    EndDefer,
+    /// Pops the result of the function, runs deferred expressions and
+    /// returns.
+    Return,
+    /// Stops the program: the operation is not supported, the constant
+    /// [`u32`] explains why.
+    Unsupported(u32),
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Constant(constant) => write!(f, "constant {constant}"),
+            Self::Unit => write!(f, "unit"),
+            Self::Pop => write!(f, "pop"),
+            Self::Dup => write!(f, "dup"),
+            Self::LoadLocal(slot) => write!(f, "load_local {slot}"),
+            Self::StoreLocal(slot) => write!(f, "store_local {slot}"),
+            Self::LoadName(symbol) => write!(f, "load_name {symbol}"),
+            Self::StoreName(symbol) => write!(f, "store_name {symbol}"),
+            Self::List(len) => write!(f, "list {len}"),
+            Self::Struct(layout) => write!(f, "struct {layout}"),
+            Self::GetField(symbol) => write!(f, "get_field {symbol}"),
+            Self::SetField(symbol) => write!(f, "set_field {symbol}"),
+            Self::GetIndex => write!(f, "get_index"),
+            Self::SetIndex => write!(f, "set_index"),
+            Self::Binary(operator) => write!(f, "binary {operator}"),
+            Self::Unary(operator) => write!(f, "unary {operator}"),
+            Self::Cast(r#type) => write!(f, "cast {type}"),
+            Self::Coerce(r#type) => write!(f, "coerce {type}"),
+            Self::Jump(target) => write!(f, "jump {target}"),
+            Self::JumpIfFalse(target) => write!(f, "jump_if_false {target}"),
+            Self::JumpIfTrue(target) => write!(f, "jump_if_true {target}"),
+            Self::JumpIfArgument(param, target) => {
+                write!(f, "jump_if_argument {param} {target}")
+            }
+            Self::JumpIfSome(target) => write!(f, "jump_if_some {target}"),
+            Self::Try => write!(f, "try"),
+            Self::Unwrap => write!(f, "unwrap"),
+            Self::Call(args) => write!(f, "call {args}"),
+            Self::CallName(symbol, args) => write!(f, "call_name {symbol} {args}"),
+            Self::CallMethod(symbol, args) => write!(f, "call_method {symbol} {args}"),
+            Self::Defer(defer) => write!(f, "defer {defer}"),
+            Self::EndDefer => write!(f, "end_defer"),
+            Self::Return => write!(f, "return"),
+            Self::Unsupported(constant) => write!(f, "unsupported {constant}"),
+        }
+    }
+}
+
+/// Compiled function, method or initializer of the global variable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: String,
+    /// Number of parameters, they take the first slots.
+    pub params: u32,
+    /// Number of parameters without default values.
+    pub required: u32,
+    /// Whether the first parameter is `self`, so the method takes
+    /// the receiver.
+    pub takes_self: bool,
+    /// Number of slots for parameters and local variables.
+    pub locals: u32,
+    /// Declared return type, the result is coerced into it.
+    pub return_type: Option<u32>,
+    pub code: Vec<Instruction>,
+    /// Span of every instruction.
+    pub spans: Vec<Span>,
+    /// Offsets of deferred expressions in the code.
+    pub defers: Vec<u32>,
+}
+
+/// Global variable or constant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Global {
+    pub name: String,
+    /// Function, that computes the initial value.
+    pub initializer: u32,
+}
+
+/// Order of fields in the struct literal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enum {
+    pub name: String,
+    pub variants: Vec<String>,
+}
+
+/// Implementation block: methods of the type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Impl {
+    /// Name of the type, see [`Value::type_name`](crate::value::Value::type_name).
+    pub r#type: String,
+    pub r#trait: Option<String>,
+    pub methods: Vec<u32>,
+}
+
+/// Trait with its default methods.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trait {
+    pub name: String,
+    pub methods: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Module {
+    /// Indices of the imported modules.
+    pub imports: Vec<usize>,
+    pub constants: Vec<eval::Value>,
+    /// Symbol table: names of globals, functions, fields and methods, that
+    /// the code refers to.
+    pub names: Vec<String>,
+    /// Declared types and types of casts.
+    pub types: Vec<Type>,
+    /// Type aliases of the module, used to resolve `types`.
+    pub aliases: Vec<TypeAlias>,
+    /// Names of newtypes of the module: other values are not coerced into
+    /// them. Newtypes are created by compiled top level functions.
+    pub newtypes: Vec<String>,
+    pub layouts: Vec<Layout>,
+    pub functions: Vec<Function>,
+    /// Top level functions, others are methods and initializers.
+    pub top_level: Vec<u32>,
+    /// Globals in order of declaration.
+    pub globals: Vec<Global>,
+    pub enums: Vec<Enum>,
+    pub impls: Vec<Impl>,
+    pub traits: Vec<Trait>,
+}
+
+impl Module {
+    /// Explains operands of the instruction.
+    fn comment(&self, instruction: &Instruction) -> Option<String> {
+        let name = |symbol: &u32| self.names[*symbol as usize].clone();
+
+        Some(match instruction {
+            Instruction::Constant(constant) | Instruction::Unsupported(constant) => {
+                self.constants[*constant as usize].to_string()
+            }
+            Instruction::LoadName(symbol)
+            | Instruction::StoreName(symbol)
+            | Instruction::GetField(symbol)
+            | Instruction::SetField(symbol)
+            | Instruction::CallName(symbol, _)
+            | Instruction::CallMethod(symbol, _) => name(symbol),
+            Instruction::Struct(layout) => {
+                let layout = &self.layouts[*layout as usize];
+                format!("{} {{ {} }}", layout.name, layout.fields.join(", "))
+            }
+            Instruction::Cast(r#type) | Instruction::Coerce(r#type) => {
+                self.types[*r#type as usize].value.to_string()
+            }
+            _ => return None,
+        })
+    }
+
+    fn disassemble_function(
+        &self,
+        output: &mut String,
+        title: &str,
+        function: u32,
+        source: &str,
+        line_index: &LineIndex,
+    ) {
+        let function = &self.functions[function as usize];
+
+        let _ = writeln!(
+            output,
+            "{title} ({} parameter(s), {} slot(s)):",
+            function.params, function.locals
+        );
+
+        let defers = function
+            .defers
+            .iter()
+            .enumerate()
+            .map(|(i, &offset)| (offset, i))
+            .collect::<Vec<_>>();
+        let mut previous_line = None;
+
+        for (offset, (instruction, span)) in function.code.iter().zip(&function.spans).enumerate() {
+            if let Some((_, defer)) = defers.iter().find(|(start, _)| *start as usize == offset) {
+                let _ = writeln!(output, "  deferred {defer}:");
+            }
+
+            let location = if *span == Span::default() {
+                "".to_owned()
+            } else {
+                let (line, column) = line_index.line_column(source, span.range.start);
+
+                if previous_line == Some(line) {
+                    format!("{:>4}", "|")
+                } else {
+                    previous_line = Some(line);
+                    format!("{line:>4}:{column}")
+                }
+            };
+
+            let instruction_text = match self.comment(instruction) {
+                Some(comment) => format!("{:<24} ; {comment}", instruction.to_string()),
+                None => instruction.to_string(),
+            };
+
+            let _ = writeln!(output, "  {offset:04} {location:<8} {instruction_text}");
+        }
+    }
+
+    /// Human-readable listing of the module for `ry disasm`. `source` is
+    /// the source code, that the module is compiled from, it is used to
+    /// show line numbers.
+    pub fn disassemble(&self, source: &str) -> String {
+        let line_index = LineIndex::new(source);
+        let mut output = String::new();
+
+        for global in &self.globals {
+            let title = format!("global `{}`", global.name);
+            self.disassemble_function(&mut output, &title, global.initializer, source, &line_index);
+            output.push('\n');
+        }
+
+        for &function in &self.top_level {
+            let title = format!("fun `{}`", self.functions[function as usize].name);
+            self.disassemble_function(&mut output, &title, function, source, &line_index);
+            output.push('\n');
+        }
+
+        let methods = self
+            .impls
+            .iter()
+            .map(|r#impl| (&r#impl.r#type, &r#impl.methods))
+            .chain(
+                self.traits
+                    .iter()
+                    .map(|r#trait| (&r#trait.name, &r#trait.methods)),
+            );
+
+        for (owner, methods) in methods {
+            for &method in methods {
+                let title = format!("fun `{owner}::{}`", self.functions[method as usize].name);
+                self.disassemble_function(&mut output, &title, method, source, &line_index);
+                output.push('\n');
+            }
+        }
+
+        output.pop();
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{compiler::compile_module, test_utils::analyzed};
+
+    #[test]
+    fn disassemble_test() {
+        let source = "fun twice(a i32) i32 {\n    defer println(\"done\");\n    a * 2\n}";
+        let ast = analyzed(source);
+
+        assert_eq!(
+            compile_module(&ast, vec![]).disassemble(source),
+            "fun `twice` (1 parameter(s), 1 slot(s)):
+  0000    1:11  load_local 0
+  0001    |     coerce 0                 ; i32
+  0002    |     store_local 0
+  0003    2:11  defer 0
+  0004    3:5   load_local 0
+  0005    |     constant 0               ; 2
+  0006    |     binary `*`
+  0007    1:5   return
+  deferred 0:
+  0008    2:19  constant 1               ; \"done\"
+  0009    |     call_name 0 1            ; println
+  0010    |     end_defer
+"
+        );
+    }
+}
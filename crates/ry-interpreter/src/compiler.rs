@@ -0,0 +1,871 @@
+//! `compiler.rs` - compiles analyzed AST into [`bytecode`] for the virtual
+//! machine.
+//!
+//! The compiler preserves the semantics of the tree-walking interpreter:
+//! every expression leaves exactly one value on the stack, statements
+//! leave nothing, blocks leave the value of the last expression without
+//! semicolon (or `()`). Locals get a new slot for every declaration, so
+//! shadowed variables keep their values.
+use std::{collections::HashMap, mem};
+
+use ry_ast::{
+    location::{Span, WithSpan},
+    token::{RawToken, Token},
+    *,
+};
+use ry_static_analyzer::{
+    eval::{self, Evaluator, Int},
+    globals,
+    types::TypeAliases,
+};
+
+use crate::{
+    bytecode::{self, Enum, Function, Global, Impl, Instruction, Layout, Trait},
+    compound_operator, type_name,
+};
+
+/// Compiles every module of the program. Indices of modules are preserved.
+pub fn compile(modules: &[globals::Module]) -> Vec<bytecode::Module> {
+    modules
+        .iter()
+        .map(|module| compile_module(module.ast, module.imports.clone()))
+        .collect()
+}
+
+/// Compiles the module with the given indices of imported modules.
+pub fn compile_module(ast: &ProgramUnit, imports: Vec<usize>) -> bytecode::Module {
+    let aliases = ast
+        .top_level_statements
+        .iter()
+        .filter_map(|(_, statement)| match statement {
+            TopLevelStatement::TypeAlias(alias) => Some(alias.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let newtypes = ast
+        .top_level_statements
+        .iter()
+        .filter_map(|(_, statement)| match statement {
+            TopLevelStatement::Newtype(newtype) => Some(newtype.name.value.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut compiler = Compiler {
+        aliases: aliases.iter().cloned().collect(),
+        module: bytecode::Module {
+            imports,
+            aliases,
+            newtypes,
+            ..Default::default()
+        },
+        evaluator: Evaluator::with_constants(HashMap::new()).with_aliases(TypeAliases::new(ast)),
+        constants: HashMap::new(),
+        names: HashMap::new(),
+        types: HashMap::new(),
+        function: FunctionState::default(),
+    };
+
+    for (_, statement) in &ast.top_level_statements {
+        compiler.top_level_statement(statement);
+    }
+
+    compiler.module
+}
+
+/// State of the function being compiled.
+#[derive(Default)]
+struct FunctionState<'a> {
+    code: Vec<Instruction>,
+    spans: Vec<Span>,
+    /// Names of locals with their slots, innermost scope is the last.
+    scopes: Vec<Vec<(&'a str, u32)>>,
+    locals: u32,
+    /// Deferred expressions, compiled after the body.
+    defers: Vec<&'a Expression>,
+}
+
+struct Compiler<'a> {
+    module: bytecode::Module,
+    aliases: TypeAliases,
+    /// Folds negative literals.
+    evaluator: Evaluator<'a>,
+    /// Indices of constants by their debug representation.
+    constants: HashMap<String, u32>,
+    names: HashMap<String, u32>,
+    types: HashMap<String, u32>,
+    function: FunctionState<'a>,
+}
+
+impl<'a> Compiler<'a> {
+    fn top_level_statement(&mut self, statement: &'a TopLevelStatement) {
+        match statement {
+            TopLevelStatement::FunctionDecl(decl) => {
+                let function = self.function(
+                    &decl.def.name,
+                    &decl.def.params,
+                    decl.def.return_type.as_ref(),
+                    &decl.stmts,
+                );
+                self.module.top_level.push(function);
+            }
+            TopLevelStatement::ConstDecl(ConstDecl {
+                name,
+                r#type,
+                value,
+                ..
+            })
+            | TopLevelStatement::VarDecl(VarDecl {
+                name,
+                r#type,
+                value,
+                ..
+            }) => {
+                let initializer = self.initializer(name, r#type.as_ref(), value);
+
+                self.module.globals.push(Global {
+                    name: name.value.clone(),
+                    initializer,
+                });
+            }
+            TopLevelStatement::EnumDecl(decl) => self.module.enums.push(Enum {
+                name: decl.name.value.clone(),
+                variants: decl
+                    .variants
+                    .iter()
+                    .map(|variant| variant.value.clone())
+                    .collect(),
+            }),
+            TopLevelStatement::TraitDecl(decl) => {
+                let methods = self.methods(&decl.methods);
+
+                self.module.traits.push(Trait {
+                    name: decl.name.value.clone(),
+                    methods,
+                });
+            }
+            TopLevelStatement::Impl(r#impl) => {
+                let resolved = self
+                    .aliases
+                    .resolve(&r#impl.r#type)
+                    .unwrap_or_else(|_| r#impl.r#type.clone());
+
+                let Some(r#type) = type_name(&resolved) else {
+                    return;
+                };
+
+                let methods = self.methods(&r#impl.methods);
+
+                self.module.impls.push(Impl {
+                    r#type,
+                    r#trait: r#impl.r#trait.as_ref().and_then(type_name),
+                    methods,
+                });
+            }
+            TopLevelStatement::Newtype(newtype) => {
+                let function = self.newtype(newtype);
+                self.module.top_level.push(function);
+            }
+            TopLevelStatement::StructDecl(_) | TopLevelStatement::TypeAlias(_) => {}
+        }
+    }
+
+    /// Compiles the function, which creates the value of the newtype from
+    /// the wrapped value.
+    fn newtype(&mut self, newtype: &Newtype) -> u32 {
+        self.function = FunctionState::default();
+        self.function.locals = 1;
+
+        let span = &newtype.name.span;
+
+        self.module.layouts.push(Layout {
+            name: newtype.name.value.clone(),
+            fields: vec![Newtype::FIELD.to_owned()],
+        });
+        let layout = self.module.layouts.len() as u32 - 1;
+
+        self.emit(Instruction::LoadLocal(0), span);
+        self.coerce(Some(&newtype.value), span);
+        self.emit(Instruction::Struct(layout), span);
+        self.emit(Instruction::Return, span);
+
+        self.finish_function(Function {
+            name: newtype.name.value.clone(),
+            params: 1,
+            required: 1,
+            takes_self: false,
+            locals: 0,
+            return_type: None,
+            code: vec![],
+            spans: vec![],
+            defers: vec![],
+        })
+    }
+
+    /// Compiles methods with bodies.
+    fn methods(&mut self, methods: &'a [(String, TraitMethod)]) -> Vec<u32> {
+        methods
+            .iter()
+            .filter_map(|(_, method)| {
+                let body = method.body.as_ref()?;
+
+                Some(self.function(
+                    &method.name,
+                    &method.params,
+                    method.return_type.as_ref(),
+                    body,
+                ))
+            })
+            .collect()
+    }
+
+    /// Compiles the initializer of the global variable into the function
+    /// without parameters.
+    fn initializer(
+        &mut self,
+        name: &WithSpan<String>,
+        r#type: Option<&Type>,
+        value: &'a Expression,
+    ) -> u32 {
+        self.function = FunctionState::default();
+        self.function.scopes.push(vec![]);
+
+        self.expression(value);
+        self.coerce(r#type, &value.span);
+        self.emit(Instruction::Return, &value.span);
+
+        self.finish_function(Function {
+            name: name.value.clone(),
+            params: 0,
+            required: 0,
+            takes_self: false,
+            locals: 0,
+            return_type: None,
+            code: vec![],
+            spans: vec![],
+            defers: vec![],
+        })
+    }
+
+    fn function(
+        &mut self,
+        name: &WithSpan<String>,
+        params: &'a [FunctionParam],
+        return_type: Option<&Type>,
+        body: &'a [Statement],
+    ) -> u32 {
+        self.function = FunctionState::default();
+        self.function.scopes.push(vec![]);
+
+        // Parameters take the first slots. Default values are evaluated in
+        // the callee, they may refer to previous parameters.
+        self.function.locals = params.len() as u32;
+
+        for (i, param) in params.iter().enumerate() {
+            if let Some(default) = &param.default_value {
+                let jump = self.emit(Instruction::JumpIfArgument(i as u32, 0), &default.span);
+                self.expression(default);
+                self.emit(Instruction::StoreLocal(i as u32), &default.span);
+                self.patch(jump);
+            }
+
+            let slot = i as u32;
+            self.function.scopes[0].push((&param.name.value, slot));
+
+            if self.coerced(&param.r#type) {
+                self.emit(Instruction::LoadLocal(slot), &param.name.span);
+                self.coerce(Some(&param.r#type), &param.name.span);
+                self.emit(Instruction::StoreLocal(slot), &param.name.span);
+            }
+        }
+
+        self.statements(body, &name.span);
+        self.emit(Instruction::Return, &name.span);
+
+        let return_type = return_type.map(|r#type| self.r#type(r#type));
+
+        self.finish_function(Function {
+            name: name.value.clone(),
+            params: params.len() as u32,
+            required: params
+                .iter()
+                .take_while(|param| param.default_value.is_none())
+                .count() as u32,
+            takes_self: params
+                .first()
+                .is_some_and(|param| param.name.value == "self"),
+            locals: 0,
+            return_type,
+            code: vec![],
+            spans: vec![],
+            defers: vec![],
+        })
+    }
+
+    /// Compiles deferred expressions and adds the function to the module.
+    fn finish_function(&mut self, mut function: Function) -> u32 {
+        // Deferred expressions may contain other `defer` statements.
+        let mut i = 0;
+
+        while let Some(&expression) = self.function.defers.get(i) {
+            function.defers.push(self.function.code.len() as u32);
+            self.expression(expression);
+            self.emit(Instruction::EndDefer, &expression.span);
+            i += 1;
+        }
+
+        let state = mem::take(&mut self.function);
+        function.code = state.code;
+        function.spans = state.spans;
+        function.locals = state.locals;
+
+        self.module.functions.push(function);
+        self.module.functions.len() as u32 - 1
+    }
+
+    fn emit(&mut self, instruction: Instruction, span: &Span) -> usize {
+        self.function.code.push(instruction);
+        self.function.spans.push(span.clone());
+        self.function.code.len() - 1
+    }
+
+    /// Sets the target of the jump to the next instruction.
+    fn patch(&mut self, jump: usize) {
+        let next = self.function.code.len() as u32;
+
+        match &mut self.function.code[jump] {
+            Instruction::Jump(target)
+            | Instruction::JumpIfFalse(target)
+            | Instruction::JumpIfTrue(target)
+            | Instruction::JumpIfArgument(_, target)
+            | Instruction::JumpIfSome(target) => *target = next,
+            instruction => unreachable!("{instruction} is not a jump"),
+        }
+    }
+
+    fn constant(&mut self, value: eval::Value) -> u32 {
+        let constants = &mut self.module.constants;
+
+        *self
+            .constants
+            .entry(format!("{value:?}"))
+            .or_insert_with(|| {
+                constants.push(value);
+                constants.len() as u32 - 1
+            })
+    }
+
+    fn name(&mut self, name: &str) -> u32 {
+        if let Some(&symbol) = self.names.get(name) {
+            return symbol;
+        }
+
+        self.module.names.push(name.to_owned());
+        let symbol = self.module.names.len() as u32 - 1;
+        self.names.insert(name.to_owned(), symbol);
+        symbol
+    }
+
+    fn r#type(&mut self, r#type: &Type) -> u32 {
+        let types = &mut self.module.types;
+
+        *self
+            .types
+            .entry(r#type.value.to_string())
+            .or_insert_with(|| {
+                types.push(r#type.clone());
+                types.len() as u32 - 1
+            })
+    }
+
+    /// Whether coercion into the type may change the value: only primitive
+    /// types are coerced.
+    fn coerced(&self, r#type: &Type) -> bool {
+        let resolved = self
+            .aliases
+            .resolve(r#type)
+            .unwrap_or_else(|_| r#type.clone());
+
+        matches!(&*resolved.value, RawType::Primary(_, generics) if generics.is_empty())
+    }
+
+    fn coerce(&mut self, r#type: Option<&Type>, span: &Span) {
+        if let Some(r#type) = r#type.filter(|r#type| self.coerced(r#type)) {
+            let r#type = self.r#type(r#type);
+            self.emit(Instruction::Coerce(r#type), span);
+        }
+    }
+
+    fn unsupported(&mut self, message: &str, span: &Span) {
+        let message = self.constant(eval::Value::String(message.to_owned()));
+        self.emit(Instruction::Unsupported(message), span);
+    }
+
+    fn declare(&mut self, name: &'a str) -> u32 {
+        let slot = self.function.locals;
+        self.function.locals += 1;
+
+        self.function
+            .scopes
+            .last_mut()
+            .expect("function has at least one scope")
+            .push((name, slot));
+        slot
+    }
+
+    fn local(&self, name: &str) -> Option<u32> {
+        self.function
+            .scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|(local, _)| *local == name)
+            .map(|(_, slot)| *slot)
+    }
+
+    fn block(&mut self, statements: &'a [Statement], span: &Span) {
+        self.function.scopes.push(vec![]);
+        self.statements(statements, span);
+        self.function.scopes.pop();
+    }
+
+    /// Compiles statements in the current scope, they leave the value of
+    /// the last expression without semicolon.
+    fn statements(&mut self, statements: &'a [Statement], span: &Span) {
+        for (i, statement) in statements.iter().enumerate() {
+            match statement {
+                Statement::Expression(expression) => {
+                    self.expression(expression);
+                    self.emit(Instruction::Pop, &expression.span);
+                }
+                Statement::ExpressionWithoutSemicolon(expression) => {
+                    self.expression(expression);
+
+                    if i + 1 < statements.len() {
+                        self.emit(Instruction::Pop, &expression.span);
+                    }
+                }
+                Statement::Return(expression) => {
+                    self.expression(expression);
+                    self.emit(Instruction::Return, &expression.span);
+                }
+                Statement::Defer(expression) => {
+                    let defer = self.function.defers.len() as u32;
+                    self.function.defers.push(expression);
+                    self.emit(Instruction::Defer(defer), &expression.span);
+                }
+                Statement::Var(name, r#type, value) => {
+                    self.expression(value);
+                    self.coerce(r#type.as_ref(), &name.span);
+
+                    let slot = self.declare(&name.value);
+                    self.emit(Instruction::StoreLocal(slot), &name.span);
+                }
+            }
+        }
+
+        if !matches!(
+            statements.last(),
+            Some(Statement::ExpressionWithoutSemicolon(_))
+        ) {
+            self.emit(Instruction::Unit, span);
+        }
+    }
+
+    fn expression(&mut self, expression: &'a Expression) {
+        let span = &expression.span;
+
+        match &*expression.value {
+            RawExpression::String(value) => {
+                let constant = self.constant(eval::Value::String(value.clone()));
+                self.emit(Instruction::Constant(constant), span);
+            }
+            RawExpression::Int(value, suffix) => {
+                let constant = self.constant(eval::Value::Int(Int::from(*value), *suffix));
+                self.emit(Instruction::Constant(constant), span);
+            }
+            RawExpression::Float(value, suffix) => {
+                let constant = self.constant(eval::Value::Float(*value, *suffix));
+                self.emit(Instruction::Constant(constant), span);
+            }
+            RawExpression::Bool(value) => {
+                let constant = self.constant(eval::Value::Bool(*value));
+                self.emit(Instruction::Constant(constant), span);
+            }
+            RawExpression::Char(value) => {
+                let constant = self.constant(eval::Value::Char(*value));
+                self.emit(Instruction::Constant(constant), span);
+            }
+            RawExpression::Imag(_) => self.unsupported("imaginary numbers are not supported", span),
+            RawExpression::Map(_) => self.unsupported("maps are not supported", span),
+            RawExpression::StaticName(name) => match self.local(name) {
+                Some(slot) => {
+                    self.emit(Instruction::LoadLocal(slot), span);
+                }
+                None => {
+                    let symbol = self.name(name);
+                    self.emit(Instruction::LoadName(symbol), span);
+                }
+            },
+            RawExpression::List(items) => {
+                for item in items {
+                    self.expression(item);
+                }
+
+                self.emit(Instruction::List(items.len() as u32), span);
+            }
+            RawExpression::Struct(name, fields) => {
+                let mut fields = fields.iter().collect::<Vec<_>>();
+                fields.sort_by_key(|(_, (span, _))| span.range.start);
+
+                for (_, (_, value)) in &fields {
+                    self.expression(&value.value);
+                }
+
+                self.module.layouts.push(Layout {
+                    name: name.value.clone(),
+                    fields: fields.iter().map(|(field, _)| (*field).clone()).collect(),
+                });
+
+                let layout = self.module.layouts.len() as u32 - 1;
+                self.emit(Instruction::Struct(layout), span);
+            }
+            RawExpression::Binary(left, operator, right) => {
+                self.binary(left, &operator.value, right, span)
+            }
+            RawExpression::As(inner, r#type) => {
+                self.expression(inner);
+
+                let r#type = self.r#type(r#type);
+                self.emit(Instruction::Cast(r#type), span);
+            }
+            RawExpression::PrefixOrPostfix(operator, inner) => {
+                self.prefix_or_postfix(expression, operator, inner)
+            }
+            RawExpression::Property(receiver, field) => {
+                self.expression(receiver);
+
+                let symbol = self.name(&field.value);
+                self.emit(Instruction::GetField(symbol), &field.span);
+            }
+            RawExpression::Call(_, callee, args) => match &*callee.value {
+                RawExpression::Property(receiver, method) => {
+                    self.expression(receiver);
+                    self.arguments(args);
+
+                    let symbol = self.name(&method.value);
+                    self.emit(Instruction::CallMethod(symbol, args.len() as u32), span);
+                }
+                RawExpression::StaticName(name) if self.local(name).is_none() => {
+                    self.arguments(args);
+
+                    let symbol = self.name(name);
+                    self.emit(Instruction::CallName(symbol, args.len() as u32), span);
+                }
+                _ => {
+                    self.expression(callee);
+                    self.arguments(args);
+                    self.emit(Instruction::Call(args.len() as u32), span);
+                }
+            },
+            RawExpression::Index(list, index) => {
+                self.expression(list);
+                self.expression(index);
+                self.emit(Instruction::GetIndex, span);
+            }
+            RawExpression::If((condition, block), else_ifs, r#else) => {
+                let mut ends = vec![];
+
+                for (condition, block) in std::iter::once((condition, block))
+                    .chain(else_ifs.iter().map(|(condition, block)| (condition, block)))
+                {
+                    self.expression(condition);
+                    let next = self.emit(Instruction::JumpIfFalse(0), &condition.span);
+
+                    self.block(block, span);
+                    ends.push(self.emit(Instruction::Jump(0), span));
+                    self.patch(next);
+                }
+
+                match r#else {
+                    Some(block) => self.block(block, span),
+                    None => {
+                        self.emit(Instruction::Unit, span);
+                    }
+                }
+
+                for end in ends {
+                    self.patch(end);
+                }
+            }
+            RawExpression::While(condition, block) => {
+                let start = self.function.code.len() as u32;
+
+                self.expression(condition);
+                let end = self.emit(Instruction::JumpIfFalse(0), &condition.span);
+
+                self.block(block, span);
+                self.emit(Instruction::Pop, span);
+                self.emit(Instruction::Jump(start), span);
+
+                self.patch(end);
+                self.emit(Instruction::Unit, span);
+            }
+        }
+    }
+
+    fn arguments(&mut self, args: &'a [Expression]) {
+        for arg in args {
+            self.expression(arg);
+        }
+    }
+
+    fn binary(
+        &mut self,
+        left: &'a Expression,
+        operator: &RawToken,
+        right: &'a Expression,
+        span: &Span,
+    ) {
+        match operator {
+            RawToken::Assign => {
+                self.expression(right);
+                self.assign(left);
+                self.emit(Instruction::Unit, span);
+            }
+            RawToken::AndAnd | RawToken::OrOr => {
+                // Right operand is evaluated only if it is needed.
+                let or = *operator == RawToken::OrOr;
+                let jump = |target| {
+                    if or {
+                        Instruction::JumpIfTrue(target)
+                    } else {
+                        Instruction::JumpIfFalse(target)
+                    }
+                };
+
+                self.expression(left);
+                let short_left = self.emit(jump(0), &left.span);
+                self.expression(right);
+                let short_right = self.emit(jump(0), &right.span);
+
+                let constant = self.constant(eval::Value::Bool(!or));
+                self.emit(Instruction::Constant(constant), span);
+                let end = self.emit(Instruction::Jump(0), span);
+
+                self.patch(short_left);
+                self.patch(short_right);
+                let constant = self.constant(eval::Value::Bool(or));
+                self.emit(Instruction::Constant(constant), span);
+                self.patch(end);
+            }
+            RawToken::Elvis => {
+                self.expression(left);
+                let end = self.emit(Instruction::JumpIfSome(0), &left.span);
+                self.expression(right);
+                self.patch(end);
+            }
+            operator => match compound_operator(operator) {
+                Some(raw) => {
+                    self.expression(left);
+                    self.expression(right);
+                    self.emit(Instruction::Binary(raw), span);
+                    self.assign(left);
+                    self.emit(Instruction::Unit, span);
+                }
+                None => {
+                    self.expression(left);
+                    self.expression(right);
+                    self.emit(Instruction::Binary(operator.clone()), span);
+                }
+            },
+        }
+    }
+
+    fn prefix_or_postfix(
+        &mut self,
+        expression: &'a Expression,
+        operator: &'a Token,
+        inner: &'a Expression,
+    ) {
+        let span = &expression.span;
+
+        match &operator.value {
+            // Negative literals, like `-128i8`, may not have positive
+            // counterpart of the same type, so they are folded.
+            RawToken::Minus if matches!(&*inner.value, RawExpression::Int(..)) => {
+                match self.evaluator.evaluate(expression) {
+                    Ok(value) => {
+                        let constant = self.constant(value);
+                        self.emit(Instruction::Constant(constant), span);
+                    }
+                    Err(_) => {
+                        // The error is reported again at runtime.
+                        self.evaluator.errors.clear();
+                        self.expression(inner);
+                        self.emit(Instruction::Unary(RawToken::Minus), span);
+                    }
+                }
+            }
+            RawToken::PlusPlus | RawToken::MinusMinus => {
+                let raw = if operator.value == RawToken::PlusPlus {
+                    RawToken::Plus
+                } else {
+                    RawToken::Minus
+                };
+                let one = self.constant(eval::Value::Int(Int::Signed(1), None));
+                let prefix = operator.span.range.start < inner.span.range.start;
+
+                self.expression(inner);
+
+                if prefix {
+                    self.emit(Instruction::Constant(one), span);
+                    self.emit(Instruction::Binary(raw), span);
+                    self.emit(Instruction::Dup, span);
+                } else {
+                    self.emit(Instruction::Dup, span);
+                    self.emit(Instruction::Constant(one), span);
+                    self.emit(Instruction::Binary(raw), span);
+                }
+
+                self.assign(inner);
+            }
+            RawToken::QuestionMark => {
+                self.expression(inner);
+                self.emit(Instruction::Try, span);
+            }
+            RawToken::BangBang => {
+                self.expression(inner);
+                self.emit(Instruction::Unwrap, span);
+            }
+            operator => {
+                self.expression(inner);
+                self.emit(Instruction::Unary(operator.clone()), span);
+            }
+        }
+    }
+
+    /// Pops the value on the top of the stack into the target.
+    fn assign(&mut self, target: &'a Expression) {
+        let span = &target.span;
+
+        match &*target.value {
+            RawExpression::StaticName(name) => match self.local(name) {
+                Some(slot) => {
+                    self.emit(Instruction::StoreLocal(slot), span);
+                }
+                None => {
+                    let symbol = self.name(name);
+                    self.emit(Instruction::StoreName(symbol), span);
+                }
+            },
+            RawExpression::Property(receiver, field) => {
+                self.expression(receiver);
+
+                let symbol = self.name(&field.value);
+                self.emit(Instruction::SetField(symbol), &field.span);
+            }
+            RawExpression::Index(list, index) => {
+                self.expression(list);
+                self.expression(index);
+                self.emit(Instruction::SetIndex, span);
+            }
+            _ => self.unsupported("cannot assign to this expression", span),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::analyzed;
+
+    /// Compiles the only function of the program.
+    fn compiled(source: &str) -> Function {
+        let mut module = compile_module(&analyzed(source), vec![]);
+        assert_eq!(module.functions.len(), 1);
+
+        module.functions.pop().unwrap()
+    }
+
+    #[test]
+    fn jump_targets_test() {
+        use Instruction::*;
+
+        let function =
+            compiled("fun f(a bool, b bool) i32 { if a { 1 } else if b { 2 } else { 3 } }");
+
+        // Every failed condition jumps to the next branch, every taken
+        // branch jumps past the last one.
+        assert_eq!(
+            function.code[6..],
+            [
+                LoadLocal(0),
+                JumpIfFalse(10),
+                Constant(0),
+                Jump(15),
+                LoadLocal(1),
+                JumpIfFalse(14),
+                Constant(1),
+                Jump(15),
+                Constant(2),
+                Return,
+            ]
+        );
+
+        let function = compiled("fun f() { var i = 0; while i < 3 { i++; } }");
+
+        // The loop jumps back to its condition, which exits to `()` of
+        // the `while` expression.
+        assert_eq!(
+            function.code,
+            [
+                Constant(0),
+                StoreLocal(0),
+                LoadLocal(0),
+                Constant(1),
+                Binary(RawToken::LessThan),
+                JumpIfFalse(15),
+                LoadLocal(0),
+                Dup,
+                Constant(2),
+                Binary(RawToken::Plus),
+                StoreLocal(0),
+                Pop,
+                Unit,
+                Pop,
+                Jump(2),
+                Unit,
+                Return,
+            ]
+        );
+    }
+
+    #[test]
+    fn defer_order_test() {
+        use Instruction::*;
+
+        let function = compiled("fun f() { defer println(\"a\"); defer println(\"b\"); }");
+
+        // Deferred expressions are scheduled and compiled in the order of
+        // declaration after the body, the virtual machine runs them in
+        // reverse order on return.
+        assert_eq!(
+            function.code,
+            [
+                Defer(0),
+                Defer(1),
+                Unit,
+                Return,
+                Constant(0),
+                CallName(0, 1),
+                EndDefer,
+                Constant(1),
+                CallName(0, 1),
+                EndDefer,
+            ]
+        );
+        assert_eq!(function.defers, [4, 7]);
+    }
+}
@@ -0,0 +1,105 @@
+//! `error.rs` - defines errors, that stop execution of the program.
+use codespan_reporting::diagnostic::{Diagnostic, Label};
+
+use ry_ast::location::Span;
+use ry_report::Reporter;
+use ry_static_analyzer::error::AnalyzerError;
+
+#[derive(Debug, PartialEq)]
+pub enum RuntimeError {
+    /// Entry point of the program doesn't define `main` function.
+    NoMain,
+
+    /// `panic` was called with the message [`String`].
+    Panic(Span, String),
+
+    /// Arithmetic operation, comparison or cast of primitive values failed:
+    /// overflow, division by zero or operands of different types. These are
+    /// the same errors, that are reported for constant expressions.
+    Operation(Box<AnalyzerError>),
+
+    /// Operation can't be applied to the values. [`String`] is
+    /// the explanation.
+    InvalidOperation(Span, String),
+
+    /// Name [`String`] is not declared.
+    UndefinedName(Span, String),
+
+    /// Value of the type (2-nd [`String`]) has no method (1-st [`String`]).
+    NoMethod(Span, String, String),
+
+    /// Function [`String`] is called with wrong number of arguments.
+    /// 1-st [`usize`] is the expected number, 2-nd is the given one.
+    ArgumentCount(Span, String, usize, usize),
+
+    /// Index [`i128`] is out of bounds of the list or string with
+    /// the length [`usize`].
+    IndexOutOfBounds(Span, i128, usize),
+
+    /// `!!` is applied to `none`.
+    UnwrapNone(Span),
+
+    /// Nesting of function calls exceeded the limit [`usize`].
+    StackOverflow(Span, usize),
+}
+
+impl<'source> Reporter<'source> for RuntimeError {
+    fn build_diagnostic(&self, file_id: usize) -> Diagnostic<usize> {
+        let error = |span: &Span, message: String, label: &str| {
+            Diagnostic::error()
+                .with_message(message)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone()).with_message(label)
+                ])
+        };
+
+        match self {
+            Self::NoMain => Diagnostic::error()
+                .with_message("`main` function is not found in the entry point of the program"),
+            Self::Panic(span, message) => {
+                error(span, format!("program panicked: {message}"), "panicked here")
+            }
+            Self::Operation(error) => {
+                let mut diagnostic = error.build_diagnostic(file_id);
+
+                // Codes and messages of the analyzer errors are about compile
+                // time checks, the label explains what happened.
+                diagnostic.code = None;
+                diagnostic.message = "program panicked".to_owned();
+                diagnostic
+            }
+            Self::InvalidOperation(span, message) => {
+                error(span, format!("program panicked: {message}"), "in this expression")
+            }
+            Self::UndefinedName(span, name) => {
+                error(span, format!("cannot find `{name}` in this scope"), "not found")
+            }
+            Self::NoMethod(span, method, r#type) => error(
+                span,
+                format!("no method `{method}` found for `{type}`"),
+                "method not found",
+            ),
+            Self::ArgumentCount(span, name, expected, found) => error(
+                span,
+                format!("function `{name}` takes {expected} argument(s), but {found} were given"),
+                "wrong number of arguments",
+            ),
+            Self::IndexOutOfBounds(span, index, len) => error(
+                span,
+                format!("program panicked: index out of bounds: the length is {len}, but the index is {index}"),
+                "index out of bounds",
+            ),
+            Self::UnwrapNone(span) => error(
+                span,
+                "program panicked: `!!` applied to `none`".to_owned(),
+                "this value is `none`",
+            ),
+            Self::StackOverflow(span, limit) => error(
+                span,
+                format!("program panicked: more than {limit} nested function calls"),
+                "stack overflow in this call",
+            )
+            .with_notes(vec!["note: probably, recursion doesn't terminate".to_owned()]),
+        }
+    }
+}
@@ -0,0 +1,1020 @@
+//! `lib.rs` - tree-walking interpreter of Ry programs.
+//!
+//! Executes analyzed (and constant folded) AST directly. Operations on
+//! numbers, bools, chars and strings are performed by the constant
+//! evaluator of the static analyzer, so that the program behaves the same
+//! way at runtime as its constant expressions at compile time: integer
+//! overflow and division by zero stop the program.
+//!
+//! All modules of the program share a single namespace. Global variables
+//! and constants are initialized in the order of
+//! [`initialization_order`], then `main` of the entry point (module `0`)
+//! is called. Deferred expressions run when the function returns normally,
+//! but not when the program panics.
+//!
+//! Programs can also be compiled into [`bytecode`] by [`compiler`] and
+//! executed by [`vm::Vm`], which behaves the same way, but runs faster.
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    io::Write,
+    mem,
+    rc::Rc,
+    thread,
+};
+
+use ry_ast::{
+    location::{Span, WithSpan},
+    token::{RawToken, Token},
+    *,
+};
+use ry_static_analyzer::{
+    eval::{self, Evaluator, Int},
+    globals::{initialization_order, Module},
+    types::TypeAliases,
+};
+
+use crate::{builtins::BUILTINS, error::RuntimeError, value::Value};
+
+pub mod builtins;
+pub mod bytecode;
+pub mod compiler;
+pub mod error;
+pub mod object;
+mod operations;
+#[cfg(test)]
+mod test_utils;
+pub mod value;
+pub mod vm;
+
+/// Default limit of nested function calls, see
+/// [`Interpreter::with_max_depth`].
+pub const DEFAULT_MAX_DEPTH: usize = 1000;
+
+/// Stack size of the thread, that runs the program, see
+/// [`with_program_stack`].
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// Calls the function in the thread with the stack of [`STACK_SIZE`], that
+/// is big enough for [`DEFAULT_MAX_DEPTH`] nested calls of the program.
+pub fn with_program_stack<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    thread::scope(|scope| {
+        thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(scope, f)
+            .expect("cannot spawn thread for the program")
+            .join()
+            .expect("interpreter panicked")
+    })
+}
+
+/// Reason to leave the function early.
+enum Unwind {
+    Return(Value),
+    /// Error with the index of the module, where it happened.
+    Error(Box<(usize, RuntimeError)>),
+}
+
+type Flow<T> = Result<T, Unwind>;
+
+/// Function or method, that can be called.
+#[derive(Clone, Copy)]
+struct Callable<'a> {
+    module: usize,
+    params: &'a [FunctionParam],
+    return_type: Option<&'a Type>,
+    body: &'a [Statement],
+}
+
+impl<'a> Callable<'a> {
+    fn function(module: usize, decl: &'a FunctionDecl) -> Self {
+        Self {
+            module,
+            params: &decl.def.params,
+            return_type: decl.def.return_type.as_ref(),
+            body: &decl.stmts,
+        }
+    }
+
+    /// Returns `None` for trait methods without body.
+    fn method(module: usize, method: &'a TraitMethod) -> Option<Self> {
+        Some(Self {
+            module,
+            params: &method.params,
+            return_type: method.return_type.as_ref(),
+            body: method.body.as_ref()?,
+        })
+    }
+
+    fn takes_self(&self) -> bool {
+        self.params
+            .first()
+            .is_some_and(|param| param.name.value == "self")
+    }
+}
+
+/// State of the function being executed.
+struct Frame<'a> {
+    module: usize,
+    scopes: Vec<HashMap<String, Value>>,
+    defers: Vec<&'a Expression>,
+}
+
+impl Frame<'_> {
+    fn new(module: usize) -> Self {
+        Self {
+            module,
+            scopes: vec![HashMap::new()],
+            defers: vec![],
+        }
+    }
+}
+
+pub struct Interpreter<'a, 'o> {
+    modules: &'a [Module<'a>],
+    /// Evaluators of primitive operations, one per module (they know type
+    /// aliases of the module).
+    evaluators: Vec<Evaluator<'a>>,
+    functions: HashMap<&'a str, (usize, &'a FunctionDecl)>,
+    /// Methods of the type (by name of the type and of the method).
+    methods: HashMap<String, HashMap<&'a str, Callable<'a>>>,
+    enums: HashMap<&'a str, &'a EnumDecl>,
+    newtypes: HashMap<&'a str, (usize, &'a Newtype)>,
+    globals: HashMap<String, Value>,
+    frames: Vec<Frame<'a>>,
+    max_depth: usize,
+    output: &'o mut dyn Write,
+}
+
+/// Name of the type, that methods are implemented for: name of struct,
+/// enum or primitive type, `list` or `option`.
+pub(crate) fn type_name(r#type: &Type) -> Option<String> {
+    match &*r#type.value {
+        RawType::Primary(name, _) => Some(name.value.clone()),
+        RawType::Array(..) => Some("list".to_owned()),
+        RawType::Option(_) => Some("option".to_owned()),
+        RawType::Pointer(inner) => type_name(inner),
+        RawType::Generic(_) => None,
+    }
+}
+
+/// Binary operator applied by the compound assignment.
+pub(crate) fn compound_operator(operator: &RawToken) -> Option<RawToken> {
+    match operator {
+        RawToken::PlusEq => Some(RawToken::Plus),
+        RawToken::MinusEq => Some(RawToken::Minus),
+        RawToken::AsteriskEq => Some(RawToken::Asterisk),
+        RawToken::SlashEq => Some(RawToken::Slash),
+        RawToken::XorEq => Some(RawToken::Xor),
+        RawToken::OrEq => Some(RawToken::Or),
+        _ => None,
+    }
+}
+
+impl<'a, 'o> Interpreter<'a, 'o> {
+    /// Prepares the program for execution. Module `0` is the entry point,
+    /// declarations in it shadow ones of the imported modules. Output of
+    /// `print` and `println` is written to `output`.
+    pub fn new(modules: &'a [Module<'a>], output: &'o mut dyn Write) -> Self {
+        let mut interpreter = Self {
+            modules,
+            evaluators: modules
+                .iter()
+                .map(|module| {
+                    Evaluator::with_constants(HashMap::new())
+                        .with_aliases(TypeAliases::new(module.ast))
+                })
+                .collect(),
+            functions: HashMap::new(),
+            methods: HashMap::new(),
+            enums: HashMap::new(),
+            newtypes: HashMap::new(),
+            globals: HashMap::new(),
+            frames: vec![],
+            max_depth: DEFAULT_MAX_DEPTH,
+            output,
+        };
+
+        let mut traits = HashMap::new();
+
+        for (i, module) in modules.iter().enumerate() {
+            for (_, statement) in &module.ast.top_level_statements {
+                match statement {
+                    TopLevelStatement::FunctionDecl(decl) => {
+                        interpreter
+                            .functions
+                            .entry(&decl.def.name.value)
+                            .or_insert((i, decl));
+                    }
+                    TopLevelStatement::EnumDecl(decl) => {
+                        interpreter.enums.entry(&decl.name.value).or_insert(decl);
+                    }
+                    TopLevelStatement::Newtype(decl) => {
+                        interpreter
+                            .newtypes
+                            .entry(&decl.name.value)
+                            .or_insert((i, decl));
+                    }
+                    TopLevelStatement::TraitDecl(decl) => {
+                        traits.entry(decl.name.value.as_str()).or_insert((i, decl));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // Inherent methods take precedence over trait ones, methods of
+        // the implementation - over default methods of the trait.
+        let implementations = modules.iter().enumerate().flat_map(|(i, module)| {
+            module
+                .ast
+                .top_level_statements
+                .iter()
+                .filter_map(move |(_, statement)| match statement {
+                    TopLevelStatement::Impl(r#impl) => Some((i, r#impl)),
+                    _ => None,
+                })
+        });
+        let (inherent, trait_impls): (Vec<_>, Vec<_>) =
+            implementations.partition(|(_, r#impl)| r#impl.r#trait.is_none());
+
+        for (i, r#impl) in inherent.into_iter().chain(trait_impls) {
+            let resolved = TypeAliases::new(modules[i].ast)
+                .resolve(&r#impl.r#type)
+                .unwrap_or_else(|_| r#impl.r#type.clone());
+
+            let Some(name) = type_name(&resolved) else {
+                continue;
+            };
+
+            let methods = interpreter.methods.entry(name).or_default();
+
+            for (_, method) in &r#impl.methods {
+                if let Some(callable) = Callable::method(i, method) {
+                    methods.entry(&method.name.value).or_insert(callable);
+                }
+            }
+
+            let trait_decl = r#impl
+                .r#trait
+                .as_ref()
+                .and_then(type_name)
+                .and_then(|name| traits.get(name.as_str()));
+
+            if let Some(&(module, trait_decl)) = trait_decl {
+                for (_, method) in &trait_decl.methods {
+                    if let Some(callable) = Callable::method(module, method) {
+                        methods.entry(&method.name.value).or_insert(callable);
+                    }
+                }
+            }
+        }
+
+        interpreter
+    }
+
+    /// Limits nesting of function calls, deeper calls stop the program with
+    /// [`RuntimeError::StackOverflow`]. Each call takes some native stack,
+    /// so the limit must match the stack size of the thread (see
+    /// [`with_program_stack`]).
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets values of global variables and constants, which are not
+    /// initialized again by [`Interpreter::initialize`] (for example, kept
+    /// between inputs of the REPL).
+    pub fn with_globals(mut self, globals: HashMap<String, Value>) -> Self {
+        self.globals = globals;
+        self
+    }
+
+    /// Values of global variables and constants.
+    pub fn globals(&self) -> &HashMap<String, Value> {
+        &self.globals
+    }
+
+    /// Initializes global variables and calls `main`. Returns the value
+    /// returned by `main`, or the error with the index of the module, where
+    /// it happened.
+    pub fn run(mut self) -> Result<Value, (usize, RuntimeError)> {
+        self.initialize()?;
+
+        let Some((0, _)) = self.functions.get("main") else {
+            return Err((0, RuntimeError::NoMain));
+        };
+
+        self.call("main", vec![])
+    }
+
+    /// Initializes constants and global variables of all modules.
+    pub fn initialize(&mut self) -> Result<(), (usize, RuntimeError)> {
+        let modules = self.modules;
+
+        for module in initialization_order(modules) {
+            self.frames.push(Frame::new(module));
+
+            for (_, statement) in &modules[module].ast.top_level_statements {
+                let (name, r#type, value) = match statement {
+                    TopLevelStatement::ConstDecl(decl) => (&decl.name, &decl.r#type, &decl.value),
+                    TopLevelStatement::VarDecl(decl) => (&decl.name, &decl.r#type, &decl.value),
+                    _ => continue,
+                };
+
+                if self.globals.contains_key(&name.value) {
+                    continue;
+                }
+
+                let result = self
+                    .expression(value)
+                    .and_then(|result| self.coerce(result, r#type.as_ref(), &value.span));
+
+                match result {
+                    Ok(value) => {
+                        self.globals.insert(name.value.clone(), value);
+                    }
+                    Err(unwind) => return Err(self.leave(unwind)),
+                }
+            }
+
+            self.frames.pop();
+        }
+
+        Ok(())
+    }
+
+    /// Calls top level function (or `Type::method`) `name` of the program.
+    pub fn call(&mut self, name: &str, args: Vec<Value>) -> Result<Value, (usize, RuntimeError)> {
+        self.frames.push(Frame::new(0));
+
+        let result = self.call_function(name, args, &Span::default());
+
+        self.frames.pop();
+        result.map_err(|unwind| self.leave(unwind))
+    }
+
+    /// Executes statements of the `module` in the scope with local
+    /// `variables`, then runs deferred expressions. Returns the value of
+    /// the last expression without semicolon (or of `return`) and
+    /// variables after execution, including declared ones.
+    pub fn evaluate(
+        &mut self,
+        module: usize,
+        statements: &'a [Statement],
+        variables: HashMap<String, Value>,
+    ) -> Result<(Value, HashMap<String, Value>), (usize, RuntimeError)> {
+        let mut frame = Frame::new(module);
+        frame.scopes = vec![variables];
+        self.frames.push(frame);
+
+        let result = match self.statements(statements) {
+            Ok(value) | Err(Unwind::Return(value)) => Ok(value),
+            Err(error) => Err(error),
+        }
+        .and_then(|value| {
+            let defers = mem::take(&mut self.frame().defers);
+
+            for expression in defers.into_iter().rev() {
+                self.expression(expression)?;
+            }
+
+            Ok(value)
+        });
+
+        match result {
+            Ok(value) => {
+                let mut frame = self.frames.pop().expect("frame is pushed above");
+                Ok((value, frame.scopes.remove(0)))
+            }
+            Err(unwind) => Err(self.leave(unwind)),
+        }
+    }
+
+    /// Converts the reason of unwinding to the top into error and resets
+    /// the call stack.
+    fn leave(&mut self, unwind: Unwind) -> (usize, RuntimeError) {
+        self.frames.clear();
+
+        match unwind {
+            Unwind::Error(error) => *error,
+            Unwind::Return(_) => unreachable!("return outside of function"),
+        }
+    }
+
+    fn frame(&mut self) -> &mut Frame<'a> {
+        self.frames
+            .last_mut()
+            .expect("no function is being executed")
+    }
+
+    fn module(&self) -> usize {
+        self.frames.last().map_or(0, |frame| frame.module)
+    }
+
+    /// Error in the current module.
+    fn fail(&self, error: RuntimeError) -> Unwind {
+        Unwind::Error(Box::new((self.module(), error)))
+    }
+
+    /// Converts the error of the operation into unwinding from the current
+    /// module.
+    fn check<T>(&self, result: Result<T, RuntimeError>) -> Flow<T> {
+        result.map_err(|error| self.fail(error))
+    }
+
+    /// Evaluator of primitive operations in the current module.
+    fn evaluator(&mut self) -> &mut Evaluator<'a> {
+        let module = self.module();
+        &mut self.evaluators[module]
+    }
+
+    /// Converts primitive value into the declared type: untyped number
+    /// literals get the type.
+    fn coerce(&mut self, value: Value, r#type: Option<&Type>, span: &Span) -> Flow<Value> {
+        let Some(r#type) = r#type else {
+            return Ok(value);
+        };
+
+        let result = operations::coerce(self.evaluator(), value, r#type, span);
+        self.check(result)
+    }
+
+    fn declare(&mut self, name: &str, value: Value) {
+        self.frame()
+            .scopes
+            .last_mut()
+            .expect("function has at least one scope")
+            .insert(name.to_owned(), value);
+    }
+
+    /// Finds the variable (local or global) to read or assign it.
+    fn variable(&mut self, name: &str) -> Option<&mut Value> {
+        let frame = self.frames.last_mut()?;
+
+        match frame
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+        {
+            Some(value) => Some(value),
+            None => self.globals.get_mut(name),
+        }
+    }
+
+    fn name(&mut self, name: &str, span: &Span) -> Flow<Value> {
+        if let Some(value) = self.variable(name) {
+            return Ok(value.clone());
+        }
+
+        if name == "none" {
+            return Ok(Value::Option(None));
+        }
+
+        if self.functions.contains_key(name)
+            || self.newtypes.contains_key(name)
+            || BUILTINS.contains(&name)
+        {
+            return Ok(Value::Function(name.to_owned()));
+        }
+
+        if let Some((r#type, item)) = name.rsplit_once("::") {
+            if let Some(decl) = self.enums.get(r#type) {
+                if decl.variants.iter().any(|variant| variant.value == item) {
+                    return Ok(Value::Variant(r#type.to_owned(), item.to_owned()));
+                }
+            }
+
+            if self
+                .methods
+                .get(r#type)
+                .is_some_and(|methods| methods.contains_key(item))
+            {
+                return Ok(Value::Function(name.to_owned()));
+            }
+        }
+
+        Err(self.fail(RuntimeError::UndefinedName(span.clone(), name.to_owned())))
+    }
+
+    fn block(&mut self, statements: &'a [Statement]) -> Flow<Value> {
+        self.frame().scopes.push(HashMap::new());
+
+        let result = self.statements(statements);
+
+        self.frame().scopes.pop();
+        result
+    }
+
+    /// Executes statements in the current scope. Returns the value of
+    /// the last expression without semicolon.
+    fn statements(&mut self, statements: &'a [Statement]) -> Flow<Value> {
+        let mut result = Value::Unit;
+
+        for statement in statements {
+            result = Value::Unit;
+
+            match statement {
+                Statement::Expression(expression) => {
+                    self.expression(expression)?;
+                }
+                Statement::ExpressionWithoutSemicolon(expression) => {
+                    result = self.expression(expression)?;
+                }
+                Statement::Return(expression) => {
+                    let value = self.expression(expression)?;
+                    return Err(Unwind::Return(value));
+                }
+                Statement::Defer(expression) => self.frame().defers.push(expression),
+                Statement::Var(name, r#type, value) => {
+                    let value = self.expression(value)?;
+                    let value = self.coerce(value, r#type.as_ref(), &name.span)?;
+
+                    self.declare(&name.value, value);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn expression(&mut self, expression: &'a Expression) -> Flow<Value> {
+        let span = &expression.span;
+
+        match &*expression.value {
+            RawExpression::String(value) => Ok(Value::string(value.clone())),
+            RawExpression::Int(value, suffix) => Ok(Value::Primitive(eval::Value::Int(
+                Int::from(*value),
+                *suffix,
+            ))),
+            RawExpression::Float(value, suffix) => {
+                Ok(Value::Primitive(eval::Value::Float(*value, *suffix)))
+            }
+            RawExpression::Bool(value) => Ok(Value::bool(*value)),
+            RawExpression::Char(value) => Ok(Value::Primitive(eval::Value::Char(*value))),
+            RawExpression::Imag(_) => Err(self.fail(RuntimeError::InvalidOperation(
+                span.clone(),
+                "imaginary numbers are not supported".to_owned(),
+            ))),
+            RawExpression::Map(_) => Err(self.fail(RuntimeError::InvalidOperation(
+                span.clone(),
+                "maps are not supported".to_owned(),
+            ))),
+            RawExpression::StaticName(name) => self.name(name, span),
+            RawExpression::List(items) => {
+                let items = items
+                    .iter()
+                    .map(|item| self.expression(item))
+                    .collect::<Flow<Vec<_>>>()?;
+
+                Ok(Value::list(items))
+            }
+            RawExpression::Struct(name, fields) => {
+                let mut values = BTreeMap::new();
+
+                for (field, (_, value)) in fields {
+                    values.insert(field.clone(), self.expression(&value.value)?);
+                }
+
+                Ok(Value::Struct(
+                    name.value.clone(),
+                    Rc::new(RefCell::new(values)),
+                ))
+            }
+            RawExpression::Binary(left, operator, right) => {
+                self.binary(left, operator, right, span)
+            }
+            RawExpression::As(inner, r#type) => {
+                let value = self.expression(inner)?;
+                let result = operations::cast(self.evaluator(), value, r#type, span);
+
+                self.check(result)
+            }
+            RawExpression::PrefixOrPostfix(operator, inner) => {
+                self.prefix_or_postfix(expression, operator, inner)
+            }
+            RawExpression::Property(receiver, field) => {
+                let receiver = self.expression(receiver)?;
+
+                self.check(operations::field(&receiver, &field.value, &field.span))
+            }
+            RawExpression::Call(_, callee, args) => {
+                if let RawExpression::Property(receiver, method) = &*callee.value {
+                    let receiver = self.expression(receiver)?;
+
+                    // Field of function type is called like a method.
+                    let callee = match &receiver {
+                        Value::Struct(_, fields) => fields.borrow().get(&method.value).cloned(),
+                        _ => None,
+                    };
+
+                    let args = self.arguments(args)?;
+
+                    return match callee {
+                        Some(callee) => self.call_value(callee, args, span),
+                        None => self.call_method(receiver, method, args, span),
+                    };
+                }
+
+                let callee = self.expression(callee)?;
+                let args = self.arguments(args)?;
+
+                self.call_value(callee, args, span)
+            }
+            RawExpression::Index(list, index) => {
+                let list = self.expression(list)?;
+                let index = self.expression(index)?;
+
+                self.check(operations::index(&list, &index, span))
+            }
+            RawExpression::If((condition, block), else_ifs, r#else) => {
+                if self.condition(condition)? {
+                    return self.block(block);
+                }
+
+                for (condition, block) in else_ifs {
+                    if self.condition(condition)? {
+                        return self.block(block);
+                    }
+                }
+
+                match r#else {
+                    Some(block) => self.block(block),
+                    None => Ok(Value::Unit),
+                }
+            }
+            RawExpression::While(condition, block) => {
+                while self.condition(condition)? {
+                    self.block(block)?;
+                }
+
+                Ok(Value::Unit)
+            }
+        }
+    }
+
+    fn arguments(&mut self, args: &'a [Expression]) -> Flow<Vec<Value>> {
+        args.iter().map(|arg| self.expression(arg)).collect()
+    }
+
+    fn condition(&mut self, condition: &'a Expression) -> Flow<bool> {
+        let value = self.expression(condition)?;
+
+        self.check(operations::condition(value, &condition.span))
+    }
+
+    fn binary(
+        &mut self,
+        left: &'a Expression,
+        operator: &'a Token,
+        right: &'a Expression,
+        span: &Span,
+    ) -> Flow<Value> {
+        match &operator.value {
+            RawToken::Assign => {
+                let value = self.expression(right)?;
+                self.assign(left, value)?;
+
+                return Ok(Value::Unit);
+            }
+            RawToken::AndAnd | RawToken::OrOr => {
+                let left = self.condition(left)?;
+
+                // Right operand is evaluated only if it is needed.
+                if left == (operator.value == RawToken::OrOr) {
+                    return Ok(Value::bool(left));
+                }
+
+                return self.condition(right).map(Value::bool);
+            }
+            RawToken::Elvis => {
+                return match self.expression(left)? {
+                    Value::Option(Some(value)) => Ok(*value),
+                    Value::Option(None) => self.expression(right),
+                    value => Ok(value),
+                };
+            }
+            _ => {}
+        }
+
+        if let Some(raw) = compound_operator(&operator.value) {
+            let current = self.expression(left)?;
+            let value = self.expression(right)?;
+            let value = self.operation(
+                current,
+                &WithSpan::new(raw, operator.span.clone()),
+                value,
+                span,
+            )?;
+            self.assign(left, value)?;
+
+            return Ok(Value::Unit);
+        }
+
+        let left = self.expression(left)?;
+        let right = self.expression(right)?;
+
+        self.operation(left, operator, right, span)
+    }
+
+    /// Applies binary operator to the evaluated operands.
+    fn operation(
+        &mut self,
+        left: Value,
+        operator: &Token,
+        right: Value,
+        span: &Span,
+    ) -> Flow<Value> {
+        let result = operations::binary(self.evaluator(), left, operator, right, span);
+        self.check(result)
+    }
+
+    fn prefix_or_postfix(
+        &mut self,
+        expression: &'a Expression,
+        operator: &'a Token,
+        inner: &'a Expression,
+    ) -> Flow<Value> {
+        let span = &expression.span;
+
+        match &operator.value {
+            // Negative literals, like `-128i8`, may not have positive
+            // counterpart of the same type.
+            RawToken::Minus if matches!(&*inner.value, RawExpression::Int(..)) => {
+                let evaluator = self.evaluator();
+                let result = evaluator.evaluate(expression);
+                let result = operations::primitive(evaluator, result, span, String::new);
+
+                self.check(result).map(Value::Primitive)
+            }
+            RawToken::PlusPlus | RawToken::MinusMinus => {
+                let raw = if operator.value == RawToken::PlusPlus {
+                    RawToken::Plus
+                } else {
+                    RawToken::Minus
+                };
+
+                let old = self.expression(inner)?;
+                let one = Value::Primitive(eval::Value::Int(Int::Signed(1), None));
+                let new = self.operation(
+                    old.clone(),
+                    &WithSpan::new(raw, operator.span.clone()),
+                    one,
+                    span,
+                )?;
+
+                self.assign(inner, new.clone())?;
+
+                let prefix = operator.span.range.start < inner.span.range.start;
+                Ok(if prefix { new } else { old })
+            }
+            RawToken::QuestionMark => match self.expression(inner)? {
+                Value::Option(Some(value)) => Ok(*value),
+                Value::Option(None) => Err(Unwind::Return(Value::Option(None))),
+                value => Ok(value),
+            },
+            RawToken::BangBang => match self.expression(inner)? {
+                Value::Option(Some(value)) => Ok(*value),
+                Value::Option(None) => Err(self.fail(RuntimeError::UnwrapNone(span.clone()))),
+                value => Ok(value),
+            },
+            _ => {
+                let value = self.expression(inner)?;
+                let result = operations::unary(self.evaluator(), operator, value, span);
+
+                self.check(result)
+            }
+        }
+    }
+
+    fn assign(&mut self, target: &'a Expression, value: Value) -> Flow<()> {
+        let span = &target.span;
+
+        match &*target.value {
+            RawExpression::StaticName(name) => match self.variable(name) {
+                Some(variable) => {
+                    *variable = value;
+                    Ok(())
+                }
+                None => Err(self.fail(RuntimeError::UndefinedName(span.clone(), name.clone()))),
+            },
+            RawExpression::Property(receiver, field) => {
+                let receiver = self.expression(receiver)?;
+
+                self.check(operations::set_field(
+                    &receiver,
+                    &field.value,
+                    value,
+                    &field.span,
+                ))
+            }
+            RawExpression::Index(list, index) => {
+                let list = self.expression(list)?;
+                let index = self.expression(index)?;
+
+                self.check(operations::set_index(&list, &index, value, span))
+            }
+            _ => Err(self.fail(RuntimeError::InvalidOperation(
+                span.clone(),
+                "cannot assign to this expression".to_owned(),
+            ))),
+        }
+    }
+
+    fn call_value(&mut self, callee: Value, args: Vec<Value>, span: &Span) -> Flow<Value> {
+        match callee {
+            Value::Function(name) => self.call_function(&name, args, span),
+            callee => Err(self.fail(RuntimeError::InvalidOperation(
+                span.clone(),
+                format!("`{}` is not a function", callee.type_name()),
+            ))),
+        }
+    }
+
+    fn call_method(
+        &mut self,
+        receiver: Value,
+        method: &WithSpan<String>,
+        mut args: Vec<Value>,
+        span: &Span,
+    ) -> Flow<Value> {
+        let r#type = receiver.type_name();
+
+        let Some(callable) = self
+            .methods
+            .get(&r#type)
+            .and_then(|methods| methods.get(method.value.as_str()))
+            .copied()
+        else {
+            return Err(self.fail(RuntimeError::NoMethod(
+                method.span.clone(),
+                method.value.clone(),
+                r#type,
+            )));
+        };
+
+        if callable.takes_self() {
+            args.insert(0, receiver);
+        }
+
+        self.invoke(&format!("{type}::{}", method.value), &callable, args, span)
+    }
+
+    /// Calls function (or `Type::method`, builtin, or creates the value of
+    /// the newtype) by its name.
+    fn call_function(&mut self, name: &str, args: Vec<Value>, span: &Span) -> Flow<Value> {
+        if let Some(&(module, decl)) = self.functions.get(name) {
+            return self.invoke(name, &Callable::function(module, decl), args, span);
+        }
+
+        if let Some(&(module, decl)) = self.newtypes.get(name) {
+            let value = match <[Value; 1]>::try_from(args) {
+                Ok([value]) => value,
+                Err(args) => {
+                    return Err(self.fail(RuntimeError::ArgumentCount(
+                        span.clone(),
+                        name.to_owned(),
+                        1,
+                        args.len(),
+                    )))
+                }
+            };
+
+            let result = operations::coerce(&mut self.evaluators[module], value, &decl.value, span);
+            let value = self.check(result)?;
+
+            return Ok(Value::newtype(name, value));
+        }
+
+        let method = name
+            .rsplit_once("::")
+            .and_then(|(r#type, method)| self.methods.get(r#type)?.get(method).copied());
+
+        if let Some(callable) = method {
+            return self.invoke(name, &callable, args, span);
+        }
+
+        if BUILTINS.contains(&name) {
+            let module = self.module();
+            let result = operations::call_builtin(
+                &mut self.evaluators[module],
+                name,
+                args,
+                span,
+                &mut *self.output,
+            );
+
+            return self.check(result);
+        }
+
+        Err(self.fail(RuntimeError::UndefinedName(span.clone(), name.to_owned())))
+    }
+
+    fn invoke(
+        &mut self,
+        name: &str,
+        callable: &Callable<'a>,
+        args: Vec<Value>,
+        span: &Span,
+    ) -> Flow<Value> {
+        if self.frames.len() > self.max_depth {
+            return Err(self.fail(RuntimeError::StackOverflow(span.clone(), self.max_depth)));
+        }
+
+        let required = callable
+            .params
+            .iter()
+            .take_while(|param| param.default_value.is_none())
+            .count();
+
+        if args.len() < required || args.len() > callable.params.len() {
+            return Err(self.fail(RuntimeError::ArgumentCount(
+                span.clone(),
+                name.to_owned(),
+                callable.params.len(),
+                args.len(),
+            )));
+        }
+
+        self.frames.push(Frame::new(callable.module));
+
+        let result = self.execute(callable, args);
+
+        self.frames.pop();
+        result
+    }
+
+    /// Executes the body of the function in the new frame.
+    fn execute(&mut self, callable: &Callable<'a>, args: Vec<Value>) -> Flow<Value> {
+        let mut args = args.into_iter();
+
+        for param in callable.params {
+            let value = match (args.next(), &param.default_value) {
+                (Some(value), _) => value,
+                (None, Some(default)) => self.expression(default)?,
+                (None, None) => unreachable!("number of arguments is checked"),
+            };
+            let value = self.coerce(value, Some(&param.r#type), &param.name.span)?;
+
+            self.declare(&param.name.value, value);
+        }
+
+        let value = match self.statements(callable.body) {
+            Ok(value) | Err(Unwind::Return(value)) => value,
+            Err(error) => return Err(error),
+        };
+
+        let defers = mem::take(&mut self.frame().defers);
+
+        for expression in defers.into_iter().rev() {
+            self.expression(expression)?;
+        }
+
+        let span = callable
+            .return_type
+            .map_or_else(Span::default, |r#type| r#type.span.clone());
+
+        self.coerce(value, callable.return_type, &span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ry_parser::Parser;
+
+    use super::*;
+    use crate::test_utils::analyzed;
+
+    #[test]
+    fn evaluate_test() {
+        let ast = analyzed("var calls = 0;\nfun count() i32 { calls += 1; calls }");
+        let statements = Parser::new("var b = a + count(); defer println(b); b * 2")
+            .parse_statements()
+            .unwrap();
+
+        let modules = [Module {
+            ast: &ast,
+            imports: vec![],
+        }];
+        let mut output = vec![];
+        let globals = HashMap::from([(
+            "calls".to_owned(),
+            Value::Primitive(eval::Value::Int(Int::Signed(5), None)),
+        )]);
+
+        let mut interpreter = Interpreter::new(&modules, &mut output).with_globals(globals);
+        interpreter.initialize().unwrap();
+
+        let variables = HashMap::from([(
+            "a".to_owned(),
+            Value::Primitive(eval::Value::Int(Int::Signed(10), None)),
+        )]);
+        let (value, variables) = interpreter.evaluate(0, &statements, variables).unwrap();
+
+        assert_eq!(value.to_string(), "32");
+        assert_eq!(variables["b"].to_string(), "16");
+        assert_eq!(interpreter.globals()["calls"].to_string(), "6");
+        assert_eq!(String::from_utf8(output).unwrap(), "16\n");
+    }
+}
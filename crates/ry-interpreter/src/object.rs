@@ -0,0 +1,715 @@
+//! `object.rs` - on-disk format of compiled modules (object files).
+//!
+//! Object file consists of the header and the payload:
+//!
+//! | Field            | Encoding                                   |
+//! |------------------|--------------------------------------------|
+//! | magic            | `RYBC`                                     |
+//! | format version   | `u32`, see [`FORMAT_VERSION`]              |
+//! | compiler version | string                                     |
+//! | source hash      | SHA-256 of the source code, 32 bytes       |
+//! | payload length   | `u64`                                      |
+//! | payload checksum | SHA-256 of the payload, 32 bytes           |
+//!
+//! The payload contains sections of [`Module`] in order of its fields:
+//! imports, constant pool, symbol table, types, aliases, newtypes, layouts,
+//! functions (code with debug spans and offsets of deferred expressions),
+//! top level functions, globals, enums, impls and traits. Integers are
+//! little-endian, strings and lists are prefixed with their length (`u32`).
+//! Parts of the AST (types, aliases and operators) are stored as JSON,
+//! the same way as analyzed programs are cached.
+//!
+//! Objects are validated when they are read: files of other versions,
+//! truncated or damaged files and modules with operands out of bounds are
+//! rejected, so the virtual machine never executes invalid bytecode.
+use std::fmt;
+
+use ry_ast::{
+    location::Span,
+    token::{NumberSuffix, RawToken},
+};
+use ry_static_analyzer::eval::{self, Int};
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::bytecode::{Enum, Function, Global, Impl, Instruction, Layout, Module, Trait};
+
+const MAGIC: &[u8; 4] = b"RYBC";
+
+/// Version of the object file format, it changes with the layout of
+/// the file or the instruction set.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// Version of the compiler, objects of other versions are not loaded.
+const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// SHA-256 hash, used to check, that the object is compiled from the same
+/// source code.
+pub type Hash = [u8; 32];
+
+pub fn hash(data: &[u8]) -> Hash {
+    Sha256::digest(data).into()
+}
+
+/// Why the object file can't be loaded.
+#[derive(Debug, PartialEq)]
+pub enum ObjectError {
+    /// The file is not an object file.
+    NotObject,
+
+    /// The file has unsupported version [`u32`] of the format.
+    FormatVersion(u32),
+
+    /// The file is written by another version [`String`] of the compiler.
+    CompilerVersion(String),
+
+    /// The file is truncated, damaged or contains invalid bytecode.
+    /// [`String`] is the explanation.
+    Corrupted(String),
+}
+
+impl fmt::Display for ObjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotObject => write!(f, "file is not a compiled Ry module"),
+            Self::FormatVersion(version) => write!(
+                f,
+                "object format version {version} is not supported (expected {FORMAT_VERSION})"
+            ),
+            Self::CompilerVersion(version) => write!(
+                f,
+                "module is compiled by compiler {version} (expected {COMPILER_VERSION})"
+            ),
+            Self::Corrupted(reason) => write!(f, "object file is corrupted: {reason}"),
+        }
+    }
+}
+
+type ReadResult<T> = Result<T, ObjectError>;
+
+fn corrupted<T>(reason: impl Into<String>) -> ReadResult<T> {
+    Err(ObjectError::Corrupted(reason.into()))
+}
+
+/// Compiled module together with the hash of its source code.
+#[derive(Debug, PartialEq)]
+pub struct Object {
+    pub source_hash: Hash,
+    pub module: Module,
+}
+
+impl Object {
+    pub fn new(source: &str, module: Module) -> Self {
+        Self {
+            source_hash: hash(source.as_bytes()),
+            module,
+        }
+    }
+
+    /// Whether the module is compiled from the `source`.
+    pub fn is_compiled_from(&self, source: &str) -> bool {
+        self.source_hash == hash(source.as_bytes())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut payload = Writer::default();
+        payload.module(&self.module);
+        let payload = payload.bytes;
+
+        let mut writer = Writer::default();
+        writer.bytes.extend_from_slice(MAGIC);
+        writer.u32(FORMAT_VERSION);
+        writer.str(COMPILER_VERSION);
+        writer.bytes.extend_from_slice(&self.source_hash);
+        writer.u64(payload.len() as u64);
+        writer.bytes.extend_from_slice(&hash(&payload));
+        writer.bytes.extend_from_slice(&payload);
+
+        writer.bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> ReadResult<Self> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(ObjectError::NotObject);
+        }
+
+        let mut reader = Reader {
+            bytes,
+            position: MAGIC.len(),
+        };
+
+        let version = reader.u32()?;
+
+        if version != FORMAT_VERSION {
+            return Err(ObjectError::FormatVersion(version));
+        }
+
+        let compiler = reader.string()?;
+
+        if compiler != COMPILER_VERSION {
+            return Err(ObjectError::CompilerVersion(compiler));
+        }
+
+        let source_hash = reader.hash()?;
+        let len = reader.u64()?;
+        let checksum = reader.hash()?;
+        let payload = &bytes[reader.position..];
+
+        if payload.len() as u64 != len {
+            return corrupted(format!(
+                "payload has {} bytes, expected {len}",
+                payload.len()
+            ));
+        }
+
+        if hash(payload) != checksum {
+            return corrupted("checksum of the payload doesn't match");
+        }
+
+        let mut reader = Reader {
+            bytes: payload,
+            position: 0,
+        };
+        let module = reader.module()?;
+        validate(&module)?;
+
+        Ok(Self {
+            source_hash,
+            module,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn str(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    fn json(&mut self, value: &impl Serialize) {
+        self.str(&serde_json::to_string(value).expect("AST is serializable"));
+    }
+
+    fn list<T>(&mut self, items: &[T], mut write: impl FnMut(&mut Self, &T)) {
+        self.u32(items.len() as u32);
+
+        for item in items {
+            write(self, item);
+        }
+    }
+
+    fn suffix(&mut self, suffix: Option<NumberSuffix>) {
+        self.str(&suffix.map(|suffix| suffix.to_string()).unwrap_or_default());
+    }
+
+    fn constant(&mut self, constant: &eval::Value) {
+        match constant {
+            eval::Value::Int(value, suffix) => {
+                match value {
+                    Int::Signed(value) => {
+                        self.u8(0);
+                        self.bytes.extend_from_slice(&value.to_le_bytes());
+                    }
+                    Int::Unsigned(value) => {
+                        self.u8(1);
+                        self.bytes.extend_from_slice(&value.to_le_bytes());
+                    }
+                }
+
+                self.suffix(*suffix);
+            }
+            eval::Value::Float(value, suffix) => {
+                self.u8(2);
+                self.u64(value.to_bits());
+                self.suffix(*suffix);
+            }
+            eval::Value::Bool(value) => {
+                self.u8(3);
+                self.u8(u8::from(*value));
+            }
+            eval::Value::Char(value) => {
+                self.u8(4);
+                self.u32(u32::from(*value));
+            }
+            eval::Value::String(value) => {
+                self.u8(5);
+                self.str(value);
+            }
+        }
+    }
+
+    fn instruction(&mut self, instruction: &Instruction) {
+        let (opcode, operands): (u8, &[u32]) = match instruction {
+            Instruction::Constant(constant) => (0, &[*constant]),
+            Instruction::Unit => (1, &[]),
+            Instruction::Pop => (2, &[]),
+            Instruction::Dup => (3, &[]),
+            Instruction::LoadLocal(slot) => (4, &[*slot]),
+            Instruction::StoreLocal(slot) => (5, &[*slot]),
+            Instruction::LoadName(symbol) => (6, &[*symbol]),
+            Instruction::StoreName(symbol) => (7, &[*symbol]),
+            Instruction::List(len) => (8, &[*len]),
+            Instruction::Struct(layout) => (9, &[*layout]),
+            Instruction::GetField(symbol) => (10, &[*symbol]),
+            Instruction::SetField(symbol) => (11, &[*symbol]),
+            Instruction::GetIndex => (12, &[]),
+            Instruction::SetIndex => (13, &[]),
+            Instruction::Binary(operator) | Instruction::Unary(operator) => {
+                let opcode = if matches!(instruction, Instruction::Binary(_)) {
+                    14
+                } else {
+                    15
+                };
+
+                self.u8(opcode);
+                self.json(operator);
+                return;
+            }
+            Instruction::Cast(r#type) => (16, &[*r#type]),
+            Instruction::Coerce(r#type) => (17, &[*r#type]),
+            Instruction::Jump(target) => (18, &[*target]),
+            Instruction::JumpIfFalse(target) => (19, &[*target]),
+            Instruction::JumpIfTrue(target) => (20, &[*target]),
+            Instruction::JumpIfArgument(param, target) => (21, &[*param, *target]),
+            Instruction::JumpIfSome(target) => (22, &[*target]),
+            Instruction::Try => (23, &[]),
+            Instruction::Unwrap => (24, &[]),
+            Instruction::Call(args) => (25, &[*args]),
+            Instruction::CallName(symbol, args) => (26, &[*symbol, *args]),
+            Instruction::CallMethod(symbol, args) => (27, &[*symbol, *args]),
+            Instruction::Defer(defer) => (28, &[*defer]),
+            Instruction::EndDefer => (29, &[]),
+            Instruction::Return => (30, &[]),
+            Instruction::Unsupported(message) => (31, &[*message]),
+        };
+
+        self.u8(opcode);
+
+        for operand in operands {
+            self.u32(*operand);
+        }
+    }
+
+    fn function(&mut self, function: &Function) {
+        self.str(&function.name);
+        self.u32(function.params);
+        self.u32(function.required);
+        self.u8(u8::from(function.takes_self));
+        self.u32(function.locals);
+        self.u32(function.return_type.map_or(u32::MAX, |r#type| r#type));
+        self.list(&function.code, Self::instruction);
+        self.list(&function.spans, |writer, span| {
+            writer.u64(span.range.start as u64);
+            writer.u64(span.range.end as u64);
+        });
+        self.list(&function.defers, |writer, offset| writer.u32(*offset));
+    }
+
+    fn module(&mut self, module: &Module) {
+        self.list(&module.imports, |writer, import| writer.u32(*import as u32));
+        self.list(&module.constants, Self::constant);
+        self.list(&module.names, |writer, name| writer.str(name));
+        self.list(&module.types, |writer, r#type| writer.json(r#type));
+        self.list(&module.aliases, |writer, alias| writer.json(alias));
+        self.list(&module.newtypes, |writer, newtype| writer.str(newtype));
+        self.list(&module.layouts, |writer, layout| {
+            writer.str(&layout.name);
+            writer.list(&layout.fields, |writer, field| writer.str(field));
+        });
+        self.list(&module.functions, Self::function);
+        self.list(&module.top_level, |writer, function| writer.u32(*function));
+        self.list(&module.globals, |writer, global| {
+            writer.str(&global.name);
+            writer.u32(global.initializer);
+        });
+        self.list(&module.enums, |writer, r#enum| {
+            writer.str(&r#enum.name);
+            writer.list(&r#enum.variants, |writer, variant| writer.str(variant));
+        });
+        self.list(&module.impls, |writer, r#impl| {
+            writer.str(&r#impl.r#type);
+            writer.str(r#impl.r#trait.as_deref().unwrap_or_default());
+            writer.list(&r#impl.methods, |writer, method| writer.u32(*method));
+        });
+        self.list(&module.traits, |writer, r#trait| {
+            writer.str(&r#trait.name);
+            writer.list(&r#trait.methods, |writer, method| writer.u32(*method));
+        });
+    }
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+    position: usize,
+}
+
+impl<'b> Reader<'b> {
+    fn take(&mut self, len: usize) -> ReadResult<&'b [u8]> {
+        match self
+            .bytes
+            .get(self.position..self.position.saturating_add(len))
+        {
+            Some(bytes) => {
+                self.position += len;
+                Ok(bytes)
+            }
+            None => corrupted("unexpected end of the file"),
+        }
+    }
+
+    fn array<const N: usize>(&mut self) -> ReadResult<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("length is checked"))
+    }
+
+    fn u8(&mut self) -> ReadResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> ReadResult<u32> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> ReadResult<u64> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    fn hash(&mut self) -> ReadResult<Hash> {
+        self.array()
+    }
+
+    fn bool(&mut self) -> ReadResult<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => corrupted(format!("invalid bool {value}")),
+        }
+    }
+
+    fn string(&mut self) -> ReadResult<String> {
+        let len = self.u32()? as usize;
+
+        match std::str::from_utf8(self.take(len)?) {
+            Ok(string) => Ok(string.to_owned()),
+            Err(_) => corrupted("string is not valid UTF-8"),
+        }
+    }
+
+    fn json<T: DeserializeOwned>(&mut self) -> ReadResult<T> {
+        serde_json::from_str(&self.string()?)
+            .or_else(|e| corrupted(format!("invalid syntax tree: {e}")))
+    }
+
+    fn list<T>(&mut self, mut read: impl FnMut(&mut Self) -> ReadResult<T>) -> ReadResult<Vec<T>> {
+        let len = self.u32()? as usize;
+        // Length is not trusted for preallocation, every item takes at
+        // least one byte.
+        let mut items = Vec::with_capacity(len.min(self.bytes.len() - self.position));
+
+        for _ in 0..len {
+            items.push(read(self)?);
+        }
+
+        Ok(items)
+    }
+
+    fn suffix(&mut self) -> ReadResult<Option<NumberSuffix>> {
+        let name = self.string()?;
+
+        if name.is_empty() {
+            return Ok(None);
+        }
+
+        match NumberSuffix::from_name(&name) {
+            Some(suffix) => Ok(Some(suffix)),
+            None => corrupted(format!("invalid number suffix `{name}`")),
+        }
+    }
+
+    fn constant(&mut self) -> ReadResult<eval::Value> {
+        Ok(match self.u8()? {
+            0 => {
+                let value = i128::from_le_bytes(self.array()?);
+                eval::Value::Int(Int::Signed(value), self.suffix()?)
+            }
+            1 => {
+                let value = u128::from_le_bytes(self.array()?);
+                eval::Value::Int(Int::Unsigned(value), self.suffix()?)
+            }
+            2 => {
+                let value = f64::from_bits(self.u64()?);
+                eval::Value::Float(value, self.suffix()?)
+            }
+            3 => eval::Value::Bool(self.bool()?),
+            4 => match char::from_u32(self.u32()?) {
+                Some(value) => eval::Value::Char(value),
+                None => return corrupted("invalid char constant"),
+            },
+            5 => eval::Value::String(self.string()?),
+            tag => return corrupted(format!("invalid constant tag {tag}")),
+        })
+    }
+
+    fn instruction(&mut self) -> ReadResult<Instruction> {
+        Ok(match self.u8()? {
+            0 => Instruction::Constant(self.u32()?),
+            1 => Instruction::Unit,
+            2 => Instruction::Pop,
+            3 => Instruction::Dup,
+            4 => Instruction::LoadLocal(self.u32()?),
+            5 => Instruction::StoreLocal(self.u32()?),
+            6 => Instruction::LoadName(self.u32()?),
+            7 => Instruction::StoreName(self.u32()?),
+            8 => Instruction::List(self.u32()?),
+            9 => Instruction::Struct(self.u32()?),
+            10 => Instruction::GetField(self.u32()?),
+            11 => Instruction::SetField(self.u32()?),
+            12 => Instruction::GetIndex,
+            13 => Instruction::SetIndex,
+            14 => Instruction::Binary(self.json::<RawToken>()?),
+            15 => Instruction::Unary(self.json::<RawToken>()?),
+            16 => Instruction::Cast(self.u32()?),
+            17 => Instruction::Coerce(self.u32()?),
+            18 => Instruction::Jump(self.u32()?),
+            19 => Instruction::JumpIfFalse(self.u32()?),
+            20 => Instruction::JumpIfTrue(self.u32()?),
+            21 => Instruction::JumpIfArgument(self.u32()?, self.u32()?),
+            22 => Instruction::JumpIfSome(self.u32()?),
+            23 => Instruction::Try,
+            24 => Instruction::Unwrap,
+            25 => Instruction::Call(self.u32()?),
+            26 => Instruction::CallName(self.u32()?, self.u32()?),
+            27 => Instruction::CallMethod(self.u32()?, self.u32()?),
+            28 => Instruction::Defer(self.u32()?),
+            29 => Instruction::EndDefer,
+            30 => Instruction::Return,
+            31 => Instruction::Unsupported(self.u32()?),
+            opcode => return corrupted(format!("invalid opcode {opcode}")),
+        })
+    }
+
+    fn function(&mut self) -> ReadResult<Function> {
+        Ok(Function {
+            name: self.string()?,
+            params: self.u32()?,
+            required: self.u32()?,
+            takes_self: self.bool()?,
+            locals: self.u32()?,
+            return_type: Some(self.u32()?).filter(|&r#type| r#type != u32::MAX),
+            code: self.list(Self::instruction)?,
+            spans: self
+                .list(|reader| Ok(Span::new(reader.u64()? as usize, reader.u64()? as usize)))?,
+            defers: self.list(Self::u32)?,
+        })
+    }
+
+    fn module(&mut self) -> ReadResult<Module> {
+        let module = Module {
+            imports: self.list(|reader| Ok(reader.u32()? as usize))?,
+            constants: self.list(Self::constant)?,
+            names: self.list(Self::string)?,
+            types: self.list(Self::json)?,
+            aliases: self.list(Self::json)?,
+            newtypes: self.list(Self::string)?,
+            layouts: self.list(|reader| {
+                Ok(Layout {
+                    name: reader.string()?,
+                    fields: reader.list(Self::string)?,
+                })
+            })?,
+            functions: self.list(Self::function)?,
+            top_level: self.list(Self::u32)?,
+            globals: self.list(|reader| {
+                Ok(Global {
+                    name: reader.string()?,
+                    initializer: reader.u32()?,
+                })
+            })?,
+            enums: self.list(|reader| {
+                Ok(Enum {
+                    name: reader.string()?,
+                    variants: reader.list(Self::string)?,
+                })
+            })?,
+            impls: self.list(|reader| {
+                Ok(Impl {
+                    r#type: reader.string()?,
+                    r#trait: Some(reader.string()?).filter(|name| !name.is_empty()),
+                    methods: reader.list(Self::u32)?,
+                })
+            })?,
+            traits: self.list(|reader| {
+                Ok(Trait {
+                    name: reader.string()?,
+                    methods: reader.list(Self::u32)?,
+                })
+            })?,
+        };
+
+        if self.position != self.bytes.len() {
+            return corrupted("unexpected data after the module");
+        }
+
+        Ok(module)
+    }
+}
+
+/// Checks, that operands of instructions refer to existing items of
+/// the module, so that the virtual machine can index them without checks.
+fn validate(module: &Module) -> ReadResult<()> {
+    let check = |index: u32, len: usize, what: &str| {
+        if (index as usize) < len {
+            Ok(())
+        } else {
+            corrupted(format!("{what} {index} is out of bounds"))
+        }
+    };
+
+    let functions = module.functions.len();
+    let references = module
+        .top_level
+        .iter()
+        .chain(module.globals.iter().map(|global| &global.initializer))
+        .chain(module.impls.iter().flat_map(|r#impl| &r#impl.methods))
+        .chain(module.traits.iter().flat_map(|r#trait| &r#trait.methods));
+
+    for &function in references {
+        check(function, functions, "function")?;
+    }
+
+    for function in &module.functions {
+        let code = function.code.len();
+
+        if function.spans.len() != code {
+            return corrupted(format!("spans of `{}` don't match its code", function.name));
+        }
+
+        if function.params > function.locals || function.required > function.params {
+            return corrupted(format!("invalid parameters of `{}`", function.name));
+        }
+
+        if let Some(r#type) = function.return_type {
+            check(r#type, module.types.len(), "type")?;
+        }
+
+        for &offset in &function.defers {
+            check(offset, code, "offset")?;
+        }
+
+        // Every function ends with `return` or `end_defer`, so execution
+        // never goes past the end of the code.
+        if !matches!(
+            function.code.last(),
+            Some(Instruction::Return | Instruction::EndDefer)
+        ) {
+            return corrupted(format!("`{}` doesn't end with return", function.name));
+        }
+
+        for instruction in &function.code {
+            match *instruction {
+                Instruction::Constant(constant) | Instruction::Unsupported(constant) => {
+                    check(constant, module.constants.len(), "constant")?
+                }
+                Instruction::LoadLocal(slot) | Instruction::StoreLocal(slot) => {
+                    check(slot, function.locals as usize, "slot")?
+                }
+                Instruction::LoadName(symbol)
+                | Instruction::StoreName(symbol)
+                | Instruction::GetField(symbol)
+                | Instruction::SetField(symbol)
+                | Instruction::CallName(symbol, _)
+                | Instruction::CallMethod(symbol, _) => {
+                    check(symbol, module.names.len(), "symbol")?
+                }
+                Instruction::Struct(layout) => check(layout, module.layouts.len(), "layout")?,
+                Instruction::Cast(r#type) | Instruction::Coerce(r#type) => {
+                    check(r#type, module.types.len(), "type")?
+                }
+                Instruction::Jump(target)
+                | Instruction::JumpIfFalse(target)
+                | Instruction::JumpIfTrue(target)
+                | Instruction::JumpIfArgument(_, target)
+                | Instruction::JumpIfSome(target) => check(target, code, "jump target")?,
+                Instruction::Defer(defer) => check(defer, function.defers.len(), "defer")?,
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compiler::compile_module, test_utils::analyzed};
+
+    #[test]
+    fn object_test() {
+        let source = "type Number = i64;
+            const NAME = \"a\";
+            enum Color { Red }
+            trait Describe { fun describe(self Self) string { \"value\" } }
+            impl Describe for Number {}
+
+            fun main(a Number = -5, b f32 = 0.5) Number? {
+                defer println(\"%s %c %v\", NAME, 'ы', Color::Red);
+                var c = a as u128 > 340282366920938463463374607431768211455 || b == 1.0;
+                if !c { some(a + 1) } else { none }
+            }";
+
+        let ast = analyzed(source);
+
+        let object = Object::new(source, compile_module(&ast, vec![1]));
+        let bytes = object.to_bytes();
+
+        assert_eq!(Object::from_bytes(&bytes), Ok(object));
+
+        let loaded = Object::from_bytes(&bytes).unwrap();
+        assert!(loaded.is_compiled_from(source));
+        assert!(!loaded.is_compiled_from("fun main() {}"));
+
+        assert_eq!(
+            Object::from_bytes(b"{\"compiler\": \"0.1.0\"}"),
+            Err(ObjectError::NotObject)
+        );
+
+        let mut other_version = bytes.clone();
+        other_version[4] = 99;
+        assert_eq!(
+            Object::from_bytes(&other_version),
+            Err(ObjectError::FormatVersion(99))
+        );
+
+        let mut damaged = bytes.clone();
+        *damaged.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            Object::from_bytes(&damaged),
+            Err(ObjectError::Corrupted(_))
+        ));
+        assert!(matches!(
+            Object::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ObjectError::Corrupted(_))
+        ));
+        assert!(matches!(
+            Object::from_bytes(&bytes[..10]),
+            Err(ObjectError::Corrupted(_))
+        ));
+    }
+}
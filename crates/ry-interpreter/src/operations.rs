@@ -0,0 +1,271 @@
+//! `operations.rs` - operations on values, shared by the interpreter and
+//! the virtual machine, so that both execute programs the same way.
+use std::io::Write;
+
+use ry_ast::{
+    location::{Span, WithSpan},
+    token::{RawToken, Token},
+    Type,
+};
+use ry_static_analyzer::eval::{self, EvalError, EvalResult, Evaluator};
+
+use crate::{builtins, error::RuntimeError, value::Value};
+
+/// Converts the result of primitive operation. `invalid` describes
+/// the operation, if it can't be applied to the values.
+pub(crate) fn primitive<T>(
+    evaluator: &mut Evaluator,
+    result: EvalResult<T>,
+    span: &Span,
+    invalid: impl FnOnce() -> String,
+) -> Result<T, RuntimeError> {
+    match result {
+        Ok(value) => Ok(value),
+        Err(EvalError::Reported) => {
+            let error = evaluator.errors.pop().expect("error must be reported");
+
+            Err(RuntimeError::Operation(Box::new(error)))
+        }
+        Err(EvalError::NotConstant(_)) => {
+            Err(RuntimeError::InvalidOperation(span.clone(), invalid()))
+        }
+    }
+}
+
+/// Converts primitive value into the declared type: untyped number
+/// literals get the type.
+pub(crate) fn coerce(
+    evaluator: &mut Evaluator,
+    value: Value,
+    r#type: &Type,
+    span: &Span,
+) -> Result<Value, RuntimeError> {
+    let Value::Primitive(primitive) = value else {
+        return Ok(value);
+    };
+
+    let result = evaluator.coerce(primitive, r#type, span);
+
+    primitive_result(evaluator, result, span, String::new)
+}
+
+fn primitive_result(
+    evaluator: &mut Evaluator,
+    result: EvalResult<eval::Value>,
+    span: &Span,
+    invalid: impl FnOnce() -> String,
+) -> Result<Value, RuntimeError> {
+    primitive(evaluator, result, span, invalid).map(Value::Primitive)
+}
+
+/// Applies `as` cast. Casts to non-primitive types don't change the value.
+pub(crate) fn cast(
+    evaluator: &mut Evaluator,
+    value: Value,
+    r#type: &Type,
+    span: &Span,
+) -> Result<Value, RuntimeError> {
+    let Value::Primitive(primitive) = value else {
+        return Ok(value);
+    };
+
+    match evaluator.cast(primitive.clone(), r#type, span) {
+        Err(EvalError::NotConstant(_)) => Ok(Value::Primitive(primitive)),
+        result => primitive_result(evaluator, result, span, String::new),
+    }
+}
+
+/// Applies binary operator (except for assignments, `&&`, `||` and `?:`)
+/// to the evaluated operands.
+pub(crate) fn binary(
+    evaluator: &mut Evaluator,
+    left: Value,
+    operator: &Token,
+    right: Value,
+    span: &Span,
+) -> Result<Value, RuntimeError> {
+    let invalid = |left: &str, right: &str| {
+        RuntimeError::InvalidOperation(
+            span.clone(),
+            format!("cannot apply {} to `{left}` and `{right}`", operator.value),
+        )
+    };
+
+    match (left, right) {
+        (Value::Primitive(left), Value::Primitive(right)) => {
+            // Evaluator reports invalid operations on primitive values
+            // itself, so the description is never used.
+            let result = evaluator.binary(left, operator, right, span);
+
+            primitive_result(evaluator, result, span, || {
+                format!("cannot apply {} to the operands", operator.value)
+            })
+        }
+        (left, right) => match operator.value {
+            RawToken::Eq => Ok(Value::bool(left == right)),
+            RawToken::NotEq => Ok(Value::bool(left != right)),
+            RawToken::Plus => match (&left, &right) {
+                (Value::List(left), Value::List(right)) => {
+                    let mut items = left.borrow().clone();
+                    items.extend(right.borrow().iter().cloned());
+
+                    Ok(Value::list(items))
+                }
+                _ => Err(invalid(&left.type_name(), &right.type_name())),
+            },
+            _ => Err(invalid(&left.type_name(), &right.type_name())),
+        },
+    }
+}
+
+/// Applies prefix operator `-`, `!` or `~`.
+pub(crate) fn unary(
+    evaluator: &mut Evaluator,
+    operator: &Token,
+    value: Value,
+    span: &Span,
+) -> Result<Value, RuntimeError> {
+    let Value::Primitive(primitive) = value else {
+        return Err(RuntimeError::InvalidOperation(
+            span.clone(),
+            format!("cannot apply {} to `{}`", operator.value, value.type_name()),
+        ));
+    };
+
+    // See the comment in [`binary`].
+    let result = evaluator.unary(operator, primitive, span);
+
+    primitive_result(evaluator, result, span, || {
+        format!("cannot apply {} to the operand", operator.value)
+    })
+}
+
+/// Checks, that the value of the condition is `bool`.
+pub(crate) fn condition(value: Value, span: &Span) -> Result<bool, RuntimeError> {
+    match value {
+        Value::Primitive(eval::Value::Bool(value)) => Ok(value),
+        value => Err(RuntimeError::InvalidOperation(
+            span.clone(),
+            format!("condition must be `bool`, found `{}`", value.type_name()),
+        )),
+    }
+}
+
+fn no_field(receiver: &Value, field: &str, span: &Span) -> RuntimeError {
+    RuntimeError::InvalidOperation(
+        span.clone(),
+        format!("`{}` has no field `{field}`", receiver.type_name()),
+    )
+}
+
+pub(crate) fn field(receiver: &Value, field: &str, span: &Span) -> Result<Value, RuntimeError> {
+    if let Value::Struct(_, fields) = receiver {
+        if let Some(value) = fields.borrow().get(field) {
+            return Ok(value.clone());
+        }
+    }
+
+    Err(no_field(receiver, field, span))
+}
+
+pub(crate) fn set_field(
+    receiver: &Value,
+    field: &str,
+    value: Value,
+    span: &Span,
+) -> Result<(), RuntimeError> {
+    if let Value::Struct(_, fields) = receiver {
+        if let Some(slot) = fields.borrow_mut().get_mut(field) {
+            *slot = value;
+            return Ok(());
+        }
+    }
+
+    Err(no_field(receiver, field, span))
+}
+
+/// Checks that the index is in bounds of the sequence of length `len`.
+fn position(index: &Value, len: usize, span: &Span) -> Result<usize, RuntimeError> {
+    let Value::Primitive(eval::Value::Int(index, _)) = index else {
+        return Err(RuntimeError::InvalidOperation(
+            span.clone(),
+            format!("index must be an integer, found `{}`", index.type_name()),
+        ));
+    };
+
+    match index
+        .to_u128()
+        .and_then(|index| usize::try_from(index).ok())
+    {
+        Some(position) if position < len => Ok(position),
+        _ => Err(RuntimeError::IndexOutOfBounds(
+            span.clone(),
+            index.to_i128().unwrap_or(i128::MAX),
+            len,
+        )),
+    }
+}
+
+pub(crate) fn index(list: &Value, index: &Value, span: &Span) -> Result<Value, RuntimeError> {
+    match list {
+        Value::List(items) => {
+            let items = items.borrow();
+            let position = position(index, items.len(), span)?;
+
+            Ok(items[position].clone())
+        }
+        Value::Primitive(eval::Value::String(string)) => {
+            let position = position(index, string.chars().count(), span)?;
+            let char = string.chars().nth(position).expect("index is checked");
+
+            Ok(Value::Primitive(eval::Value::Char(char)))
+        }
+        _ => Err(RuntimeError::InvalidOperation(
+            span.clone(),
+            format!("cannot index into `{}`", list.type_name()),
+        )),
+    }
+}
+
+pub(crate) fn set_index(
+    list: &Value,
+    index: &Value,
+    value: Value,
+    span: &Span,
+) -> Result<(), RuntimeError> {
+    let Value::List(items) = list else {
+        return Err(RuntimeError::InvalidOperation(
+            span.clone(),
+            format!("cannot assign to element of `{}`", list.type_name()),
+        ));
+    };
+
+    let len = items.borrow().len();
+    let position = position(index, len, span)?;
+    items.borrow_mut()[position] = value;
+
+    Ok(())
+}
+
+/// Calls builtin function `name` (one of [`builtins::BUILTINS`]).
+pub(crate) fn call_builtin(
+    evaluator: &mut Evaluator,
+    name: &str,
+    args: Vec<Value>,
+    span: &Span,
+    output: &mut dyn Write,
+) -> Result<Value, RuntimeError> {
+    // Operands of `assert_eq` are compared like with `==`, so that untyped
+    // literals are equal to the same typed numbers.
+    if let ("assert_eq", [left, right, ..]) = (name, &args[..]) {
+        let operator = WithSpan::new(RawToken::Eq, span.clone());
+
+        if let Ok(Value::Primitive(eval::Value::Bool(true))) =
+            binary(evaluator, left.clone(), &operator, right.clone(), span)
+        {
+            return Ok(Value::Unit);
+        }
+    }
+
+    builtins::call(name, args, span, output)
+}
@@ -0,0 +1,282 @@
+//! `test_utils.rs` - helpers and programs shared by tests of the interpreter
+//! and the virtual machine.
+use ry_ast::ProgramUnit;
+use ry_parser::Parser;
+use ry_report::Reporter;
+use ry_static_analyzer::{analyze, globals::Module};
+
+use crate::{compiler::compile, error::RuntimeError, vm::Vm, Interpreter};
+
+/// Program, that both the interpreter and the virtual machine must run
+/// the same way.
+pub(crate) struct Fixture {
+    pub(crate) source: &'static str,
+    /// What the program prints.
+    pub(crate) output: &'static str,
+    /// Message of the error, that stops the program, with the message of
+    /// its label, if any.
+    pub(crate) error: Option<&'static str>,
+}
+
+pub(crate) const FIXTURES: &[Fixture] = &[
+    Fixture {
+        source: "fun fib(n u64) u64 {
+                     if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+                 }
+
+                 fun main() {
+                     var a i32 = 3;
+
+                     while a > 0 {
+                         println(\"%d: %d\", a, fib(a as u64 * 5));
+                         a--;
+                     }
+
+                     var b = [1, 2] + [3];
+                     b[0] += 10;
+                     var c = ++b[1];
+                     println(\"%v %d %s %d\", b, len(b), a == 0 && b[2] == 3, c);
+
+                     var x = 1;
+                     if true { var x = 2; println(\"%d\", x); }
+                     println(\"%d %s\", x, false || x == 1);
+                 }",
+        output: "3: 610\n2: 55\n1: 5\n[11, 3, 3] 3 true 3\n2\n1 true\n",
+        error: None,
+    },
+    Fixture {
+        source: "const GREETING = \"hello\";
+                 var counter = 0;
+
+                 pub enum Color { Red, Green }
+
+                 fun greet(name string = \"world\", times i32 = len(name)) {
+                     defer println(\"bye %s\", name);
+                     counter++;
+                     println(\"%s %s %d\", GREETING, name, times);
+                 }
+
+                 fun div(a i32, b i32) i32? {
+                     if b == 0 { return none; }
+                     some(a / b)
+                 }
+
+                 fun half(a i32) i32? {
+                     defer println(\"half\");
+                     var result = div(a, 2)?;
+                     some(result)
+                 }
+
+                 fun main() {
+                     greet();
+                     greet(\"ry\", 7);
+                     println(\"%d %v %v %d\", counter, div(1, 0), half(7), div(6, 3) ?: 0);
+                     println(\"%v %s\", Color::Red, Color::Red != Color::Green);
+
+                     var f = div;
+                     println(\"%v\", f(4, 2));
+                 }",
+        output: "hello world 5\nbye world\nhello ry 7\nbye ry\nhalf\n2 none some(3) 2\n\
+                 Color::Red true\nsome(2)\n",
+        error: None,
+    },
+    Fixture {
+        source: "trait Describe {
+                     fun name(self Self) string;
+                     fun describe(self Self) string { \"it is \" + self.name() }
+                 }
+
+                 struct Meters {}
+
+                 impl Meters {
+                     fun unit() string { \"m\" }
+                 }
+
+                 impl Describe for i32 {
+                     fun name(self Self) string { \"number\" }
+                 }
+
+                 fun main() {
+                     var a i32 = 1;
+                     println(\"%s %s\", a.describe(), Meters::unit());
+                 }",
+        output: "it is number m\n",
+        error: None,
+    },
+    Fixture {
+        source: "type Meters f64;
+
+                 impl Meters {
+                     fun km(self Self) f64 { self.value / 1000.0 }
+                 }
+
+                 fun total(a Meters, b Meters) Meters { Meters(a.value + b.value) }
+
+                 fun main() {
+                     var m = total(Meters(1500), Meters(500.0));
+                     println(\"%v %v %v %s\", m, m.km(), m.value, m == Meters(2000));
+                 }",
+        output: "Meters { value: 2000.0 } 2.0 2000.0 true\n",
+        error: None,
+    },
+    Fixture {
+        source: "fun main() {
+                     var a i8 = 1;
+                     assert(a < 2);
+                     assert_eq(a, 1);
+                     assert_eq([1], [1]);
+                 }",
+        output: "",
+        error: None,
+    },
+    Fixture {
+        source: "fun main() { var a = 0; println(\"%d\", 1 / a); }",
+        output: "",
+        error: Some("program panicked (attempt to compute `1 / 0`, which divides by zero)"),
+    },
+    Fixture {
+        source: "fun main() { var a = [1]; a[1]; }",
+        output: "",
+        error: Some(
+            "program panicked: index out of bounds: the length is 1, but the index is 1 \
+             (index out of bounds)",
+        ),
+    },
+    Fixture {
+        source: "fun main() { println(\"before\"); panic(\"%d failed\", 1); }",
+        output: "before\n",
+        error: Some("program panicked: 1 failed (panicked here)"),
+    },
+    Fixture {
+        source: "fun main() { var a i32? = none; a!!; }",
+        output: "",
+        error: Some("program panicked: `!!` applied to `none` (this value is `none`)"),
+    },
+    Fixture {
+        source: "fun f() { f(); } fun main() { f(); }",
+        output: "",
+        error: Some(
+            "program panicked: more than 64 nested function calls (stack overflow in this call)",
+        ),
+    },
+    Fixture {
+        source: "fun main() { var a = true; a.foo(); }",
+        output: "",
+        error: Some("no method `foo` found for `bool` (method not found)"),
+    },
+    Fixture {
+        source: "fun main() { a = 1; }",
+        output: "",
+        error: Some("cannot find `a` in this scope (not found)"),
+    },
+    Fixture {
+        source: "fun f(a i32) {} fun main() { f(); }",
+        output: "",
+        error: Some(
+            "function `f` takes 1 argument(s), but 0 were given (wrong number of arguments)",
+        ),
+    },
+    Fixture {
+        source: "fun test() {}",
+        output: "",
+        error: Some("`main` function is not found in the entry point of the program"),
+    },
+    Fixture {
+        source: "fun main() { assert(false); }",
+        output: "",
+        error: Some("program panicked: assertion failed (panicked here)"),
+    },
+    Fixture {
+        source: "fun main() { assert_eq(\"a\", \"b\", \"step %d\", 2); }",
+        output: "",
+        error: Some(
+            "program panicked: assertion `left == right` failed: step 2\n  left: \"a\"\n \
+             right: \"b\" (panicked here)",
+        ),
+    },
+    Fixture {
+        source: "fun main() { defer println(\"a\"); defer println(\"b\"); println(\"c\"); }",
+        output: "c\nb\na\n",
+        error: None,
+    },
+    // Defers don't run, when the program panics.
+    Fixture {
+        source: "fun main() { defer println(\"deferred\"); panic(); }",
+        output: "",
+        error: Some("program panicked: explicit panic (panicked here)"),
+    },
+    Fixture {
+        source: "type Meters f64;\nfun main() { var m Meters = 1.5; }",
+        output: "",
+        error: Some("program panicked (expected `Meters`, found `float`)"),
+    },
+    Fixture {
+        source: "type Meters f64;\nfun main() { Meters(\"1\"); }",
+        output: "",
+        error: Some("program panicked (expected `f64`, found `string`)"),
+    },
+    Fixture {
+        source: "type Meters f64;\nfun main() { Meters(1, 2); }",
+        output: "",
+        error: Some(
+            "function `Meters` takes 1 argument(s), but 2 were given (wrong number of arguments)",
+        ),
+    },
+];
+
+/// Parses and analyzes the program, which must not have errors.
+pub(crate) fn analyzed(source: &str) -> ProgramUnit {
+    let mut ast = Parser::new(source).parse().unwrap();
+    assert_eq!(analyze(&mut ast), vec![]);
+
+    ast
+}
+
+/// Message of the error with the message of its label, see
+/// [`Fixture::error`].
+fn error_message(error: &RuntimeError) -> String {
+    let diagnostic = error.build_diagnostic(0);
+
+    match diagnostic.labels.first() {
+        Some(label) => format!("{} ({})", diagnostic.message, label.message),
+        None => diagnostic.message,
+    }
+}
+
+/// Runs the program by the interpreter, returns its output and the error
+/// message, if any. Calls are limited to 64 nested ones.
+pub(crate) fn interpret(source: &str) -> (String, Option<String>) {
+    let ast = analyzed(source);
+    let modules = [Module {
+        ast: &ast,
+        imports: vec![],
+    }];
+    let mut output = vec![];
+
+    let error = Interpreter::new(&modules, &mut output)
+        .with_max_depth(64)
+        .run()
+        .err()
+        .map(|(_, error)| error_message(&error));
+
+    (String::from_utf8(output).unwrap(), error)
+}
+
+/// Runs the program by the virtual machine, see [`interpret`].
+pub(crate) fn execute(source: &str) -> (String, Option<String>) {
+    let ast = analyzed(source);
+    let modules = [Module {
+        ast: &ast,
+        imports: vec![],
+    }];
+    let compiled = compile(&modules);
+    let mut output = vec![];
+
+    let error = Vm::new(&compiled, &mut output)
+        .with_max_depth(64)
+        .run()
+        .err()
+        .map(|(_, error)| error_message(&error));
+
+    (String::from_utf8(output).unwrap(), error)
+}
@@ -0,0 +1,155 @@
+//! `value.rs` - defines values of the interpreted program.
+use std::{cell::RefCell, collections::BTreeMap, fmt, rc::Rc};
+
+use ry_ast::Newtype;
+use ry_static_analyzer::eval;
+
+/// Runtime value. Lists and structs are shared: copies of the value refer
+/// to the same elements, so changes made through one of them are visible
+/// through others.
+#[derive(Debug, Clone)]
+pub enum Value {
+    /// Result of expressions without value: calls of functions without
+    /// return type, assignments and loops.
+    Unit,
+    /// Number, bool, char or string. Operations on them behave exactly like
+    /// during constant evaluation.
+    Primitive(eval::Value),
+    List(Rc<RefCell<Vec<Value>>>),
+    Option(Option<Box<Value>>),
+    /// Struct [`String`] with its fields.
+    Struct(String, Rc<RefCell<BTreeMap<String, Value>>>),
+    /// Enum variant: names of the enum and the variant.
+    Variant(String, String),
+    /// Top level function or builtin.
+    Function(String),
+}
+
+impl Value {
+    pub fn list(items: Vec<Value>) -> Self {
+        Self::List(Rc::new(RefCell::new(items)))
+    }
+
+    pub fn string(value: impl Into<String>) -> Self {
+        Self::Primitive(eval::Value::String(value.into()))
+    }
+
+    pub fn bool(value: bool) -> Self {
+        Self::Primitive(eval::Value::Bool(value))
+    }
+
+    /// Value of the newtype `name`: struct with the single field
+    /// [`Newtype::FIELD`].
+    pub fn newtype(name: impl Into<String>, value: Value) -> Self {
+        Self::Struct(
+            name.into(),
+            Rc::new(RefCell::new(BTreeMap::from([(
+                Newtype::FIELD.to_owned(),
+                value,
+            )]))),
+        )
+    }
+
+    /// Name of the type of the value. It is used to find methods of
+    /// the value and in error messages.
+    pub fn type_name(&self) -> String {
+        match self {
+            Self::Unit => "()".to_owned(),
+            Self::Primitive(value) => value.type_name(),
+            Self::List(_) => "list".to_owned(),
+            Self::Option(_) => "option".to_owned(),
+            Self::Struct(name, _) | Self::Variant(name, _) => name.clone(),
+            Self::Function(_) => "function".to_owned(),
+        }
+    }
+
+    /// Formats the value the way it is shown nested in other values
+    /// (strings and chars are quoted), used in messages of assertions.
+    pub fn quoted(&self) -> String {
+        struct Quoted<'v>(&'v Value);
+
+        impl fmt::Display for Quoted<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt_nested(f)
+            }
+        }
+
+        Quoted(self).to_string()
+    }
+
+    /// Formats nested values: unlike [`fmt::Display`], strings and chars
+    /// are quoted.
+    fn fmt_nested(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Primitive(eval::Value::String(value)) => write!(f, "{value:?}"),
+            Self::Primitive(eval::Value::Char(value)) => write!(f, "{value:?}"),
+            _ => write!(f, "{self}"),
+        }
+    }
+}
+
+impl PartialEq for Value {
+    /// Values are compared structurally, numbers of different types are
+    /// never equal.
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Unit, Self::Unit) => true,
+            (Self::Primitive(left), Self::Primitive(right)) => left == right,
+            (Self::List(left), Self::List(right)) => *left.borrow() == *right.borrow(),
+            (Self::Option(left), Self::Option(right)) => left == right,
+            (Self::Struct(left_name, left), Self::Struct(right_name, right)) => {
+                left_name == right_name && *left.borrow() == *right.borrow()
+            }
+            (Self::Variant(left_enum, left), Self::Variant(right_enum, right)) => {
+                left_enum == right_enum && left == right
+            }
+            (Self::Function(left), Self::Function(right)) => left == right,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    /// Formats the value the way `print` shows it.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unit => write!(f, "()"),
+            Self::Primitive(eval::Value::Int(value, _)) => write!(f, "{value}"),
+            Self::Primitive(eval::Value::Float(value, _)) => write!(f, "{value:?}"),
+            Self::Primitive(eval::Value::Bool(value)) => write!(f, "{value}"),
+            Self::Primitive(eval::Value::Char(value)) => write!(f, "{value}"),
+            Self::Primitive(eval::Value::String(value)) => write!(f, "{value}"),
+            Self::List(items) => {
+                write!(f, "[")?;
+
+                for (i, item) in items.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    item.fmt_nested(f)?;
+                }
+
+                write!(f, "]")
+            }
+            Self::Option(None) => write!(f, "none"),
+            Self::Option(Some(value)) => {
+                write!(f, "some(")?;
+                value.fmt_nested(f)?;
+                write!(f, ")")
+            }
+            Self::Struct(name, fields) => {
+                write!(f, "{name} {{")?;
+
+                for (i, (field, value)) in fields.borrow().iter().enumerate() {
+                    write!(f, "{} {field}: ", if i > 0 { "," } else { "" })?;
+                    value.fmt_nested(f)?;
+                }
+
+                write!(f, " }}")
+            }
+            Self::Variant(name, variant) => write!(f, "{name}::{variant}"),
+            Self::Function(name) => write!(f, "fun {name}"),
+        }
+    }
+}
@@ -0,0 +1,780 @@
+//! `vm.rs` - stack-based virtual machine, that executes compiled
+//! [`bytecode`].
+//!
+//! The machine behaves exactly like [`Interpreter`](crate::Interpreter):
+//! operations on values are shared with it, names are resolved in the same
+//! order and declarations of the entry point shadow ones of the imported
+//! modules. Names of all modules are resolved once, when the program is
+//! loaded. Function calls don't take native stack, but their nesting is
+//! still limited by [`Vm::with_max_depth`].
+use std::{cell::RefCell, collections::HashMap, io::Write, mem, rc::Rc};
+
+use ry_ast::{
+    location::{Span, WithSpan},
+    token::NumberSuffix,
+    RawType,
+};
+use ry_static_analyzer::{
+    eval::{self, Evaluator},
+    globals::import_order,
+    types::TypeAliases,
+};
+
+use crate::{
+    builtins::BUILTINS,
+    bytecode::{Function, Instruction, Module},
+    error::RuntimeError,
+    operations,
+    value::Value,
+    DEFAULT_MAX_DEPTH,
+};
+
+/// Function, method or builtin, that can be called.
+#[derive(Clone, Copy)]
+enum Callee<'a> {
+    Function(usize, &'a Function),
+    Builtin(&'a str),
+}
+
+/// What the name in the symbol table of the module refers to.
+#[derive(Clone, Copy)]
+enum Symbol<'a> {
+    /// Slot of the global variable or constant.
+    Global(usize),
+    Callable(Callee<'a>),
+    /// Enum and variant names.
+    Variant(&'a str, &'a str),
+    None,
+    Undefined,
+}
+
+/// State of the function being executed.
+struct Frame<'a> {
+    module: usize,
+    function: &'a Function,
+    /// Index of the next instruction.
+    ip: usize,
+    /// Index of the first slot on the stack.
+    base: usize,
+    /// Number of given arguments.
+    argc: u32,
+    /// Deferred expressions to run, when the function returns.
+    defers: Vec<u32>,
+    /// Value returned by the function, while deferred expressions run.
+    result: Value,
+}
+
+pub struct Vm<'a, 'o> {
+    modules: &'a [Module],
+    /// Evaluators of primitive operations, one per module (they know type
+    /// aliases of the module).
+    evaluators: Vec<Evaluator<'a>>,
+    /// Number suffixes of types of every module, values of these types
+    /// don't need coercion.
+    suffixes: Vec<Vec<Option<NumberSuffix>>>,
+    /// Resolved symbol tables of modules.
+    symbols: Vec<Vec<Symbol<'a>>>,
+    functions: HashMap<&'a str, Callee<'a>>,
+    /// Methods of the type (by name of the type and of the method).
+    methods: HashMap<&'a str, HashMap<&'a str, Callee<'a>>>,
+    /// Slots of global variables and constants by their names.
+    global_slots: HashMap<&'a str, usize>,
+    globals: Vec<Option<Value>>,
+    stack: Vec<Value>,
+    frames: Vec<Frame<'a>>,
+    max_depth: usize,
+    output: &'o mut dyn Write,
+}
+
+impl<'a, 'o> Vm<'a, 'o> {
+    /// Loads the compiled program. Module `0` is the entry point. Output
+    /// of `print` and `println` is written to `output`.
+    pub fn new(modules: &'a [Module], output: &'o mut dyn Write) -> Self {
+        let mut functions = HashMap::new();
+        let mut enums = HashMap::new();
+        let mut traits = HashMap::new();
+        let mut global_slots = HashMap::new();
+
+        for (i, module) in modules.iter().enumerate() {
+            for &function in &module.top_level {
+                let function = &module.functions[function as usize];
+
+                functions
+                    .entry(function.name.as_str())
+                    .or_insert(Callee::Function(i, function));
+            }
+
+            for decl in &module.enums {
+                enums.entry(decl.name.as_str()).or_insert(decl);
+            }
+
+            for decl in &module.traits {
+                traits.entry(decl.name.as_str()).or_insert((i, decl));
+            }
+
+            for global in &module.globals {
+                let slots = global_slots.len();
+                global_slots.entry(global.name.as_str()).or_insert(slots);
+            }
+        }
+
+        // Inherent methods take precedence over trait ones, methods of
+        // the implementation - over default methods of the trait.
+        let implementations = modules
+            .iter()
+            .enumerate()
+            .flat_map(|(i, module)| module.impls.iter().map(move |r#impl| (i, r#impl)));
+        let (inherent, trait_impls): (Vec<_>, Vec<_>) =
+            implementations.partition(|(_, r#impl)| r#impl.r#trait.is_none());
+
+        let mut methods: HashMap<&str, HashMap<&str, Callee>> = HashMap::new();
+
+        for (i, r#impl) in inherent.into_iter().chain(trait_impls) {
+            let type_methods = methods.entry(r#impl.r#type.as_str()).or_default();
+            let default_methods = r#impl
+                .r#trait
+                .as_ref()
+                .and_then(|name| traits.get(name.as_str()))
+                .map(|&(module, decl)| (module, &decl.methods));
+
+            let own_methods = std::iter::once((i, &r#impl.methods));
+
+            for (module, module_methods) in own_methods.chain(default_methods) {
+                for &method in module_methods {
+                    let method = &modules[module].functions[method as usize];
+
+                    type_methods
+                        .entry(method.name.as_str())
+                        .or_insert(Callee::Function(module, method));
+                }
+            }
+        }
+
+        let symbols = modules
+            .iter()
+            .map(|module| {
+                module
+                    .names
+                    .iter()
+                    .map(|name| Self::resolve(name, &functions, &methods, &enums, &global_slots))
+                    .collect()
+            })
+            .collect();
+
+        let suffixes = modules
+            .iter()
+            .map(|module| {
+                let aliases = module.aliases.iter().cloned().collect::<TypeAliases>();
+
+                module
+                    .types
+                    .iter()
+                    .map(|r#type| match aliases.resolve(r#type).map(|t| t.value) {
+                        Ok(resolved) => match *resolved {
+                            RawType::Primary(name, generics) if generics.is_empty() => {
+                                NumberSuffix::from_name(&name.value)
+                            }
+                            _ => None,
+                        },
+                        Err(_) => None,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            modules,
+            evaluators: modules
+                .iter()
+                .map(|module| {
+                    let aliases = module.aliases.iter().cloned().collect::<TypeAliases>();
+
+                    Evaluator::with_constants(HashMap::new())
+                        .with_aliases(aliases.with_newtypes(module.newtypes.iter().cloned()))
+                })
+                .collect(),
+            suffixes,
+            symbols,
+            functions,
+            methods,
+            globals: vec![None; global_slots.len()],
+            global_slots,
+            stack: vec![],
+            frames: vec![],
+            max_depth: DEFAULT_MAX_DEPTH,
+            output,
+        }
+    }
+
+    /// Resolves the name the same way, as the interpreter does it at
+    /// runtime.
+    fn resolve(
+        name: &'a str,
+        functions: &HashMap<&'a str, Callee<'a>>,
+        methods: &HashMap<&'a str, HashMap<&'a str, Callee<'a>>>,
+        enums: &HashMap<&'a str, &'a crate::bytecode::Enum>,
+        global_slots: &HashMap<&'a str, usize>,
+    ) -> Symbol<'a> {
+        if let Some(&slot) = global_slots.get(name) {
+            return Symbol::Global(slot);
+        }
+
+        if name == "none" {
+            return Symbol::None;
+        }
+
+        if let Some(&callee) = functions.get(name) {
+            return Symbol::Callable(callee);
+        }
+
+        if let Some(builtin) = BUILTINS.iter().find(|builtin| **builtin == name) {
+            return Symbol::Callable(Callee::Builtin(builtin));
+        }
+
+        if let Some((r#type, item)) = name.rsplit_once("::") {
+            if let Some(decl) = enums.get(r#type) {
+                if decl.variants.iter().any(|variant| variant == item) {
+                    return Symbol::Variant(r#type, item);
+                }
+            }
+
+            if let Some(&callee) = methods.get(r#type).and_then(|methods| methods.get(item)) {
+                return Symbol::Callable(callee);
+            }
+        }
+
+        Symbol::Undefined
+    }
+
+    /// Limits nesting of function calls, deeper calls stop the program with
+    /// [`RuntimeError::StackOverflow`].
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Initializes global variables and calls `main`. Returns the value
+    /// returned by `main`, or the error with the index of the module, where
+    /// it happened.
+    pub fn run(mut self) -> Result<Value, (usize, RuntimeError)> {
+        self.initialize()?;
+
+        let Some(Callee::Function(0, _)) = self.functions.get("main") else {
+            return Err((0, RuntimeError::NoMain));
+        };
+
+        self.call("main", vec![])
+    }
+
+    /// Initializes constants and global variables of all modules.
+    pub fn initialize(&mut self) -> Result<(), (usize, RuntimeError)> {
+        let modules = self.modules;
+        let imports = modules
+            .iter()
+            .map(|module| module.imports.as_slice())
+            .collect::<Vec<_>>();
+
+        for module in import_order(&imports) {
+            for global in &modules[module].globals {
+                let slot = self.global_slots[global.name.as_str()];
+
+                if self.globals[slot].is_some() {
+                    continue;
+                }
+
+                let initializer = &modules[module].functions[global.initializer as usize];
+                self.push_frame(module, initializer, 0);
+
+                let value = self.execute(0).map_err(|error| self.leave(error))?;
+                self.globals[slot] = Some(value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Calls top level function (or `Type::method`, or builtin) `name` of
+    /// the program.
+    pub fn call(&mut self, name: &str, args: Vec<Value>) -> Result<Value, (usize, RuntimeError)> {
+        let Some(callee) = self.callee(name) else {
+            return Err((
+                0,
+                RuntimeError::UndefinedName(Span::default(), name.to_owned()),
+            ));
+        };
+
+        let argc = args.len() as u32;
+        self.stack.extend(args);
+
+        let result = self
+            .invoke(|| name.to_owned(), callee, argc, &Span::default())
+            .and_then(|()| {
+                if self.frames.is_empty() {
+                    Ok(self.stack.pop().expect("builtin returns a value"))
+                } else {
+                    self.execute(0)
+                }
+            });
+
+        result.map_err(|error| self.leave(error))
+    }
+
+    /// Adds the module to the error and resets the state of the machine.
+    fn leave(&mut self, error: RuntimeError) -> (usize, RuntimeError) {
+        let module = self.frames.last().map_or(0, |frame| frame.module);
+
+        self.frames.clear();
+        self.stack.clear();
+        (module, error)
+    }
+
+    fn callee(&self, name: &str) -> Option<Callee<'a>> {
+        if let Some(&callee) = self.functions.get(name) {
+            return Some(callee);
+        }
+
+        let method = name
+            .rsplit_once("::")
+            .and_then(|(r#type, method)| self.methods.get(r#type)?.get(method).copied());
+
+        if method.is_some() {
+            return method;
+        }
+
+        BUILTINS
+            .iter()
+            .find(|builtin| **builtin == name)
+            .map(|builtin| Callee::Builtin(builtin))
+    }
+
+    fn push(&mut self, value: Value) {
+        self.stack.push(value);
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("stack is balanced by the compiler")
+    }
+
+    fn frame(&mut self) -> &mut Frame<'a> {
+        self.frames
+            .last_mut()
+            .expect("no function is being executed")
+    }
+
+    fn push_frame(&mut self, module: usize, function: &'a Function, argc: u32) {
+        let base = self.stack.len() - argc as usize;
+        self.stack
+            .resize(base + function.locals as usize, Value::Unit);
+
+        self.frames.push(Frame {
+            module,
+            function,
+            ip: 0,
+            base,
+            argc,
+            defers: vec![],
+            result: Value::Unit,
+        });
+    }
+
+    /// Calls the function with `argc` arguments on the top of the stack.
+    /// Builtins are called immediately, functions get a new frame.
+    fn invoke(
+        &mut self,
+        name: impl FnOnce() -> String,
+        callee: Callee<'a>,
+        argc: u32,
+        span: &Span,
+    ) -> Result<(), RuntimeError> {
+        match callee {
+            Callee::Builtin(builtin) => {
+                let module = self.frames.last().map_or(0, |frame| frame.module);
+                let args = self.stack.split_off(self.stack.len() - argc as usize);
+                let value = operations::call_builtin(
+                    &mut self.evaluators[module],
+                    builtin,
+                    args,
+                    span,
+                    &mut *self.output,
+                )?;
+
+                self.push(value);
+            }
+            Callee::Function(module, function) => {
+                if self.frames.len() >= self.max_depth {
+                    return Err(RuntimeError::StackOverflow(span.clone(), self.max_depth));
+                }
+
+                if argc < function.required || argc > function.params {
+                    return Err(RuntimeError::ArgumentCount(
+                        span.clone(),
+                        name(),
+                        function.params as usize,
+                        argc as usize,
+                    ));
+                }
+
+                self.push_frame(module, function, argc);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn call_value(&mut self, callee: Value, argc: u32, span: &Span) -> Result<(), RuntimeError> {
+        match callee {
+            Value::Function(name) => match self.callee(&name) {
+                Some(callee) => self.invoke(|| name, callee, argc, span),
+                None => Err(RuntimeError::UndefinedName(span.clone(), name)),
+            },
+            callee => Err(RuntimeError::InvalidOperation(
+                span.clone(),
+                format!("`{}` is not a function", callee.type_name()),
+            )),
+        }
+    }
+
+    fn call_method(&mut self, method: &str, argc: u32, span: &Span) -> Result<(), RuntimeError> {
+        let position = self.stack.len() - argc as usize - 1;
+        let receiver = &self.stack[position];
+
+        // Field of function type is called like a method.
+        let field = match receiver {
+            Value::Struct(_, fields) => fields.borrow().get(method).cloned(),
+            _ => None,
+        };
+
+        if let Some(callee) = field {
+            self.stack.remove(position);
+            return self.call_value(callee, argc, span);
+        }
+
+        let r#type = receiver.type_name();
+
+        let Some(&callee) = self
+            .methods
+            .get(r#type.as_str())
+            .and_then(|methods| methods.get(method))
+        else {
+            return Err(RuntimeError::NoMethod(
+                span.clone(),
+                method.to_owned(),
+                r#type,
+            ));
+        };
+
+        let argc = match callee {
+            Callee::Function(_, function) if function.takes_self => argc + 1,
+            _ => {
+                self.stack.remove(position);
+                argc
+            }
+        };
+
+        self.invoke(|| format!("{type}::{method}"), callee, argc, span)
+    }
+
+    fn load(&self, module: usize, symbol: u32, span: &Span) -> Result<Value, RuntimeError> {
+        let name = &self.modules[module].names[symbol as usize];
+
+        match self.symbols[module][symbol as usize] {
+            Symbol::Global(slot) => self.globals[slot]
+                .clone()
+                .ok_or_else(|| RuntimeError::UndefinedName(span.clone(), name.clone())),
+            Symbol::Callable(_) => Ok(Value::Function(name.clone())),
+            Symbol::Variant(r#enum, variant) => {
+                Ok(Value::Variant(r#enum.to_owned(), variant.to_owned()))
+            }
+            Symbol::None => Ok(Value::Option(None)),
+            Symbol::Undefined => Err(RuntimeError::UndefinedName(span.clone(), name.clone())),
+        }
+    }
+
+    fn coerce(
+        &mut self,
+        module: usize,
+        value: Value,
+        r#type: u32,
+        span: &Span,
+    ) -> Result<Value, RuntimeError> {
+        if let (
+            Value::Primitive(
+                eval::Value::Int(_, Some(suffix)) | eval::Value::Float(_, Some(suffix)),
+            ),
+            Some(expected),
+        ) = (&value, self.suffixes[module][r#type as usize])
+        {
+            if *suffix == expected {
+                return Ok(value);
+            }
+        }
+
+        operations::coerce(
+            &mut self.evaluators[module],
+            value,
+            &self.modules[module].types[r#type as usize],
+            span,
+        )
+    }
+
+    /// Leaves the function: runs the next deferred expression or returns
+    /// the result. Returns the result, when the frame at `depth` returns.
+    fn finish(&mut self, depth: usize) -> Result<Option<Value>, RuntimeError> {
+        let frame = self.frame();
+
+        if let Some(defer) = frame.defers.pop() {
+            frame.ip = frame.function.defers[defer as usize] as usize;
+            return Ok(None);
+        }
+
+        let (module, function) = (frame.module, frame.function);
+        let mut value = mem::replace(&mut frame.result, Value::Unit);
+
+        if let Some(r#type) = function.return_type {
+            let span = self.modules[module].types[r#type as usize].span.clone();
+            value = self.coerce(module, value, r#type, &span)?;
+        }
+
+        let frame = self.frames.pop().expect("frame is checked above");
+        self.stack.truncate(frame.base);
+
+        if self.frames.len() == depth {
+            return Ok(Some(value));
+        }
+
+        self.push(value);
+        Ok(None)
+    }
+
+    /// Executes instructions, until the frame at `depth` returns.
+    fn execute(&mut self, depth: usize) -> Result<Value, RuntimeError> {
+        let modules = self.modules;
+
+        loop {
+            let frame = self.frame();
+            let (module, function, base, ip) = (frame.module, frame.function, frame.base, frame.ip);
+            frame.ip += 1;
+
+            let span = &function.spans[ip];
+            let names = &modules[module].names;
+
+            match &function.code[ip] {
+                Instruction::Constant(constant) => {
+                    let value = modules[module].constants[*constant as usize].clone();
+                    self.push(Value::Primitive(value));
+                }
+                Instruction::Unit => self.push(Value::Unit),
+                Instruction::Pop => {
+                    self.pop();
+                }
+                Instruction::Dup => {
+                    let value = self.stack.last().expect("stack is not empty").clone();
+                    self.push(value);
+                }
+                Instruction::LoadLocal(slot) => {
+                    let value = self.stack[base + *slot as usize].clone();
+                    self.push(value);
+                }
+                Instruction::StoreLocal(slot) => {
+                    let value = self.pop();
+                    self.stack[base + *slot as usize] = value;
+                }
+                Instruction::LoadName(symbol) => {
+                    let value = self.load(module, *symbol, span)?;
+                    self.push(value);
+                }
+                Instruction::StoreName(symbol) => {
+                    let value = self.pop();
+
+                    match self.symbols[module][*symbol as usize] {
+                        Symbol::Global(slot) if self.globals[slot].is_some() => {
+                            self.globals[slot] = Some(value);
+                        }
+                        _ => {
+                            return Err(RuntimeError::UndefinedName(
+                                span.clone(),
+                                names[*symbol as usize].clone(),
+                            ))
+                        }
+                    }
+                }
+                Instruction::List(len) => {
+                    let items = self.stack.split_off(self.stack.len() - *len as usize);
+                    self.push(Value::list(items));
+                }
+                Instruction::Struct(layout) => {
+                    let layout = &modules[module].layouts[*layout as usize];
+                    let values = self.stack.split_off(self.stack.len() - layout.fields.len());
+                    let fields = layout.fields.iter().cloned().zip(values).collect();
+
+                    self.push(Value::Struct(
+                        layout.name.clone(),
+                        Rc::new(RefCell::new(fields)),
+                    ));
+                }
+                Instruction::GetField(symbol) => {
+                    let receiver = self.pop();
+                    let value = operations::field(&receiver, &names[*symbol as usize], span)?;
+                    self.push(value);
+                }
+                Instruction::SetField(symbol) => {
+                    let receiver = self.pop();
+                    let value = self.pop();
+                    operations::set_field(&receiver, &names[*symbol as usize], value, span)?;
+                }
+                Instruction::GetIndex => {
+                    let index = self.pop();
+                    let list = self.pop();
+                    let value = operations::index(&list, &index, span)?;
+                    self.push(value);
+                }
+                Instruction::SetIndex => {
+                    let index = self.pop();
+                    let list = self.pop();
+                    let value = self.pop();
+                    operations::set_index(&list, &index, value, span)?;
+                }
+                Instruction::Binary(operator) => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let operator = WithSpan::new(operator.clone(), span.clone());
+                    let value = operations::binary(
+                        &mut self.evaluators[module],
+                        left,
+                        &operator,
+                        right,
+                        span,
+                    )?;
+                    self.push(value);
+                }
+                Instruction::Unary(operator) => {
+                    let value = self.pop();
+                    let operator = WithSpan::new(operator.clone(), span.clone());
+                    let value =
+                        operations::unary(&mut self.evaluators[module], &operator, value, span)?;
+                    self.push(value);
+                }
+                Instruction::Cast(r#type) => {
+                    let value = self.pop();
+                    let value = operations::cast(
+                        &mut self.evaluators[module],
+                        value,
+                        &modules[module].types[*r#type as usize],
+                        span,
+                    )?;
+                    self.push(value);
+                }
+                Instruction::Coerce(r#type) => {
+                    let value = self.pop();
+                    let value = self.coerce(module, value, *r#type, span)?;
+                    self.push(value);
+                }
+                Instruction::Jump(target) => self.frame().ip = *target as usize,
+                Instruction::JumpIfFalse(target) | Instruction::JumpIfTrue(target) => {
+                    let value = self.pop();
+                    let expected = matches!(function.code[ip], Instruction::JumpIfTrue(_));
+
+                    if operations::condition(value, span)? == expected {
+                        self.frame().ip = *target as usize;
+                    }
+                }
+                Instruction::JumpIfArgument(param, target) => {
+                    let frame = self.frame();
+
+                    if *param < frame.argc {
+                        frame.ip = *target as usize;
+                    }
+                }
+                Instruction::JumpIfSome(target) => match self.pop() {
+                    Value::Option(None) => {}
+                    Value::Option(Some(value)) => {
+                        self.push(*value);
+                        self.frame().ip = *target as usize;
+                    }
+                    value => {
+                        self.push(value);
+                        self.frame().ip = *target as usize;
+                    }
+                },
+                Instruction::Try => match self.pop() {
+                    Value::Option(None) => {
+                        self.frame().result = Value::Option(None);
+
+                        if let Some(value) = self.finish(depth)? {
+                            return Ok(value);
+                        }
+                    }
+                    Value::Option(Some(value)) => self.push(*value),
+                    value => self.push(value),
+                },
+                Instruction::Unwrap => match self.pop() {
+                    Value::Option(None) => return Err(RuntimeError::UnwrapNone(span.clone())),
+                    Value::Option(Some(value)) => self.push(*value),
+                    value => self.push(value),
+                },
+                Instruction::Call(argc) => {
+                    let callee = self.stack.remove(self.stack.len() - *argc as usize - 1);
+                    self.call_value(callee, *argc, span)?;
+                }
+                Instruction::CallName(symbol, argc) => {
+                    let name = &names[*symbol as usize];
+
+                    match self.symbols[module][*symbol as usize] {
+                        Symbol::Callable(callee) => {
+                            self.invoke(|| name.clone(), callee, *argc, span)?
+                        }
+                        _ => {
+                            let callee = self.load(module, *symbol, span)?;
+                            self.call_value(callee, *argc, span)?;
+                        }
+                    }
+                }
+                Instruction::CallMethod(symbol, argc) => {
+                    self.call_method(&names[*symbol as usize], *argc, span)?;
+                }
+                Instruction::Defer(defer) => self.frame().defers.push(*defer),
+                Instruction::EndDefer => {
+                    self.pop();
+
+                    if let Some(value) = self.finish(depth)? {
+                        return Ok(value);
+                    }
+                }
+                Instruction::Return => {
+                    let value = self.pop();
+                    self.frame().result = value;
+
+                    if let Some(value) = self.finish(depth)? {
+                        return Ok(value);
+                    }
+                }
+                Instruction::Unsupported(message) => {
+                    let message = match &modules[module].constants[*message as usize] {
+                        eval::Value::String(message) => message.clone(),
+                        constant => constant.to_string(),
+                    };
+
+                    return Err(RuntimeError::InvalidOperation(span.clone(), message));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{execute, interpret, FIXTURES};
+
+    #[test]
+    fn fixtures_test() {
+        for fixture in FIXTURES {
+            let expected = (
+                fixture.output.to_owned(),
+                fixture.error.map(ToOwned::to_owned),
+            );
+
+            assert_eq!(interpret(fixture.source), expected, "{}", fixture.source);
+            assert_eq!(execute(fixture.source), expected, "{}", fixture.source);
+        }
+    }
+}
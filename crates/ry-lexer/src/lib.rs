@@ -3,7 +3,19 @@
 //! Lexer is a part of parser (first stage of compilation), state machine
 //! that converts Ry source text into [`Token`]s.
 //!
-//! Whitespaces are ignored during scanning process.
+//! Whitespaces are ignored during scanning process, unless trivia is
+//! enabled with [`Lexer::with_trivia`]:
+//!
+//! ```
+//! use ry_lexer::Lexer;
+//! use ry_ast::token::RawToken;
+//!
+//! let mut lexer = Lexer::new("a // b").with_trivia();
+//!
+//! assert_eq!(lexer.next().unwrap().value, RawToken::Identifier("a".to_owned()));
+//! assert_eq!(lexer.next().unwrap().value, RawToken::Whitespace);
+//! assert_eq!(lexer.next().unwrap().value, RawToken::Comment(" b".to_owned()));
+//! ```
 //!
 //! Inherited multiline comments are not supported:
 //! This is not valid:
@@ -34,6 +46,19 @@
 //! assert_eq!(lexer.next().unwrap().value, RawToken::Invalid(LexerError::UnexpectedChar('#')));
 //! ```
 //!
+//! After an invalid literal lexer skips the rest of it (or the rest of the
+//! line, if the literal is unterminated), so scanning can be continued:
+//!
+//! ```
+//! use ry_lexer::Lexer;
+//! use ry_ast::token::{RawToken, LexerError};
+//!
+//! let mut lexer = Lexer::new("\"\\q\" 1");
+//!
+//! assert_eq!(lexer.next().unwrap().value, RawToken::Invalid(LexerError::UnknownEscapeSequence));
+//! assert_eq!(lexer.next().unwrap().value, RawToken::Int(1, None));
+//! ```
+//!
 //! Lexer doesn't emit diagnostics in the process.
 
 use ry_ast::location::*;
@@ -52,6 +77,8 @@ pub struct Lexer<'c> {
     chars: Chars<'c>,
     location: usize,
     start_location: usize,
+    /// Whether whitespaces are emitted as [`RawToken::Whitespace`].
+    trivia: bool,
 }
 
 type IterElem = Option<Token>;
@@ -70,9 +97,17 @@ impl<'c> Lexer<'c> {
             chars,
             location: 0,
             start_location: 0,
+            trivia: false,
         }
     }
 
+    /// Makes lexer emit whitespaces as [`RawToken::Whitespace`] tokens,
+    /// so that the token stream covers the whole source text.
+    pub fn with_trivia(mut self) -> Self {
+        self.trivia = true;
+        self
+    }
+
     fn eof(&self) -> bool {
         self.current == '\0'
     }
@@ -97,8 +132,16 @@ impl<'c> Lexer<'c> {
         self.advance();
     }
 
-    fn char_location(&self, character_len: usize) -> Span {
-        (self.location..self.location + character_len).into()
+    /// Location of the next `characters` characters, the span always ends on
+    /// the character boundary (and is empty at the end of file).
+    fn char_location(&self, characters: usize) -> Span {
+        let len = self.contents[self.location..]
+            .chars()
+            .take(characters)
+            .map(char::len_utf8)
+            .sum::<usize>();
+
+        (self.location..self.location + len).into()
     }
 
     fn advance_with(&mut self, raw: RawToken) -> IterElem {
@@ -128,6 +171,24 @@ impl<'c> Lexer<'c> {
         (self.start_location..self.location).into()
     }
 
+    /// Skips the rest of erroneous literal up to its closing `quote`, so that
+    /// scanning resumes right after the literal, or at the next line if the
+    /// literal is unterminated.
+    fn skip_rest_of_literal(&mut self, quote: char) {
+        while !self.eof() && self.current != '\n' {
+            let c = self.current;
+            self.advance();
+
+            if c == quote {
+                return;
+            }
+
+            if c == '\\' && self.current != '\n' {
+                self.advance();
+            }
+        }
+    }
+
     fn scan_escape(&mut self) -> Result<char, (LexerError, Span)> {
         let r = match self.current {
             'b' => Ok('\u{0008}'),
@@ -221,6 +282,14 @@ impl<'c> Lexer<'c> {
                     )),
                 }
             }
+            // Line break after `\` is not a part of the escape sequence, so
+            // that the lexer recovers on the next line.
+            '\n' => {
+                return Err((
+                    LexerError::UnknownEscapeSequence,
+                    (self.location - 1..self.location).into(),
+                ))
+            }
             _ => Err((LexerError::UnknownEscapeSequence, self.char_location(1))),
         };
 
@@ -240,17 +309,19 @@ impl<'c> Lexer<'c> {
 
         while self.current != '\'' {
             if self.current == '\\' {
-                self.start_location = self.location;
-
-                let e = self.scan_escape();
+                self.advance(); // '\'
 
-                if let Err(e) = e {
-                    return Some((RawToken::Invalid(e.0), e.1).into());
-                } else if let Ok(c) = e {
-                    result = c;
+                match self.scan_escape() {
+                    Ok(c) => result = c,
+                    Err(e) => {
+                        self.skip_rest_of_literal('\'');
+                        return Some((RawToken::Invalid(e.0), e.1).into());
+                    }
                 }
-            } else {
-                result = self.current;
+
+                size += 1;
+
+                continue;
             }
 
             if self.current == '\n' || self.eof() {
@@ -263,6 +334,7 @@ impl<'c> Lexer<'c> {
                 );
             }
 
+            result = self.current;
             size += 1;
 
             self.advance(); // c
@@ -312,14 +384,12 @@ impl<'c> Lexer<'c> {
             self.advance();
 
             if c == '\\' {
-                self.start_location = self.location;
-
-                let e = self.scan_escape();
-
-                if let Err(e) = e {
-                    return Some((RawToken::Invalid(e.0), e.1).into());
-                } else if let Ok(c) = e {
-                    buffer.push(c);
+                match self.scan_escape() {
+                    Ok(c) => buffer.push(c),
+                    Err(e) => {
+                        self.skip_rest_of_literal('"');
+                        return Some((RawToken::Invalid(e.0), e.1).into());
+                    }
                 }
             } else {
                 buffer.push(c);
@@ -347,13 +417,18 @@ impl<'c> Lexer<'c> {
             &self.advance_while(|current, _| current.is_alphanumeric() || current == '_')[1..];
 
         if self.current != '`' {
+            let span = self.span_from_start();
+            self.skip_rest_of_literal('`');
+
             return Some(Token::new(
                 RawToken::Invalid(LexerError::UnterminatedWrappedIdentifierLiteral),
-                self.span_from_start(),
+                span,
             ));
         }
 
         if name.is_empty() {
+            self.advance(); // '`'
+
             return Some(Token::new(
                 RawToken::Invalid(LexerError::EmptyWrappedIdentifierLiteral),
                 self.span_from_start(),
@@ -373,7 +448,7 @@ impl<'c> Lexer<'c> {
 
         self.advance_twice(); // '//'
 
-        let content = self.advance_while(|current, _| (current != '\n'));
+        let content = self.advance_while(|current, _| current != '\n');
 
         Some(Token::new(
             RawToken::Comment(content[2..].replace('\r', "")),
@@ -433,7 +508,7 @@ impl<'c> Lexer<'c> {
         loop {
             let t = self.next();
             match t.as_ref().unwrap().value {
-                RawToken::Comment(_) => {}
+                RawToken::Comment(_) | RawToken::Whitespace => {}
                 _ => {
                     return t;
                 }
@@ -447,7 +522,12 @@ impl<'c> Iterator for Lexer<'c> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.current.is_whitespace() {
+            self.start_location = self.location;
             self.skip_over_whitespaces();
+
+            if self.trivia {
+                return Some(Token::new(RawToken::Whitespace, self.span_from_start()));
+            }
         }
 
         match (self.current, self.next) {
@@ -1,18 +1,18 @@
 use ry_ast::{
     location::Span,
-    token::{LexerError, NumberKind, RawToken, Token},
+    token::{LexerError, NumberKind, NumberSuffix, RawToken, Token},
 };
 
 use crate::{IterElem, Lexer};
 
 #[inline]
 pub(crate) fn decimal(c: char) -> bool {
-    ('0'..='9').contains(&c)
+    c.is_ascii_digit()
 }
 
 #[inline]
 pub(crate) fn hexadecimal(c: char) -> bool {
-    ('0'..='9').contains(&c) || ('a'..='f').contains(&c.to_ascii_lowercase())
+    c.is_ascii_hexdigit()
 }
 
 #[inline]
@@ -26,13 +26,17 @@ pub(crate) fn to_digit2(c: char) -> i8 {
     }
 }
 
-fn parse_integer(buffer: &[u8], base: i8) -> Option<u64> {
-    let mut n = 0;
-    let mut pow = 1;
+fn parse_integer(buffer: &[u8], base: i8) -> Option<u128> {
+    let mut n: u128 = 0;
 
-    for i in 0..buffer.len() {
-        n += (to_digit2(buffer[buffer.len() - 1 - i] as char) as u64).checked_mul(pow)?;
-        pow = pow.checked_mul(base as u64)?;
+    for &digit in buffer {
+        if digit == b'_' {
+            continue;
+        }
+
+        n = n
+            .checked_mul(base as u128)?
+            .checked_add(to_digit2(digit as char) as u128)?;
     }
 
     Some(n)
@@ -82,6 +86,23 @@ fn invalid_separator(buffer: String) -> i32 {
 
 impl Lexer<'_> {
     pub(crate) fn scan_number(&mut self) -> IterElem {
+        let token = self.scan_number_literal();
+
+        if let Some(Token {
+            value: RawToken::Invalid(_),
+            ..
+        }) = token
+        {
+            // skip the rest of malformed number, so that it isn't scanned as separate tokens
+            self.advance_while(|current, _| {
+                current.is_alphanumeric() || current == '_' || current == '.'
+            });
+        }
+
+        token
+    }
+
+    fn scan_number_literal(&mut self) -> IterElem {
         self.start_location = self.location;
 
         let mut number_kind = NumberKind::Invalid;
@@ -177,13 +198,41 @@ impl Lexer<'_> {
             }
         }
 
-        if self.current == 'i' {
-            number_kind = NumberKind::Imag;
-            self.advance();
-        }
-
         let buffer = &self.contents[self.start_location..self.location];
 
+        // suffix: `i` for imaginary numbers or type, for example: `u8`
+        let mut suffix = None;
+
+        if self.current.is_alphabetic() {
+            let suffix_start = self.location;
+
+            while self.current.is_alphanumeric() || self.current == '_' {
+                self.advance();
+            }
+
+            let suffix_span = Span::from(suffix_start..self.location);
+
+            match &self.contents[suffix_start..self.location] {
+                "i" => number_kind = NumberKind::Imag,
+                name => match NumberSuffix::from_name(name) {
+                    Some(s) if s.is_float() => suffix = Some(s),
+                    Some(_) if number_kind == NumberKind::Float => {
+                        return Some(Token::new(
+                            RawToken::Invalid(LexerError::IntegerSuffixOnFloatLiteral),
+                            suffix_span,
+                        ));
+                    }
+                    Some(s) => suffix = Some(s),
+                    None => {
+                        return Some(Token::new(
+                            RawToken::Invalid(LexerError::UnknownNumberSuffix),
+                            suffix_span,
+                        ));
+                    }
+                },
+            }
+        }
+
         if let Some(location) = invalid_digit_location {
             if number_kind == NumberKind::Int {
                 return Some(Token::new(
@@ -208,7 +257,12 @@ impl Lexer<'_> {
                     (if base == 10 { buffer } else { &buffer[2..] }).as_bytes(),
                     base,
                 ) {
-                    Some(n) => Some(Token::new(RawToken::Int(n), self.span_from_start())),
+                    // integer with float suffix, for example: `1f32`
+                    Some(n) if suffix.is_some_and(NumberSuffix::is_float) => Some(Token::new(
+                        RawToken::Float(n as f64, suffix),
+                        self.span_from_start(),
+                    )),
+                    Some(n) => Some(Token::new(RawToken::Int(n, suffix), self.span_from_start())),
                     None => Some(Token::new(
                         RawToken::Invalid(LexerError::NumberParserError),
                         self.span_from_start(),
@@ -216,19 +270,22 @@ impl Lexer<'_> {
                 }
             }
             NumberKind::Float => Some(Token::new(
-                RawToken::Float(match buffer.parse::<f64>() {
-                    Ok(n) => n,
-                    Err(_) => {
-                        return Some(Token::new(
-                            RawToken::Invalid(LexerError::NumberParserError),
-                            self.span_from_start(),
-                        ));
-                    }
-                }),
+                RawToken::Float(
+                    match buffer.replace('_', "").parse::<f64>() {
+                        Ok(n) => n,
+                        Err(_) => {
+                            return Some(Token::new(
+                                RawToken::Invalid(LexerError::NumberParserError),
+                                self.span_from_start(),
+                            ));
+                        }
+                    },
+                    suffix,
+                ),
                 self.span_from_start(),
             )),
             NumberKind::Imag => Some(Token::new(
-                RawToken::Imag(match buffer[..buffer.len() - 1].parse::<f64>() {
+                RawToken::Imag(match buffer.replace('_', "").parse::<f64>() {
                     Ok(n) => n,
                     Err(_) => {
                         return Some(Token::new(
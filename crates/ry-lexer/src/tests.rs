@@ -49,6 +49,26 @@ mod lexer_tests {
         );
     }
 
+    #[test]
+    fn trivia_test() {
+        let mut l = Lexer::new("a \n//b\n").with_trivia();
+
+        let tokens = std::iter::from_fn(|| Some(l.next().unwrap()))
+            .take_while(|t| t.value != RawToken::EndOfFile)
+            .map(|t| (t.value, t.span.range))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (RawToken::Identifier("a".to_owned()), 0..1),
+                (RawToken::Whitespace, 1..3),
+                (RawToken::Comment("b".to_owned()), 3..6),
+                (RawToken::Whitespace, 6..7),
+            ]
+        );
+    }
+
     #[test]
     fn unexpected_char_test() {
         def_lex!(l, "#");
@@ -58,6 +78,31 @@ mod lexer_tests {
         );
     }
 
+    #[test]
+    fn unexpected_non_ascii_char_test() {
+        def_lex!(l, "a = §;");
+        l.next();
+        l.next();
+
+        let token = l.next().unwrap();
+        assert_eq!(
+            token.value,
+            RawToken::Invalid(LexerError::UnexpectedChar('§'))
+        );
+        assert_eq!(token.span.range, 4..6);
+    }
+
+    #[test]
+    fn unknown_non_ascii_escape_test() {
+        def_lex!(l, "\"\\д\"");
+        let token = l.next().unwrap();
+        assert_eq!(
+            token.value,
+            RawToken::Invalid(LexerError::UnknownEscapeSequence)
+        );
+        assert_eq!(token.span.range, 2..4);
+    }
+
     #[test]
     fn string_test() {
         def_lex!(l, "\"test\"");
@@ -112,25 +157,89 @@ mod lexer_tests {
     #[test]
     fn number_test() {
         def_lex!(l, "12345");
-        assert_eq!(l.next().unwrap().value, RawToken::Int(12345));
+        assert_eq!(l.next().unwrap().value, RawToken::Int(12345, None));
+    }
+
+    #[test]
+    fn number_with_separators_test() {
+        def_lex!(l, "1_000 1_000.5");
+        assert_eq!(l.next().unwrap().value, RawToken::Int(1000, None));
+        assert_eq!(l.next().unwrap().value, RawToken::Float(1000.5, None));
+    }
+
+    #[test]
+    fn number_suffix_test() {
+        def_lex!(l, "255u8 0xffi64 1.5f32 1f64 2i 3i8");
+        assert_eq!(
+            l.next().unwrap().value,
+            RawToken::Int(255, Some(NumberSuffix::U8))
+        );
+        assert_eq!(
+            l.next().unwrap().value,
+            RawToken::Int(255, Some(NumberSuffix::I64))
+        );
+        assert_eq!(
+            l.next().unwrap().value,
+            RawToken::Float(1.5, Some(NumberSuffix::F32))
+        );
+        assert_eq!(
+            l.next().unwrap().value,
+            RawToken::Float(1.0, Some(NumberSuffix::F64))
+        );
+        assert_eq!(l.next().unwrap().value, RawToken::Imag(2.0));
+        assert_eq!(
+            l.next().unwrap().value,
+            RawToken::Int(3, Some(NumberSuffix::I8))
+        );
+    }
+
+    #[test]
+    fn invalid_number_suffix_test() {
+        def_lex!(l, "1u7 1.5u8 2");
+
+        let token = l.next().unwrap();
+        assert_eq!(
+            token.value,
+            RawToken::Invalid(LexerError::UnknownNumberSuffix)
+        );
+        assert_eq!(token.span.range, 1..3);
+
+        assert_eq!(
+            l.next().unwrap().value,
+            RawToken::Invalid(LexerError::IntegerSuffixOnFloatLiteral)
+        );
+        assert_eq!(l.next().unwrap().value, RawToken::Int(2, None));
+    }
+
+    #[test]
+    fn big_integer_test() {
+        def_lex!(
+            l,
+            "340282366920938463463374607431768211455 340282366920938463463374607431768211456"
+        );
+        assert_eq!(l.next().unwrap().value, RawToken::Int(u128::MAX, None));
+        assert_eq!(
+            l.next().unwrap().value,
+            RawToken::Invalid(LexerError::NumberParserError)
+        );
     }
 
     #[test]
     fn number2_test() {
         def_lex!(l, "12345.12345");
-        assert_eq!(l.next().unwrap().value, RawToken::Float(12345.12345));
+        assert_eq!(l.next().unwrap().value, RawToken::Float(12345.12345, None));
     }
 
     #[test]
     fn number3_test() {
         def_lex!(l, "12345.");
-        assert_eq!(l.next().unwrap().value, RawToken::Float(12345.));
+        assert_eq!(l.next().unwrap().value, RawToken::Float(12345., None));
     }
 
     #[test]
     fn number4_test() {
         def_lex!(l, "1e3");
-        assert_eq!(l.next().unwrap().value, RawToken::Float(1e3));
+        assert_eq!(l.next().unwrap().value, RawToken::Float(1e3, None));
     }
 
     #[test]
@@ -208,13 +317,13 @@ mod lexer_tests {
     #[test]
     fn number13_test() {
         def_lex!(l, "0b1101");
-        assert_eq!(l.next().unwrap().value, RawToken::Int(13));
+        assert_eq!(l.next().unwrap().value, RawToken::Int(13, None));
     }
 
     #[test]
     fn number14_test() {
         def_lex!(l, "0x9b");
-        assert_eq!(l.next().unwrap().value, RawToken::Int(155));
+        assert_eq!(l.next().unwrap().value, RawToken::Int(155, None));
     }
 
     #[test]
@@ -229,4 +338,111 @@ mod lexer_tests {
         assert_eq!(l.next().unwrap().value, RawToken::PlusPlus);
         assert_eq!(l.next().unwrap().value, RawToken::EndOfFile);
     }
+
+    #[test]
+    fn char_test() {
+        def_lex!(l, "'a' '\\n'");
+        assert_eq!(l.next().unwrap().value, RawToken::Char('a'));
+        assert_eq!(l.next().unwrap().value, RawToken::Char('\n'));
+    }
+
+    #[test]
+    fn string_escape_test() {
+        def_lex!(l, "\"a\\tb\\u{0041}\"");
+        assert_eq!(
+            l.next().unwrap().value,
+            RawToken::String("a\tbA".to_owned())
+        );
+    }
+
+    #[test]
+    fn string_escape_recovery_test() {
+        def_lex!(l, "\"a\\qb\" 1");
+        assert_eq!(
+            l.next().unwrap().value,
+            RawToken::Invalid(LexerError::UnknownEscapeSequence)
+        );
+        assert_eq!(l.next().unwrap().value, RawToken::Int(1, None));
+    }
+
+    #[test]
+    fn unterminated_string_recovery_test() {
+        def_lex!(l, "\"a\\u{12\n1");
+        let token = l.next().unwrap();
+        assert_eq!(
+            token.value,
+            RawToken::Invalid(LexerError::ExpectedDigitInUnicodeEscapeSequence)
+        );
+        assert_eq!(l.next().unwrap().value, RawToken::Int(1, None));
+    }
+
+    #[test]
+    fn escape_at_end_of_line_recovery_test() {
+        def_lex!(l, "var s = \"a\\\nvar b = 1;");
+        assert_eq!(l.next().unwrap().value, RawToken::Var);
+        assert_eq!(
+            l.next().unwrap().value,
+            RawToken::Identifier("s".to_owned())
+        );
+        assert_eq!(l.next().unwrap().value, RawToken::Assign);
+
+        let token = l.next().unwrap();
+        assert_eq!(
+            token.value,
+            RawToken::Invalid(LexerError::UnknownEscapeSequence)
+        );
+        assert_eq!(token.span.range, 10..11);
+
+        assert_eq!(l.next().unwrap().value, RawToken::Var);
+        assert_eq!(
+            l.next().unwrap().value,
+            RawToken::Identifier("b".to_owned())
+        );
+        assert_eq!(l.next().unwrap().value, RawToken::Assign);
+        assert_eq!(l.next().unwrap().value, RawToken::Int(1, None));
+        assert_eq!(l.next().unwrap().value, RawToken::Semicolon);
+    }
+
+    #[test]
+    fn unterminated_string_span_test() {
+        def_lex!(l, "\"a\\nb\n1");
+        let token = l.next().unwrap();
+        assert_eq!(
+            token.value,
+            RawToken::Invalid(LexerError::UnterminatedStringLiteral)
+        );
+        assert_eq!(token.span.range, 0..5);
+        assert_eq!(l.next().unwrap().value, RawToken::Int(1, None));
+    }
+
+    #[test]
+    fn char_recovery_test() {
+        def_lex!(l, "'\\x{g}' 1");
+        assert_eq!(
+            l.next().unwrap().value,
+            RawToken::Invalid(LexerError::ExpectedDigitInByteEscapeSequence)
+        );
+        assert_eq!(l.next().unwrap().value, RawToken::Int(1, None));
+    }
+
+    #[test]
+    fn wrapped_id_recovery_test() {
+        def_lex!(l, "`a b` 1\n2");
+        assert_eq!(
+            l.next().unwrap().value,
+            RawToken::Invalid(LexerError::UnterminatedWrappedIdentifierLiteral)
+        );
+        assert_eq!(l.next().unwrap().value, RawToken::Int(1, None));
+        assert_eq!(l.next().unwrap().value, RawToken::Int(2, None));
+    }
+
+    #[test]
+    fn number_recovery_test() {
+        def_lex!(l, "0x1.5 + 1");
+        assert_eq!(
+            l.next().unwrap().value,
+            RawToken::Invalid(LexerError::InvalidRadixPoint)
+        );
+        assert_eq!(l.next().unwrap().value, RawToken::Plus);
+    }
 }
@@ -0,0 +1,339 @@
+//! `lib.rs` - implements language server for Ry.
+//!
+//! Server keeps contents of opened documents, publishes diagnostics
+//! for them on every change and exposes machine-applicable
+//! [`Suggestion`]s of these diagnostics as quick fix code actions.
+use std::{collections::HashMap, error::Error, ops::Range as ByteRange};
+
+use codespan_reporting::diagnostic::{Diagnostic as Report, LabelStyle, Severity};
+use lsp_server::{Connection, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as LspNotification, PublishDiagnostics,
+    },
+    request::{CodeActionRequest, Request as LspRequest},
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams, Location,
+    NumberOrString, Position, PublishDiagnosticsParams, Range, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Uri, WorkspaceEdit,
+};
+use ry_parser::{error::ParserError, Parser};
+use ry_report::{Reporter, Suggestion};
+
+type ServerResult<T> = Result<T, Box<dyn Error + Sync + Send>>;
+
+/// Starts language server, that communicates with the client through stdio.
+pub fn start() -> ServerResult<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = serde_json::to_value(ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        ..Default::default()
+    })?;
+
+    connection.initialize(capabilities)?;
+
+    Server {
+        connection,
+        documents: HashMap::new(),
+    }
+    .run()?;
+
+    io_threads.join()?;
+
+    Ok(())
+}
+
+struct Server {
+    connection: Connection,
+    documents: HashMap<Uri, String>,
+}
+
+impl Server {
+    fn run(&mut self) -> ServerResult<()> {
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+
+                    self.handle_request(request)?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> ServerResult<()> {
+        let response = match request.method.as_str() {
+            CodeActionRequest::METHOD => {
+                let params: CodeActionParams = serde_json::from_value(request.params)?;
+
+                let actions = self
+                    .documents
+                    .get(&params.text_document.uri)
+                    .map(|source| code_actions(&params.text_document.uri, source, params.range))
+                    .unwrap_or_default();
+
+                Response::new_ok(request.id, actions)
+            }
+            _ => Response::new_err(
+                request.id,
+                lsp_server::ErrorCode::MethodNotFound as i32,
+                format!("unsupported request `{}`", request.method),
+            ),
+        };
+
+        self.connection.sender.send(response.into())?;
+
+        Ok(())
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> ServerResult<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+
+                self.update_document(params.text_document.uri, params.text_document.text)?;
+            }
+            DidChangeTextDocument::METHOD => {
+                let mut params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+
+                // Full text synchronization: the last change holds the whole document.
+                if let Some(change) = params.content_changes.pop() {
+                    self.update_document(params.text_document.uri, change.text)?;
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+
+                self.documents.remove(&params.text_document.uri);
+                self.publish_diagnostics(params.text_document.uri, vec![])?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn update_document(&mut self, uri: Uri, source: String) -> ServerResult<()> {
+        let diagnostics = diagnostics(&uri, &source);
+
+        self.documents.insert(uri.clone(), source);
+        self.publish_diagnostics(uri, diagnostics)
+    }
+
+    fn publish_diagnostics(&self, uri: Uri, diagnostics: Vec<Diagnostic>) -> ServerResult<()> {
+        self.connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                PublishDiagnostics::METHOD.to_owned(),
+                PublishDiagnosticsParams {
+                    uri,
+                    diagnostics,
+                    version: None,
+                },
+            )))?;
+
+        Ok(())
+    }
+}
+
+fn parse(source: &str) -> Option<ParserError> {
+    Parser::new(source).parse().err()
+}
+
+/// Converts diagnostics of the document into LSP format.
+pub fn diagnostics(uri: &Uri, source: &str) -> Vec<Diagnostic> {
+    parse(source)
+        .map(|error| vec![to_lsp_diagnostic(uri, source, &error.build_diagnostic(0))])
+        .unwrap_or_default()
+}
+
+/// Builds quick fix code actions for suggestions of diagnostics,
+/// that intersect with the given range.
+pub fn code_actions(uri: &Uri, source: &str, range: Range) -> Vec<CodeActionOrCommand> {
+    let Some(error) = parse(source) else {
+        return vec![];
+    };
+
+    let diagnostic = to_lsp_diagnostic(uri, source, &error.build_diagnostic(0));
+
+    if diagnostic.range.end < range.start || range.end < diagnostic.range.start {
+        return vec![];
+    }
+
+    error
+        .build_suggestions()
+        .into_iter()
+        .map(|suggestion| {
+            CodeActionOrCommand::CodeAction(to_code_action(
+                uri,
+                source,
+                suggestion,
+                diagnostic.clone(),
+            ))
+        })
+        .collect()
+}
+
+fn to_code_action(
+    uri: &Uri,
+    source: &str,
+    suggestion: Suggestion,
+    diagnostic: Diagnostic,
+) -> CodeAction {
+    let edits = suggestion
+        .edits
+        .into_iter()
+        .map(|edit| TextEdit::new(to_lsp_range(source, edit.range), edit.replacement))
+        .collect();
+
+    CodeAction {
+        title: suggestion.message,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic]),
+        edit: Some(WorkspaceEdit::new(HashMap::from([(uri.clone(), edits)]))),
+        is_preferred: Some(true),
+        ..Default::default()
+    }
+}
+
+fn to_lsp_diagnostic(uri: &Uri, source: &str, report: &Report<usize>) -> Diagnostic {
+    let primary_range = report
+        .labels
+        .iter()
+        .find(|label| label.style == LabelStyle::Primary)
+        .map_or(0..0, |label| label.range.clone());
+
+    let mut message = report.message.clone();
+
+    for note in &report.notes {
+        message.push('\n');
+        message.push_str(note);
+    }
+
+    let related_information = report
+        .labels
+        .iter()
+        .filter(|label| !label.message.is_empty())
+        .map(|label| DiagnosticRelatedInformation {
+            location: Location::new(uri.clone(), to_lsp_range(source, label.range.clone())),
+            message: label.message.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    Diagnostic {
+        range: to_lsp_range(source, primary_range),
+        severity: Some(match report.severity {
+            Severity::Bug | Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Note => DiagnosticSeverity::INFORMATION,
+            Severity::Help => DiagnosticSeverity::HINT,
+        }),
+        code: report.code.clone().map(NumberOrString::String),
+        source: Some("ry".to_owned()),
+        message,
+        related_information: (!related_information.is_empty()).then_some(related_information),
+        ..Default::default()
+    }
+}
+
+fn to_lsp_range(source: &str, range: ByteRange<usize>) -> Range {
+    Range::new(position(source, range.start), position(source, range.end))
+}
+
+/// Converts byte offset into LSP position (line and UTF-16 code unit).
+pub fn position(source: &str, offset: usize) -> Position {
+    let offset = offset.min(source.len());
+    let before = &source[..offset];
+
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = before.matches('\n').count();
+    let character = before[line_start..].encode_utf16().count();
+
+    Position::new(line as u32, character as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{code_actions, diagnostics, position};
+    use lsp_types::{CodeActionOrCommand, NumberOrString, Position, Range, TextEdit, Uri};
+
+    fn uri() -> Uri {
+        Uri::from_str("file:///test.ry").unwrap()
+    }
+
+    #[test]
+    fn position_test() {
+        let source = "a\nпривет b";
+
+        assert_eq!(position(source, 0), Position::new(0, 0));
+        assert_eq!(position(source, 2), Position::new(1, 0));
+        assert_eq!(position(source, source.len()), Position::new(1, 8));
+    }
+
+    #[test]
+    fn diagnostics_test() {
+        let source = "trait A {\n    pub fun a();\n}";
+        let diagnostics = diagnostics(&uri(), source);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::String("E002".to_owned()))
+        );
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(1, 4), Position::new(1, 7))
+        );
+    }
+
+    #[test]
+    fn code_action_test() {
+        let source = "trait A {\n    pub fun a();\n}";
+        let actions = code_actions(
+            &uri(),
+            source,
+            Range::new(Position::new(1, 5), Position::new(1, 5)),
+        );
+
+        assert_eq!(actions.len(), 1);
+
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("expected code action");
+        };
+
+        assert_eq!(
+            action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri()],
+            vec![TextEdit::new(
+                Range::new(Position::new(1, 4), Position::new(1, 8)),
+                "".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn no_code_actions_outside_of_diagnostic_test() {
+        let source = "trait A {\n    pub fun a();\n}";
+
+        assert!(code_actions(
+            &uri(),
+            source,
+            Range::new(Position::new(0, 0), Position::new(0, 1))
+        )
+        .is_empty());
+    }
+}
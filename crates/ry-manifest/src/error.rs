@@ -0,0 +1,136 @@
+//! `error.rs` - defines errors found in the project manifest.
+use std::ops::Range;
+
+use codespan_reporting::diagnostic::{Diagnostic, Label};
+use ry_report::Reporter;
+
+#[derive(Debug, PartialEq)]
+pub enum ManifestError {
+    /// Manifest is not a valid JSON. [`String`] is the error message.
+    Syntax(Range<usize>, String),
+
+    /// Required field [`&str`] is missing in the object.
+    MissingField(Range<usize>, &'static str),
+
+    /// Field [`String`] is not expected in the object. [`&[&str]`] are
+    /// the expected fields.
+    UnknownField(Range<usize>, String, &'static [&'static str]),
+
+    /// Value of the field has wrong type. [`String`] is the path of
+    /// the field (for example: `dependencies.json.path`), [`&str`] is
+    /// the expected type.
+    InvalidType(Range<usize>, String, &'static str),
+
+    /// Name of the package, binary or dependency ([`&str`]) is not valid.
+    InvalidName(Range<usize>, &'static str, String),
+
+    /// Version of the package can't be parsed. [`String`]s are the version
+    /// and the error message.
+    InvalidVersion(Range<usize>, String, String),
+
+    /// Version requirement of the dependency can't be parsed. [`String`]s
+    /// are the dependency name, the requirement and the error message.
+    InvalidVersionRequirement(Range<usize>, String, String, String),
+
+    /// Dependency [`String`] has neither version requirement, nor path.
+    EmptyDependency(Range<usize>, String),
+
+    /// Entry point of the target is not a relative path to `.ry` file.
+    InvalidTargetPath(Range<usize>, String),
+
+    /// Entry point of the target [`String`] doesn't exist.
+    TargetNotFound(Range<usize>, String),
+
+    /// There is no package at the path of dependency. [`String`]s are
+    /// the dependency name and the path.
+    DependencyNotFound(Range<usize>, String, String),
+
+    /// There is no package at the path of workspace member [`String`].
+    MemberNotFound(Range<usize>, String),
+}
+
+impl ManifestError {
+    pub fn span(&self) -> &Range<usize> {
+        match self {
+            Self::Syntax(span, _)
+            | Self::MissingField(span, _)
+            | Self::UnknownField(span, ..)
+            | Self::InvalidType(span, ..)
+            | Self::InvalidName(span, ..)
+            | Self::InvalidVersion(span, ..)
+            | Self::InvalidVersionRequirement(span, ..)
+            | Self::EmptyDependency(span, _)
+            | Self::InvalidTargetPath(span, _)
+            | Self::TargetNotFound(span, _)
+            | Self::DependencyNotFound(span, ..)
+            | Self::MemberNotFound(span, _) => span,
+        }
+    }
+}
+
+fn list(items: &[&str]) -> String {
+    items
+        .iter()
+        .map(|item| format!("`{item}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl<'source> Reporter<'source> for ManifestError {
+    fn build_diagnostic(&self, file_id: usize) -> Diagnostic<usize> {
+        match self {
+            Self::Syntax(span, message) => Diagnostic::error()
+                .with_message("manifest is not a valid JSON")
+                .with_labels(vec![Label::primary(file_id, span.clone()).with_message(message)]),
+            Self::MissingField(span, field) => Diagnostic::error()
+                .with_message(format!("missing field `{field}`"))
+                .with_labels(vec![Label::primary(file_id, span.clone())
+                    .with_message(format!("`{field}` must be specified in this object"))]),
+            Self::UnknownField(span, field, expected) => Diagnostic::error()
+                .with_message(format!("unknown field `{field}`"))
+                .with_labels(vec![Label::primary(file_id, span.clone())])
+                .with_notes(vec![format!("note: expected one of: {}", list(expected))]),
+            Self::InvalidType(span, field, expected) => Diagnostic::error()
+                .with_message(format!("invalid type of `{field}`"))
+                .with_labels(vec![
+                    Label::primary(file_id, span.clone()).with_message(format!("expected {expected}"))
+                ]),
+            Self::InvalidName(span, kind, name) => Diagnostic::error()
+                .with_message(format!("invalid {kind} name `{name}`"))
+                .with_labels(vec![Label::primary(file_id, span.clone())])
+                .with_notes(vec![
+                    "note: names must start with ascii lowercase letter or `_` and consist only of ascii lowercase letters, digits and `_`".to_owned()
+                ]),
+            Self::InvalidVersion(span, version, message) => Diagnostic::error()
+                .with_message(format!("invalid version `{version}`"))
+                .with_labels(vec![Label::primary(file_id, span.clone()).with_message(message)])
+                .with_notes(vec!["note: version must be in semver format, for example: `0.1.0`".to_owned()]),
+            Self::InvalidVersionRequirement(span, name, requirement, message) => Diagnostic::error()
+                .with_message(format!("invalid version requirement `{requirement}` of dependency `{name}`"))
+                .with_labels(vec![Label::primary(file_id, span.clone()).with_message(message)])
+                .with_notes(vec!["note: for example: `1.2`, `^1.2.3`, `>=1.0, <2.0` or `*`".to_owned()]),
+            Self::EmptyDependency(span, name) => Diagnostic::error()
+                .with_message(format!("dependency `{name}` specifies neither version, nor path"))
+                .with_labels(vec![Label::primary(file_id, span.clone())])
+                .with_notes(vec![
+                    "help: add `\"version\"` to use the package from registry, or `\"path\"` to use the local one".to_owned()
+                ]),
+            Self::InvalidTargetPath(span, path) => Diagnostic::error()
+                .with_message(format!("invalid entry point `{path}`"))
+                .with_labels(vec![
+                    Label::primary(file_id, span.clone()).with_message("expected relative path to `.ry` file")
+                ]),
+            Self::TargetNotFound(span, path) => Diagnostic::error()
+                .with_message(format!("entry point `{path}` doesn't exist"))
+                .with_labels(vec![Label::primary(file_id, span.clone()).with_message("file not found")]),
+            Self::DependencyNotFound(span, name, path) => Diagnostic::error()
+                .with_message(format!("failed to find dependency `{name}` at `{path}`"))
+                .with_labels(vec![Label::primary(file_id, span.clone())
+                    .with_message("directory doesn't contain `.lapis.json`")]),
+            Self::MemberNotFound(span, path) => Diagnostic::error()
+                .with_message(format!("failed to find workspace member `{path}`"))
+                .with_labels(vec![Label::primary(file_id, span.clone())
+                    .with_message("directory doesn't contain `.lapis.json`")]),
+        }
+    }
+}
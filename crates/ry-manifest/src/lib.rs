@@ -0,0 +1,922 @@
+//! `lib.rs` - Lapis project manifest.
+//!
+//! Manifest is stored in `.lapis.json` in the root of the project:
+//!
+//! ```json
+//! {
+//!   "name": "hello",
+//!   "version": "0.1.0",
+//!   "authors": ["Adi Salimgereyev"],
+//!   "description": "Greets the world",
+//!   "license": "MIT",
+//!   "lib": "lib/lib.ry",
+//!   "bin": {
+//!     "hello": "bin/main.ry"
+//!   },
+//!   "dependencies": {
+//!     "json": "^1.2",
+//!     "utils": {
+//!       "path": "../utils"
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Only `name` and `version` are required. If `lib` or `bin` is not
+//! specified, `lib/lib.ry` and `bin/main.ry` (binary named after
+//! the package) are used, when they exist. Dependency is either a version
+//! requirement of the package from registry, or an object with `version`
+//! and (or) `path` to the local package. Several packages can be grouped
+//! into a workspace, see [`workspace`].
+pub mod error;
+mod locate;
+#[cfg(test)]
+mod test_utils;
+pub mod workspace;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use semver::{Version, VersionReq};
+use serde_json::{json, Map, Value};
+
+use crate::{error::ManifestError, locate::locate};
+
+/// Name of the manifest file in the root of the project.
+pub const MANIFEST_FILE: &str = ".lapis.json";
+
+const DEFAULT_LIB: &str = "lib/lib.ry";
+const DEFAULT_BIN: &str = "bin/main.ry";
+
+const FIELDS: &[&str] = &[
+    "name",
+    "version",
+    "authors",
+    "description",
+    "license",
+    "lib",
+    "bin",
+    "dependencies",
+];
+const DEPENDENCY_FIELDS: &[&str] = &["version", "path"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub name: String,
+    pub version: Version,
+    pub authors: Vec<String>,
+    pub description: Option<String>,
+    pub license: Option<String>,
+    /// Entry point of the library, relative to the root of the project.
+    pub lib: Option<PathBuf>,
+    /// Binaries with their entry points.
+    pub bins: BTreeMap<String, PathBuf>,
+    pub dependencies: BTreeMap<String, Dependency>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency {
+    /// Requirement for the version of the package.
+    pub version: Option<VersionReq>,
+    /// Path to the local package, relative to the root of the project.
+    pub path: Option<PathBuf>,
+}
+
+/// Whether the name can be used for a package, binary or dependency.
+pub fn is_valid_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Finds the root of the project, that the path belongs to: the closest
+/// ancestor directory containing the manifest.
+pub fn find_root(path: &Path) -> Option<&Path> {
+    path.ancestors()
+        .find(|directory| directory.join(MANIFEST_FILE).is_file())
+}
+
+impl Manifest {
+    /// Manifest of the new project with a single binary.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            version: Version::new(0, 1, 0),
+            authors: vec![],
+            description: None,
+            license: None,
+            lib: None,
+            bins: BTreeMap::from([(name.to_owned(), PathBuf::from(DEFAULT_BIN))]),
+            dependencies: BTreeMap::new(),
+        }
+    }
+
+    /// Parses and validates the manifest. Returns all errors found, sorted
+    /// by location.
+    pub fn parse(source: &str) -> Result<Self, Vec<ManifestError>> {
+        let value = serde_json::from_str::<Value>(source)
+            .map_err(|error| vec![syntax_error(source, &error)])?;
+
+        let mut validator = Validator {
+            source,
+            errors: vec![],
+        };
+
+        match validator.manifest(&value) {
+            Some(manifest) if validator.errors.is_empty() => Ok(manifest),
+            _ => {
+                validator.errors.sort_by_key(|error| error.span().start);
+                Err(validator.errors)
+            }
+        }
+    }
+
+    /// Parses the manifest of the project in `directory` and checks, that
+    /// entry points of targets and path dependencies exist. Default entry
+    /// points, that don't exist, are removed.
+    pub fn load(directory: &Path, source: &str) -> Result<Self, Vec<ManifestError>> {
+        let mut manifest = Self::parse(source)?;
+        let mut errors = vec![];
+
+        let span = |path: &[&str]| locate(source, path).map(|location| location.value);
+
+        if let Some(lib) = manifest.lib.take_if(|lib| !directory.join(lib).is_file()) {
+            if let Some(span) = span(&["lib"]) {
+                errors.push(ManifestError::TargetNotFound(
+                    span,
+                    lib.display().to_string(),
+                ));
+            }
+        }
+
+        manifest.bins.retain(|name, path| {
+            if directory.join(&*path).is_file() {
+                return true;
+            }
+
+            if let Some(span) = span(&["bin", name]) {
+                errors.push(ManifestError::TargetNotFound(
+                    span,
+                    path.display().to_string(),
+                ));
+            }
+
+            false
+        });
+
+        for (name, dependency) in &manifest.dependencies {
+            if let Some(path) = &dependency.path {
+                if !directory.join(path).join(MANIFEST_FILE).is_file() {
+                    errors.push(ManifestError::DependencyNotFound(
+                        span(&["dependencies", name, "path"]).unwrap_or_default(),
+                        name.clone(),
+                        path.display().to_string(),
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(manifest)
+        } else {
+            errors.sort_by_key(|error| error.span().start);
+            Err(errors)
+        }
+    }
+
+    /// Serializes the manifest into pretty-printed JSON, with fields in
+    /// the documented order.
+    pub fn to_json(&self) -> String {
+        let mut fields = vec![
+            ("name", json!(self.name)),
+            ("version", json!(self.version.to_string())),
+        ];
+
+        if !self.authors.is_empty() {
+            fields.push(("authors", json!(self.authors)));
+        }
+
+        if let Some(description) = &self.description {
+            fields.push(("description", json!(description)));
+        }
+
+        if let Some(license) = &self.license {
+            fields.push(("license", json!(license)));
+        }
+
+        if let Some(lib) = &self.lib {
+            fields.push(("lib", json!(path_to_string(lib))));
+        }
+
+        if !self.bins.is_empty() {
+            let bins = self
+                .bins
+                .iter()
+                .map(|(name, path)| (name.clone(), json!(path_to_string(path))))
+                .collect::<Map<_, _>>();
+
+            fields.push(("bin", Value::Object(bins)));
+        }
+
+        if !self.dependencies.is_empty() {
+            let dependencies = self
+                .dependencies
+                .iter()
+                .map(|(name, dependency)| (name.clone(), dependency.to_json()))
+                .collect::<Map<_, _>>();
+
+            fields.push(("dependencies", Value::Object(dependencies)));
+        }
+
+        let fields = fields
+            .iter()
+            .map(|(name, value)| {
+                format!(
+                    "  \"{name}\": {}",
+                    serde_json::to_string_pretty(value)
+                        .unwrap()
+                        .replace('\n', "\n  ")
+                )
+            })
+            .collect::<Vec<_>>();
+
+        format!("{{\n{}\n}}\n", fields.join(",\n"))
+    }
+}
+
+/// Adds the dependency to the manifest source (or replaces the existing
+/// one with the same name), keeping the rest of the source as is. Returns
+/// `None` if the source is not a JSON object.
+pub fn add_dependency(source: &str, name: &str, dependency: &Dependency) -> Option<String> {
+    let value = dependency.to_json();
+
+    if let Some(location) = locate(source, &["dependencies", name]) {
+        let mut source = source.to_owned();
+        source.replace_range(location.value, &value.to_string());
+        return Some(source);
+    }
+
+    // Inserts the member before the closing brace of the object.
+    let insert = |object: Range<usize>, member: String, indent: &str| {
+        let body = &source[object.start + 1..object.end - 1];
+        let mut source = source.to_owned();
+
+        if body.trim().is_empty() {
+            source.replace_range(
+                object.start..object.end,
+                &format!("{{\n{indent}  {member}\n{indent}}}"),
+            );
+        } else {
+            let end = object.start + 1 + body.trim_end().len();
+            source.insert_str(end, &format!(",\n{indent}  {member}"));
+        }
+
+        source
+    };
+
+    let member = format!("\"{name}\": {value}");
+
+    match locate(source, &["dependencies"]) {
+        Some(location) => Some(insert(location.value, member, "  ")),
+        None => {
+            let root = locate(source, &[])?.value;
+
+            if !source[root.clone()].starts_with('{') {
+                return None;
+            }
+
+            Some(insert(
+                root,
+                format!("\"dependencies\": {{\n    {member}\n  }}"),
+                "",
+            ))
+        }
+    }
+}
+
+impl Dependency {
+    fn to_json(&self) -> Value {
+        match (&self.version, &self.path) {
+            (Some(version), None) => json!(version.to_string()),
+            (version, path) => {
+                let mut object = Map::new();
+
+                if let Some(version) = version {
+                    object.insert("version".to_owned(), json!(version.to_string()));
+                }
+
+                if let Some(path) = path {
+                    object.insert("path".to_owned(), json!(path_to_string(path)));
+                }
+
+                Value::Object(object)
+            }
+        }
+    }
+}
+
+/// Paths in the manifest always use `/` as a separator.
+fn path_to_string(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Converts error of `serde_json`, which has line and column, into
+/// the diagnostic with location.
+fn syntax_error(source: &str, error: &serde_json::Error) -> ManifestError {
+    let line_start = source
+        .split_inclusive('\n')
+        .take(error.line().saturating_sub(1))
+        .map(str::len)
+        .sum::<usize>();
+
+    let mut start = (line_start + error.column().saturating_sub(1)).min(source.len());
+
+    while !source.is_char_boundary(start) {
+        start -= 1;
+    }
+
+    let end = source[start..]
+        .chars()
+        .next()
+        .map_or(start, |c| start + c.len_utf8());
+
+    // Location is already shown in the diagnostic.
+    let message = error.to_string();
+    let message = message.split(" at line ").next().unwrap_or_default();
+
+    ManifestError::Syntax(start..end, message.to_owned())
+}
+
+/// Checks types and values of the manifest fields.
+struct Validator<'s> {
+    source: &'s str,
+    errors: Vec<ManifestError>,
+}
+
+impl Validator<'_> {
+    /// Location of the value at the path (or of the whole manifest, if it
+    /// is not found).
+    fn span(&self, path: &[&str]) -> Range<usize> {
+        locate(self.source, path).map_or(0..self.source.len(), |location| location.value)
+    }
+
+    fn invalid_type(&mut self, path: &[&str], expected: &'static str) {
+        let field = if path.is_empty() {
+            "manifest".to_owned()
+        } else {
+            path.join(".")
+        };
+
+        self.errors
+            .push(ManifestError::InvalidType(self.span(path), field, expected));
+    }
+
+    /// Checks that the value is an object containing only given fields.
+    fn object<'v>(
+        &mut self,
+        value: &'v Value,
+        path: &[&str],
+        fields: &'static [&'static str],
+    ) -> Option<&'v Map<String, Value>> {
+        let Value::Object(object) = value else {
+            self.invalid_type(path, "object");
+            return None;
+        };
+
+        for key in object.keys() {
+            if !fields.contains(&key.as_str()) {
+                let span = locate(self.source, &[path, &[key.as_str()]].concat())
+                    .map_or_else(|| self.span(path), |location| location.key);
+
+                self.errors
+                    .push(ManifestError::UnknownField(span, key.clone(), fields));
+            }
+        }
+
+        Some(object)
+    }
+
+    fn required<'v>(
+        &mut self,
+        object: &'v Map<String, Value>,
+        path: &[&str],
+        field: &'static str,
+    ) -> Option<&'v Value> {
+        let value = object.get(field);
+
+        if value.is_none() {
+            let start = self.span(path).start;
+
+            self.errors
+                .push(ManifestError::MissingField(start..start + 1, field));
+        }
+
+        value
+    }
+
+    fn string<'v>(&mut self, value: &'v Value, path: &[&str]) -> Option<&'v str> {
+        match value {
+            Value::String(string) => Some(string),
+            _ => {
+                self.invalid_type(path, "string");
+                None
+            }
+        }
+    }
+
+    fn name(&mut self, name: &str, path: &[&str], kind: &'static str) -> Option<String> {
+        if is_valid_name(name) {
+            Some(name.to_owned())
+        } else {
+            self.errors.push(ManifestError::InvalidName(
+                self.span(path),
+                kind,
+                name.to_owned(),
+            ));
+            None
+        }
+    }
+
+    fn target_path(&mut self, value: &Value, path: &[&str]) -> Option<PathBuf> {
+        let string = self.string(value, path)?;
+        let target = Path::new(string);
+
+        if target.is_relative() && target.extension().is_some_and(|e| e == "ry") {
+            Some(target.to_owned())
+        } else {
+            self.errors.push(ManifestError::InvalidTargetPath(
+                self.span(path),
+                string.to_owned(),
+            ));
+            None
+        }
+    }
+
+    fn version_requirement(&mut self, value: &Value, path: &[&str]) -> Option<VersionReq> {
+        let requirement = self.string(value, path)?;
+
+        VersionReq::parse(requirement)
+            .map_err(|error| {
+                self.errors.push(ManifestError::InvalidVersionRequirement(
+                    self.span(path),
+                    path[1].to_owned(),
+                    requirement.to_owned(),
+                    error.to_string(),
+                ));
+            })
+            .ok()
+    }
+
+    fn manifest(&mut self, value: &Value) -> Option<Manifest> {
+        let object = self.object(value, &[], FIELDS)?;
+
+        let name = self
+            .required(object, &[], "name")
+            .and_then(|value| self.string(value, &["name"]))
+            .and_then(|name| self.name(name, &["name"], "package"));
+
+        let version = self
+            .required(object, &[], "version")
+            .and_then(|value| self.string(value, &["version"]))
+            .and_then(|version| {
+                Version::parse(version)
+                    .map_err(|error| {
+                        self.errors.push(ManifestError::InvalidVersion(
+                            self.span(&["version"]),
+                            version.to_owned(),
+                            error.to_string(),
+                        ));
+                    })
+                    .ok()
+            });
+
+        let authors = match object.get("authors") {
+            None => vec![],
+            Some(Value::Array(authors)) if authors.iter().all(Value::is_string) => authors
+                .iter()
+                .filter_map(|author| author.as_str().map(str::to_owned))
+                .collect(),
+            Some(_) => {
+                self.invalid_type(&["authors"], "array of strings");
+                vec![]
+            }
+        };
+
+        let description = object
+            .get("description")
+            .and_then(|value| self.string(value, &["description"]))
+            .map(str::to_owned);
+
+        let license = object
+            .get("license")
+            .and_then(|value| self.string(value, &["license"]))
+            .map(str::to_owned);
+
+        let lib = match object.get("lib") {
+            Some(value) => self.target_path(value, &["lib"]),
+            None => Some(PathBuf::from(DEFAULT_LIB)),
+        };
+
+        let bins = match object.get("bin") {
+            Some(value) => self.bins(value),
+            None => name
+                .iter()
+                .map(|name| (name.clone(), PathBuf::from(DEFAULT_BIN)))
+                .collect(),
+        };
+
+        let dependencies = match object.get("dependencies") {
+            Some(value) => self.dependencies(value),
+            None => BTreeMap::new(),
+        };
+
+        Some(Manifest {
+            name: name?,
+            version: version?,
+            authors,
+            description,
+            license,
+            lib,
+            bins,
+            dependencies,
+        })
+    }
+
+    fn bins(&mut self, value: &Value) -> BTreeMap<String, PathBuf> {
+        let Value::Object(object) = value else {
+            self.invalid_type(&["bin"], "object");
+            return BTreeMap::new();
+        };
+
+        let mut bins = BTreeMap::new();
+
+        for (name, value) in object {
+            let path = ["bin", name.as_str()];
+
+            if let (Some(name), Some(target)) = (
+                self.name(name, &path, "binary"),
+                self.target_path(value, &path),
+            ) {
+                bins.insert(name, target);
+            }
+        }
+
+        bins
+    }
+
+    fn dependencies(&mut self, value: &Value) -> BTreeMap<String, Dependency> {
+        let Value::Object(object) = value else {
+            self.invalid_type(&["dependencies"], "object");
+            return BTreeMap::new();
+        };
+
+        let mut dependencies = BTreeMap::new();
+
+        for (name, value) in object {
+            let path = ["dependencies", name.as_str()];
+
+            let dependency = match value {
+                Value::String(_) => {
+                    self.version_requirement(value, &path)
+                        .map(|version| Dependency {
+                            version: Some(version),
+                            path: None,
+                        })
+                }
+                Value::Object(_) => self.dependency(value, &path),
+                _ => {
+                    self.invalid_type(&path, "string or object");
+                    None
+                }
+            };
+
+            if let (Some(name), Some(dependency)) =
+                (self.name(name, &path, "dependency"), dependency)
+            {
+                dependencies.insert(name, dependency);
+            }
+        }
+
+        dependencies
+    }
+
+    fn dependency(&mut self, value: &Value, path: &[&str]) -> Option<Dependency> {
+        let object = self.object(value, path, DEPENDENCY_FIELDS)?;
+
+        if !object.contains_key("version") && !object.contains_key("path") {
+            self.errors.push(ManifestError::EmptyDependency(
+                self.span(path),
+                path[1].to_owned(),
+            ));
+            return None;
+        }
+
+        let version = match object.get("version") {
+            Some(value) => Some(self.version_requirement(value, &[path, &["version"]].concat())?),
+            None => None,
+        };
+
+        let dependency_path = match object.get("path") {
+            Some(value) => Some(PathBuf::from(
+                self.string(value, &[path, &["path"]].concat())?,
+            )),
+            None => None,
+        };
+
+        Some(Dependency {
+            version,
+            path: dependency_path,
+        })
+    }
+}
+
+/// Entry points of libraries, that can be imported by name: the package
+/// itself, its path dependencies and packages added with
+/// [`ModuleRoots::add_package`] (for example, from registry). Import
+/// `"json"` refers to the entry point of library `json` and
+/// `"json/parser"` to `parser.ry` in the directory of the entry point.
+/// Other imports are resolved relatively to the importing file.
+#[derive(Debug, Default, Clone)]
+pub struct ModuleRoots {
+    entries: HashMap<String, PathBuf>,
+}
+
+impl ModuleRoots {
+    /// Collects roots of the project in `directory`. Entry points of
+    /// dependencies are taken from their manifests (`lib/lib.ry` is used,
+    /// if the manifest can't be read).
+    pub fn new(directory: &Path, manifest: &Manifest) -> Self {
+        let mut entries = HashMap::new();
+
+        if let Some(lib) = &manifest.lib {
+            entries.insert(manifest.name.clone(), directory.join(lib));
+        }
+
+        for (name, dependency) in &manifest.dependencies {
+            let Some(path) = &dependency.path else {
+                continue;
+            };
+
+            let root = directory.join(path);
+
+            let lib = fs::read_to_string(root.join(MANIFEST_FILE))
+                .ok()
+                .and_then(|source| Manifest::parse(&source).ok())
+                .and_then(|manifest| manifest.lib)
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_LIB));
+
+            entries.insert(name.clone(), root.join(lib));
+        }
+
+        Self { entries }
+    }
+
+    /// Makes the library of the package in `directory` importable by
+    /// `name`.
+    pub fn add_package(&mut self, name: &str, directory: &Path, manifest: &Manifest) {
+        let lib = manifest.lib.as_deref().unwrap_or(Path::new(DEFAULT_LIB));
+
+        self.entries.insert(name.to_owned(), directory.join(lib));
+    }
+
+    /// Adds roots of another package, for example, of the workspace member.
+    pub fn merge(&mut self, other: ModuleRoots) {
+        self.entries.extend(other.entries);
+    }
+
+    /// Resolves the import path, if it starts with the name of one of
+    /// the roots.
+    pub fn resolve(&self, import: &str) -> Option<PathBuf> {
+        let (name, rest) = import.split_once('/').unwrap_or((import, ""));
+        let entry = self.entries.get(name)?;
+
+        if rest.is_empty() {
+            return Some(entry.clone());
+        }
+
+        let mut path = entry.parent().unwrap_or(Path::new("")).join(rest);
+
+        if path.extension().is_none() {
+            path.set_extension("ry");
+        }
+
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{self, temp_directory};
+
+    const MANIFEST: &str = r#"{
+  "name": "hello",
+  "version": "0.1.0",
+  "authors": [
+    "Adi Salimgereyev"
+  ],
+  "license": "MIT",
+  "bin": {
+    "hello": "bin/main.ry",
+    "tool": "bin/tool.ry"
+  },
+  "dependencies": {
+    "json": "^1.2",
+    "utils": {
+      "path": "../utils",
+      "version": "^0.3"
+    }
+  }
+}
+"#;
+
+    #[test]
+    fn parse_test() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+
+        assert_eq!(manifest.name, "hello");
+        assert_eq!(manifest.version, Version::new(0, 1, 0));
+        assert_eq!(manifest.lib, Some(PathBuf::from("lib/lib.ry")));
+        assert_eq!(
+            manifest.bins.keys().collect::<Vec<_>>(),
+            vec!["hello", "tool"]
+        );
+        assert_eq!(
+            manifest.dependencies["json"],
+            Dependency {
+                version: Some(VersionReq::parse("^1.2").unwrap()),
+                path: None
+            }
+        );
+        assert_eq!(
+            manifest.dependencies["utils"].path,
+            Some(PathBuf::from("../utils"))
+        );
+
+        // Default library entry point is not written.
+        assert_eq!(
+            Manifest {
+                lib: None,
+                ..manifest
+            }
+            .to_json(),
+            MANIFEST
+        );
+        assert_eq!(
+            Manifest::parse(&Manifest::new("hello").to_json()),
+            Ok(Manifest {
+                lib: Some(PathBuf::from("lib/lib.ry")),
+                ..Manifest::new("hello")
+            })
+        );
+    }
+
+    fn errors(source: &str) -> Vec<(String, String)> {
+        test_utils::errors(source, Manifest::parse(source))
+    }
+
+    #[test]
+    fn errors_test() {
+        assert_eq!(
+            errors("{\n  \"name\": \"hello\",\n  \"version\": 1\n"),
+            vec![("".to_owned(), "manifest is not a valid JSON".to_owned())]
+        );
+        assert_eq!(
+            errors(
+                "{\"name\": \"Hello\", \"version\": \"1.0\", \"autors\": [],\
+                 \"lib\": \"/lib.ry\", \"bin\": {\"main\": 1},\
+                 \"dependencies\": {\"json\": \"=>1\", \"utils\": {}}}"
+            ),
+            vec![
+                (
+                    "\"Hello\"".to_owned(),
+                    "invalid package name `Hello`".to_owned()
+                ),
+                ("\"1.0\"".to_owned(), "invalid version `1.0`".to_owned()),
+                ("\"autors\"".to_owned(), "unknown field `autors`".to_owned()),
+                (
+                    "\"/lib.ry\"".to_owned(),
+                    "invalid entry point `/lib.ry`".to_owned()
+                ),
+                ("1".to_owned(), "invalid type of `bin.main`".to_owned()),
+                (
+                    "\"=>1\"".to_owned(),
+                    "invalid version requirement `=>1` of dependency `json`".to_owned()
+                ),
+                (
+                    "{}".to_owned(),
+                    "dependency `utils` specifies neither version, nor path".to_owned()
+                ),
+            ]
+        );
+        assert_eq!(
+            errors("{\"dependencies\": {\"my-json\": {\"path\": 1}}}"),
+            vec![
+                ("{".to_owned(), "missing field `name`".to_owned()),
+                ("{".to_owned(), "missing field `version`".to_owned()),
+                (
+                    "{\"path\": 1}".to_owned(),
+                    "invalid dependency name `my-json`".to_owned()
+                ),
+                (
+                    "1".to_owned(),
+                    "invalid type of `dependencies.my-json.path`".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn load_test() {
+        let temp = temp_directory(&[
+            ("hello/.lapis.json", MANIFEST),
+            ("hello/bin/main.ry", ""),
+            (
+                "utils/.lapis.json",
+                "{\"name\": \"utils\", \"version\": \"0.3.0\", \"lib\": \"src/utils.ry\"}",
+            ),
+        ]);
+        let root = temp.path();
+
+        let directory = root.join("hello");
+        let errors = Manifest::load(&directory, MANIFEST).unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![ManifestError::TargetNotFound(
+                locate(MANIFEST, &["bin", "tool"]).unwrap().value,
+                "bin/tool.ry".to_owned()
+            )]
+        );
+
+        let source = MANIFEST.replace(",\n    \"tool\": \"bin/tool.ry\"", "");
+        let manifest = Manifest::load(&directory, &source).unwrap();
+
+        // `lib/lib.ry` doesn't exist.
+        assert_eq!(manifest.lib, None);
+        assert_eq!(
+            find_root(&directory.join("bin/main.ry")),
+            Some(directory.as_path())
+        );
+
+        let mut roots = ModuleRoots::new(&directory, &manifest);
+
+        assert_eq!(
+            roots.resolve("utils/io"),
+            Some(directory.join("../utils/src/io.ry"))
+        );
+        assert_eq!(
+            roots.resolve("utils"),
+            Some(directory.join("../utils/src/utils.ry"))
+        );
+        assert_eq!(roots.resolve("hello"), None);
+        assert_eq!(roots.resolve("json"), None);
+
+        let json = root.join("registry/json/1.0.0");
+        roots.add_package("json", &json, &Manifest::new("json"));
+        assert_eq!(roots.resolve("json/value"), Some(json.join("lib/value.ry")));
+    }
+
+    #[test]
+    fn add_dependency_test() {
+        let json = Dependency {
+            version: Some(VersionReq::parse("^1.2.0").unwrap()),
+            path: None,
+        };
+
+        let source = "{\n  \"name\": \"hello\",\n  \"version\": \"0.1.0\"\n}\n";
+        let source = add_dependency(source, "json", &json).unwrap();
+        assert_eq!(
+            source,
+            "{\n  \"name\": \"hello\",\n  \"version\": \"0.1.0\",\n  \"dependencies\": {\n    \"json\": \"^1.2.0\"\n  }\n}\n"
+        );
+
+        let log = Dependency {
+            version: Some(VersionReq::parse("^0.3").unwrap()),
+            path: None,
+        };
+        let source = add_dependency(&source, "log", &log).unwrap();
+        assert_eq!(
+            Manifest::parse(&source).unwrap().dependencies,
+            BTreeMap::from([("json".to_owned(), json), ("log".to_owned(), log.clone())])
+        );
+
+        // Existing dependency is replaced.
+        let source = add_dependency(&source, "json", &log).unwrap();
+        assert!(source.contains("\"json\": \"^0.3\",\n    \"log\": \"^0.3\"\n"));
+
+        assert_eq!(
+            add_dependency("{\"name\": \"a\", \"dependencies\": {}}", "log", &log),
+            Some("{\"name\": \"a\", \"dependencies\": {\n    \"log\": \"^0.3\"\n  }}".to_owned())
+        );
+        assert_eq!(add_dependency("[]", "log", &log), None);
+    }
+}
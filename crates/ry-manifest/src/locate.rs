@@ -0,0 +1,170 @@
+//! `locate.rs` - finds locations of values in the manifest source.
+//!
+//! `serde_json` doesn't keep locations of parsed values, so to point
+//! diagnostics at the right place the (already validated) source is
+//! scanned again, following the path of object keys.
+use std::ops::Range;
+
+/// Location of the object member.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Location {
+    /// Location of the key (with quotes), empty for the root value.
+    pub key: Range<usize>,
+    pub value: Range<usize>,
+}
+
+/// Finds the member at the given path of object keys (empty path is
+/// the root value). Returns `None` if some of the keys is not found.
+pub(crate) fn locate(source: &str, path: &[&str]) -> Option<Location> {
+    let mut scanner = Scanner {
+        source: source.as_bytes(),
+        position: 0,
+    };
+
+    scanner.skip_whitespace();
+
+    let start = scanner.position;
+    let mut location = Location {
+        key: start..start,
+        value: scanner.value()?,
+    };
+
+    for &key in path {
+        location = scanner.member(location.value, key, source)?;
+    }
+
+    Some(location)
+}
+
+struct Scanner<'s> {
+    source: &'s [u8],
+    position: usize,
+}
+
+impl Scanner<'_> {
+    fn current(&self) -> Option<u8> {
+        self.source.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.current().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Option<()> {
+        self.skip_whitespace();
+
+        if self.current()? == expected {
+            self.position += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    /// Skips the string (the current character is the opening quote).
+    fn string(&mut self) -> Option<Range<usize>> {
+        let start = self.position;
+        self.position += 1;
+
+        loop {
+            match self.current()? {
+                b'"' => break,
+                b'\\' => self.position += 2,
+                _ => self.position += 1,
+            }
+        }
+
+        self.position += 1;
+        Some(start..self.position)
+    }
+
+    /// Skips the value and returns its location.
+    fn value(&mut self) -> Option<Range<usize>> {
+        self.skip_whitespace();
+        let start = self.position;
+
+        match self.current()? {
+            b'"' => return self.string(),
+            b'{' | b'[' => {
+                let mut depth = 0;
+
+                loop {
+                    match self.current()? {
+                        b'"' => {
+                            self.string()?;
+                            continue;
+                        }
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => depth -= 1,
+                        _ => {}
+                    }
+
+                    self.position += 1;
+
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+            _ => {
+                while self
+                    .current()
+                    .is_some_and(|c| !matches!(c, b',' | b'}' | b']') && !c.is_ascii_whitespace())
+                {
+                    self.position += 1;
+                }
+            }
+        }
+
+        Some(start..self.position)
+    }
+
+    /// Finds the member of the object located at `object`.
+    fn member(&mut self, object: Range<usize>, key: &str, source: &str) -> Option<Location> {
+        self.position = object.start;
+        self.expect(b'{')?;
+
+        loop {
+            self.skip_whitespace();
+
+            if self.current()? != b'"' {
+                return None;
+            }
+
+            let key_location = self.string()?;
+            self.expect(b':')?;
+            let value = self.value()?;
+
+            if serde_json::from_str::<String>(&source[key_location.clone()]).ok()? == key {
+                return Some(Location {
+                    key: key_location,
+                    value,
+                });
+            }
+
+            self.expect(b',')?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locate_test() {
+        let source = "{\n  \"a\": [1, {\"b\": 2}],\n  \"c\\\"\": {\"d\": \"}\", \"e\": true}\n}";
+        let text = |path: &[&str]| {
+            locate(source, path).map(|location| (&source[location.key], &source[location.value]))
+        };
+
+        assert_eq!(text(&["a"]), Some(("\"a\"", "[1, {\"b\": 2}]")));
+        assert_eq!(text(&["c\"", "e"]), Some(("\"e\"", "true")));
+        assert_eq!(text(&["c\"", "d"]), Some(("\"d\"", "\"}\"")));
+        assert_eq!(text(&["a", "b"]), None);
+        assert_eq!(text(&["f"]), None);
+        assert_eq!(text(&[]).map(|(_, value)| value), Some(source));
+    }
+}
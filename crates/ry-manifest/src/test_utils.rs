@@ -0,0 +1,36 @@
+//! `test_utils.rs` - helpers shared by tests of manifests.
+use std::{fmt::Debug, fs};
+
+use ry_report::Reporter;
+use tempfile::TempDir;
+
+use crate::error::ManifestError;
+
+/// Creates a temporary directory with the given files (paths are relative
+/// to the directory). It is removed, when the result is dropped.
+pub(crate) fn temp_directory(files: &[(&str, &str)]) -> TempDir {
+    let directory = tempfile::tempdir().unwrap();
+
+    for (path, contents) in files {
+        let path = directory.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    directory
+}
+
+/// Parts of the source located by the errors together with their messages.
+pub(crate) fn errors<T: Debug>(
+    source: &str,
+    result: Result<T, Vec<ManifestError>>,
+) -> Vec<(String, String)> {
+    result
+        .unwrap_err()
+        .iter()
+        .map(|error| {
+            let diagnostic = error.build_diagnostic(0);
+            (source[error.span().clone()].to_owned(), diagnostic.message)
+        })
+        .collect()
+}
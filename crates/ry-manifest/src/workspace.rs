@@ -0,0 +1,196 @@
+//! `workspace.rs` - manifest of the workspace.
+//!
+//! Workspace groups several packages, that share the lockfile and
+//! the `target` directory. Its `.lapis.json` contains only member
+//! directories, relative to the root of the workspace:
+//!
+//! ```json
+//! {
+//!   "workspace": {
+//!     "members": ["app", "libs/*"]
+//!   }
+//! }
+//! ```
+//!
+//! Member ending with `/*` includes every subdirectory with the manifest.
+//! Members depend on each other with path dependencies.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+
+use crate::{error::ManifestError, locate::locate, Validator, MANIFEST_FILE};
+
+const FIELDS: &[&str] = &["workspace"];
+const WORKSPACE_FIELDS: &[&str] = &["members"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceManifest {
+    /// Directories of member packages, relative to the root of
+    /// the workspace. Globs are expanded by [`WorkspaceManifest::load`].
+    pub members: Vec<PathBuf>,
+}
+
+/// Whether the manifest source describes the workspace, not a package.
+pub fn is_workspace(source: &str) -> bool {
+    serde_json::from_str::<Value>(source)
+        .is_ok_and(|value| value.get("workspace").is_some_and(Value::is_object))
+}
+
+impl WorkspaceManifest {
+    /// Parses and validates the workspace manifest.
+    pub fn parse(source: &str) -> Result<Self, Vec<ManifestError>> {
+        let value = serde_json::from_str::<Value>(source)
+            .map_err(|error| vec![crate::syntax_error(source, &error)])?;
+
+        let mut validator = Validator {
+            source,
+            errors: vec![],
+        };
+
+        match validator.workspace(&value) {
+            Some(workspace) if validator.errors.is_empty() => Ok(workspace),
+            _ => {
+                validator.errors.sort_by_key(|error| error.span().start);
+                Err(validator.errors)
+            }
+        }
+    }
+
+    /// Parses the manifest of the workspace in `directory`, expands globs
+    /// in members and checks, that every member contains the manifest.
+    pub fn load(directory: &Path, source: &str) -> Result<Self, Vec<ManifestError>> {
+        let workspace = Self::parse(source)?;
+
+        let span = locate(source, &["workspace", "members"])
+            .map(|location| location.value)
+            .unwrap_or_default();
+
+        let mut members = vec![];
+        let mut errors = vec![];
+
+        for member in workspace.members {
+            if member.file_name().is_some_and(|name| name == "*") {
+                let parent = member.parent().unwrap_or(Path::new(""));
+
+                let mut expanded = fs::read_dir(directory.join(parent))
+                    .map(|entries| {
+                        entries
+                            .filter_map(|entry| Some(entry.ok()?.file_name()))
+                            .map(|name| parent.join(name))
+                            .filter(|path| directory.join(path).join(MANIFEST_FILE).is_file())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                expanded.sort();
+
+                members.extend(expanded);
+            } else if directory.join(&member).join(MANIFEST_FILE).is_file() {
+                members.push(member);
+            } else {
+                errors.push(ManifestError::MemberNotFound(
+                    span.clone(),
+                    member.display().to_string(),
+                ));
+            }
+        }
+
+        members.dedup();
+
+        if errors.is_empty() {
+            Ok(Self { members })
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Whether the package in `package` directory is a member of
+    /// the workspace in `directory`.
+    pub fn contains(&self, directory: &Path, package: &Path) -> bool {
+        let Ok(package) = package.canonicalize() else {
+            return false;
+        };
+
+        self.members
+            .iter()
+            .any(|member| directory.join(member).canonicalize().ok().as_ref() == Some(&package))
+    }
+}
+
+impl Validator<'_> {
+    fn workspace(&mut self, value: &Value) -> Option<WorkspaceManifest> {
+        let object = self.object(value, &[], FIELDS)?;
+        let workspace = self.required(object, &[], "workspace")?;
+        let workspace = self.object(workspace, &["workspace"], WORKSPACE_FIELDS)?;
+
+        let path = ["workspace", "members"];
+
+        let members = match self.required(workspace, &["workspace"], "members")? {
+            Value::Array(members) if members.iter().all(Value::is_string) => members
+                .iter()
+                .filter_map(|member| member.as_str().map(PathBuf::from))
+                .collect(),
+            _ => {
+                self.invalid_type(&path, "array of strings");
+                return None;
+            }
+        };
+
+        Some(WorkspaceManifest { members })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn workspace_test() {
+        let root = env::temp_dir().join("ry-manifest-workspace-test");
+        let _ = fs::remove_dir_all(&root);
+
+        for member in ["app", "libs/json", "libs/log"] {
+            fs::create_dir_all(root.join(member)).unwrap();
+            fs::write(root.join(member).join(MANIFEST_FILE), "{}").unwrap();
+        }
+        fs::create_dir_all(root.join("libs/docs")).unwrap();
+
+        let source = "{\"workspace\": {\"members\": [\"app\", \"libs/*\"]}}";
+        assert!(is_workspace(source));
+        assert!(!is_workspace("{\"name\": \"app\", \"version\": \"0.1.0\"}"));
+
+        let workspace = WorkspaceManifest::load(&root, source).unwrap();
+        assert_eq!(
+            workspace.members,
+            [
+                PathBuf::from("app"),
+                PathBuf::from("libs/json"),
+                PathBuf::from("libs/log")
+            ]
+        );
+        assert!(workspace.contains(&root, &root.join("libs/../app")));
+        assert!(!workspace.contains(&root, &root.join("libs/docs")));
+
+        let source = "{\"workspace\": {\"members\": [\"app\", \"cli\"]}}";
+        assert_eq!(
+            WorkspaceManifest::load(&root, source),
+            Err(vec![ManifestError::MemberNotFound(
+                locate(source, &["workspace", "members"]).unwrap().value,
+                "cli".to_owned()
+            )])
+        );
+
+        assert_eq!(
+            WorkspaceManifest::parse("{\"workspace\": {\"members\": \"app\"}, \"name\": \"a\"}")
+                .unwrap_err()
+                .len(),
+            2
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{error::ParserError, macros::*, Parser, ParserResult};
+
+use num_traits::ToPrimitive;
+use ry_ast::*;
+use ry_ast::{
+    location::{Span, WithSpan},
+    precedence::Precedence,
+    token::RawToken,
+};
+
+impl<'c> Parser<'c> {
+    pub(crate) fn parse_const_declaration(
+        &mut self,
+        public: Option<Span>,
+    ) -> ParserResult<TopLevelStatement> {
+        self.advance()?; // 'const'
+
+        let (name, r#type, value) = self.parse_global_binding("constant")?;
+
+        Ok(TopLevelStatement::ConstDecl(ConstDecl {
+            public,
+            name,
+            r#type,
+            value,
+        }))
+    }
+
+    /// Parses `NAME [Type] = value;` part of constant or global variable
+    /// declaration (called `node_name`).
+    pub(crate) fn parse_global_binding(
+        &mut self,
+        node_name: &str,
+    ) -> ParserResult<(WithSpan<String>, Option<Type>, Expression)> {
+        let node = format!("{node_name} declaration");
+
+        check_token0!(
+            self,
+            format!("identifier for {node_name} name"),
+            RawToken::Identifier(_),
+            &node
+        )?;
+
+        let name = (
+            self.current.value.ident().unwrap(),
+            self.current.span.clone(),
+        )
+            .into();
+
+        self.advance()?; // 'name'
+
+        let mut r#type = None;
+
+        if !self.current.value.is(&RawToken::Assign) {
+            r#type = Some(self.parse_type()?);
+        }
+
+        check_token!(self, RawToken::Assign, &node)?;
+
+        self.advance()?; // '='
+
+        let value = self.parse_expression(Precedence::Lowest.to_i8().unwrap())?;
+
+        check_token!(self, RawToken::Semicolon, &node)?;
+
+        self.advance0()?; // ';'
+
+        Ok((name, r#type, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Parser;
+    use ry_ast::{RawExpression, RawType, TopLevelStatement};
+
+    #[test]
+    fn const_decl_test() {
+        let ast = Parser::new("pub const SIZE [u8; 2] = [1, 2];")
+            .parse()
+            .unwrap();
+
+        let TopLevelStatement::ConstDecl(decl) = &ast.top_level_statements[0].1 else {
+            panic!("expected constant declaration");
+        };
+
+        assert!(decl.public.is_some());
+        assert_eq!(decl.name.value, "SIZE");
+
+        let RawType::Array(_, Some(size)) = &*decl.r#type.as_ref().unwrap().value else {
+            panic!("expected sized array type");
+        };
+
+        assert_eq!(*size.value, RawExpression::Int(2, None));
+    }
+}
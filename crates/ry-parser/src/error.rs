@@ -1,8 +1,8 @@
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 
 use ry_ast::location::*;
-use ry_ast::token::{LexerError, RawToken, Token};
-use ry_report::Reporter;
+use ry_ast::token::{LexerError, NumberSuffix, RawToken, Token};
+use ry_report::{registry::*, Edit, Reporter, Suggestion};
 
 #[derive(Debug)]
 pub enum ParserError {
@@ -20,24 +20,39 @@ pub enum ParserError {
 
     /// Appears when you try to define trait method as public.
     /// 1-st [`Span`] is location of `pub` keyword.
-    /// 2-nd [`Span`] is location of method name.
+    /// 2-nd [`Span`] is location of `pub` keyword with whitespaces after it
+    /// (what has to be removed).
+    /// 3-rd [`Span`] is location of method name.
     /// [`bool`] is weather it is method declaration or definition.
-    UnnecessaryVisibilityQualifier(Span, Span, bool),
+    UnnecessaryVisibilityQualifier(Span, Span, Span, bool),
 
     /// Appears when `import` keyword is found after top level statement(-s)
-    /// [`Span`] here is location of `import` statement.
-    ImportAfterTopLevelStatement(Span),
+    /// 1-st [`Span`] here is location of `import` statement.
+    /// 2-nd [`Span`] is location of `import` statement with whitespaces before it
+    /// (what has to be removed).
+    /// [`Edit`] inserts the import at the end of imports list.
+    ImportAfterTopLevelStatement(Span, Span, Edit),
+
+    /// Appears when number literal doesn't fit into the type of its suffix.
+    /// [`Token`] is the literal, [`bool`] is weather it is negated
+    /// with unary `-`.
+    NumberLiteralOutOfRange(Token, bool),
 }
 
 impl<'source> Reporter<'source> for ParserError {
     fn build_diagnostic(&self, file_id: usize) -> Diagnostic<usize> {
         match self {
-            Self::ErrorToken(t) => Diagnostic::error()
-                .with_message("scanning error occured")
-                .with_code("E000")
-                .with_labels(vec![
-                    Label::primary(file_id, t.span.range.clone()).with_message(t.value.to_string())
-                ]),
+            Self::ErrorToken(t) => {
+                let (code, note) = lexer_error_code_and_note(&t.value);
+
+                Diagnostic::error()
+                    .with_message(code.description)
+                    .with_code(code.code)
+                    .with_labels(vec![
+                        Label::primary(file_id, t.span.range.clone()).with_message(t.value.to_string())
+                    ])
+                    .with_notes(note.map(|n| format!("note: {n}")).into_iter().collect())
+            }
             Self::UnexpectedToken(got, expected, node_name) => {
                 let mut label_message = format!("expected {expected}");
 
@@ -47,12 +62,12 @@ impl<'source> Reporter<'source> for ParserError {
 
                 Diagnostic::error()
                     .with_message(format!("unexpected {}", got.value))
-                    .with_code("E001")
+                    .with_code(E001.code)
                     .with_labels(vec![
                         Label::primary(file_id, got.span.range.clone()).with_message(label_message)
                     ])
             }
-            Self::UnnecessaryVisibilityQualifier(pub_span, method_name_span, declaration) => {
+            Self::UnnecessaryVisibilityQualifier(pub_span, _, method_name_span, declaration) => {
                 let declaration = if *declaration { "declaration" } else { "definition" };
                 Diagnostic::error()
                     .with_message(
@@ -64,7 +79,7 @@ impl<'source> Reporter<'source> for ParserError {
                         Label::secondary(file_id, method_name_span.range.clone())
                             .with_message(format!("in this method {}", declaration)),
                     ])
-                    .with_code("E002")
+                    .with_code(E002.code)
                     .with_notes(vec![
                         "note: by default, methods within a trait possess public visibility,\nthereby obviating the need for utilizing the `pub` keyword\nat the outset of their declarations.".to_owned()
                     ])
@@ -78,20 +93,221 @@ impl<'source> Reporter<'source> for ParserError {
 
                 Diagnostic::error()
                     .with_message(format!("expected {}, found {}", expected, got.value))
-                    .with_code("E001")
+                    .with_code(E001.code)
                     .with_labels(vec![
                         Label::primary(file_id, got.span.range.clone()).with_message(label_message)
                     ])
             }
-            Self::ImportAfterTopLevelStatement(name) => {
+            Self::ImportAfterTopLevelStatement(name, _, _) => {
                 Diagnostic::error()
                     .with_message("import statement is found after top level statement(-s)".to_owned())
-                    .with_code("E003")
+                    .with_code(E003.code)
                     .with_labels(vec![
                         Label::primary(file_id, name.range.clone()).with_message("this import statement must not be here")
                     ])
                     .with_notes(vec!["note: imports are placed at the beginning of source file, so consider placing it there".to_owned()])
             }
+            Self::NumberLiteralOutOfRange(literal, negative) => {
+                let suffix = match literal.value {
+                    RawToken::Int(_, Some(suffix)) | RawToken::Float(_, Some(suffix)) => suffix,
+                    _ => unreachable!("literal without suffix can't be out of range"),
+                };
+
+                let sign = if *negative { "-" } else { "" };
+
+                Diagnostic::error()
+                    .with_message(format!("number literal is out of range for `{suffix}`"))
+                    .with_code(E009.code)
+                    .with_labels(vec![
+                        Label::primary(file_id, literal.span.range.clone()).with_message(format!(
+                            "`{sign}{}` doesn't fit into `{suffix}`",
+                            literal.value.payload().unwrap()
+                        ))
+                    ])
+                    .with_notes(vec![format!("note: the range of `{suffix}` is `{}`", suffix_range(suffix))])
+            }
+        }
+    }
+    fn build_suggestions(&self) -> Vec<Suggestion> {
+        match self {
+            Self::UnnecessaryVisibilityQualifier(_, removal_span, _, _) => vec![Suggestion::new(
+                "remove `pub`",
+                vec![Edit::removal(removal_span.range.clone())],
+            )],
+            Self::ImportAfterTopLevelStatement(_, removal_span, insertion) => {
+                vec![Suggestion::new(
+                    "move import to the beginning of the file",
+                    vec![Edit::removal(removal_span.range.clone()), insertion.clone()],
+                )]
+            }
+            _ => vec![],
         }
     }
 }
+
+/// Returns range of values of the number type, for example: `0..=255`.
+fn suffix_range(suffix: NumberSuffix) -> String {
+    use NumberSuffix::*;
+
+    match suffix {
+        I8 => format!("{}..={}", i8::MIN, i8::MAX),
+        I16 => format!("{}..={}", i16::MIN, i16::MAX),
+        I32 => format!("{}..={}", i32::MIN, i32::MAX),
+        I64 => format!("{}..={}", i64::MIN, i64::MAX),
+        I128 => format!("{}..={}", i128::MIN, i128::MAX),
+        U8 => format!("0..={}", u8::MAX),
+        U16 => format!("0..={}", u16::MAX),
+        U32 => format!("0..={}", u32::MAX),
+        U64 => format!("0..={}", u64::MAX),
+        U128 => format!("0..={}", u128::MAX),
+        F32 => format!("{:e}..={:e}", f32::MIN, f32::MAX),
+        F64 => format!("{:e}..={:e}", f64::MIN, f64::MAX),
+    }
+}
+
+/// Returns error code for the lexer error and note explaining valid form
+/// of the malformed literal.
+fn lexer_error_code_and_note(error: &LexerError) -> (&'static ErrorCode, Option<&'static str>) {
+    use LexerError::*;
+
+    match error {
+        UnexpectedChar(_) => (&E000, None),
+        UnknownEscapeSequence | EmptyEscapeSequence => (
+            &E004,
+            Some("valid escape sequences are: `\\b`, `\\f`, `\\n`, `\\r`, `\\t`, `\\'`, `\\\"`, `\\\\`,\n`\\u{XXXX}` and `\\x{XX}`"),
+        ),
+        ExpectedOpenBracketInUnicodeEscapeSequence
+        | ExpectedCloseBracketInUnicodeEscapeSequence
+        | ExpectedDigitInUnicodeEscapeSequence => (
+            &E004,
+            Some("unicode escape sequence must have the form `\\u{XXXX}`,\nwhere `XXXX` are exactly four hexadecimal digits"),
+        ),
+        InvalidUnicodeEscapeSequence => (
+            &E004,
+            Some("`\\u{D800}`-`\\u{DFFF}` are surrogate code points and can't be used as characters"),
+        ),
+        ExpectedOpenBracketInByteEscapeSequence
+        | ExpectedCloseBracketInByteEscapeSequence
+        | ExpectedDigitInByteEscapeSequence
+        | InvalidByteEscapeSequence => (
+            &E004,
+            Some("byte escape sequence must have the form `\\x{XX}`,\nwhere `XX` are exactly two hexadecimal digits"),
+        ),
+        EmptyCharLiteral | UnterminatedCharLiteral | MoreThanOneCharInCharLiteral => (
+            &E006,
+            Some("character literal must contain exactly one character or escape sequence,\nfor example: `'a'` or `'\\n'` (use double quotes for strings)"),
+        ),
+        UnterminatedWrappedIdentifierLiteral | EmptyWrappedIdentifierLiteral => (
+            &E007,
+            Some("wrapped identifier must contain at least one letter, digit or `_`\nand must be closed with backtick on the same line, for example: `` `fun` ``"),
+        ),
+        UnterminatedStringLiteral => (
+            &E008,
+            Some("string literal must be closed on the same line, use `\\n` for line breaks"),
+        ),
+        InvalidRadixPoint => (
+            &E005,
+            Some("only decimal numbers can have fractional part"),
+        ),
+        HasNoDigits => (
+            &E005,
+            Some("number must have at least one digit after its prefix, for example: `0x1f`"),
+        ),
+        ExponentRequiresDecimalMantissa => (
+            &E005,
+            Some("only decimal numbers can have an exponent, for example: `1.5e3`"),
+        ),
+        ExponentHasNoDigits => (
+            &E005,
+            Some("exponent must have at least one digit, for example: `1e10`"),
+        ),
+        InvalidDigit => (
+            &E005,
+            Some("binary numbers (`0b`) can only contain digits `0`-`1`,\noctal numbers (`0o`) only `0`-`7`"),
+        ),
+        NumberParserError => (
+            &E005,
+            Some("integer literals must fit into 128 bits"),
+        ),
+        UnderscoreMustSeperateSuccessiveDigits => (
+            &E005,
+            Some("`_` can only be placed between digits, for example: `1_000_000`"),
+        ),
+        UnknownNumberSuffix => (
+            &E005,
+            Some("valid suffixes are: `i8`, `i16`, `i32`, `i64`, `i128`, `u8`, `u16`, `u32`, `u64`,\n`u128`, `f32`, `f64` and `i` for imaginary numbers"),
+        ),
+        IntegerSuffixOnFloatLiteral => (
+            &E005,
+            Some("float literals can only have `f32` or `f64` suffix"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Parser;
+    use ry_report::{apply_suggestions, Reporter};
+
+    fn fix(source: &str) -> String {
+        let mut parser = Parser::new(source);
+        let error = parser.parse().expect_err("source must be invalid");
+
+        apply_suggestions(source, &error.build_suggestions())
+    }
+
+    #[test]
+    fn unnecessary_visibility_qualifier_fix_test() {
+        assert_eq!(
+            fix("trait ToString {\n    pub fun to_string(self Self) string;\n}"),
+            "trait ToString {\n    fun to_string(self Self) string;\n}"
+        );
+    }
+
+    #[test]
+    fn import_after_top_level_statement_fix_test() {
+        assert_eq!(
+            fix("import \"test\";\n\npub fun test() {}\n\nimport \"test2\";\n"),
+            "import \"test\";\nimport \"test2\";\n\npub fun test() {}\n"
+        );
+    }
+
+    #[test]
+    fn import_after_top_level_statement_without_imports_fix_test() {
+        assert_eq!(
+            fix("//! module\n\n/// doc\npub fun test() {}\nimport \"a\\\"b\";"),
+            "//! module\n\nimport \"a\\\"b\";\n\n/// doc\npub fun test() {}"
+        );
+    }
+
+    fn parse_error_message(source: &str) -> Option<(String, String)> {
+        let error = Parser::new(source).parse().err()?;
+        let diagnostic = error.build_diagnostic(0);
+
+        Some((diagnostic.message, diagnostic.labels[0].message.clone()))
+    }
+
+    #[test]
+    fn number_literal_range_test() {
+        assert_eq!(
+            parse_error_message("fun a() { -128i8; 255u8; 1e38f32; }"),
+            None
+        );
+        assert_eq!(
+            parse_error_message("fun a() { 128i8; }"),
+            Some((
+                "number literal is out of range for `i8`".to_owned(),
+                "`128i8` doesn't fit into `i8`".to_owned()
+            ))
+        );
+        assert_eq!(
+            parse_error_message("fun a() { -129i8; }").map(|(_, label)| label),
+            Some("`-129i8` doesn't fit into `i8`".to_owned())
+        );
+        assert_eq!(
+            parse_error_message("fun a() { -1u8; }").map(|(_, label)| label),
+            Some("`-1u8` doesn't fit into `u8`".to_owned())
+        );
+        assert!(parse_error_message("fun a() { 1e39f32; }").is_some());
+    }
+}
@@ -1,10 +1,26 @@
 use crate::{error::ParserError, macros::*, Parser, ParserResult};
 
+use std::mem::take;
+
 use num_traits::ToPrimitive;
 use ry_ast::*;
-use ry_ast::{precedence::Precedence, token::RawToken};
+use ry_ast::{
+    precedence::Precedence,
+    token::{NumberSuffix, RawToken},
+};
 
 impl<'c> Parser<'c> {
+    /// Parses the expression, that takes the whole source.
+    pub fn parse_single_expression(&mut self) -> ParserResult<Expression> {
+        self.consume_local_docstring()?;
+
+        let expression = self.parse_expression(Precedence::Lowest.to_i8().unwrap())?;
+
+        check_token!(self, RawToken::EndOfFile, "end of expression")?;
+
+        Ok(expression)
+    }
+
     pub(crate) fn parse_expression(&mut self, precedence: i8) -> ParserResult<Expression> {
         let mut left = self.parse_prefix()?;
 
@@ -72,6 +88,8 @@ impl<'c> Parser<'c> {
         let start = left.span.range.start;
         self.advance()?; // left
 
+        self.negative_literal = left.value.is(&RawToken::Minus);
+
         let expr = self.parse_expression(Precedence::PrefixOrPostfix.to_i8().unwrap())?;
         let end = expr.span.range.end;
 
@@ -94,22 +112,52 @@ impl<'c> Parser<'c> {
     pub(crate) fn parse_prefix(&mut self) -> ParserResult<Expression> {
         self.check_scanning_error()?;
 
+        let negative = take(&mut self.negative_literal);
+
         match &self.current.value {
-            RawToken::Int(i) => {
-                let value = *i;
+            RawToken::Int(i, suffix) => {
+                let (value, suffix) = (*i, *suffix);
                 let span = self.current.span.clone();
 
+                if let Some(max) = suffix.and_then(NumberSuffix::max_int) {
+                    // `-128i8` is valid, while `128i8` is not, and `-1u8`
+                    // is never valid
+                    let signed = suffix.is_some_and(NumberSuffix::is_signed);
+
+                    if value > max && !(negative && signed && value == max + 1)
+                        || negative && !signed && value != 0
+                    {
+                        return Err(ParserError::NumberLiteralOutOfRange(
+                            self.current.clone(),
+                            negative,
+                        ));
+                    }
+                }
+
                 self.advance()?; // int
 
-                Ok((Box::new(RawExpression::Int(value)), span).into())
+                Ok((Box::new(RawExpression::Int(value, suffix)), span).into())
             }
-            RawToken::Float(f) => {
-                let value = *f;
+            RawToken::Float(f, suffix) => {
+                let (value, suffix) = (*f, *suffix);
                 let span = self.current.span.clone();
 
+                let out_of_range = match suffix {
+                    Some(NumberSuffix::F32) => value > f32::MAX as f64,
+                    Some(NumberSuffix::F64) => value.is_infinite(),
+                    _ => false,
+                };
+
+                if out_of_range {
+                    return Err(ParserError::NumberLiteralOutOfRange(
+                        self.current.clone(),
+                        negative,
+                    ));
+                }
+
                 self.advance()?; // float
 
-                Ok((Box::new(RawExpression::Float(value)), span).into())
+                Ok((Box::new(RawExpression::Float(value, suffix)), span).into())
             }
             RawToken::Imag(i) => {
                 let value = *i;
@@ -338,14 +386,19 @@ impl<'c> Parser<'c> {
 
         let generics = self.parse_type_generic_part()?;
 
-        let arguments = parse_list!(self, "generics for call", &RawToken::CloseParent, false, || self
-            .parse_expression(Precedence::Lowest.to_i8().unwrap()));
+        let arguments = parse_list!(
+            self,
+            "generics for call",
+            &RawToken::CloseParent,
+            false,
+            || self.parse_expression(Precedence::Lowest.to_i8().unwrap())
+        );
 
         let end = self.previous.as_ref().unwrap().span.range.end;
 
         Ok((
             Box::new(RawExpression::Call(
-                if let Some(v) = generics { v } else { vec![] },
+                generics.unwrap_or_default(),
                 left,
                 arguments,
             )),
@@ -42,24 +42,74 @@ impl<'c> Parser<'c> {
 
         let mut return_type = None;
 
-        if !self.current.value.is(&RawToken::OpenBrace) {
+        if !self.current.value.is(&RawToken::OpenBrace) && !self.current.value.is(&RawToken::Where)
+        {
             return_type = Some(self.parse_type()?);
         }
 
+        let where_clause = self.parse_where_clause()?;
+
         let stmts = self.parse_statements_block(true)?;
 
         Ok(TopLevelStatement::FunctionDecl(FunctionDecl {
             def: FunctionDef {
+                attributes: vec![],
                 name,
                 generic_annotations,
                 params: arguments,
                 public,
                 return_type,
+                where_clause,
             },
             stmts,
         }))
     }
 
+    /// Parses function declaration with attributes: `@test fun f() {}`.
+    pub(crate) fn parse_attributed_function_declaration(
+        &mut self,
+    ) -> ParserResult<TopLevelStatement> {
+        let mut attributes = vec![];
+
+        while self.current.value.is(&RawToken::AtSign) {
+            self.advance()?; // '@'
+
+            check_token0!(
+                self,
+                "identifier for attribute name",
+                RawToken::Identifier(_),
+                "attribute"
+            )?;
+
+            attributes.push(
+                (
+                    self.current.value.ident().unwrap(),
+                    self.current.span.clone(),
+                )
+                    .into(),
+            );
+
+            self.advance()?; // name
+        }
+
+        let mut public = None;
+
+        if self.current.value.is(&RawToken::Pub) {
+            public = Some(self.current.span.clone());
+            self.advance()?; // 'pub'
+        }
+
+        check_token!(self, RawToken::Fun, "function declaration after attributes")?;
+
+        let mut declaration = self.parse_function_declaration(public)?;
+
+        if let TopLevelStatement::FunctionDecl(function) = &mut declaration {
+            function.def.attributes = attributes;
+        }
+
+        Ok(declaration)
+    }
+
     pub(crate) fn parse_function_argument(&mut self) -> ParserResult<FunctionParam> {
         check_token0!(
             self,
@@ -92,3 +142,74 @@ impl<'c> Parser<'c> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::Parser;
+    use ry_ast::*;
+
+    fn display<T: ToString>(items: &[T]) -> Vec<String> {
+        items.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn constraints_test() {
+        let ast = Parser::new(
+            "fun sum<T Number + ToString, U>(a [T]) T where U Into<T>, [T] Clone {}\n\
+             impl<T> ToString for List<T> where T ToString {\n    fun to_string(self Self) string where T Clone;\n}",
+        )
+        .parse()
+        .unwrap();
+
+        let TopLevelStatement::FunctionDecl(function) = &ast.top_level_statements[0].1 else {
+            panic!("expected function declaration");
+        };
+
+        assert_eq!(
+            display(&function.def.generic_annotations),
+            ["T Number + ToString", "U"]
+        );
+        assert_eq!(
+            display(&function.def.where_clause),
+            ["U Into<T>", "[T] Clone"]
+        );
+        assert!(function.def.return_type.is_some());
+
+        let TopLevelStatement::Impl(r#impl) = &ast.top_level_statements[1].1 else {
+            panic!("expected implementation");
+        };
+
+        assert_eq!(display(&r#impl.where_clause), ["T ToString"]);
+        assert_eq!(display(&r#impl.methods[0].1.where_clause), ["T Clone"]);
+    }
+
+    #[test]
+    fn attributes_test() {
+        let ast = Parser::new("@test @slow\npub fun works() {}\n@test fun fails() {}")
+            .parse()
+            .unwrap();
+
+        let attributes = ast
+            .top_level_statements
+            .iter()
+            .map(|(_, statement)| match statement {
+                TopLevelStatement::FunctionDecl(function) => (
+                    function.def.public.is_some(),
+                    function
+                        .def
+                        .attributes
+                        .iter()
+                        .map(|attribute| attribute.value.as_str())
+                        .collect::<Vec<_>>(),
+                ),
+                _ => panic!("expected function declaration"),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            attributes,
+            [(true, vec!["test", "slow"]), (false, vec!["test"])]
+        );
+        assert!(Parser::new("@test struct A {}").parse().is_err());
+    }
+}
@@ -19,11 +19,13 @@ impl<'c> Parser<'c> {
             r#type = self.parse_type()?;
         }
 
+        let where_clause = self.parse_where_clause()?;
+
         check_token!(self, RawToken::OpenBrace, "type implementation")?;
 
         self.advance()?; // '{'
 
-        let methods = self.parse_trait_methods()?;
+        let (associated_types, methods) = self.parse_trait_items()?;
 
         check_token!(self, RawToken::CloseBrace, "type implementation")?;
 
@@ -33,6 +35,8 @@ impl<'c> Parser<'c> {
             global_generic_annotations: generic_annotations,
             r#type,
             r#trait,
+            where_clause,
+            associated_types,
             methods,
         }))
     }
@@ -2,6 +2,7 @@ use crate::{error::ParserError, macros::*, Parser, ParserResult};
 
 use ry_ast::token::RawToken;
 use ry_ast::*;
+use ry_report::Edit;
 
 impl<'c> Parser<'c> {
     /// TODO: fix the problem with comments and imports messed up
@@ -10,12 +11,24 @@ impl<'c> Parser<'c> {
 
         while self.current.value.is(&RawToken::Import) {
             imports.push(self.parse_import()?);
+            self.imports_end = Some(self.current.span.range.end);
             self.advance()?; // ';'
         }
 
         Ok(imports)
     }
 
+    /// Builds the edit, that inserts given import at the end of imports list
+    /// (or before the first top level statement if there are no imports).
+    pub(crate) fn import_insertion_edit(&self, import: &Import) -> Edit {
+        let import = format!("import \"{}\";", escape_string(&import.filename.value));
+
+        match self.imports_end {
+            Some(end) => Edit::insertion(end, format!("\n{import}")),
+            None => Edit::insertion(self.top_level_statements_start, format!("{import}\n\n")),
+        }
+    }
+
     pub(crate) fn parse_import(&mut self) -> ParserResult<Import> {
         self.advance()?; // import
 
@@ -35,6 +48,25 @@ impl<'c> Parser<'c> {
     }
 }
 
+/// Converts string back into Ry string literal contents.
+fn escape_string(value: &str) -> String {
+    let mut result = String::new();
+
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => result.push_str(&format!("\\u{{{:04x}}}", c as u32)),
+            c => result.push(c),
+        }
+    }
+
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use crate::Parser;
@@ -9,6 +9,7 @@ use crate::error::ParserError;
 
 pub mod error;
 
+mod const_decl;
 mod r#enum;
 mod expression;
 mod function_decl;
@@ -18,6 +19,8 @@ mod statement;
 mod struct_decl;
 mod trait_decl;
 mod r#type;
+mod type_alias;
+mod var_decl;
 
 #[macro_use]
 mod macros;
@@ -26,6 +29,13 @@ pub struct Parser<'c> {
     lexer: Lexer<'c>,
     previous: Option<Token>,
     current: Token,
+    /// Location of the first top level statement (or its docstring).
+    top_level_statements_start: usize,
+    /// Location of the end of the last import.
+    imports_end: Option<usize>,
+    /// Whether the next parsed literal is an operand of unary `-`
+    /// (affects range checking of signed integer literals).
+    negative_literal: bool,
 }
 
 pub(crate) type ParserResult<T> = Result<T, ParserError>;
@@ -40,6 +50,9 @@ impl<'c> Parser<'c> {
             lexer,
             previous: None,
             current,
+            top_level_statements_start: 0,
+            imports_end: None,
+            negative_literal: false,
         }
     }
 
@@ -73,16 +86,25 @@ impl<'c> Parser<'c> {
 
     pub(crate) fn consume_fst_docstring(&mut self) -> ParserResult<(String, String)> {
         let (mut module_docstring, mut local_docstring) = ("".to_owned(), "".to_owned());
+        let mut local_docstring_start = None;
+
         loop {
             if let RawToken::Comment(s) = &self.current.value {
                 if let Some(stripped) = s.strip_prefix('!') {
                     module_docstring.push_str(stripped.trim());
                     module_docstring.push('\n');
-                } else if let Some(stripped) = s.strip_prefix('/') {
-                    local_docstring.push_str(stripped.trim());
-                    local_docstring.push('\n');
+                } else {
+                    local_docstring_start.get_or_insert(self.current.span.range.start);
+
+                    if let Some(stripped) = s.strip_prefix('/') {
+                        local_docstring.push_str(stripped.trim());
+                        local_docstring.push('\n');
+                    }
                 }
             } else {
+                self.top_level_statements_start =
+                    local_docstring_start.unwrap_or(self.current.span.range.start);
+
                 module_docstring.pop();
                 local_docstring.pop();
                 return Ok((module_docstring, local_docstring));
@@ -134,6 +156,10 @@ impl<'c> Parser<'c> {
                     RawToken::Trait => self.parse_trait_declaration(None)?,
                     RawToken::Enum => self.parse_enum_declaration(None)?,
                     RawToken::Impl => self.parse_impl()?,
+                    RawToken::Const => self.parse_const_declaration(None)?,
+                    RawToken::Var => self.parse_global_var_declaration(None)?,
+                    RawToken::Type => self.parse_type_alias(None)?,
+                    RawToken::AtSign => self.parse_attributed_function_declaration()?,
                     RawToken::Pub => {
                         self.advance()?;
 
@@ -152,6 +178,15 @@ impl<'c> Parser<'c> {
                             RawToken::Enum => {
                                 self.parse_enum_declaration(Some(self.current.span.clone()))?
                             }
+                            RawToken::Const => {
+                                self.parse_const_declaration(Some(self.current.span.clone()))?
+                            }
+                            RawToken::Var => {
+                                self.parse_global_var_declaration(Some(self.current.span.clone()))?
+                            }
+                            RawToken::Type => {
+                                self.parse_type_alias(Some(self.current.span.clone()))?
+                            }
                             _ => {
                                 return Err(ParserError::UnexpectedToken(
                                     self.current.clone(),
@@ -163,14 +198,20 @@ impl<'c> Parser<'c> {
                     }
                     RawToken::Import => {
                         let start = self.current.span.range.start;
+                        let removal_start = self
+                            .previous
+                            .as_ref()
+                            .map_or(start, |previous| previous.span.range.end);
 
-                        self.parse_import()?;
+                        let import = self.parse_import()?;
 
                         let end = self.current.span.range.end;
                         self.advance()?; // ';'
 
                         return Err(ParserError::ImportAfterTopLevelStatement(
                             (start..end).into(),
+                            (removal_start..end).into(),
+                            self.import_insertion_edit(&import),
                         ));
                     }
                     RawToken::EndOfFile => break,
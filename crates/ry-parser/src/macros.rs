@@ -1,5 +1,5 @@
 macro_rules! check_token {
-    ($p: ident, $expected: expr, $expected_for: literal) => {
+    ($p: ident, $expected: expr, $expected_for: expr) => {
         if let RawToken::Invalid(e) = $p.current.value {
             Err(ParserError::ErrorToken((e, $p.current.span.clone()).into()))
         } else if !&$p.current.value.is(&$expected) {
@@ -8,6 +8,29 @@ use ry_ast::*;
 use ry_ast::{precedence::Precedence, token::RawToken};
 
 impl<'c> Parser<'c> {
+    /// Parses statements up to the end of the source (for example, input
+    /// of the REPL). Like in a block, only the last expression may be
+    /// without semicolon.
+    pub fn parse_statements(&mut self) -> ParserResult<Vec<Statement>> {
+        self.consume_local_docstring()?;
+
+        let mut stmts = vec![];
+
+        while !self.current.value.is(&RawToken::EndOfFile) {
+            let (stmt, last) = self.parse_statement()?;
+
+            stmts.push(stmt);
+
+            if last {
+                break;
+            }
+        }
+
+        check_token!(self, RawToken::EndOfFile, "end of statements")?;
+
+        Ok(stmts)
+    }
+
     pub(crate) fn parse_statements_block(
         &mut self,
         top_level: bool,
@@ -111,3 +134,28 @@ impl<'c> Parser<'c> {
         Ok((statement, last_statement_in_block))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::Parser;
+    use ry_ast::Statement;
+
+    #[test]
+    fn statements_test() {
+        let stmts = Parser::new("// comment\nvar a = 1;\nprintln(a);\na + 1")
+            .parse_statements()
+            .unwrap();
+
+        assert!(matches!(
+            &stmts[..],
+            [
+                Statement::Var(..),
+                Statement::Expression(_),
+                Statement::ExpressionWithoutSemicolon(_)
+            ]
+        ));
+
+        assert!(Parser::new("a + 1 b").parse_statements().is_err());
+        assert!(Parser::new("").parse_statements().unwrap().is_empty());
+    }
+}
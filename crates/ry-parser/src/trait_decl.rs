@@ -4,6 +4,9 @@ use ry_ast::location::WithSpan;
 use ry_ast::*;
 use ry_ast::{location::Span, token::RawToken};
 
+/// Associated types and methods of trait or implementation.
+type TraitItems = (Vec<(String, AssociatedType)>, Vec<(String, TraitMethod)>);
+
 impl<'c> Parser<'c> {
     pub(crate) fn parse_trait_declaration(
         &mut self,
@@ -28,11 +31,17 @@ impl<'c> Parser<'c> {
 
         let generic_annotations = self.parse_generic_annotations()?;
 
+        let mut supertraits = vec![];
+
+        if !self.current.value.is(&RawToken::OpenBrace) {
+            supertraits = self.parse_constraints()?;
+        }
+
         check_token!(self, RawToken::OpenBrace, "trait declaration")?;
 
         self.advance0()?; // '{'
 
-        let methods = self.parse_trait_methods()?;
+        let (associated_types, methods) = self.parse_trait_items()?;
 
         check_token!(self, RawToken::CloseBrace, "trait declaration")?;
 
@@ -42,11 +51,15 @@ impl<'c> Parser<'c> {
             public,
             generic_annotations,
             name,
+            supertraits,
+            associated_types,
             methods,
         }))
     }
 
-    pub(crate) fn parse_trait_methods(&mut self) -> ParserResult<Vec<(String, TraitMethod)>> {
+    /// Parses associated types and methods of trait or implementation.
+    pub(crate) fn parse_trait_items(&mut self) -> ParserResult<TraitItems> {
+        let mut associated_types = vec![];
         let mut definitions = vec![];
 
         let mut unnecessary_qualifier_error_span = None;
@@ -55,8 +68,20 @@ impl<'c> Parser<'c> {
             self.consume_local_docstring()?;
 
             if self.current.value.is(&RawToken::Pub) {
-                unnecessary_qualifier_error_span = Some(self.current.span.clone());
+                let pub_span = self.current.span.clone();
                 self.advance()?;
+
+                unnecessary_qualifier_error_span = Some((
+                    pub_span.clone(),
+                    Span::new(pub_span.range.start, self.current.span.range.start),
+                ));
+            }
+
+            if self.current.value.is(&RawToken::Type) && unnecessary_qualifier_error_span.is_none()
+            {
+                let associated_type = self.parse_associated_type()?;
+                associated_types.push((self.consume_local_docstring()?, associated_type));
+                continue;
             }
 
             let trait_def = self.parse_trait_method()?;
@@ -64,16 +89,62 @@ impl<'c> Parser<'c> {
             let name_span = trait_def.name.span.clone();
             definitions.push((self.consume_local_docstring()?, trait_def));
 
-            if let Some(s) = unnecessary_qualifier_error_span {
+            if let Some((pub_span, removal_span)) = unnecessary_qualifier_error_span {
                 return Err(ParserError::UnnecessaryVisibilityQualifier(
-                    s,
+                    pub_span,
+                    removal_span,
                     name_span,
                     declaration,
                 ));
             }
         }
 
-        Ok(definitions)
+        Ok((associated_types, definitions))
+    }
+
+    /// Parses `type Name [Constraints] [= Type];`.
+    fn parse_associated_type(&mut self) -> ParserResult<AssociatedType> {
+        self.advance()?; // 'type'
+
+        check_token0!(
+            self,
+            "identifier for associated type name",
+            RawToken::Identifier(_),
+            "associated type"
+        )?;
+
+        let name = (
+            self.current.value.ident().unwrap(),
+            self.current.span.clone(),
+        )
+            .into();
+
+        self.advance()?; // name
+
+        let mut constraints = vec![];
+
+        if !self.current.value.is(&RawToken::Assign) && !self.current.value.is(&RawToken::Semicolon)
+        {
+            constraints = self.parse_constraints()?;
+        }
+
+        let mut value = None;
+
+        if self.current.value.is(&RawToken::Assign) {
+            self.advance()?; // '='
+
+            value = Some(self.parse_type()?);
+        }
+
+        check_token!(self, RawToken::Semicolon, "associated type")?;
+
+        self.advance0()?; // ';'
+
+        Ok(AssociatedType {
+            name,
+            constraints,
+            value,
+        })
     }
 
     fn parse_trait_method(&mut self) -> ParserResult<TraitMethod> {
@@ -114,10 +185,13 @@ impl<'c> Parser<'c> {
 
         if !self.current.value.is(&RawToken::Semicolon)
             && !self.current.value.is(&RawToken::OpenBrace)
+            && !self.current.value.is(&RawToken::Where)
         {
             return_type = Some(self.parse_type()?);
         }
 
+        let where_clause = self.parse_where_clause()?;
+
         let mut body = None;
 
         match self.current.value {
@@ -139,7 +213,59 @@ impl<'c> Parser<'c> {
             generic_annotations,
             params: arguments,
             return_type,
+            where_clause,
             body,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::Parser;
+    use ry_ast::*;
+
+    #[test]
+    fn trait_items_test() {
+        let ast = Parser::new(
+            "trait Iterator Clone + Into<string> {\n    type Item ToString = i32;\n    fun next(self Self) Item?;\n}\n\
+             impl Iterator for Range {\n    type Item = u64;\n    fun next(self Self) u64? {}\n}",
+        )
+        .parse()
+        .unwrap();
+
+        let TopLevelStatement::TraitDecl(trait_decl) = &ast.top_level_statements[0].1 else {
+            panic!("expected trait declaration");
+        };
+
+        assert_eq!(
+            trait_decl
+                .supertraits
+                .iter()
+                .map(|supertrait| supertrait.value.to_string())
+                .collect::<Vec<_>>(),
+            ["Clone", "Into<string>"]
+        );
+
+        let item = &trait_decl.associated_types[0].1;
+        assert_eq!(item.name.value, "Item");
+        assert_eq!(item.constraints.len(), 1);
+        assert!(item.value.is_some());
+        assert_eq!(trait_decl.methods.len(), 1);
+
+        let TopLevelStatement::Impl(r#impl) = &ast.top_level_statements[1].1 else {
+            panic!("expected implementation");
+        };
+
+        assert_eq!(
+            r#impl.associated_types[0]
+                .1
+                .value
+                .as_ref()
+                .unwrap()
+                .value
+                .to_string(),
+            "u64"
+        );
+        assert_eq!(r#impl.methods.len(), 1);
+    }
+}
@@ -1,8 +1,10 @@
 use crate::{error::ParserError, macros::*, Parser, ParserResult};
 
+use num_traits::ToPrimitive;
 use ry_ast::*;
 use ry_ast::{
     location::{Span, WithSpan},
+    precedence::Precedence,
     token::*,
 };
 
@@ -62,7 +64,7 @@ impl<'c> Parser<'c> {
     }
 
     fn parse_primary_type(&mut self) -> ParserResult<Type> {
-        let start = self.current.span.range.end;
+        let start = self.current.span.range.start;
         let name = self.parse_name()?;
         let mut end = self.current.span.range.end;
         let generic_part = self.parse_type_generic_part()?;
@@ -72,14 +74,7 @@ impl<'c> Parser<'c> {
         }
 
         Ok(WithSpan::new(
-            Box::new(RawType::Primary(
-                name,
-                if let Some(v) = generic_part {
-                    v
-                } else {
-                    vec![]
-                },
-            )),
+            Box::new(RawType::Primary(name, generic_part.unwrap_or_default())),
             Span::new(start, end),
         ))
     }
@@ -107,6 +102,14 @@ impl<'c> Parser<'c> {
 
         let inner_type = self.parse_type()?;
 
+        let mut size = None;
+
+        if self.current.value.is(&RawToken::Semicolon) {
+            self.advance()?; // ';'
+
+            size = Some(self.parse_expression(Precedence::Lowest.to_i8().unwrap())?);
+        }
+
         check_token!(self, RawToken::CloseBracket, "array type")?;
 
         let end = self.current.span.range.end;
@@ -114,7 +117,7 @@ impl<'c> Parser<'c> {
         self.advance()?; // ']'
 
         Ok(WithSpan::new(
-            Box::new(RawType::Array(inner_type)),
+            Box::new(RawType::Array(inner_type, size)),
             Span::new(start, end),
         ))
     }
@@ -156,17 +159,17 @@ impl<'c> Parser<'c> {
                 "generic annotation"
             )?;
 
-            let generic = self.parse_generic()?;
+            let name = self.parse_generic()?;
 
-            let mut constraint = None;
+            let mut constraints = vec![];
 
             if !self.current.value.is(&RawToken::Comma)
                 && !self.current.value.is(&RawToken::GreaterThan)
             {
-                constraint = Some(self.parse_type()?);
+                constraints = self.parse_constraints()?;
             }
 
-            generics.push((generic, constraint));
+            generics.push(GenericAnnotation { name, constraints });
 
             if !self.current.value.is(&RawToken::Comma) {
                 check_token!(self, RawToken::GreaterThan, "generic annotations")?;
@@ -180,6 +183,47 @@ impl<'c> Parser<'c> {
         }
     }
 
+    /// Parses constraints separated by `+`, for example: `Number + ToString`.
+    pub(crate) fn parse_constraints(&mut self) -> ParserResult<Vec<Type>> {
+        let mut constraints = vec![self.parse_type()?];
+
+        while self.current.value.is(&RawToken::Plus) {
+            self.advance()?; // '+'
+
+            constraints.push(self.parse_type()?);
+        }
+
+        Ok(constraints)
+    }
+
+    /// Parses `where` clause, if it is present, for example:
+    /// `where T Number + ToString, [T] Into<string>`.
+    pub(crate) fn parse_where_clause(&mut self) -> ParserResult<WhereClause> {
+        let mut where_clause = vec![];
+
+        if !self.current.value.is(&RawToken::Where) {
+            return Ok(where_clause);
+        }
+
+        self.advance()?; // 'where'
+
+        loop {
+            let r#type = self.parse_type()?;
+            let constraints = self.parse_constraints()?;
+
+            where_clause.push(WhereClauseItem {
+                r#type,
+                constraints,
+            });
+
+            if !self.current.value.is(&RawToken::Comma) {
+                return Ok(where_clause);
+            }
+
+            self.advance()?; // ','
+        }
+    }
+
     pub fn parse_generic(&mut self) -> ParserResult<WithSpan<String>> {
         let start = self.current.span.range.start;
 
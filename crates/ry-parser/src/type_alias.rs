@@ -0,0 +1,118 @@
+use crate::{error::ParserError, macros::*, Parser, ParserResult};
+
+use ry_ast::*;
+use ry_ast::{location::Span, token::RawToken};
+
+impl<'c> Parser<'c> {
+    pub(crate) fn parse_type_alias(
+        &mut self,
+        public: Option<Span>,
+    ) -> ParserResult<TopLevelStatement> {
+        self.advance()?; // 'type'
+
+        check_token0!(
+            self,
+            "identifier for type alias name",
+            RawToken::Identifier(_),
+            "type alias"
+        )?;
+
+        let name = (
+            self.current.value.ident().unwrap(),
+            self.current.span.clone(),
+        )
+            .into();
+
+        self.advance()?; // 'name'
+
+        let generic_annotations = self.parse_generic_annotations()?;
+
+        // `type Meters f64;` (without `=`) declares a newtype.
+        let newtype = !self.current.value.is(&RawToken::Assign);
+
+        if !newtype {
+            self.advance()?; // '='
+        }
+
+        let value = self.parse_type()?;
+
+        check_token!(self, RawToken::Semicolon, "type alias")?;
+
+        self.advance0()?; // ';'
+
+        Ok(if newtype {
+            TopLevelStatement::Newtype(Newtype {
+                public,
+                name,
+                generic_annotations,
+                value,
+            })
+        } else {
+            TopLevelStatement::TypeAlias(TypeAlias {
+                public,
+                name,
+                generic_annotations,
+                value,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Parser;
+    use ry_ast::TopLevelStatement;
+
+    #[test]
+    fn type_alias_test() {
+        let ast = Parser::new("/// Rows by name.\npub type Table<T> = Map<string, [T?; 4]>;")
+            .parse()
+            .unwrap();
+
+        let (docstring, TopLevelStatement::TypeAlias(alias)) = &ast.top_level_statements[0] else {
+            panic!("expected type alias");
+        };
+
+        assert_eq!(docstring, "Rows by name.");
+        assert_eq!(
+            alias.to_string(),
+            "pub type Table<T> = Map<string, [T?; 4]>"
+        );
+        assert_eq!(alias.value.span.range, 38..58);
+    }
+
+    #[test]
+    fn docstring_after_type_alias_test() {
+        let ast = Parser::new("type A = i32;\n/// Meters.\ntype B f64;\n/// Size.\nconst C = 1;")
+            .parse()
+            .unwrap();
+
+        let docstrings = ast
+            .top_level_statements
+            .iter()
+            .map(|(docstring, _)| docstring.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(docstrings, ["", "Meters.", "Size."]);
+    }
+
+    #[test]
+    fn newtype_test() {
+        let ast = Parser::new(
+            "type Meters f64;
+pub type Id<T> [T];",
+        )
+        .parse()
+        .unwrap();
+
+        let [(_, TopLevelStatement::Newtype(meters)), (_, TopLevelStatement::Newtype(id))] =
+            &ast.top_level_statements[..]
+        else {
+            panic!("expected newtypes");
+        };
+
+        assert_eq!(meters.to_string(), "type Meters f64");
+        assert_eq!(id.to_string(), "pub type Id<T> [T]");
+        assert!(Parser::new("type Meters;").parse().is_err());
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{Parser, ParserResult};
+
+use ry_ast::location::Span;
+use ry_ast::*;
+
+impl<'c> Parser<'c> {
+    pub(crate) fn parse_global_var_declaration(
+        &mut self,
+        public: Option<Span>,
+    ) -> ParserResult<TopLevelStatement> {
+        self.advance()?; // 'var'
+
+        let (name, r#type, value) = self.parse_global_binding("global variable")?;
+
+        Ok(TopLevelStatement::VarDecl(VarDecl {
+            public,
+            name,
+            r#type,
+            value,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Parser;
+    use ry_ast::TopLevelStatement;
+
+    #[test]
+    fn global_var_decl_test() {
+        let ast = Parser::new("/// Number of calls.\npub var calls u64 = 0;\nvar name = \"ry\";")
+            .parse()
+            .unwrap();
+
+        let (docstring, TopLevelStatement::VarDecl(decl)) = &ast.top_level_statements[0] else {
+            panic!("expected global variable declaration");
+        };
+
+        assert_eq!(docstring, "Number of calls.");
+        assert!(decl.public.is_some());
+        assert_eq!(decl.name.value, "calls");
+        assert!(decl.r#type.is_some());
+
+        let (_, TopLevelStatement::VarDecl(decl)) = &ast.top_level_statements[1] else {
+            panic!("expected global variable declaration");
+        };
+
+        assert!(decl.public.is_none() && decl.r#type.is_none());
+    }
+}
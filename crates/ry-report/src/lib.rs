@@ -1,3 +1,7 @@
+pub mod registry;
+
+use std::ops::Range;
+
 use codespan_reporting::{
     diagnostic::Diagnostic,
     files::SimpleFiles,
@@ -37,6 +41,82 @@ impl Default for ReporterState<'_> {
     }
 }
 
+/// Single text replacement in the source file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl Edit {
+    pub fn new(range: Range<usize>, replacement: impl Into<String>) -> Self {
+        Self {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+
+    pub fn removal(range: Range<usize>) -> Self {
+        Self::new(range, "")
+    }
+
+    pub fn insertion(location: usize, text: impl Into<String>) -> Self {
+        Self::new(location..location, text)
+    }
+
+    fn conflicts_with(&self, other: &Edit) -> bool {
+        self.range.start == other.range.start
+            || (self.range.start < other.range.end && other.range.start < self.range.end)
+    }
+}
+
+/// Machine-applicable fix attached to a diagnostic: list of [`Edit`]s, that
+/// must be applied all together.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    pub message: String,
+    pub edits: Vec<Edit>,
+}
+
+impl Suggestion {
+    pub fn new(message: impl Into<String>, edits: Vec<Edit>) -> Self {
+        Self {
+            message: message.into(),
+            edits,
+        }
+    }
+}
+
+/// Applies suggestions to the source text.
+///
+/// Suggestion is skipped when any of its edits overlaps with edit of
+/// previously accepted suggestion, so the result never depends on
+/// the order in which conflicting edits would be applied.
+pub fn apply_suggestions(source: &str, suggestions: &[Suggestion]) -> String {
+    let mut accepted: Vec<&Edit> = vec![];
+
+    for suggestion in suggestions {
+        let conflicts = suggestion.edits.iter().enumerate().any(|(i, edit)| {
+            accepted.iter().any(|a| edit.conflicts_with(a))
+                || suggestion.edits[..i].iter().any(|e| edit.conflicts_with(e))
+        });
+
+        if !conflicts {
+            accepted.extend(suggestion.edits.iter());
+        }
+    }
+
+    accepted.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+
+    let mut result = source.to_owned();
+
+    for edit in accepted {
+        result.replace_range(edit.range.clone(), &edit.replacement);
+    }
+
+    result
+}
+
 pub trait Reporter<'source> {
     fn emit_diagnostic(
         &self,
@@ -54,4 +134,61 @@ pub trait Reporter<'source> {
     }
 
     fn build_diagnostic(&self, file_id: usize) -> Diagnostic<usize>;
+
+    /// Machine-applicable fixes for the reported problem (used by `ry fix`
+    /// and code actions in the language server).
+    fn build_suggestions(&self) -> Vec<Suggestion> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{apply_suggestions, Edit, Suggestion};
+
+    #[test]
+    fn apply_single_suggestion_test() {
+        let source = "pub fun test() {}";
+
+        assert_eq!(
+            apply_suggestions(
+                source,
+                &[Suggestion::new("remove `pub`", vec![Edit::removal(0..4)])]
+            ),
+            "fun test() {}"
+        );
+    }
+
+    #[test]
+    fn apply_move_suggestion_test() {
+        let source = "a;\nb;\nc;";
+
+        assert_eq!(
+            apply_suggestions(
+                source,
+                &[Suggestion::new(
+                    "move `c;`",
+                    vec![Edit::removal(5..8), Edit::insertion(0, "c;\n")]
+                )]
+            ),
+            "c;\na;\nb;"
+        );
+    }
+
+    #[test]
+    fn conflicting_suggestions_test() {
+        let source = "abcdef";
+
+        assert_eq!(
+            apply_suggestions(
+                source,
+                &[
+                    Suggestion::new("1", vec![Edit::new(1..3, "X")]),
+                    Suggestion::new("2", vec![Edit::new(2..4, "Y")]),
+                    Suggestion::new("3", vec![Edit::new(4..5, "Z")]),
+                ]
+            ),
+            "aXdZf"
+        );
+    }
 }
@@ -0,0 +1,127 @@
+//! `registry.rs` - defines registry of all error codes that Ry toolchain
+//! can emit, together with their long-form documentation.
+//!
+//! Documentation of each code lives in `errors/<CODE>/` directory:
+//! `explanation.md` and two examples: `erroneous.ry` (must produce the
+//! error) and `corrected.ry` (must not). Everything is compiled into the
+//! binary, so `ry explain <CODE>` works without any additional files.
+
+/// Error code with its documentation.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ErrorCode {
+    pub code: &'static str,
+    /// One-line description of the error.
+    pub description: &'static str,
+    pub explanation: &'static str,
+    pub erroneous_example: &'static str,
+    pub corrected_example: &'static str,
+}
+
+macro_rules! error_codes {
+    ($($code: ident: $description: literal,)*) => {
+        $(
+            pub const $code: ErrorCode = ErrorCode {
+                code: stringify!($code),
+                description: $description,
+                explanation: include_str!(concat!("../errors/", stringify!($code), "/explanation.md")),
+                erroneous_example: include_str!(concat!("../errors/", stringify!($code), "/erroneous.ry")),
+                corrected_example: include_str!(concat!("../errors/", stringify!($code), "/corrected.ry")),
+            };
+        )*
+
+        /// All registered error codes.
+        pub static REGISTRY: &[ErrorCode] = &[$($code),*];
+    };
+}
+
+error_codes! {
+    E000: "unexpected character",
+    E001: "unexpected token",
+    E002: "unnecessary visibility qualifier in trait method",
+    E003: "import after top level statement(-s)",
+    E004: "invalid escape sequence",
+    E005: "invalid number literal",
+    E006: "invalid character literal",
+    E007: "invalid wrapped identifier",
+    E008: "unterminated string literal",
+    E009: "number literal is out of range for its type",
+    E010: "arithmetic overflow in constant expression",
+    E011: "division by zero in constant expression",
+    E012: "expression is not constant",
+    E013: "invalid operation in constant expression",
+    E014: "cycle in constant declarations",
+    E015: "global variable is used before its initialization",
+    E016: "cycle in type aliases",
+    E017: "wrong number of generic arguments for type alias",
+    E018: "trait bound is not satisfied",
+    E019: "not all trait items are implemented",
+    E020: "item is not a member of trait",
+    E021: "method has incompatible signature with trait",
+    E022: "supertrait is not implemented",
+    E023: "conflicting trait implementations",
+    E024: "no method found for type",
+    E025: "multiple applicable methods",
+    E026: "name is defined multiple times",
+    E027: "unresolved import",
+}
+
+/// Finds registered error code (case insensitive).
+pub fn find(code: &str) -> Option<&'static ErrorCode> {
+    REGISTRY
+        .iter()
+        .find(|error_code| error_code.code.eq_ignore_ascii_case(code))
+}
+
+impl ErrorCode {
+    /// Renders long-form documentation of the error code, printed by `ry explain`.
+    pub fn render_explanation(&self) -> String {
+        format!(
+            "{}: {}\n\n{}\nErroneous code example:\n\n{}\nCorrected code example:\n\n{}",
+            self.code,
+            self.description,
+            self.explanation,
+            indent(self.erroneous_example),
+            indent(self.corrected_example)
+        )
+    }
+}
+
+fn indent(code: &str) -> String {
+    code.lines().map(|line| format!("    {line}\n")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry::{find, REGISTRY};
+
+    #[test]
+    fn codes_are_unique_and_sorted_test() {
+        for pair in REGISTRY.windows(2) {
+            assert!(
+                pair[0].code < pair[1].code,
+                "{} >= {}",
+                pair[0].code,
+                pair[1].code
+            );
+        }
+    }
+
+    #[test]
+    fn find_test() {
+        assert_eq!(find("e001").map(|c| c.code), Some("E001"));
+        assert_eq!(find("unknown"), None);
+    }
+
+    #[test]
+    fn codes_are_listed_in_errors_md_test() {
+        let errors_md = include_str!("../../ERRORS.md");
+
+        for error_code in REGISTRY {
+            assert!(
+                errors_md.contains(&format!("`{}`", error_code.code)),
+                "{} is not listed in crates/ERRORS.md",
+                error_code.code
+            );
+        }
+    }
+}
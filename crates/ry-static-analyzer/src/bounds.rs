@@ -0,0 +1,390 @@
+//! `bounds.rs` - checks trait bounds of generic parameters at call sites.
+//!
+//! Generic arguments are either given explicitly (`sum$(<i32> a, b)`) or
+//! inferred from types of the arguments, as far as [`crate::methods`] can
+//! tell them without a type checker: from declared types of variables and
+//! parameters, suffixed literals, struct literals and so on (`sum(1)` is
+//! not checked, because type of `1` is not known yet). Parameters, that
+//! are not inferred, are not checked, and neither is the `where` clause.
+//!
+//! Arguments are checked only against traits declared in the file, others
+//! are assumed to be implemented: the argument must have an implementation
+//! of the trait, or, if it is a generic parameter itself, have the trait
+//! among its own constraints (given in generic annotations or `where`
+//! clause) or their subtraits.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use ry_ast::{visit::*, *};
+
+use crate::{
+    error::AnalyzerError,
+    methods::{generic_arguments, infer_generic_arguments, GenericCalls},
+    types::{substitute, TypeAliases},
+};
+
+/// Checks that generic arguments of function calls satisfy constraints of
+/// the corresponding generic parameters. Returns errors found, sorted by
+/// location.
+pub fn check_trait_bounds(ast: &ProgramUnit) -> Vec<AnalyzerError> {
+    let mut checker = BoundsChecker {
+        traits: HashMap::new(),
+        impls: vec![],
+        functions: HashMap::new(),
+        aliases: TypeAliases::new(ast),
+        generic_calls: infer_generic_arguments(ast),
+        scopes: vec![],
+        errors: vec![],
+    };
+
+    for (_, statement) in &ast.top_level_statements {
+        match statement {
+            TopLevelStatement::TraitDecl(trait_decl) => {
+                checker
+                    .traits
+                    .entry(trait_decl.name.value.as_str())
+                    .or_insert(&trait_decl.supertraits);
+            }
+            TopLevelStatement::Impl(r#impl) if r#impl.r#trait.is_some() => {
+                checker.impls.push(r#impl);
+            }
+            TopLevelStatement::FunctionDecl(function) => {
+                checker
+                    .functions
+                    .entry(function.def.name.value.as_str())
+                    .or_insert(&function.def);
+            }
+            _ => {}
+        }
+    }
+
+    checker.visit_program_unit(ast);
+
+    checker.errors.sort_by_key(|error| error.span().range.start);
+    checker.errors
+}
+
+/// Traits, that generic parameters in scope are constrained with.
+type Bounds = HashMap<String, Vec<String>>;
+
+struct BoundsChecker<'a> {
+    /// Declared traits with their supertraits.
+    traits: HashMap<&'a str, &'a Vec<Type>>,
+    impls: Vec<&'a Impl>,
+    functions: HashMap<&'a str, &'a FunctionDef>,
+    aliases: TypeAliases,
+    /// Inferred generic arguments of calls without explicit ones.
+    generic_calls: GenericCalls,
+    scopes: Vec<Bounds>,
+    errors: Vec<AnalyzerError>,
+}
+
+/// Name of the trait (or type) without generic arguments.
+pub(crate) fn head(r#type: &Type) -> Option<&str> {
+    match &*r#type.value {
+        RawType::Primary(name, _) => Some(&name.value),
+        _ => None,
+    }
+}
+
+pub(crate) fn bounds(annotations: &GenericAnnotations, where_clause: &WhereClause) -> Bounds {
+    let mut bounds = Bounds::new();
+
+    for annotation in annotations {
+        bounds
+            .entry(annotation.name.value.clone())
+            .or_default()
+            .extend(
+                annotation
+                    .constraints
+                    .iter()
+                    .filter_map(head)
+                    .map(str::to_owned),
+            );
+    }
+
+    for item in where_clause {
+        if let RawType::Primary(name, arguments) = &*item.r#type.value {
+            if arguments.is_empty() {
+                bounds
+                    .entry(name.value.clone())
+                    .or_default()
+                    .extend(item.constraints.iter().filter_map(head).map(str::to_owned));
+            }
+        }
+    }
+
+    bounds
+}
+
+/// Whether the type of implementation can be the given type. Generic
+/// parameters of the implementation match any type.
+pub(crate) fn matches(impl_type: &Type, r#type: &Type, params: &GenericAnnotations) -> bool {
+    match (&*impl_type.value, &*r#type.value) {
+        (RawType::Primary(name, arguments), _)
+            if arguments.is_empty() && params.iter().any(|p| p.name.value == name.value) =>
+        {
+            true
+        }
+        (RawType::Primary(a, _), RawType::Primary(b, _)) => a.value == b.value,
+        (RawType::Array(a, _), RawType::Array(b, _))
+        | (RawType::Pointer(a), RawType::Pointer(b))
+        | (RawType::Option(a), RawType::Option(b)) => matches(a, b, params),
+        _ => false,
+    }
+}
+
+impl BoundsChecker<'_> {
+    fn with_scope<F>(&mut self, bounds: Bounds, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        self.scopes.push(bounds);
+        f(self);
+        self.scopes.pop();
+    }
+
+    /// Whether the type implements the trait. Traits, that are not
+    /// declared in the file, are assumed to be implemented.
+    fn implements(&self, r#type: &Type, r#trait: &str) -> bool {
+        if !self.traits.contains_key(r#trait) {
+            return true;
+        }
+
+        let Ok(r#type) = self.aliases.resolve(r#type) else {
+            return true;
+        };
+
+        if let RawType::Primary(name, arguments) = &*r#type.value {
+            if let Some(bounds) = self
+                .scopes
+                .iter()
+                .rev()
+                .find_map(|scope| scope.get(&name.value))
+                .filter(|_| arguments.is_empty())
+            {
+                return bounds
+                    .iter()
+                    .any(|bound| self.inherits(bound, r#trait, &mut vec![]));
+            }
+        }
+
+        self.impls.iter().any(|r#impl| {
+            r#impl.r#trait.as_ref().and_then(head) == Some(r#trait)
+                && matches(&r#impl.r#type, &r#type, &r#impl.global_generic_annotations)
+        })
+    }
+
+    /// Whether the trait is the given one or inherits it (directly or
+    /// through other supertraits).
+    fn inherits<'t>(
+        &'t self,
+        r#trait: &'t str,
+        ancestor: &str,
+        visited: &mut Vec<&'t str>,
+    ) -> bool {
+        if r#trait == ancestor {
+            return true;
+        }
+
+        if visited.contains(&r#trait) {
+            return false;
+        }
+
+        visited.push(r#trait);
+
+        self.traits.get(r#trait).is_some_and(|supertraits| {
+            supertraits
+                .iter()
+                .filter_map(head)
+                .any(|supertrait| self.inherits(supertrait, ancestor, visited))
+        })
+    }
+
+    /// Checks constraints of `function` with generic parameters replaced
+    /// with `arguments`. Unsatisfied constraint is reported at the generic
+    /// argument, if it is given explicitly, otherwise at the call.
+    fn check_call<K>(
+        &mut self,
+        function: &FunctionDef,
+        arguments: &HashMap<K, Type>,
+        explicit: bool,
+        call: &Expression,
+    ) where
+        K: Borrow<str> + Eq + Hash,
+    {
+        let mut requirements = vec![];
+
+        for annotation in &function.generic_annotations {
+            let Some(argument) = arguments.get(annotation.name.value.as_str()) else {
+                continue;
+            };
+
+            let span = explicit.then(|| argument.span.clone());
+
+            for constraint in &annotation.constraints {
+                requirements.push((argument.clone(), constraint, span.clone()));
+            }
+        }
+
+        // `where` clause can mention any of the parameters.
+        if arguments.len() == function.generic_annotations.len() {
+            for item in &function.where_clause {
+                let r#type = substitute(&item.r#type, arguments);
+
+                for constraint in &item.constraints {
+                    requirements.push((r#type.clone(), constraint, None));
+                }
+            }
+        }
+
+        for (r#type, constraint, span) in requirements {
+            let Some(r#trait) = head(constraint) else {
+                continue;
+            };
+
+            if !self.implements(&r#type, r#trait) {
+                self.errors.push(AnalyzerError::UnsatisfiedTraitBound(
+                    span.unwrap_or_else(|| call.span.clone()),
+                    r#type.value.to_string(),
+                    r#trait.to_owned(),
+                    constraint.span.clone(),
+                    function.name.value.clone(),
+                ));
+            }
+        }
+    }
+}
+
+impl Visitor for BoundsChecker<'_> {
+    fn visit_function_decl(&mut self, function_decl: &FunctionDecl) {
+        let bounds = bounds(
+            &function_decl.def.generic_annotations,
+            &function_decl.def.where_clause,
+        );
+
+        self.with_scope(bounds, |s| walk_function_decl(s, function_decl));
+    }
+
+    fn visit_trait_decl(&mut self, trait_decl: &TraitDecl) {
+        let mut bounds = bounds(&trait_decl.generic_annotations, &vec![]);
+        // `Self` implements the trait and (through it) its supertraits.
+        bounds.insert("Self".to_owned(), vec![trait_decl.name.value.clone()]);
+
+        self.with_scope(bounds, |s| walk_trait_decl(s, trait_decl));
+    }
+
+    fn visit_impl(&mut self, r#impl: &Impl) {
+        let bounds = bounds(&r#impl.global_generic_annotations, &r#impl.where_clause);
+
+        self.with_scope(bounds, |s| walk_impl(s, r#impl));
+    }
+
+    fn visit_trait_method(&mut self, method: &TraitMethod) {
+        let bounds = bounds(&method.generic_annotations, &method.where_clause);
+
+        self.with_scope(bounds, |s| walk_trait_method(s, method));
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        if let RawExpression::Call(generics, caller, _) = &*expression.value {
+            if let RawExpression::StaticName(name) = &*caller.value {
+                if let Some(&function) = self.functions.get(name.as_str()) {
+                    if !generics.is_empty() {
+                        let arguments = generic_arguments(function, generics, &[]);
+                        self.check_call(function, &arguments, true, expression);
+                    } else if let Some(arguments) =
+                        self.generic_calls.get(&expression.span.range).cloned()
+                    {
+                        self.check_call(function, &arguments, false, expression);
+                    }
+                }
+            }
+        }
+
+        walk_expression(self, expression);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    fn errors(source: &str) -> Vec<(String, String)> {
+        test_utils::errors(source, |ast| check_trait_bounds(ast))
+    }
+
+    #[test]
+    fn bounds_test() {
+        let prelude = "trait Show {}\ntrait Number Show {}\nimpl Number for i32 {}\n\
+                       impl<T> Show for [T] {}\n\
+                       fun sum<T Number + Show>(a T) T where [T] Show { a; }\n";
+
+        assert_eq!(
+            errors(&format!(
+                "{prelude}fun f<U>(a U) where U Show + Number {{ sum$(<U> a); }}"
+            )),
+            vec![]
+        );
+        assert_eq!(
+            errors(&format!(
+                "{prelude}fun f() {{ sum$(<i32> 1); sum$(<[i32]> []); }}"
+            )),
+            vec![
+                (
+                    "E018".to_owned(),
+                    "type `i32` doesn't implement trait `Show`".to_owned()
+                ),
+                (
+                    "E018".to_owned(),
+                    "type `[i32]` doesn't implement trait `Number`".to_owned()
+                )
+            ]
+        );
+        assert_eq!(
+            errors(&format!("{prelude}fun f<U Show>(a U) {{ sum$(<U> a); }}")),
+            vec![(
+                "E018".to_owned(),
+                "type `U` doesn't implement trait `Number`".to_owned()
+            )]
+        );
+        // `Number` inherits `Show`.
+        assert_eq!(
+            errors(&format!("{prelude}fun f<U Number>(a U) {{ sum$(<U> a); }}")),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn inferred_test() {
+        let prelude = "trait Show {}\ntrait Number {}\nimpl Number for i32 {}\n\
+                       impl Show for i32 {}\nimpl<T> Show for [T] {}\n\
+                       fun sum<T Number>(a T) T where [T] Show { a; }\n\
+                       fun first<T Number, U Show>(a [T], b U?) {}\n";
+
+        let errors = |source: &str| {
+            errors(&format!("{prelude}{source}"))
+                .into_iter()
+                .map(|(_, message)| message)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            errors("fun f(n i32, s string, xs [string]) { sum(n); sum(s); sum(1); first(xs, n); }"),
+            vec![
+                "type `string` doesn't implement trait `Number`",
+                "type `string` doesn't implement trait `Number`",
+            ]
+        );
+        // `U` can't be inferred from `none`, `where` clause is checked with
+        // all the parameters.
+        assert_eq!(
+            errors("type Bytes = [i32];\nfun f(b Bytes) { var c = b; first(c, none); sum(c[0]); }"),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            errors("trait Other {}\nfun g<V>(a V) where [V] Other {}\nfun f() { g(true); }"),
+            vec!["type `[bool]` doesn't implement trait `Other`"]
+        );
+    }
+}
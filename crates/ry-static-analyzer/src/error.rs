@@ -0,0 +1,337 @@
+//! `error.rs` - defines errors emitted by the static analyzer.
+use codespan_reporting::diagnostic::{Diagnostic, Label};
+
+use ry_ast::{location::Span, token::NumberSuffix};
+use ry_report::{registry::*, Reporter};
+
+#[derive(Debug, PartialEq)]
+pub enum AnalyzerError {
+    /// Result of the constant expression doesn't fit into its type.
+    /// [`String`] is the computed expression (with evaluated operands),
+    /// [`Option<NumberSuffix>`] is the type (`None` for untyped integers,
+    /// which are limited to 128 bits).
+    Overflow(Span, String, Option<NumberSuffix>),
+
+    /// Division (or remainder) by zero in constant expression.
+    /// [`String`] is the computed expression.
+    DivisionByZero(Span, String),
+
+    /// Expression must be evaluated at compile time, but it is not constant.
+    /// [`&str`] describes where the expression is used, for example:
+    /// `"array size"`.
+    NotConstant(Span, &'static str),
+
+    /// Operation can't be applied to the given operands, for example:
+    /// `true + 1` or `-A` with `A u8`. [`String`] is the explanation.
+    InvalidOperation(Span, String),
+
+    /// Operands have different types, or the value doesn't match
+    /// the declared type of constant. 1-st [`String`] is the expected type
+    /// description (quoted, possibly with the value of type alias), 2-nd is
+    /// the found type.
+    MismatchedTypes(Span, String, String),
+
+    /// Constant depends on itself. [`Span`] is the location of the name of
+    /// the first constant in the cycle, [`Vec<String>`] are names of
+    /// constants in the cycle.
+    ConstantCycle(Span, Vec<String>),
+
+    /// Global variable [`String`] is used in initializer of another global
+    /// variable before it is initialized. 1-st [`Span`] is the location of
+    /// usage, 2-nd one is the location of declaration (if it is in the same
+    /// file). 2-nd [`String`] is the function, which reads the variable, if
+    /// it is not read by the initializer directly.
+    GlobalUsedBeforeInitialization(Span, String, Option<Span>, Option<String>),
+
+    /// Type alias refers to itself. [`Span`] is the location of the name of
+    /// the first alias in the cycle, [`Vec<String>`] are names of aliases
+    /// in the cycle.
+    TypeAliasCycle(Span, Vec<String>),
+
+    /// Type alias [`String`] is used with wrong number of generic arguments.
+    /// 1-st [`usize`] is the expected number, 2-nd is the given one.
+    TypeAliasArity(Span, String, usize, usize),
+
+    /// Generic argument doesn't satisfy the constraint of the generic
+    /// parameter. 1-st [`Span`] is the location of the argument (or of
+    /// the call), 2-nd one is the location of the constraint. [`String`]s
+    /// are the argument type, the trait and the called function.
+    UnsatisfiedTraitBound(Span, String, String, Span, String),
+
+    /// Implementation doesn't define required items of the trait [`String`].
+    /// [`Span`] is the location of the trait in the implementation,
+    /// [`Vec<String>`] are names of missing methods and associated types.
+    MissingTraitItems(Span, String, Vec<String>),
+
+    /// Item of the implementation is not a member of the trait. [`&str`] is
+    /// the kind of the item (`"method"` or `"associated type"`), 1-st
+    /// [`String`] is its name, 2-nd is the trait.
+    NotTraitMember(Span, &'static str, String, String),
+
+    /// Method (1-st [`String`]) of the implementation has different
+    /// signature, than in the trait (2-nd [`String`]). 3-rd and 4-th
+    /// [`String`]s are the expected and found signatures.
+    IncompatibleMethod(Span, String, String, String, String),
+
+    /// Type [`String`] implements the trait (2-nd [`String`]), but not its
+    /// supertrait (3-rd one). [`Span`] is the location of the type in
+    /// the implementation.
+    SupertraitNotImplemented(Span, String, String, String),
+
+    /// Trait (1-st [`String`]) is implemented for the type (2-nd one) more
+    /// than once. 1-st [`Span`] is the location of the type in the second
+    /// implementation, 2-nd one - in the first.
+    ConflictingImplementations(Span, String, String, Span),
+
+    /// Method (1-st [`String`]) is not found for the type of receiver
+    /// (2-nd [`String`]). [`Option<String>`] is the note, which explains
+    /// why.
+    NoMethod(Span, String, String, Option<String>),
+
+    /// Method [`String`] is defined in several traits implemented for
+    /// the type of receiver. [`Vec<String>`] are the traits.
+    AmbiguousMethod(Span, String, Vec<String>),
+
+    /// Name [`String`] is defined more than once in the namespace [`&str`]
+    /// (`"type"` or `"value"`) of the file. 1-st [`Span`] is the location of
+    /// the redefinition, 2-nd one - of the first definition.
+    Redefinition(Span, String, &'static str, Span),
+
+    /// Imported file can't be read. [`Span`] is the location of the file
+    /// name in the import, [`String`]s are the name and the resolved path.
+    UnresolvedImport(Span, String, String),
+}
+
+impl AnalyzerError {
+    pub fn span(&self) -> &Span {
+        match self {
+            Self::Overflow(span, ..)
+            | Self::DivisionByZero(span, _)
+            | Self::NotConstant(span, _)
+            | Self::InvalidOperation(span, _)
+            | Self::MismatchedTypes(span, ..)
+            | Self::ConstantCycle(span, _)
+            | Self::GlobalUsedBeforeInitialization(span, ..)
+            | Self::TypeAliasCycle(span, _)
+            | Self::TypeAliasArity(span, ..)
+            | Self::UnsatisfiedTraitBound(span, ..)
+            | Self::MissingTraitItems(span, ..)
+            | Self::NotTraitMember(span, ..)
+            | Self::IncompatibleMethod(span, ..)
+            | Self::SupertraitNotImplemented(span, ..)
+            | Self::ConflictingImplementations(span, ..)
+            | Self::NoMethod(span, ..)
+            | Self::AmbiguousMethod(span, ..)
+            | Self::Redefinition(span, ..)
+            | Self::UnresolvedImport(span, ..) => span,
+        }
+    }
+}
+
+impl<'source> Reporter<'source> for AnalyzerError {
+    fn build_diagnostic(&self, file_id: usize) -> Diagnostic<usize> {
+        match self {
+            Self::Overflow(span, expression, r#type) => {
+                let label = match r#type {
+                    Some(r#type) => format!("attempt to compute `{expression}`, which overflows `{type}`"),
+                    None => format!("attempt to compute `{expression}`, which doesn't fit into 128 bits"),
+                };
+
+                Diagnostic::error()
+                    .with_message("this arithmetic operation will overflow")
+                    .with_code(E010.code)
+                    .with_labels(vec![Label::primary(file_id, span.range.clone()).with_message(label)])
+            }
+            Self::DivisionByZero(span, expression) => Diagnostic::error()
+                .with_message("this operation will panic at runtime")
+                .with_code(E011.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone())
+                        .with_message(format!("attempt to compute `{expression}`, which divides by zero"))
+                ]),
+            Self::NotConstant(span, context) => Diagnostic::error()
+                .with_message(format!("{context} is not a constant expression"))
+                .with_code(E012.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone())
+                        .with_message("this can't be evaluated at compile time")
+                ])
+                .with_notes(vec![format!(
+                    "note: {context} must be known at compile time, so it can only contain literals,\nconstants, operators and `as` casts"
+                )]),
+            Self::InvalidOperation(span, message) => Diagnostic::error()
+                .with_message(message)
+                .with_code(E013.code)
+                .with_labels(vec![Label::primary(file_id, span.range.clone())]),
+            Self::MismatchedTypes(span, expected, found) => Diagnostic::error()
+                .with_message("mismatched types")
+                .with_code(E013.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone())
+                        .with_message(format!("expected {expected}, found `{found}`"))
+                ])
+                .with_notes(vec!["note: use `as` to convert between number types".to_owned()]),
+            Self::ConstantCycle(span, cycle) => Diagnostic::error()
+                .with_message(format!("cycle detected when evaluating constant `{}`", cycle[0]))
+                .with_code(E014.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone())
+                        .with_message("this constant depends on itself")
+                ])
+                .with_notes(vec![format!("note: {}", cycle_path(cycle))]),
+            Self::GlobalUsedBeforeInitialization(span, name, declaration, function) => {
+                let label = match function {
+                    Some(function) => format!("`{function}` reads `{name}`, which is not initialized yet"),
+                    None => format!("`{name}` is not initialized yet"),
+                };
+                let mut labels = vec![Label::primary(file_id, span.range.clone()).with_message(label)];
+
+                if let Some(declaration) = declaration {
+                    labels.push(
+                        Label::secondary(file_id, declaration.range.clone())
+                            .with_message(format!("`{name}` is declared here")),
+                    );
+                }
+
+                Diagnostic::error()
+                    .with_message(format!("global variable `{name}` is used before its initialization"))
+                    .with_code(E015.code)
+                    .with_labels(labels)
+                    .with_notes(vec!["note: global variables are initialized in order of declaration,\nafter global variables of imported files".to_owned()])
+            }
+            Self::TypeAliasCycle(span, cycle) => Diagnostic::error()
+                .with_message(format!("cycle detected when expanding type alias `{}`", cycle[0]))
+                .with_code(E016.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone())
+                        .with_message("this type alias refers to itself")
+                ])
+                .with_notes(vec![
+                    format!("note: {}", cycle_path(cycle)),
+                    "note: use struct to define recursive type".to_owned(),
+                ]),
+            Self::TypeAliasArity(span, name, expected, found) => Diagnostic::error()
+                .with_message(format!(
+                    "type alias `{name}` takes {expected} generic argument{}, but {found} {} given",
+                    if *expected == 1 { "" } else { "s" },
+                    if *found == 1 { "was" } else { "were" },
+                ))
+                .with_code(E017.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone())
+                        .with_message(format!("expected {expected} generic argument{}", if *expected == 1 { "" } else { "s" }))
+                ]),
+            Self::UnsatisfiedTraitBound(span, r#type, r#trait, bound, function) => Diagnostic::error()
+                .with_message(format!("type `{type}` doesn't implement trait `{trait}`"))
+                .with_code(E018.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone())
+                        .with_message(format!("the trait `{trait}` is not implemented for `{type}`")),
+                    Label::secondary(file_id, bound.range.clone())
+                        .with_message(format!("required by this bound in `{function}`")),
+                ]),
+            Self::MissingTraitItems(span, r#trait, missing) => Diagnostic::error()
+                .with_message(format!("not all items of trait `{trait}` are implemented"))
+                .with_code(E019.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone()).with_message(format!(
+                        "missing {}",
+                        missing
+                            .iter()
+                            .map(|name| format!("`{name}`"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                ]),
+            Self::NotTraitMember(span, kind, name, r#trait) => Diagnostic::error()
+                .with_message(format!("{kind} `{name}` is not a member of trait `{trait}`"))
+                .with_code(E020.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone())
+                        .with_message(format!("not a member of trait `{trait}`"))
+                ]),
+            Self::IncompatibleMethod(span, method, r#trait, expected, found) => Diagnostic::error()
+                .with_message(format!(
+                    "method `{method}` has incompatible signature with trait `{trait}`"
+                ))
+                .with_code(E021.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone())
+                        .with_message(format!("expected `{expected}`"))
+                ])
+                .with_notes(vec![format!("note: expected `{expected}`\n         found `{found}`")]),
+            Self::SupertraitNotImplemented(span, r#type, r#trait, supertrait) => Diagnostic::error()
+                .with_message(format!(
+                    "type `{type}` doesn't implement trait `{supertrait}`, which is required by `{trait}`"
+                ))
+                .with_code(E022.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone())
+                        .with_message(format!("the trait `{supertrait}` is not implemented for `{type}`"))
+                ])
+                .with_notes(vec![format!("note: `{supertrait}` is a supertrait of `{trait}`")]),
+            Self::ConflictingImplementations(span, r#trait, r#type, first) => Diagnostic::error()
+                .with_message(format!(
+                    "conflicting implementations of trait `{trait}` for type `{type}`"
+                ))
+                .with_code(E023.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone())
+                        .with_message("conflicting implementation"),
+                    Label::secondary(file_id, first.range.clone())
+                        .with_message("first implementation here"),
+                ]),
+            Self::NoMethod(span, method, r#type, note) => Diagnostic::error()
+                .with_message(format!("no method named `{method}` found for type `{type}`"))
+                .with_code(E024.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone())
+                        .with_message(format!("method not found in `{type}`"))
+                ])
+                .with_notes(note.iter().cloned().collect()),
+            Self::AmbiguousMethod(span, method, traits) => Diagnostic::error()
+                .with_message(format!("multiple applicable methods named `{method}` found"))
+                .with_code(E025.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone())
+                        .with_message(format!(
+                            "`{method}` is defined in traits {}",
+                            traits
+                                .iter()
+                                .map(|r#trait| format!("`{trait}`"))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ))
+                ])
+                .with_notes(vec!["note: call the method through the trait to disambiguate it".to_owned()]),
+            Self::Redefinition(span, name, namespace, first) => Diagnostic::error()
+                .with_message(format!("the name `{name}` is defined multiple times"))
+                .with_code(E026.code)
+                .with_labels(vec![
+                    Label::primary(file_id, span.range.clone())
+                        .with_message(format!("`{name}` redefined here")),
+                    Label::secondary(file_id, first.range.clone())
+                        .with_message(format!("previous definition of `{name}` here")),
+                ])
+                .with_notes(vec![format!(
+                    "note: `{name}` must be defined only once in the {namespace} namespace of the file"
+                )]),
+            Self::UnresolvedImport(span, name, path) => Diagnostic::error()
+                .with_message(format!("unresolved import `{name}`"))
+                .with_code(E027.code)
+                .with_labels(vec![Label::primary(file_id, span.range.clone())
+                    .with_message(format!("cannot read `{path}`"))])
+                .with_notes(vec!["note: imports are resolved relatively to the importing file,\nor inside of the module root, if it starts with its name".to_owned()]),
+        }
+    }
+}
+
+/// Formats the cycle as `` `a` -> `b` -> `a` ``.
+fn cycle_path(cycle: &[String]) -> String {
+    cycle
+        .iter()
+        .chain(cycle.first())
+        .map(|name| format!("`{name}`"))
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
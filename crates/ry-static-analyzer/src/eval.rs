@@ -0,0 +1,993 @@
+//! `eval.rs` - compile-time evaluation of constant expressions.
+//!
+//! Only literals, constants, operators and `as` casts can be evaluated.
+//! Integer operations are checked: overflow and division by zero are
+//! reported, like in the generated code they would panic. Untyped integer
+//! literals take the type of the other operand, or are limited to 128 bits.
+use std::{cmp::Ordering, collections::HashMap, fmt};
+
+use ry_ast::{
+    location::{Span, WithSpan},
+    token::{NumberSuffix, RawToken, Token},
+    *,
+};
+
+use crate::{error::AnalyzerError, types::TypeAliases};
+
+/// Integer value. Values that fit into [`i128`] are always stored as
+/// [`Int::Signed`], so that every value has a single representation.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Int {
+    Signed(i128),
+    Unsigned(u128),
+}
+
+impl Int {
+    pub fn to_i128(self) -> Option<i128> {
+        match self {
+            Self::Signed(value) => Some(value),
+            Self::Unsigned(value) => value.try_into().ok(),
+        }
+    }
+
+    pub fn to_u128(self) -> Option<u128> {
+        match self {
+            Self::Signed(value) => value.try_into().ok(),
+            Self::Unsigned(value) => Some(value),
+        }
+    }
+
+    pub fn to_f64(self) -> f64 {
+        match self {
+            Self::Signed(value) => value as f64,
+            Self::Unsigned(value) => value as f64,
+        }
+    }
+
+    /// Two's complement representation of the value.
+    fn bit_pattern(self) -> u128 {
+        match self {
+            Self::Signed(value) => value as u128,
+            Self::Unsigned(value) => value,
+        }
+    }
+
+    /// Whether the value is in range of the integer type. Untyped integers
+    /// (`None`) are only limited by 128 bits.
+    pub fn fits(self, suffix: Option<NumberSuffix>) -> bool {
+        let Some(max) = suffix.and_then(NumberSuffix::max_int) else {
+            return suffix.is_none();
+        };
+
+        if suffix.is_some_and(NumberSuffix::is_signed) {
+            self.to_i128()
+                .is_some_and(|value| value >= -(max as i128) - 1 && value <= max as i128)
+        } else {
+            self.to_u128().is_some_and(|value| value <= max)
+        }
+    }
+
+    /// Truncates the value to the width of the integer type, the same way
+    /// as `as` casts do.
+    pub fn wrap(self, suffix: NumberSuffix) -> Int {
+        let bits = bits(suffix);
+        let mask = if bits == 128 {
+            u128::MAX
+        } else {
+            (1 << bits) - 1
+        };
+        let truncated = self.bit_pattern() & mask;
+
+        if suffix.is_signed() && truncated >> (bits - 1) == 1 {
+            Int::Signed((truncated | !mask) as i128)
+        } else {
+            Int::from(truncated)
+        }
+    }
+}
+
+impl From<u128> for Int {
+    fn from(value: u128) -> Self {
+        match value.try_into() {
+            Ok(value) => Self::Signed(value),
+            Err(_) => Self::Unsigned(value),
+        }
+    }
+}
+
+impl PartialOrd for Int {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Int {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Signed(left), Self::Signed(right)) => left.cmp(right),
+            (Self::Unsigned(left), Self::Unsigned(right)) => left.cmp(right),
+            (Self::Signed(_), Self::Unsigned(_)) => Ordering::Less,
+            (Self::Unsigned(_), Self::Signed(_)) => Ordering::Greater,
+        }
+    }
+}
+
+impl fmt::Display for Int {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Signed(value) => write!(f, "{value}"),
+            Self::Unsigned(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// Number of bits in the integer type.
+fn bits(suffix: NumberSuffix) -> u32 {
+    let max = suffix.max_int().expect("integer type expected");
+
+    max.count_ones() + suffix.is_signed() as u32
+}
+
+/// Value of the constant expression.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Int(Int, Option<NumberSuffix>),
+    Float(f64, Option<NumberSuffix>),
+    Bool(bool),
+    Char(char),
+    String(String),
+}
+
+impl Value {
+    /// Name of the type of the value, used in error messages.
+    pub fn type_name(&self) -> String {
+        match self {
+            Self::Int(_, Some(suffix)) | Self::Float(_, Some(suffix)) => suffix.to_string(),
+            Self::Int(_, None) => "integer".to_owned(),
+            Self::Float(_, None) => "float".to_owned(),
+            Self::Bool(_) => "bool".to_owned(),
+            Self::Char(_) => "char".to_owned(),
+            Self::String(_) => "string".to_owned(),
+        }
+    }
+
+    /// Converts the value back into expression, so that it can replace
+    /// the folded one. Negative numbers become `-` applied to a literal.
+    pub fn to_expression(&self, span: &Span) -> Expression {
+        let (negative, expression) = match self {
+            Self::Int(value, suffix) => match value {
+                Int::Signed(value) => (
+                    *value < 0,
+                    RawExpression::Int(value.unsigned_abs(), *suffix),
+                ),
+                Int::Unsigned(value) => (false, RawExpression::Int(*value, *suffix)),
+            },
+            Self::Float(value, suffix) => (
+                value.is_sign_negative(),
+                RawExpression::Float(value.abs(), *suffix),
+            ),
+            Self::Bool(value) => (false, RawExpression::Bool(*value)),
+            Self::Char(value) => (false, RawExpression::Char(*value)),
+            Self::String(value) => (false, RawExpression::String(value.clone())),
+        };
+
+        let expression = WithSpan::new(Box::new(expression), span.clone());
+
+        if negative {
+            WithSpan::new(
+                Box::new(RawExpression::PrefixOrPostfix(
+                    WithSpan::new(RawToken::Minus, span.clone()),
+                    expression,
+                )),
+                span.clone(),
+            )
+        } else {
+            expression
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(value, suffix) => {
+                write!(f, "{value}")?;
+                suffix.map_or(Ok(()), |suffix| write!(f, "{suffix}"))
+            }
+            Self::Float(value, suffix) => {
+                write!(f, "{value:?}")?;
+                suffix.map_or(Ok(()), |suffix| write!(f, "{suffix}"))
+            }
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Char(value) => write!(f, "{value:?}"),
+            Self::String(value) => write!(f, "{value:?}"),
+        }
+    }
+}
+
+/// Why the expression wasn't evaluated.
+#[derive(Debug, PartialEq)]
+pub enum EvalError {
+    /// Expression (located in [`Span`]) is not constant. It's not an error
+    /// by itself: only some places require constant expressions.
+    NotConstant(Span),
+    /// Evaluation failed and the error is already reported.
+    Reported,
+}
+
+pub type EvalResult<T> = Result<T, EvalError>;
+
+/// State of the top level constant.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Constant {
+    Evaluated(Value),
+    /// Evaluation of the constant failed, its usages are not reported again.
+    Poisoned,
+}
+
+pub struct Evaluator<'a> {
+    /// Declarations of constants, that are not evaluated yet.
+    declarations: HashMap<&'a str, &'a ConstDecl>,
+    constants: HashMap<String, Constant>,
+    /// Constants, that are being evaluated, used to detect cycles.
+    evaluating: Vec<&'a str>,
+    aliases: TypeAliases,
+    /// Names of local variables and parameters, which shadow constants.
+    pub locals: Vec<String>,
+    pub errors: Vec<AnalyzerError>,
+}
+
+impl<'a> Evaluator<'a> {
+    /// Evaluates all top level constants of the program.
+    pub fn evaluate_constants(
+        ast: &'a ProgramUnit,
+    ) -> (HashMap<String, Constant>, Vec<AnalyzerError>) {
+        let mut evaluator =
+            Self::with_constants(HashMap::new()).with_aliases(TypeAliases::new(ast));
+
+        // Redefinitions are reported by [`crate::names`], the first
+        // declaration is used.
+        for (_, statement) in &ast.top_level_statements {
+            if let TopLevelStatement::ConstDecl(decl) = statement {
+                evaluator
+                    .declarations
+                    .entry(decl.name.value.as_str())
+                    .or_insert(decl);
+            }
+        }
+
+        let mut names = evaluator
+            .declarations
+            .values()
+            .map(|decl| &decl.name)
+            .collect::<Vec<_>>();
+        names.sort_by_key(|name| name.span.range.start);
+
+        for name in names {
+            let _ = evaluator.evaluate_constant(&name.value, &name.span);
+        }
+
+        (evaluator.constants, evaluator.errors)
+    }
+
+    /// Evaluator, that knows only already evaluated constants.
+    pub fn with_constants(constants: HashMap<String, Constant>) -> Self {
+        Self {
+            declarations: HashMap::new(),
+            constants,
+            evaluating: vec![],
+            aliases: TypeAliases::default(),
+            locals: vec![],
+            errors: vec![],
+        }
+    }
+
+    pub fn with_aliases(mut self, aliases: TypeAliases) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    pub fn constant(&self, name: &str) -> Option<&Constant> {
+        self.constants.get(name)
+    }
+
+    fn report(&mut self, error: AnalyzerError) -> EvalError {
+        self.errors.push(error);
+        EvalError::Reported
+    }
+
+    /// Reports [`EvalError::NotConstant`] in a place, which requires constant
+    /// expression.
+    pub fn require_constant<T>(
+        &mut self,
+        result: EvalResult<T>,
+        context: &'static str,
+    ) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(EvalError::NotConstant(span)) => {
+                self.report(AnalyzerError::NotConstant(span, context));
+                None
+            }
+            Err(EvalError::Reported) => None,
+        }
+    }
+
+    fn evaluate_constant(&mut self, name: &str, span: &Span) -> EvalResult<Value> {
+        if self.locals.iter().any(|local| local == name) {
+            return Err(EvalError::NotConstant(span.clone()));
+        }
+
+        match self.constants.get(name) {
+            Some(Constant::Evaluated(value)) => return Ok(value.clone()),
+            Some(Constant::Poisoned) => return Err(EvalError::Reported),
+            None => {}
+        }
+
+        let Some(&decl) = self.declarations.get(name) else {
+            return Err(EvalError::NotConstant(span.clone()));
+        };
+
+        if let Some(start) = self.evaluating.iter().position(|&n| n == name) {
+            let cycle = self.evaluating[start..]
+                .iter()
+                .map(|&n| n.to_owned())
+                .collect();
+            let first = self.declarations[self.evaluating[start]];
+
+            return Err(self.report(AnalyzerError::ConstantCycle(first.name.span.clone(), cycle)));
+        }
+
+        self.evaluating.push(&decl.name.value);
+
+        let result = self.evaluate(&decl.value);
+        let result =
+            self.require_constant(result, "value of constant")
+                .and_then(|value| match &decl.r#type {
+                    Some(r#type) => self.coerce(value, r#type, &decl.value.span).ok(),
+                    None => Some(value),
+                });
+
+        self.evaluating.pop();
+
+        match result {
+            Some(value) => {
+                self.constants
+                    .insert(name.to_owned(), Constant::Evaluated(value.clone()));
+                Ok(value)
+            }
+            None => {
+                self.constants.insert(name.to_owned(), Constant::Poisoned);
+                Err(EvalError::Reported)
+            }
+        }
+    }
+
+    /// Converts the value into the declared type: untyped literals get
+    /// the type, other values must already have it. Types, which can't have
+    /// constant values, are not checked.
+    pub fn coerce(&mut self, value: Value, r#type: &Type, span: &Span) -> EvalResult<Value> {
+        let Ok(resolved) = self.aliases.resolve(r#type) else {
+            // Invalid alias is reported by the type alias check.
+            return Ok(value);
+        };
+
+        let RawType::Primary(name, generics) = &*resolved.value else {
+            return Ok(value);
+        };
+
+        if !generics.is_empty() {
+            return Ok(value);
+        }
+
+        let expected = self.aliases.describe(r#type);
+        let mismatched = |value: &Value| {
+            AnalyzerError::MismatchedTypes(span.clone(), expected.clone(), value.type_name())
+        };
+
+        Ok(match (NumberSuffix::from_name(&name.value), value) {
+            (Some(suffix), Value::Int(value, None)) if suffix.is_float() => {
+                Value::Float(round(value.to_f64(), suffix), Some(suffix))
+            }
+            (Some(suffix), Value::Int(value, None)) if !value.fits(Some(suffix)) => {
+                return Err(self.report(AnalyzerError::Overflow(
+                    span.clone(),
+                    value.to_string(),
+                    Some(suffix),
+                )));
+            }
+            (Some(suffix), Value::Int(value, None)) => Value::Int(value, Some(suffix)),
+            (Some(suffix), Value::Float(value, None)) if suffix.is_float() => {
+                Value::Float(round(value, suffix), Some(suffix))
+            }
+            (Some(suffix), value @ (Value::Int(_, Some(s)) | Value::Float(_, Some(s))))
+                if s == suffix =>
+            {
+                value
+            }
+            (Some(_), value) => return Err(self.report(mismatched(&value))),
+            (None, value) => match (name.value.as_str(), &value) {
+                ("bool", Value::Bool(_))
+                | ("char", Value::Char(_))
+                | ("string", Value::String(_)) => value,
+                ("bool" | "char" | "string", _) => return Err(self.report(mismatched(&value))),
+                (name, _) if self.aliases.is_newtype(name) => {
+                    return Err(self.report(mismatched(&value)))
+                }
+                _ => value,
+            },
+        })
+    }
+
+    pub fn evaluate(&mut self, expression: &Expression) -> EvalResult<Value> {
+        let span = &expression.span;
+
+        match &*expression.value {
+            RawExpression::Int(value, suffix) => Ok(Value::Int(Int::from(*value), *suffix)),
+            RawExpression::Float(value, suffix) => Ok(Value::Float(*value, *suffix)),
+            RawExpression::Bool(value) => Ok(Value::Bool(*value)),
+            RawExpression::Char(value) => Ok(Value::Char(*value)),
+            RawExpression::String(value) => Ok(Value::String(value.clone())),
+            RawExpression::StaticName(name) => self.evaluate_constant(name, span),
+            RawExpression::Binary(left, operator, right) => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+
+                self.binary(left, operator, right, span)
+            }
+            RawExpression::PrefixOrPostfix(operator, inner) => {
+                // `-128i8` is a valid literal, although `128i8` is not.
+                if let (RawToken::Minus, RawExpression::Int(value, suffix)) =
+                    (&operator.value, &*inner.value)
+                {
+                    return match 0i128.checked_sub_unsigned(*value).map(Int::Signed) {
+                        Some(negated) if negated.fits(*suffix) => Ok(Value::Int(negated, *suffix)),
+                        _ => Err(self.report(AnalyzerError::Overflow(
+                            span.clone(),
+                            format!("-{value}"),
+                            *suffix,
+                        ))),
+                    };
+                }
+
+                let inner = self.evaluate(inner)?;
+
+                self.unary(operator, inner, span)
+            }
+            RawExpression::As(inner, r#type) => {
+                let inner = self.evaluate(inner)?;
+
+                self.cast(inner, r#type, span)
+            }
+            _ => Err(EvalError::NotConstant(span.clone())),
+        }
+    }
+
+    fn invalid_operation(&mut self, operator: &Token, value: &Value, span: &Span) -> EvalError {
+        self.report(AnalyzerError::InvalidOperation(
+            span.clone(),
+            format!("cannot apply {} to `{}`", operator.value, value.type_name()),
+        ))
+    }
+
+    /// Applies prefix operator to the value.
+    pub fn unary(&mut self, operator: &Token, value: Value, span: &Span) -> EvalResult<Value> {
+        let result = match (&operator.value, &value) {
+            (RawToken::Minus, Value::Int(_, Some(suffix))) if !suffix.is_signed() => {
+                return Err(self.invalid_operation(operator, &value, span));
+            }
+            (RawToken::Minus, Value::Int(int, suffix)) => {
+                let negated = match int {
+                    Int::Signed(int) => int.checked_neg(),
+                    Int::Unsigned(int) => 0i128.checked_sub_unsigned(*int),
+                };
+
+                match negated.map(Int::Signed) {
+                    Some(negated) if negated.fits(*suffix) => Value::Int(negated, *suffix),
+                    _ => {
+                        return Err(self.report(AnalyzerError::Overflow(
+                            span.clone(),
+                            format!("-{value}"),
+                            *suffix,
+                        )))
+                    }
+                }
+            }
+            (RawToken::Minus, Value::Float(float, suffix)) => Value::Float(-float, *suffix),
+            (RawToken::Bang, Value::Bool(bool)) => Value::Bool(!bool),
+            (RawToken::Not, Value::Int(int, Some(suffix))) => {
+                Value::Int(Int::from(!int.bit_pattern()).wrap(*suffix), Some(*suffix))
+            }
+            (RawToken::Not, Value::Int(int, None)) => Value::Int(
+                match int {
+                    Int::Signed(int) => Int::Signed(!int),
+                    Int::Unsigned(int) => Int::from(!int),
+                },
+                None,
+            ),
+            (RawToken::Minus | RawToken::Bang | RawToken::Not, _) => {
+                return Err(self.invalid_operation(operator, &value, span));
+            }
+            // Postfix operators and increments are never constant.
+            _ => return Err(EvalError::NotConstant(span.clone())),
+        };
+
+        Ok(result)
+    }
+
+    /// Applies binary operator (except for assignments and `?:`) to
+    /// the values.
+    pub fn binary(
+        &mut self,
+        left: Value,
+        operator: &Token,
+        right: Value,
+        span: &Span,
+    ) -> EvalResult<Value> {
+        let expression = || format!("{left} {} {right}", raw_operator(operator));
+
+        // Shift amount and integer exponent can have any integer type.
+        if let (Value::Int(value, suffix), Value::Int(amount, _)) = (&left, &right) {
+            match operator.value {
+                RawToken::LeftShift | RawToken::RightShift => {
+                    return match shift(&operator.value, *value, *amount, *suffix) {
+                        Some(result) => Ok(Value::Int(result, *suffix)),
+                        None => Err(self.report(AnalyzerError::Overflow(
+                            span.clone(),
+                            expression(),
+                            *suffix,
+                        ))),
+                    };
+                }
+                RawToken::AsteriskAsterisk => {
+                    let result = amount
+                        .to_u128()
+                        .and_then(|amount| u32::try_from(amount).ok())
+                        .and_then(|amount| power(*value, amount, *suffix));
+
+                    return match result {
+                        Some(result) => Ok(Value::Int(result, *suffix)),
+                        None if amount.to_i128().is_some_and(|amount| amount < 0) => Err(self
+                            .report(AnalyzerError::InvalidOperation(
+                                span.clone(),
+                                format!("cannot raise integer to negative power `{amount}`"),
+                            ))),
+                        None => Err(self.report(AnalyzerError::Overflow(
+                            span.clone(),
+                            expression(),
+                            *suffix,
+                        ))),
+                    };
+                }
+                _ => {}
+            }
+        }
+
+        let (left, right) = self.unify(left, right, span)?;
+        let expression = || format!("{left} {} {right}", raw_operator(operator));
+
+        if let Some(ordering) = compare(&left, &right) {
+            let result = match operator.value {
+                RawToken::Eq => Some(ordering.is_eq()),
+                RawToken::NotEq => Some(ordering.is_ne()),
+                RawToken::LessThan => Some(ordering.is_lt()),
+                RawToken::LessThanOrEq => Some(ordering.is_le()),
+                RawToken::GreaterThan => Some(ordering.is_gt()),
+                RawToken::GreaterThanOrEq => Some(ordering.is_ge()),
+                _ => None,
+            };
+
+            if let Some(result) = result {
+                return Ok(Value::Bool(result));
+            }
+        }
+
+        let result = match (&left, &operator.value, &right) {
+            (
+                Value::Int(_, _),
+                RawToken::Slash | RawToken::Percent,
+                Value::Int(Int::Signed(0), _),
+            ) => {
+                return Err(self.report(AnalyzerError::DivisionByZero(span.clone(), expression())));
+            }
+            (
+                Value::Int(l, suffix),
+                RawToken::Plus
+                | RawToken::Minus
+                | RawToken::Asterisk
+                | RawToken::Slash
+                | RawToken::Percent
+                | RawToken::And
+                | RawToken::Or
+                | RawToken::Xor,
+                Value::Int(r, _),
+            ) => match arithmetic(&operator.value, *l, *r, *suffix) {
+                Some(result) => Value::Int(result, *suffix),
+                None => {
+                    return Err(self.report(AnalyzerError::Overflow(
+                        span.clone(),
+                        expression(),
+                        *suffix,
+                    )))
+                }
+            },
+            (Value::Float(_, _), RawToken::Slash | RawToken::Percent, Value::Float(r, _))
+                if *r == 0.0 =>
+            {
+                return Err(self.report(AnalyzerError::DivisionByZero(span.clone(), expression())));
+            }
+            (
+                Value::Float(l, suffix),
+                RawToken::Plus
+                | RawToken::Minus
+                | RawToken::Asterisk
+                | RawToken::Slash
+                | RawToken::Percent
+                | RawToken::AsteriskAsterisk,
+                Value::Float(r, _),
+            ) => {
+                let result = match operator.value {
+                    RawToken::Plus => l + r,
+                    RawToken::Minus => l - r,
+                    RawToken::Asterisk => l * r,
+                    RawToken::Slash => l / r,
+                    RawToken::Percent => l % r,
+                    _ => l.powf(*r),
+                };
+                let result = suffix.map_or(result, |suffix| round(result, suffix));
+
+                if result.is_infinite() {
+                    return Err(self.report(AnalyzerError::Overflow(
+                        span.clone(),
+                        expression(),
+                        Some(suffix.unwrap_or(NumberSuffix::F64)),
+                    )));
+                } else if result.is_nan() {
+                    return Err(self.report(AnalyzerError::InvalidOperation(
+                        span.clone(),
+                        format!("result of `{}` is not a number", expression()),
+                    )));
+                }
+
+                Value::Float(result, *suffix)
+            }
+            (Value::Bool(l), _, Value::Bool(r)) => match operator.value {
+                RawToken::AndAnd | RawToken::And => Value::Bool(*l && *r),
+                RawToken::OrOr | RawToken::Or => Value::Bool(*l || *r),
+                RawToken::Xor => Value::Bool(l ^ r),
+                _ => return Err(self.invalid_operation(operator, &left, span)),
+            },
+            (Value::String(l), RawToken::Plus, Value::String(r)) => {
+                Value::String(format!("{l}{r}"))
+            }
+            _ => return Err(self.invalid_operation(operator, &left, span)),
+        };
+
+        Ok(result)
+    }
+
+    /// Brings operands of binary operator to the same type: untyped integer
+    /// literals take the type of the other operand.
+    fn unify(&mut self, left: Value, right: Value, span: &Span) -> EvalResult<(Value, Value)> {
+        let suffix = match (&left, &right) {
+            (Value::Int(_, l) | Value::Float(_, l), Value::Int(_, r) | Value::Float(_, r)) => {
+                match (l, r) {
+                    (Some(l), Some(r)) if l != r => {
+                        return Err(self.report(AnalyzerError::MismatchedTypes(
+                            span.clone(),
+                            format!("`{l}`"),
+                            r.to_string(),
+                        )));
+                    }
+                    _ => l.or(*r),
+                }
+            }
+            _ => return Ok((left, right)),
+        };
+
+        let is_float = matches!(left, Value::Float(..))
+            || matches!(right, Value::Float(..))
+            || suffix.is_some_and(NumberSuffix::is_float);
+
+        let mut convert = |value: Value| match value {
+            Value::Int(_, Some(_)) if is_float => Err(self.report(AnalyzerError::MismatchedTypes(
+                span.clone(),
+                "`float`".to_owned(),
+                value.type_name(),
+            ))),
+            Value::Int(int, _) if is_float => Ok(Value::Float(int.to_f64(), suffix)),
+            Value::Int(int, _) if !int.fits(suffix) => Err(self.report(AnalyzerError::Overflow(
+                span.clone(),
+                int.to_string(),
+                suffix,
+            ))),
+            Value::Int(int, _) => Ok(Value::Int(int, suffix)),
+            Value::Float(float, _) => Ok(Value::Float(float, suffix)),
+            _ => unreachable!("only numbers are unified"),
+        };
+
+        Ok((convert(left)?, convert(right)?))
+    }
+
+    /// Converts the value into the primitive type.
+    pub fn cast(&mut self, value: Value, r#type: &Type, span: &Span) -> EvalResult<Value> {
+        let Ok(resolved) = self.aliases.resolve(r#type) else {
+            return Err(EvalError::NotConstant(span.clone()));
+        };
+
+        let RawType::Primary(name, generics) = &*resolved.value else {
+            return Err(EvalError::NotConstant(span.clone()));
+        };
+
+        if !generics.is_empty() {
+            return Err(EvalError::NotConstant(span.clone()));
+        }
+
+        let Some(suffix) = NumberSuffix::from_name(&name.value) else {
+            return match (name.value.as_str(), &value) {
+                ("bool", Value::Bool(_))
+                | ("char", Value::Char(_))
+                | ("string", Value::String(_)) => Ok(value),
+                ("bool" | "char" | "string", _) => {
+                    Err(self.report(AnalyzerError::InvalidOperation(
+                        span.clone(),
+                        format!(
+                            "cannot cast `{}` as {}",
+                            value.type_name(),
+                            self.aliases.describe(r#type)
+                        ),
+                    )))
+                }
+                _ => Err(EvalError::NotConstant(span.clone())),
+            };
+        };
+
+        let result = match value {
+            Value::Int(int, _) if suffix.is_float() => {
+                Value::Float(round(int.to_f64(), suffix), Some(suffix))
+            }
+            Value::Int(int, _) => Value::Int(int.wrap(suffix), Some(suffix)),
+            Value::Float(float, _) if suffix.is_float() => {
+                Value::Float(round(float, suffix), Some(suffix))
+            }
+            Value::Float(float, _) => Value::Int(saturate(float, suffix), Some(suffix)),
+            Value::Bool(bool) if !suffix.is_float() => {
+                Value::Int(Int::Signed(bool as i128), Some(suffix))
+            }
+            Value::Char(char) if !suffix.is_float() => {
+                Value::Int(Int::Signed(char as i128).wrap(suffix), Some(suffix))
+            }
+            _ => {
+                return Err(self.report(AnalyzerError::InvalidOperation(
+                    span.clone(),
+                    format!(
+                        "cannot cast `{}` as {}",
+                        value.type_name(),
+                        self.aliases.describe(r#type)
+                    ),
+                )))
+            }
+        };
+
+        if let Value::Float(float, _) = result {
+            if float.is_infinite() {
+                return Err(self.report(AnalyzerError::Overflow(
+                    span.clone(),
+                    format!("{value} as {suffix}"),
+                    Some(suffix),
+                )));
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Operator without backticks, for example: `+`.
+fn raw_operator(operator: &Token) -> String {
+    operator.value.to_string().trim_matches('`').to_owned()
+}
+
+/// Rounds the number to the precision of the float type.
+fn round(value: f64, suffix: NumberSuffix) -> f64 {
+    if suffix == NumberSuffix::F32 {
+        value as f32 as f64
+    } else {
+        value
+    }
+}
+
+/// Converts float into integer type, saturating on overflow (like `as` does).
+fn saturate(value: f64, suffix: NumberSuffix) -> Int {
+    let max = suffix.max_int().expect("integer type expected");
+
+    if suffix.is_signed() {
+        Int::Signed((value as i128).clamp(-(max as i128) - 1, max as i128))
+    } else {
+        Int::from((value as u128).min(max))
+    }
+}
+
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Int(l, _), Value::Int(r, _)) => Some(l.cmp(r)),
+        (Value::Float(l, _), Value::Float(r, _)) => l.partial_cmp(r),
+        (Value::Bool(l), Value::Bool(r)) => Some(l.cmp(r)),
+        (Value::Char(l), Value::Char(r)) => Some(l.cmp(r)),
+        (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        _ => None,
+    }
+}
+
+macro_rules! checked_operation {
+    ($name: ident, $t: ty) => {
+        fn $name(operator: &RawToken, left: $t, right: $t) -> Option<$t> {
+            match operator {
+                RawToken::Plus => left.checked_add(right),
+                RawToken::Minus => left.checked_sub(right),
+                RawToken::Asterisk => left.checked_mul(right),
+                RawToken::Slash => left.checked_div(right),
+                RawToken::Percent => left.checked_rem(right),
+                RawToken::And => Some(left & right),
+                RawToken::Or => Some(left | right),
+                RawToken::Xor => Some(left ^ right),
+                _ => unreachable!("not an arithmetic operator"),
+            }
+        }
+    };
+}
+
+checked_operation!(signed_operation, i128);
+checked_operation!(unsigned_operation, u128);
+
+/// Computes arithmetic operation, returns `None` on overflow. Untyped
+/// integers are computed as [`i128`], and as [`u128`] if the result
+/// doesn't fit.
+fn arithmetic(
+    operator: &RawToken,
+    left: Int,
+    right: Int,
+    suffix: Option<NumberSuffix>,
+) -> Option<Int> {
+    let unsigned = suffix.is_some_and(|suffix| !suffix.is_signed());
+
+    if !unsigned {
+        if let (Some(l), Some(r)) = (left.to_i128(), right.to_i128()) {
+            match signed_operation(operator, l, r) {
+                Some(result) => return Some(Int::Signed(result)).filter(|r| r.fits(suffix)),
+                None if suffix.is_some() => return None,
+                None => {}
+            }
+        }
+    }
+
+    unsigned_operation(operator, left.to_u128()?, right.to_u128()?)
+        .map(Int::from)
+        .filter(|result| result.fits(suffix))
+}
+
+/// Computes shift, returns `None` if the amount is not less than number
+/// of bits in the type. Bits shifted out to the left are discarded.
+fn shift(
+    operator: &RawToken,
+    value: Int,
+    amount: Int,
+    suffix: Option<NumberSuffix>,
+) -> Option<Int> {
+    let bits = suffix.map_or(128, bits);
+    let amount = amount.to_u128().filter(|&amount| amount < bits as u128)? as u32;
+
+    Some(match (operator, value) {
+        (RawToken::LeftShift, _) => {
+            let shifted = value.bit_pattern() << amount;
+
+            match (suffix, value) {
+                (Some(suffix), _) => Int::from(shifted).wrap(suffix),
+                (None, Int::Signed(_)) => Int::Signed(shifted as i128),
+                (None, Int::Unsigned(_)) => Int::from(shifted),
+            }
+        }
+        (_, Int::Signed(value)) => Int::Signed(value >> amount),
+        (_, Int::Unsigned(value)) => Int::from(value >> amount),
+    })
+}
+
+fn power(value: Int, exponent: u32, suffix: Option<NumberSuffix>) -> Option<Int> {
+    let result = match value.to_i128() {
+        Some(value) if suffix.is_none_or(NumberSuffix::is_signed) => {
+            value.checked_pow(exponent).map(Int::Signed)
+        }
+        _ => None,
+    };
+
+    result
+        .or_else(|| value.to_u128()?.checked_pow(exponent).map(Int::from))
+        .filter(|result| result.fits(suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use ry_ast::*;
+
+    use crate::{
+        fold_constants,
+        test_utils::{errors, parse},
+    };
+
+    /// Folds the expression, returns the result as a source code or
+    /// the code of the first error.
+    fn eval(expression: &str) -> String {
+        let source =
+            format!("const A u8 = 200;\nconst B = (A as i16) * -2;\nfun f() {{ {expression}; }}");
+        if let Some((code, _)) = errors(&source, fold_constants).into_iter().next() {
+            return code;
+        }
+
+        let mut ast = parse(&source);
+        fold_constants(&mut ast);
+
+        let (_, TopLevelStatement::FunctionDecl(function)) = &ast.top_level_statements[2] else {
+            unreachable!()
+        };
+        let Statement::Expression(expression) = &function.stmts[0] else {
+            unreachable!()
+        };
+
+        let mut evaluator = super::Evaluator::with_constants(Default::default());
+        evaluator.evaluate(expression).unwrap().to_string()
+    }
+
+    #[test]
+    fn arithmetic_test() {
+        assert_eq!(eval("3 + 2 * 4"), "11");
+        assert_eq!(eval("7 / 2 - 10 % 4"), "1");
+        assert_eq!(eval("2 ** 10"), "1024");
+        assert_eq!(eval("1.5 * 2"), "3.0");
+        assert_eq!(eval("1 << 4 | 1"), "17");
+        assert_eq!(eval("~0u8"), "255u8");
+        assert_eq!(eval("-128i8 / 2"), "-64i8");
+        assert_eq!(eval("0.1f32 + 0.2f32"), "0.30000001192092896f32");
+    }
+
+    #[test]
+    fn comparison_and_logic_test() {
+        assert_eq!(eval("1 < 2 && !(3 == 3)"), "false");
+        assert_eq!(eval("'a' < 'b' || false"), "true");
+        assert_eq!(eval("\"ab\" + \"c\""), "\"abc\"");
+    }
+
+    #[test]
+    fn constants_and_casts_test() {
+        assert_eq!(eval("A + 55"), "255u8");
+        assert_eq!(eval("B"), "-400i16");
+        assert_eq!(eval("300 as u8"), "44u8");
+        assert_eq!(eval("(-1) as u16"), "65535u16");
+        assert_eq!(eval("1e10 as i32"), "2147483647i32");
+        assert_eq!(eval("'a' as u8"), "97u8");
+    }
+
+    #[test]
+    fn errors_test() {
+        assert_eq!(eval("A + 56"), "E010");
+        assert_eq!(eval("-128i8 - 1"), "E010");
+        assert_eq!(eval("-(1u8)"), "E010");
+        assert_eq!(eval("170141183460469231731687303715884105727 * 4"), "E010");
+        assert_eq!(eval("1u8 << 8"), "E010");
+        assert_eq!(eval("1 / (A - 200)"), "E011");
+        assert_eq!(eval("1.0 % 0"), "E011");
+        assert_eq!(eval("1u8 + 1i8"), "E013");
+        assert_eq!(eval("-A"), "E013");
+        assert_eq!(eval("true + 1"), "E013");
+    }
+
+    #[test]
+    fn newtype_test() {
+        assert_eq!(
+            errors(
+                "type Meters f64;\ntype Length = Meters;\nconst A Length = 1.5;",
+                fold_constants
+            ),
+            vec![("E013".to_owned(), "mismatched types".to_owned())]
+        );
+    }
+}
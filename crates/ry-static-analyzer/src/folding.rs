@@ -0,0 +1,194 @@
+//! `folding.rs` - replaces constant expressions with their values.
+use std::collections::HashMap;
+
+use ry_ast::{visit_mut::*, *};
+
+use crate::{
+    error::AnalyzerError,
+    eval::{Constant, EvalError, Evaluator, Value},
+    types::TypeAliases,
+};
+
+pub(crate) struct ConstantFolder {
+    pub(crate) evaluator: Evaluator<'static>,
+}
+
+impl ConstantFolder {
+    pub(crate) fn new(constants: HashMap<String, Constant>, aliases: TypeAliases) -> Self {
+        Self {
+            evaluator: Evaluator::with_constants(constants).with_aliases(aliases),
+        }
+    }
+
+    /// Folds expression in a place, which requires constant expression.
+    fn fold_required(
+        &mut self,
+        expression: &mut Expression,
+        context: &'static str,
+    ) -> Option<Value> {
+        let result = self.evaluator.evaluate(expression);
+        let value = self.evaluator.require_constant(result, context)?;
+
+        *expression = value.to_expression(&expression.span);
+        Some(value)
+    }
+
+    /// Runs `f` with new scope of local variables.
+    fn with_scope<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let locals = self.evaluator.locals.len();
+        f(self);
+        self.evaluator.locals.truncate(locals);
+    }
+}
+
+impl VisitorMut for ConstantFolder {
+    fn visit_const_decl_mut(&mut self, const_decl: &mut ConstDecl) {
+        if let Some(r#type) = &mut const_decl.r#type {
+            self.visit_type_mut(r#type);
+        }
+
+        // Value is already evaluated (and checked) before folding.
+        if let Some(Constant::Evaluated(value)) = self.evaluator.constant(&const_decl.name.value) {
+            const_decl.value = value.to_expression(&const_decl.value.span);
+        }
+    }
+
+    fn visit_function_decl_mut(&mut self, function_decl: &mut FunctionDecl) {
+        self.with_scope(|s| walk_function_decl_mut(s, function_decl));
+    }
+
+    fn visit_trait_method_mut(&mut self, method: &mut TraitMethod) {
+        self.with_scope(|s| walk_trait_method_mut(s, method));
+    }
+
+    fn visit_function_param_mut(&mut self, param: &mut FunctionParam) {
+        self.visit_type_mut(&mut param.r#type);
+
+        if let Some(default_value) = &mut param.default_value {
+            self.visit_expression_mut(default_value);
+        }
+
+        self.evaluator.locals.push(param.name.value.clone());
+    }
+
+    fn visit_statements_block_mut(&mut self, block: &mut StatementsBlock) {
+        self.with_scope(|s| walk_statements_block_mut(s, block));
+    }
+
+    fn visit_statement_mut(&mut self, statement: &mut Statement) {
+        walk_statement_mut(self, statement);
+
+        if let Statement::Var(name, ..) = statement {
+            self.evaluator.locals.push(name.value.clone());
+        }
+    }
+
+    fn visit_expression_mut(&mut self, expression: &mut Expression) {
+        match self.evaluator.evaluate(expression) {
+            Ok(value) => *expression = value.to_expression(&expression.span),
+            Err(EvalError::NotConstant(_)) => walk_expression_mut(self, expression),
+            Err(EvalError::Reported) => {}
+        }
+    }
+
+    fn visit_type_mut(&mut self, r#type: &mut Type) {
+        let RawType::Array(inner, Some(size)) = &mut *r#type.value else {
+            return walk_type_mut(self, r#type);
+        };
+
+        self.visit_type_mut(inner);
+
+        match self.fold_required(size, "array size") {
+            Some(Value::Int(size, _)) if size.to_u128().is_some() => {}
+            Some(value) => self.evaluator.errors.push(AnalyzerError::InvalidOperation(
+                size.span.clone(),
+                format!("array size must be a non-negative integer, found `{value}`"),
+            )),
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ry_ast::*;
+
+    use crate::{
+        fold_constants,
+        test_utils::{self, parse},
+    };
+
+    #[test]
+    fn folding_test() {
+        let mut ast =
+            parse("const N = 2 * 2;\nfun f(a [i32; N + 1], b i32 = N - 5) { var N = 1; N + 1; }");
+
+        assert_eq!(fold_constants(&mut ast), vec![]);
+
+        let (_, TopLevelStatement::FunctionDecl(function)) = &ast.top_level_statements[1] else {
+            unreachable!()
+        };
+        let RawType::Array(_, Some(size)) = &*function.def.params[0].r#type.value else {
+            unreachable!()
+        };
+
+        assert_eq!(*size.value, RawExpression::Int(5, None));
+        assert!(matches!(
+            &*function.def.params[1].default_value.as_ref().unwrap().value,
+            RawExpression::PrefixOrPostfix(_, one) if *one.value == RawExpression::Int(1, None)
+        ));
+        // `N` is shadowed by the local variable.
+        assert!(matches!(
+            function.stmts[1],
+            Statement::Expression(ref e) if matches!(*e.value, RawExpression::Binary(..))
+        ));
+    }
+
+    fn errors(source: &str) -> Vec<(String, String)> {
+        test_utils::errors(source, fold_constants)
+    }
+
+    #[test]
+    fn errors_test() {
+        assert_eq!(
+            errors("const A = B;\nconst B = C + 1;\nconst C = B;\nconst D = A;"),
+            vec![(
+                "E014".to_owned(),
+                "cycle detected when evaluating constant `B`".to_owned()
+            )]
+        );
+        assert_eq!(
+            errors("fun f(n i32) { var a [u8; n] = []; var b [u8; -1] = []; }"),
+            vec![
+                (
+                    "E012".to_owned(),
+                    "array size is not a constant expression".to_owned()
+                ),
+                (
+                    "E013".to_owned(),
+                    "array size must be a non-negative integer, found `-1`".to_owned()
+                )
+            ]
+        );
+        assert_eq!(
+            errors("const A bool = 1;\nfun f() { 1 / 0; }"),
+            vec![
+                ("E013".to_owned(), "mismatched types".to_owned()),
+                (
+                    "E011".to_owned(),
+                    "this operation will panic at runtime".to_owned()
+                )
+            ]
+        );
+        assert_eq!(
+            errors("type Byte = u8;\nconst A Byte = 300;\nconst B = 1 as Byte + A;"),
+            vec![(
+                "E010".to_owned(),
+                "this arithmetic operation will overflow".to_owned()
+            )]
+        );
+    }
+}
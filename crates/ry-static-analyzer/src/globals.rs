@@ -0,0 +1,454 @@
+//! `globals.rs` - checks initialization order of global variables.
+//!
+//! Files are initialized after files they import, global variables inside
+//! of a file - in order of declaration. Initializer of global variable can't
+//! use variables, that are not initialized yet (the variable itself, ones
+//! declared later or in the file, which imports the current one).
+//!
+//! Names are resolved in the file of the initializer first, then in
+//! the imported files (nearest imports first), then in the rest of
+//! the program. Bodies of top level functions used by the initializer are
+//! checked too, as they may be called during initialization. Methods are
+//! not followed, because their receiver type is not known here.
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use ry_ast::{location::Span, visit::*, *};
+
+use crate::error::AnalyzerError;
+
+/// Source file of the program, see [`check_initialization_order`].
+pub struct Module<'a> {
+    pub ast: &'a ProgramUnit,
+    /// Indices of the imported modules.
+    pub imports: Vec<usize>,
+}
+
+/// Checks that global variables are not used in initializers before they
+/// are initialized. Module `0` is the entry point. Returns errors together
+/// with indices of modules, where they were found.
+pub fn check_initialization_order(modules: &[Module]) -> Vec<(usize, AnalyzerError)> {
+    let order = initialization_order(modules);
+
+    let mut rank = vec![0; modules.len()];
+
+    for (i, &module) in order.iter().enumerate() {
+        rank[module] = i;
+    }
+
+    let scopes = Scopes::new(modules, &order);
+    let mut errors = vec![];
+
+    for &module in &order {
+        for (position, (_, statement)) in
+            modules[module].ast.top_level_statements.iter().enumerate()
+        {
+            let TopLevelStatement::VarDecl(decl) = statement else {
+                continue;
+            };
+
+            for read in scopes.reads(module, &decl.value) {
+                if (rank[read.declared_in], read.declared_at) >= (rank[module], position) {
+                    errors.push((
+                        module,
+                        AnalyzerError::GlobalUsedBeforeInitialization(
+                            read.span,
+                            read.name,
+                            (read.declared_in == module).then(|| read.declaration.clone()),
+                            read.function,
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+/// Top level declarations of the module, which initializers can use.
+#[derive(Default)]
+struct Declarations<'a> {
+    /// Global variables with their positions in the module and locations
+    /// of names.
+    globals: HashMap<&'a str, (usize, &'a Span)>,
+    functions: HashMap<&'a str, &'a FunctionDecl>,
+}
+
+enum Declaration<'a> {
+    /// Module, position in it and location of the name.
+    Global(usize, usize, &'a Span),
+    Function(usize, &'a FunctionDecl),
+}
+
+/// Global variable read by the initializer.
+struct Read<'a> {
+    name: String,
+    /// Location of the usage (or of the function, which reads it) in
+    /// the initializer.
+    span: Span,
+    /// Function used by the initializer, which reads the variable.
+    function: Option<String>,
+    declared_in: usize,
+    declared_at: usize,
+    declaration: &'a Span,
+}
+
+/// Declarations visible from each module of the program.
+struct Scopes<'a> {
+    declarations: Vec<Declarations<'a>>,
+    /// Modules, where names are looked up, in order of priority.
+    lookup: Vec<Vec<usize>>,
+}
+
+impl<'a> Scopes<'a> {
+    fn new(modules: &[Module<'a>], order: &[usize]) -> Self {
+        let declarations = modules
+            .iter()
+            .map(|module| {
+                let mut declarations = Declarations::default();
+
+                // Redefinitions are reported by [`crate::names`], the first
+                // declaration is used.
+                for (position, (_, statement)) in module.ast.top_level_statements.iter().enumerate()
+                {
+                    match statement {
+                        TopLevelStatement::VarDecl(decl) => {
+                            declarations
+                                .globals
+                                .entry(decl.name.value.as_str())
+                                .or_insert((position, &decl.name.span));
+                        }
+                        TopLevelStatement::FunctionDecl(decl) => {
+                            declarations
+                                .functions
+                                .entry(decl.def.name.value.as_str())
+                                .or_insert(decl);
+                        }
+                        _ => {}
+                    }
+                }
+
+                declarations
+            })
+            .collect();
+
+        let lookup = (0..modules.len())
+            .map(|module| {
+                // The module itself and its imports (breadth first).
+                let mut lookup = vec![module];
+                let mut queue = VecDeque::from([module]);
+
+                while let Some(current) = queue.pop_front() {
+                    for &import in &modules[current].imports {
+                        if !lookup.contains(&import) {
+                            lookup.push(import);
+                            queue.push_back(import);
+                        }
+                    }
+                }
+
+                let rest = order
+                    .iter()
+                    .filter(|module| !lookup.contains(module))
+                    .copied()
+                    .collect::<Vec<_>>();
+                lookup.extend(rest);
+                lookup
+            })
+            .collect();
+
+        Self {
+            declarations,
+            lookup,
+        }
+    }
+
+    fn resolve(&self, module: usize, name: &str) -> Option<Declaration<'a>> {
+        self.lookup[module].iter().find_map(|&module| {
+            let declarations = &self.declarations[module];
+
+            match declarations.globals.get(name) {
+                Some(&(position, span)) => Some(Declaration::Global(module, position, span)),
+                None => declarations
+                    .functions
+                    .get(name)
+                    .map(|&function| Declaration::Function(module, function)),
+            }
+        })
+    }
+
+    /// Global variables read by the initializer in the `module`: directly
+    /// or by top level functions it uses (transitively).
+    fn reads(&self, module: usize, initializer: &Expression) -> Vec<Read<'a>> {
+        let mut collector = ReferencesCollector::default();
+        collector.visit_expression(initializer);
+
+        let mut reads = vec![];
+        self.collect_reads(
+            module,
+            collector.references,
+            None,
+            &mut HashSet::new(),
+            &mut reads,
+        );
+        reads
+    }
+
+    /// Resolves `references` in the `module`. `function` is the function used
+    /// by the initializer (with the location of its usage), if references
+    /// are inside of it.
+    fn collect_reads(
+        &self,
+        module: usize,
+        references: Vec<(String, Span)>,
+        function: Option<&(String, Span)>,
+        visited: &mut HashSet<(usize, &'a str)>,
+        reads: &mut Vec<Read<'a>>,
+    ) {
+        for (name, span) in references {
+            match self.resolve(module, &name) {
+                Some(Declaration::Global(declared_in, declared_at, declaration)) => {
+                    let span = function.map_or(span, |(_, span)| span.clone());
+
+                    if !reads
+                        .iter()
+                        .any(|read| read.name == name && read.span == span)
+                    {
+                        reads.push(Read {
+                            name,
+                            span,
+                            function: function.map(|(function, _)| function.clone()),
+                            declared_in,
+                            declared_at,
+                            declaration,
+                        });
+                    }
+                }
+                Some(Declaration::Function(declared_in, decl))
+                    if visited.insert((declared_in, &decl.def.name.value)) =>
+                {
+                    let mut collector = ReferencesCollector::default();
+                    let mut params = vec![];
+
+                    for param in &decl.def.params {
+                        if let Some(default_value) = &param.default_value {
+                            collector.visit_expression(default_value);
+                        }
+
+                        params.push(param.name.value.clone());
+                    }
+
+                    collector.locals.push(params);
+
+                    collector.visit_statements_block(&decl.stmts);
+
+                    let function = function.cloned().unwrap_or((name, span));
+                    self.collect_reads(
+                        declared_in,
+                        collector.references,
+                        Some(&function),
+                        visited,
+                        reads,
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Modules in order of initialization: imported modules go first.
+pub fn initialization_order(modules: &[Module]) -> Vec<usize> {
+    import_order(
+        &modules
+            .iter()
+            .map(|module| module.imports.as_slice())
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// [`initialization_order`] of modules with the given imports, for
+/// programs, which are already compiled.
+pub fn import_order(imports: &[&[usize]]) -> Vec<usize> {
+    fn visit(module: usize, imports: &[&[usize]], visited: &mut [bool], order: &mut Vec<usize>) {
+        if visited[module] {
+            return;
+        }
+
+        visited[module] = true;
+
+        for &import in imports[module] {
+            visit(import, imports, visited, order);
+        }
+
+        order.push(module);
+    }
+
+    let mut visited = vec![false; imports.len()];
+    let mut order = vec![];
+
+    for module in 0..imports.len() {
+        visit(module, imports, &mut visited, &mut order);
+    }
+
+    order
+}
+
+/// Collects names used in the expression, except for local variables.
+#[derive(Default)]
+struct ReferencesCollector {
+    references: Vec<(String, Span)>,
+    /// Local variables of every enclosing block, innermost last.
+    locals: Vec<Vec<String>>,
+}
+
+impl Visitor for ReferencesCollector {
+    fn visit_statements_block(&mut self, block: &[Statement]) {
+        self.locals.push(vec![]);
+
+        walk_statements_block(self, block);
+
+        self.locals.pop();
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement);
+
+        if let (Statement::Var(name, ..), Some(scope)) = (statement, self.locals.last_mut()) {
+            scope.push(name.value.clone());
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        if let RawExpression::StaticName(name) = &*expression.value {
+            if !self.locals.iter().flatten().any(|local| local == name) {
+                self.references
+                    .push((name.clone(), expression.span.clone()));
+            }
+        }
+
+        walk_expression(self, expression);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ry_report::Reporter;
+
+    use crate::{
+        globals::{check_initialization_order, Module},
+        test_utils::parse,
+    };
+
+    fn errors(sources: &[(&str, Vec<usize>)]) -> Vec<(usize, String)> {
+        let asts = sources
+            .iter()
+            .map(|(source, _)| parse(source))
+            .collect::<Vec<_>>();
+        let modules = asts
+            .iter()
+            .zip(sources)
+            .map(|(ast, (_, imports))| Module {
+                ast,
+                imports: imports.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        check_initialization_order(&modules)
+            .into_iter()
+            .map(|(module, error)| (module, error.build_diagnostic(0).message))
+            .collect()
+    }
+
+    #[test]
+    fn single_file_test() {
+        assert_eq!(
+            errors(&[(
+                "var a = 1;\nvar b = a + c;\nvar c = if a { var c = 2; c } else { 3 };\nvar d = d;",
+                vec![]
+            )]),
+            vec![
+                (
+                    0,
+                    "global variable `c` is used before its initialization".to_owned()
+                ),
+                (
+                    0,
+                    "global variable `d` is used before its initialization".to_owned()
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn imports_test() {
+        // `main` imports `config`, so `config` is initialized first.
+        assert_eq!(
+            errors(&[
+                (
+                    "import \"config\";\nvar port = default_port + 1;\nvar name = \"ry\";",
+                    vec![1]
+                ),
+                ("var default_port = 8000;\nvar title = name;", vec![]),
+            ]),
+            vec![(
+                1,
+                "global variable `name` is used before its initialization".to_owned()
+            )]
+        );
+        // Declarations of the file shadow ones of the imported file.
+        assert_eq!(
+            errors(&[
+                ("import \"b\";\nvar y = x;\nvar x = 2;\nvar z = w;", vec![1]),
+                ("var x = 1;\nvar w = 3;", vec![]),
+            ]),
+            vec![(
+                0,
+                "global variable `x` is used before its initialization".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn functions_test() {
+        assert_eq!(
+            errors(&[
+                (
+                    "import \"util\";\nvar a = f();\nvar b = 1;\nfun f() i32 { g(b) }\n\
+                     fun g(b i32 = c) i32 { b + h() }\nvar c = 2;\nvar d = f();",
+                    vec![1]
+                ),
+                ("fun h() i32 { 1 }\nfun i() i32 { a }\nvar e = i();", vec![]),
+            ]),
+            vec![
+                (
+                    1,
+                    "global variable `a` is used before its initialization".to_owned()
+                ),
+                (
+                    0,
+                    "global variable `c` is used before its initialization".to_owned()
+                ),
+                (
+                    0,
+                    "global variable `b` is used before its initialization".to_owned()
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn block_scopes_test() {
+        // Local `G` is only visible in the block, the last `G` is global.
+        assert_eq!(
+            errors(&[(
+                "var A = f();\nvar G = 5;\nfun f() i32 { if true { var G = 1; } G }",
+                vec![]
+            )]),
+            vec![(
+                0,
+                "global variable `G` is used before its initialization".to_owned()
+            )]
+        );
+    }
+}
@@ -1,14 +1,80 @@
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
-}
+//! `lib.rs` - static analysis of Ry programs.
+//!
+//! For now it evaluates constant expressions at compile time: folds them
+//! into literals (including default parameter values), computes top level
+//! `const` declarations and reports overflows, divisions by zero and
+//! non-constant expressions in places, where a constant is required
+//! (values of constants and array sizes). It also checks initialization
+//! order of global variables, see [`globals`], resolves type aliases,
+//! see [`types`], checks trait bounds of generic arguments, see
+//! [`bounds`], trait implementations, see [`traits`], resolves method
+//! calls, see [`methods`], and reports names defined more than once, see
+//! [`names`].
+use ry_ast::{visit_mut::VisitorMut, ProgramUnit};
+
+use crate::{
+    bounds::check_trait_bounds,
+    error::AnalyzerError,
+    eval::Evaluator,
+    folding::ConstantFolder,
+    methods::resolve_methods,
+    names::check_redefinitions,
+    traits::check_implementations,
+    types::{check_type_aliases, TypeAliases},
+};
 
+pub mod bounds;
+pub mod error;
+pub mod eval;
+mod folding;
+pub mod globals;
+pub mod methods;
+pub mod names;
 #[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
+mod test_utils;
+pub mod traits;
+pub mod types;
+
+/// Runs checks of a single file, which don't depend on other files: checks
+/// uniqueness of names, type aliases, trait bounds, implementations and
+/// method calls and folds constants (see
+/// [`fold_constants`]). Returns errors
+/// found, sorted by location.
+pub fn analyze(ast: &mut ProgramUnit) -> Vec<AnalyzerError> {
+    let mut errors = check_redefinitions(ast);
+    errors.append(&mut check_type_aliases(ast));
+    errors.append(&mut check_trait_bounds(ast));
+    errors.append(&mut check_implementations(ast));
+    errors.append(&mut resolve_methods(ast));
+    errors.append(&mut fold_constants(ast));
+
+    errors.sort_by_key(|error| error.span().range.start);
+    errors
+}
+
+/// Evaluates constants and folds constant expressions of the program in
+/// place. Returns errors found, sorted by location.
+///
+/// ```
+/// use ry_ast::{RawExpression, TopLevelStatement};
+/// use ry_parser::Parser;
+/// use ry_static_analyzer::fold_constants;
+///
+/// let mut ast = Parser::new("const SIZE = 3 + 2;").parse().unwrap();
+/// assert!(fold_constants(&mut ast).is_empty());
+///
+/// let TopLevelStatement::ConstDecl(decl) = &ast.top_level_statements[0].1 else {
+///     unreachable!()
+/// };
+/// assert_eq!(*decl.value.value, RawExpression::Int(5, None));
+/// ```
+pub fn fold_constants(ast: &mut ProgramUnit) -> Vec<AnalyzerError> {
+    let (constants, mut errors) = Evaluator::evaluate_constants(ast);
+
+    let mut folder = ConstantFolder::new(constants, TypeAliases::new(ast));
+    folder.visit_program_unit_mut(ast);
+
+    errors.append(&mut folder.evaluator.errors);
+    errors.sort_by_key(|error| error.span().range.start);
+    errors
 }
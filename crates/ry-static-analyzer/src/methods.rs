@@ -0,0 +1,782 @@
+//! `methods.rs` - resolves method calls.
+//!
+//! Method call `a.b(c)` is looked up in implementations for the type of
+//! the receiver `a`: methods of inherent implementations (`impl Point`)
+//! take precedence over methods of implemented traits. Methods of generic
+//! parameters come from traits in their constraints. Receiver is
+//! automatically referenced for methods taking `self *Self` and
+//! dereferenced for methods taking `self Self`, when it is a pointer.
+//!
+//! There is no type checker yet, so types of receivers are only inferred
+//! from declarations (parameters, variables, constants), literals, struct
+//! literals, fields, `as` casts and calls. Missing methods are only
+//! reported for structs, enums, newtypes and generic parameters declared in
+//! the file. Newtypes don't have methods of the wrapped type.
+use std::{collections::HashMap, ops::Range};
+
+use ry_ast::{
+    location::{Span, WithSpan},
+    token::RawToken,
+    visit::*,
+    *,
+};
+
+use crate::{
+    bounds::{bounds, head, matches},
+    error::AnalyzerError,
+    types::{substitute, TypeAliases},
+};
+
+/// Resolves method calls of the program. Returns errors found, sorted by
+/// location.
+pub fn resolve_methods(ast: &ProgramUnit) -> Vec<AnalyzerError> {
+    let mut errors = resolver(ast).errors;
+    errors.sort_by_key(|error| error.span().range.start);
+    errors
+}
+
+/// Generic arguments of calls of generic functions (by locations of
+/// the calls), which are not given explicitly, but can be inferred from
+/// types of the arguments. Arguments, that can't be inferred, are missing.
+pub(crate) fn infer_generic_arguments(ast: &ProgramUnit) -> GenericCalls {
+    resolver(ast).generic_calls
+}
+
+fn resolver(ast: &ProgramUnit) -> MethodResolver<'_> {
+    let mut resolver = MethodResolver {
+        aliases: TypeAliases::new(ast),
+        ..Default::default()
+    };
+
+    // Redefinitions are reported by [`crate::names`], the first definition
+    // is used.
+    for (_, statement) in &ast.top_level_statements {
+        match statement {
+            TopLevelStatement::StructDecl(struct_decl) => {
+                resolver
+                    .structs
+                    .insert(&struct_decl.name.value, Some(struct_decl));
+            }
+            TopLevelStatement::EnumDecl(enum_decl) => {
+                resolver.structs.insert(&enum_decl.name.value, None);
+            }
+            TopLevelStatement::Newtype(newtype) => {
+                resolver.structs.insert(&newtype.name.value, None);
+                resolver.newtypes.insert(&newtype.name.value, newtype);
+            }
+            TopLevelStatement::TraitDecl(trait_decl) => {
+                resolver
+                    .traits
+                    .entry(&trait_decl.name.value)
+                    .or_insert(trait_decl);
+            }
+            TopLevelStatement::Impl(r#impl) => resolver.impls.push(r#impl),
+            TopLevelStatement::FunctionDecl(function) => {
+                resolver
+                    .functions
+                    .entry(&function.def.name.value)
+                    .or_insert(&function.def);
+            }
+            TopLevelStatement::ConstDecl(ConstDecl {
+                name,
+                r#type,
+                value,
+                ..
+            })
+            | TopLevelStatement::VarDecl(VarDecl {
+                name,
+                r#type,
+                value,
+                ..
+            }) => {
+                let r#type = r#type.clone().or_else(|| resolver.infer(value));
+                resolver.globals.entry(&name.value).or_insert(r#type);
+            }
+            _ => {}
+        }
+    }
+
+    resolver.visit_program_unit(ast);
+    resolver
+}
+
+/// Method found for the receiver.
+enum Lookup<'a> {
+    /// [`String`] is the type, which implements the method (without
+    /// pointers).
+    Found(String, &'a TraitMethod),
+    Ambiguous(Vec<String>),
+    /// There is no such method. [`Option<String>`] is the hint.
+    NotFound(Option<String>),
+    /// Type of receiver is not known well enough to look up methods.
+    Unknown,
+}
+
+/// Generic arguments of calls by their locations.
+pub(crate) type GenericCalls = HashMap<Range<usize>, HashMap<String, Type>>;
+
+#[derive(Default)]
+struct MethodResolver<'a> {
+    aliases: TypeAliases,
+    /// Structs, enums and newtypes (`None`) declared in the file.
+    structs: HashMap<&'a str, Option<&'a StructDecl>>,
+    newtypes: HashMap<&'a str, &'a Newtype>,
+    traits: HashMap<&'a str, &'a TraitDecl>,
+    impls: Vec<&'a Impl>,
+    functions: HashMap<&'a str, &'a FunctionDef>,
+    globals: HashMap<&'a str, Option<Type>>,
+    /// Local variables with their types (if known).
+    locals: Vec<HashMap<String, Option<Type>>>,
+    /// Constraints of generic parameters in scope.
+    generics: Vec<HashMap<String, Vec<String>>>,
+    /// Type, which `Self` refers to in the current implementation.
+    self_type: Option<Type>,
+    errors: Vec<AnalyzerError>,
+    generic_calls: GenericCalls,
+}
+
+fn named(name: &str, span: &Span) -> Type {
+    WithSpan::new(
+        Box::new(RawType::Primary(
+            WithSpan::new(name.to_owned(), span.clone()),
+            vec![],
+        )),
+        span.clone(),
+    )
+}
+
+/// Whether the function is a method (has `self` parameter), rather than
+/// an associated function.
+fn is_method(method: &TraitMethod) -> bool {
+    method
+        .params
+        .first()
+        .is_some_and(|param| param.name.value == "self")
+}
+
+/// Generic arguments of the call of `function`: explicitly given ones or,
+/// if there are none, ones inferred by matching types of parameters with
+/// types of the arguments (`None`, if unknown).
+pub(crate) fn generic_arguments<'f>(
+    function: &'f FunctionDef,
+    generics: &[Type],
+    arguments: &[Option<Type>],
+) -> HashMap<&'f str, Type> {
+    let mut inferred = HashMap::new();
+
+    if !generics.is_empty() {
+        for (annotation, argument) in function.generic_annotations.iter().zip(generics) {
+            inferred.insert(annotation.name.value.as_str(), argument.clone());
+        }
+
+        return inferred;
+    }
+
+    for (param, argument) in function.params.iter().zip(arguments) {
+        if let Some(argument) = argument {
+            unify(
+                &param.r#type,
+                argument,
+                &function.generic_annotations,
+                &mut inferred,
+            );
+        }
+    }
+
+    inferred
+}
+
+/// Binds generic parameters in the type of parameter to the corresponding
+/// parts of the type of argument. The first binding of the parameter wins.
+fn unify<'f>(
+    param: &'f Type,
+    argument: &Type,
+    generics: &'f GenericAnnotations,
+    inferred: &mut HashMap<&'f str, Type>,
+) {
+    match (&*param.value, &*argument.value) {
+        (RawType::Primary(name, arguments), _)
+            if arguments.is_empty() && generics.iter().any(|g| g.name.value == name.value) =>
+        {
+            inferred
+                .entry(name.value.as_str())
+                .or_insert_with(|| argument.clone());
+        }
+        (RawType::Primary(a, a_arguments), RawType::Primary(b, b_arguments))
+            if a.value == b.value && a_arguments.len() == b_arguments.len() =>
+        {
+            for (param, argument) in a_arguments.iter().zip(b_arguments) {
+                unify(param, argument, generics, inferred);
+            }
+        }
+        (RawType::Array(a, _), RawType::Array(b, _))
+        | (RawType::Pointer(a), RawType::Pointer(b))
+        | (RawType::Option(a), RawType::Option(b)) => unify(a, b, generics, inferred),
+        _ => {}
+    }
+}
+
+impl<'a> MethodResolver<'a> {
+    fn with_scope<F>(&mut self, generics: HashMap<String, Vec<String>>, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        self.locals.push(HashMap::new());
+        self.generics.push(generics);
+        f(self);
+        self.generics.pop();
+        self.locals.pop();
+    }
+
+    fn declare(&mut self, name: &str, r#type: Option<Type>) {
+        if let Some(scope) = self.locals.last_mut() {
+            scope.insert(name.to_owned(), r#type);
+        }
+    }
+
+    fn variable(&self, name: &str) -> Option<Type> {
+        match self.locals.iter().rev().find_map(|scope| scope.get(name)) {
+            Some(r#type) => r#type.clone(),
+            None => self.globals.get(name).cloned().flatten(),
+        }
+    }
+
+    fn generic_bounds(&self, name: &str) -> Option<&Vec<String>> {
+        self.generics.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// Traits, which the constraint implies: itself and its supertraits.
+    fn with_supertraits(&self, r#trait: &'a str, traits: &mut Vec<&'a str>) {
+        if traits.contains(&r#trait) {
+            return;
+        }
+
+        traits.push(r#trait);
+
+        if let Some(trait_decl) = self.traits.get(r#trait) {
+            for supertrait in trait_decl.supertraits.iter().filter_map(head) {
+                self.with_supertraits(supertrait, traits);
+            }
+        }
+    }
+
+    /// Infers type of the expression, if it is possible without type
+    /// checking.
+    fn infer(&self, expression: &Expression) -> Option<Type> {
+        let span = &expression.span;
+
+        match &*expression.value {
+            RawExpression::String(_) => Some(named("string", span)),
+            RawExpression::Bool(_) => Some(named("bool", span)),
+            RawExpression::Char(_) => Some(named("char", span)),
+            RawExpression::Int(_, Some(suffix)) | RawExpression::Float(_, Some(suffix)) => {
+                Some(named(&suffix.to_string(), span))
+            }
+            RawExpression::StaticName(name) => self.variable(name),
+            RawExpression::As(_, r#type) => Some(r#type.clone()),
+            RawExpression::Struct(name, _) => Some(named(&name.value, &name.span)),
+            RawExpression::Property(object, field) => {
+                let r#type = self.receiver_type(object)?;
+
+                if let Some(newtype) = self.newtypes.get(head(&r#type)?) {
+                    return (field.value == Newtype::FIELD
+                        && newtype.generic_annotations.is_empty())
+                    .then(|| newtype.value.clone());
+                }
+
+                let Some(Some(struct_decl)) = self.structs.get(head(&r#type)?) else {
+                    return None;
+                };
+
+                if !struct_decl.generic_annotations.is_empty() {
+                    return None;
+                }
+
+                struct_decl
+                    .members
+                    .iter()
+                    .find(|(_, member)| member.name.value == field.value)
+                    .map(|(_, member)| member.r#type.clone())
+            }
+            RawExpression::Call(generics, callee, arguments) => match &*callee.value {
+                RawExpression::StaticName(name) if self.variable(name).is_none() => {
+                    // Newtype is called to create its value.
+                    if let Some(newtype) = self.newtypes.get(name.as_str()) {
+                        return newtype
+                            .generic_annotations
+                            .is_empty()
+                            .then(|| named(name, span));
+                    }
+
+                    let function = self.functions.get(name.as_str())?;
+                    let return_type = function.return_type.as_ref()?;
+                    let inferred = self.generic_arguments(function, generics, arguments);
+
+                    // Return type can't mention parameters, that are not inferred.
+                    (inferred.len() == function.generic_annotations.len())
+                        .then(|| substitute(return_type, &inferred))
+                }
+                RawExpression::Property(receiver, name) => {
+                    let r#type = self.receiver_type(receiver)?;
+                    let Lookup::Found(implementor, method) = self.lookup(&r#type, &name.value)
+                    else {
+                        return None;
+                    };
+
+                    let return_type = method.return_type.as_ref()?;
+
+                    match &*return_type.value {
+                        RawType::Primary(name, arguments)
+                            if name.value == "Self" && arguments.is_empty() =>
+                        {
+                            Some(named(&implementor, span))
+                        }
+                        _ if method.generic_annotations.is_empty() => Some(return_type.clone()),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            RawExpression::PrefixOrPostfix(operator, inner)
+                if operator.value.is(&RawToken::BangBang) =>
+            {
+                match *self.infer(inner)?.value {
+                    RawType::Option(inner) => Some(inner),
+                    _ => None,
+                }
+            }
+            RawExpression::Index(array, _) => match *self.infer(array)?.value {
+                RawType::Array(inner, _) => Some(inner),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Generic arguments of the call of `function` with the given
+    /// arguments, see [`generic_arguments`].
+    fn generic_arguments(
+        &self,
+        function: &'a FunctionDef,
+        generics: &[Type],
+        arguments: &[Expression],
+    ) -> HashMap<&'a str, Type> {
+        let arguments = if generics.is_empty() {
+            arguments
+                .iter()
+                .map(|argument| self.receiver_type(argument))
+                .collect()
+        } else {
+            vec![]
+        };
+
+        generic_arguments(function, generics, &arguments)
+    }
+
+    /// Type of the receiver with resolved aliases. `Self` is replaced with
+    /// the type of implementation (inside of trait it stays generic).
+    fn receiver_type(&self, receiver: &Expression) -> Option<Type> {
+        let r#type = self.infer(receiver)?;
+        Some(self.replace_self(self.aliases.resolve(&r#type).ok()?))
+    }
+
+    /// Replaces `Self` (possibly behind pointers) with the type of
+    /// implementation.
+    fn replace_self(&self, r#type: Type) -> Type {
+        if let RawType::Pointer(inner) = &*r#type.value {
+            let inner = self.replace_self(inner.clone());
+            return WithSpan::new(Box::new(RawType::Pointer(inner)), r#type.span);
+        }
+
+        match &self.self_type {
+            Some(self_type) if head(&r#type) == Some("Self") => self_type.clone(),
+            _ => r#type,
+        }
+    }
+
+    fn lookup(&self, r#type: &Type, method: &str) -> Lookup<'a> {
+        let Ok(mut r#type) = self.aliases.resolve(r#type) else {
+            return Lookup::Unknown;
+        };
+
+        while let RawType::Pointer(inner) = *r#type.value {
+            r#type = inner;
+        }
+
+        let Some(name) = head(&r#type).map(str::to_owned) else {
+            return Lookup::Unknown;
+        };
+
+        // Generic parameter: methods of traits in its constraints.
+        if let Some(bounds) = self.generic_bounds(&name) {
+            let mut traits = vec![];
+
+            for bound in bounds {
+                let Some((&bound, _)) = self.traits.get_key_value(bound.as_str()) else {
+                    return Lookup::Unknown;
+                };
+
+                self.with_supertraits(bound, &mut traits);
+            }
+
+            return match self.trait_methods(&traits, &name, method) {
+                Lookup::NotFound(_) => Lookup::NotFound(self.hint(&name, method, None)),
+                found => found,
+            };
+        }
+
+        let mut inherent = None;
+        let mut traits = vec![];
+        let mut unknown_traits = false;
+
+        for r#impl in &self.impls {
+            if !matches(&r#impl.r#type, &r#type, &r#impl.global_generic_annotations) {
+                continue;
+            }
+
+            let found = r#impl
+                .methods
+                .iter()
+                .map(|(_, m)| m)
+                .find(|m| m.name.value == method);
+
+            match r#impl.r#trait.as_ref().and_then(head) {
+                None => inherent = inherent.or(found),
+                Some(r#trait) => match self.traits.get_key_value(r#trait) {
+                    Some((&r#trait, _)) => self.with_supertraits(r#trait, &mut traits),
+                    None => unknown_traits = true,
+                },
+            }
+        }
+
+        if let Some(found) = inherent.filter(|found| is_method(found)) {
+            return Lookup::Found(name, found);
+        }
+
+        match self.trait_methods(&traits, &name, method) {
+            Lookup::NotFound(_) if unknown_traits || !self.structs.contains_key(name.as_str()) => {
+                Lookup::Unknown
+            }
+            Lookup::NotFound(_) => Lookup::NotFound(self.hint(&name, method, inherent)),
+            lookup => lookup,
+        }
+    }
+
+    /// Looks up the method of the type in the traits.
+    fn trait_methods(&self, traits: &[&'a str], r#type: &str, method: &str) -> Lookup<'a> {
+        let candidates = traits
+            .iter()
+            .filter_map(|&r#trait| {
+                self.traits[r#trait]
+                    .methods
+                    .iter()
+                    .find(|(_, m)| m.name.value == method && is_method(m))
+                    .map(|(_, m)| (r#trait, m))
+            })
+            .collect::<Vec<_>>();
+
+        match candidates.as_slice() {
+            [] => Lookup::NotFound(None),
+            [(_, found)] => Lookup::Found(r#type.to_owned(), found),
+            _ => Lookup::Ambiguous(
+                candidates
+                    .iter()
+                    .map(|(r#trait, _)| r#trait.to_string())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Explains, why the method is not found.
+    fn hint(&self, r#type: &str, method: &str, inherent: Option<&TraitMethod>) -> Option<String> {
+        if inherent.is_some() {
+            return Some(format!(
+                "note: `{method}` is an associated function of `{type}`, not a method,\n\
+                 because it doesn't have `self` parameter"
+            ));
+        }
+
+        if let Some(Some(struct_decl)) = self.structs.get(r#type) {
+            if struct_decl
+                .members
+                .iter()
+                .any(|(_, member)| member.name.value == method)
+            {
+                return Some(format!(
+                    "note: `{method}` is a field of `{type}`, not a method"
+                ));
+            }
+        }
+
+        let mut traits = self
+            .traits
+            .values()
+            .filter(|t| t.methods.iter().any(|(_, m)| m.name.value == method))
+            .map(|t| format!("`{}`", t.name.value))
+            .collect::<Vec<_>>();
+        traits.sort();
+
+        if traits.is_empty() {
+            None
+        } else {
+            Some(format!(
+                "help: the method is defined in trait {}, which is not implemented for `{type}`",
+                traits.join(", ")
+            ))
+        }
+    }
+}
+
+impl<'a> Visitor for MethodResolver<'a> {
+    fn visit_function_decl(&mut self, function_decl: &FunctionDecl) {
+        let generics = bounds(
+            &function_decl.def.generic_annotations,
+            &function_decl.def.where_clause,
+        );
+
+        self.with_scope(generics, |s| {
+            for param in &function_decl.def.params {
+                s.declare(&param.name.value, Some(param.r#type.clone()));
+            }
+
+            s.visit_statements_block(&function_decl.stmts);
+        });
+    }
+
+    fn visit_trait_decl(&mut self, trait_decl: &TraitDecl) {
+        let mut generics = bounds(&trait_decl.generic_annotations, &vec![]);
+        generics.insert("Self".to_owned(), vec![trait_decl.name.value.clone()]);
+
+        self.with_scope(generics, |s| walk_trait_decl(s, trait_decl));
+    }
+
+    fn visit_impl(&mut self, r#impl: &Impl) {
+        let generics = bounds(&r#impl.global_generic_annotations, &r#impl.where_clause);
+
+        self.self_type = Some(r#impl.r#type.clone());
+        self.with_scope(generics, |s| walk_impl(s, r#impl));
+        self.self_type = None;
+    }
+
+    fn visit_trait_method(&mut self, method: &TraitMethod) {
+        let generics = bounds(&method.generic_annotations, &method.where_clause);
+
+        self.with_scope(generics, |s| {
+            for param in &method.params {
+                s.declare(&param.name.value, Some(param.r#type.clone()));
+            }
+
+            if let Some(body) = &method.body {
+                s.visit_statements_block(body);
+            }
+        });
+    }
+
+    fn visit_statements_block(&mut self, block: &[Statement]) {
+        self.with_scope(HashMap::new(), |s| walk_statements_block(s, block));
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement);
+
+        if let Statement::Var(name, r#type, value) = statement {
+            let r#type = r#type.clone().or_else(|| self.infer(value));
+            self.declare(&name.value, r#type);
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        if let RawExpression::Call(generics, callee, arguments) = &*expression.value {
+            if let RawExpression::StaticName(name) = &*callee.value {
+                if let Some(&function) = self
+                    .functions
+                    .get(name.as_str())
+                    .filter(|_| generics.is_empty() && self.variable(name).is_none())
+                    .filter(|function| !function.generic_annotations.is_empty())
+                {
+                    let inferred = self
+                        .generic_arguments(function, generics, arguments)
+                        .into_iter()
+                        .map(|(name, r#type)| (name.to_owned(), r#type))
+                        .collect();
+
+                    self.generic_calls
+                        .insert(expression.span.range.clone(), inferred);
+                }
+            }
+
+            if let RawExpression::Property(receiver, name) = &*callee.value {
+                if let Some(r#type) = self.receiver_type(receiver) {
+                    match self.lookup(&r#type, &name.value) {
+                        Lookup::Found(..) => {}
+                        Lookup::Ambiguous(traits) => {
+                            self.errors.push(AnalyzerError::AmbiguousMethod(
+                                name.span.clone(),
+                                name.value.clone(),
+                                traits,
+                            ));
+                        }
+                        Lookup::NotFound(hint) => {
+                            self.errors.push(AnalyzerError::NoMethod(
+                                name.span.clone(),
+                                name.value.clone(),
+                                r#type.value.to_string(),
+                                hint,
+                            ));
+                        }
+                        Lookup::Unknown => {}
+                    }
+                }
+            }
+        }
+
+        walk_expression(self, expression);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{diagnostics, parse};
+
+    #[test]
+    fn resolution_test() {
+        let source = "trait Shape {\n    fun area(self Self) f64;\n    fun describe(self Self) { self.area(); }\n}\n\
+                      struct Point { x f64; }\n\
+                      impl Point {\n    fun scale(self *Self, k f64) {}\n    fun len(self Self) f64 {}\n}\n\
+                      impl Shape for Point {\n    fun area(self Self) f64 { self.len(); }\n}\n\
+                      fun f<T Shape>(p Point, q *Point, t T) {\n    \
+                      p.scale(2.0);\n    q.len();\n    q.scale(1.0);\n    p.area();\n    t.describe();\n    \
+                      var r = p;\n    r.len();\n}";
+        let ast = parse(source);
+
+        assert_eq!(resolve_methods(&ast), vec![]);
+
+        let mut resolver = resolver(&ast);
+        resolver
+            .generics
+            .push(HashMap::from([("T".to_owned(), vec!["Shape".to_owned()])]));
+
+        let point = named("Point", &Span::default());
+        let pointer =
+            |r#type: Type| WithSpan::new(Box::new(RawType::Pointer(r#type)), Span::default());
+
+        let lookup = |r#type: &Type, method: &str| match resolver.lookup(r#type, method) {
+            Lookup::Found(implementor, found) => {
+                Some((implementor, found.params[0].r#type.value.to_string()))
+            }
+            _ => None,
+        };
+
+        let found =
+            |implementor: &str, receiver: &str| Some((implementor.to_owned(), receiver.to_owned()));
+
+        // Receiver is referenced or dereferenced, as the method needs.
+        assert_eq!(lookup(&point, "scale"), found("Point", "*Self"));
+        assert_eq!(
+            lookup(&pointer(point.clone()), "len"),
+            found("Point", "Self")
+        );
+        assert_eq!(
+            lookup(&pointer(pointer(point.clone())), "scale"),
+            found("Point", "*Self")
+        );
+        assert_eq!(lookup(&point, "area"), found("Point", "Self"));
+        assert_eq!(
+            lookup(&named("T", &Span::default()), "describe"),
+            found("T", "Self")
+        );
+        assert_eq!(lookup(&point, "describe"), found("Point", "Self"));
+        assert_eq!(lookup(&point, "missing"), None);
+    }
+
+    #[test]
+    fn errors_test() {
+        let errors = diagnostics(
+            "struct Point { x f64; }\nimpl Point { fun new() Point {} }\n\
+             trait A { fun go(self Self); }\ntrait B { fun go(self Self); }\n\
+             trait Named { fun name(self Self) string; }\n\
+             impl A for Point { fun go(self Self) {} }\nimpl B for Point { fun go(self Self) {} }\n\
+             fun f<T>(p Point, t T, n i32) {\n    \
+             p.new();\n    p.x();\n    p.name();\n    p.go();\n    t.go();\n    n.abs();\n    \"s\".len();\n}",
+            |ast| resolve_methods(ast),
+        )
+        .into_iter()
+        .map(|diagnostic| (diagnostic.message, diagnostic.notes.first().cloned()))
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "no method named `new` found for type `Point`".to_owned(),
+                    Some(
+                        "note: `new` is an associated function of `Point`, not a method,\n\
+                         because it doesn't have `self` parameter"
+                            .to_owned()
+                    )
+                ),
+                (
+                    "no method named `x` found for type `Point`".to_owned(),
+                    Some("note: `x` is a field of `Point`, not a method".to_owned())
+                ),
+                (
+                    "no method named `name` found for type `Point`".to_owned(),
+                    Some(
+                        "help: the method is defined in trait `Named`, which is not implemented for `Point`"
+                            .to_owned()
+                    )
+                ),
+                (
+                    "multiple applicable methods named `go` found".to_owned(),
+                    Some("note: call the method through the trait to disambiguate it".to_owned())
+                ),
+                (
+                    "no method named `go` found for type `T`".to_owned(),
+                    Some(
+                        "help: the method is defined in trait `A`, `B`, which is not implemented for `T`"
+                            .to_owned()
+                    )
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn generic_call_test() {
+        let errors = diagnostics(
+            "struct Point { x f64; }\n\
+             fun id<T>(a T) T { a }\nfun wrap<T>(a T) [T] {}\nfun make<T>() T {}\n\
+             fun f(p Point) {\n    id(p).len();\n    wrap(p)[0].len();\n    make().len();\n}",
+            |ast| resolve_methods(ast),
+        )
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec![
+                "no method named `len` found for type `Point`".to_owned(),
+                "no method named `len` found for type `Point`".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn newtype_test() {
+        let errors = diagnostics(
+            "type Meters f64;\nimpl Meters { fun km(self Self) f64 { self.value / 1000.0 } }\n\
+             fun f() {\n    Meters(1.5).km();\n    Meters(1.5).value.abs();\n    Meters(1.5).abs();\n}",
+            |ast| resolve_methods(ast),
+        )
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec!["no method named `abs` found for type `Meters`".to_owned()]
+        );
+    }
+}
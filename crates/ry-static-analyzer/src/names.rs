@@ -0,0 +1,91 @@
+//! `names.rs` - checks, that top level names of the file are unique.
+//!
+//! Functions, constants and global variables are in the value namespace,
+//! structs, enums, traits and type aliases - in the type namespace.
+//! Newtypes are in both of them, as they are called to create values.
+//! Other checks use the first definition of the name, later ones are
+//! reported here.
+use std::collections::HashMap;
+
+use ry_ast::{location::WithSpan, *};
+
+use crate::error::AnalyzerError;
+
+/// Reports names, that are defined more than once in the same namespace.
+/// Returns errors found, sorted by location.
+pub fn check_redefinitions(ast: &ProgramUnit) -> Vec<AnalyzerError> {
+    let mut namespaces = HashMap::new();
+    let mut errors = vec![];
+
+    for (_, statement) in &ast.top_level_statements {
+        let (name, namespaces_of_name): (_, &[_]) = match statement {
+            TopLevelStatement::FunctionDecl(function) => (&function.def.name, &["value"]),
+            TopLevelStatement::ConstDecl(ConstDecl { name, .. })
+            | TopLevelStatement::VarDecl(VarDecl { name, .. }) => (name, &["value"]),
+            TopLevelStatement::StructDecl(StructDecl { name, .. })
+            | TopLevelStatement::EnumDecl(EnumDecl { name, .. })
+            | TopLevelStatement::TraitDecl(TraitDecl { name, .. })
+            | TopLevelStatement::TypeAlias(TypeAlias { name, .. }) => (name, &["type"]),
+            TopLevelStatement::Newtype(Newtype { name, .. }) => (name, &["type", "value"]),
+            TopLevelStatement::Impl(_) => continue,
+        };
+
+        for &namespace in namespaces_of_name {
+            let names: &mut HashMap<&str, &WithSpan<String>> =
+                namespaces.entry(namespace).or_default();
+
+            match names.get(name.value.as_str()) {
+                Some(first) => errors.push(AnalyzerError::Redefinition(
+                    name.span.clone(),
+                    name.value.clone(),
+                    namespace,
+                    first.span.clone(),
+                )),
+                None => {
+                    names.insert(&name.value, name);
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    fn errors(source: &str) -> Vec<(String, String)> {
+        test_utils::errors(source, |ast| check_redefinitions(ast))
+    }
+
+    #[test]
+    fn redefinition_test() {
+        let redefined = |name: &str| {
+            (
+                "E026".to_owned(),
+                format!("the name `{name}` is defined multiple times"),
+            )
+        };
+
+        assert_eq!(
+            errors(
+                "const A = 1;\nconst A = 2;\nvar A = 3;\nfun A() {}\n\
+                 struct A {}\ntype A = i32;\nenum B {}\ntrait B {}\nfun f() {}"
+            ),
+            vec![
+                redefined("A"),
+                redefined("A"),
+                redefined("A"),
+                redefined("A"),
+                redefined("B")
+            ]
+        );
+        assert_eq!(errors("struct Point {}\nfun Point() Point {}"), vec![]);
+        assert_eq!(
+            errors("type Meters f64;\nfun Meters() {}\nstruct Meters {}"),
+            vec![redefined("Meters"), redefined("Meters")]
+        );
+    }
+}
@@ -0,0 +1,35 @@
+//! `test_utils.rs` - helpers shared by tests of the analyzer.
+use codespan_reporting::diagnostic::Diagnostic;
+use ry_ast::ProgramUnit;
+use ry_parser::Parser;
+use ry_report::Reporter;
+
+use crate::error::AnalyzerError;
+
+/// Parses the source, which must not have syntax errors.
+pub(crate) fn parse(source: &str) -> ProgramUnit {
+    Parser::new(source).parse().unwrap()
+}
+
+/// Runs the check on the source and returns diagnostics of errors found.
+pub(crate) fn diagnostics(
+    source: &str,
+    check: impl FnOnce(&mut ProgramUnit) -> Vec<AnalyzerError>,
+) -> Vec<Diagnostic<usize>> {
+    check(&mut parse(source))
+        .iter()
+        .map(|error| error.build_diagnostic(0))
+        .collect()
+}
+
+/// Runs the check on the source and returns codes and messages of errors
+/// found.
+pub(crate) fn errors(
+    source: &str,
+    check: impl FnOnce(&mut ProgramUnit) -> Vec<AnalyzerError>,
+) -> Vec<(String, String)> {
+    diagnostics(source, check)
+        .into_iter()
+        .map(|diagnostic| (diagnostic.code.unwrap(), diagnostic.message))
+        .collect()
+}
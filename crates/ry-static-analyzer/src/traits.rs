@@ -0,0 +1,389 @@
+//! `traits.rs` - checks trait implementations.
+//!
+//! Implementation of the trait declared in the file must define all
+//! required methods (ones without default body) and associated types
+//! (ones without default value), can't define items, which are not members
+//! of the trait, and its methods must have the same signatures as in the
+//! trait (with `Self`, generic parameters and associated types of the trait
+//! substituted). Signatures are compared structurally: names of parameters
+//! don't matter and generic parameters of the methods are matched by
+//! position. Type implementing the trait must implement its supertraits
+//! too, and the same trait can't be implemented for the type twice.
+use std::collections::HashMap;
+
+use ry_ast::{location::WithSpan, *};
+
+use crate::{
+    bounds::{head, matches},
+    error::AnalyzerError,
+    types::{substitute, TypeAliases},
+};
+
+/// Checks implementations of traits declared in the file. Returns errors
+/// found, sorted by location.
+pub fn check_implementations(ast: &ProgramUnit) -> Vec<AnalyzerError> {
+    let mut traits = HashMap::new();
+    let mut impls = vec![];
+
+    for (_, statement) in &ast.top_level_statements {
+        match statement {
+            TopLevelStatement::TraitDecl(trait_decl) => {
+                traits
+                    .entry(trait_decl.name.value.as_str())
+                    .or_insert(trait_decl);
+            }
+            TopLevelStatement::Impl(r#impl) => {
+                if let Some(r#trait) = r#impl.r#trait.as_ref().and_then(head) {
+                    impls.push((r#trait, r#impl));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let aliases = TypeAliases::new(ast);
+    let mut errors = vec![];
+
+    for (i, &(name, r#impl)) in impls.iter().enumerate() {
+        let Some(trait_decl) = traits.get(name) else {
+            continue;
+        };
+
+        check_items(trait_decl, r#impl, &aliases, &mut errors);
+
+        for supertrait in trait_decl.supertraits.iter().filter_map(head) {
+            let implemented = impls.iter().any(|&(other, other_impl)| {
+                other == supertrait
+                    && matches(
+                        &other_impl.r#type,
+                        &r#impl.r#type,
+                        &other_impl.global_generic_annotations,
+                    )
+            });
+
+            if traits.contains_key(supertrait) && !implemented {
+                errors.push(AnalyzerError::SupertraitNotImplemented(
+                    r#impl.r#type.span.clone(),
+                    r#impl.r#type.value.to_string(),
+                    name.to_owned(),
+                    supertrait.to_owned(),
+                ));
+            }
+        }
+
+        let r#type = resolve(&aliases, &r#impl.r#type).value.to_string();
+
+        if let Some((_, first)) = impls[..i].iter().find(|&&(other, other_impl)| {
+            other == name && resolve(&aliases, &other_impl.r#type).value.to_string() == r#type
+        }) {
+            errors.push(AnalyzerError::ConflictingImplementations(
+                r#impl.r#type.span.clone(),
+                name.to_owned(),
+                r#type,
+                first.r#type.span.clone(),
+            ));
+        }
+    }
+
+    errors.sort_by_key(|error| error.span().range.start);
+    errors
+}
+
+fn resolve(aliases: &TypeAliases, r#type: &Type) -> Type {
+    aliases.resolve(r#type).unwrap_or_else(|_| r#type.clone())
+}
+
+fn check_items(
+    trait_decl: &TraitDecl,
+    r#impl: &Impl,
+    aliases: &TypeAliases,
+    errors: &mut Vec<AnalyzerError>,
+) {
+    let trait_name = &trait_decl.name.value;
+    let trait_span = &r#impl.r#trait.as_ref().unwrap().span;
+
+    let mut missing = vec![];
+
+    for (_, associated_type) in &trait_decl.associated_types {
+        let name = &associated_type.name.value;
+
+        if associated_type.value.is_none()
+            && !r#impl
+                .associated_types
+                .iter()
+                .any(|(_, t)| t.name.value == *name)
+        {
+            missing.push(name.clone());
+        }
+    }
+
+    for (_, method) in &trait_decl.methods {
+        if method.body.is_none()
+            && !r#impl
+                .methods
+                .iter()
+                .any(|(_, m)| m.name.value == method.name.value)
+        {
+            missing.push(method.name.value.clone());
+        }
+    }
+
+    if !missing.is_empty() {
+        errors.push(AnalyzerError::MissingTraitItems(
+            trait_span.clone(),
+            trait_name.clone(),
+            missing,
+        ));
+    }
+
+    for (_, associated_type) in &r#impl.associated_types {
+        if !trait_decl
+            .associated_types
+            .iter()
+            .any(|(_, t)| t.name.value == associated_type.name.value)
+        {
+            errors.push(AnalyzerError::NotTraitMember(
+                associated_type.name.span.clone(),
+                "associated type",
+                associated_type.name.value.clone(),
+                trait_name.clone(),
+            ));
+        }
+    }
+
+    let substitutions = substitutions(trait_decl, r#impl);
+
+    for (_, method) in &r#impl.methods {
+        let Some((_, expected)) = trait_decl
+            .methods
+            .iter()
+            .find(|(_, m)| m.name.value == method.name.value)
+        else {
+            errors.push(AnalyzerError::NotTraitMember(
+                method.name.span.clone(),
+                "method",
+                method.name.value.clone(),
+                trait_name.clone(),
+            ));
+            continue;
+        };
+
+        if !compatible(expected, method, &substitutions, aliases) {
+            errors.push(AnalyzerError::IncompatibleMethod(
+                method.name.span.clone(),
+                method.name.value.clone(),
+                trait_name.clone(),
+                signature(expected, &substitutions, aliases),
+                signature(method, &substitutions, aliases),
+            ));
+        }
+    }
+}
+
+/// Types, which replace `Self`, generic parameters and associated types of
+/// the trait in the implementation.
+fn substitutions<'a>(trait_decl: &'a TraitDecl, r#impl: &'a Impl) -> HashMap<&'a str, &'a Type> {
+    let mut substitutions = HashMap::new();
+
+    substitutions.insert("Self", &r#impl.r#type);
+
+    if let Some(RawType::Primary(_, arguments)) = r#impl.r#trait.as_ref().map(|t| &*t.value) {
+        for (annotation, argument) in trait_decl.generic_annotations.iter().zip(arguments) {
+            substitutions.insert(annotation.name.value.as_str(), argument);
+        }
+    }
+
+    for (_, associated_type) in trait_decl
+        .associated_types
+        .iter()
+        .chain(&r#impl.associated_types)
+    {
+        if let Some(value) = &associated_type.value {
+            substitutions.insert(associated_type.name.value.as_str(), value);
+        }
+    }
+
+    substitutions
+}
+
+/// The type with substituted generic parameters and resolved aliases.
+fn substituted(
+    r#type: &Type,
+    substitutions: &HashMap<&str, &Type>,
+    aliases: &TypeAliases,
+) -> String {
+    resolve(aliases, &substitute(r#type, substitutions))
+        .value
+        .to_string()
+}
+
+/// Whether the method of the implementation has the same signature as
+/// the method of the trait: the same number of generic parameters and
+/// parameters, the same types of parameters and the same return type.
+/// Generic parameters of the implementation are replaced with the ones of
+/// the trait at the same positions.
+fn compatible(
+    expected: &TraitMethod,
+    found: &TraitMethod,
+    substitutions: &HashMap<&str, &Type>,
+    aliases: &TypeAliases,
+) -> bool {
+    if expected.generic_annotations.len() != found.generic_annotations.len()
+        || expected.params.len() != found.params.len()
+    {
+        return false;
+    }
+
+    let generics = expected
+        .generic_annotations
+        .iter()
+        .map(|annotation| {
+            let raw = RawType::Primary(annotation.name.clone(), vec![]);
+            WithSpan::new(Box::new(raw), annotation.name.span.clone())
+        })
+        .collect::<Vec<_>>();
+
+    let mut found_substitutions = substitutions.clone();
+
+    for (annotation, generic) in found.generic_annotations.iter().zip(&generics) {
+        found_substitutions.insert(annotation.name.value.as_str(), generic);
+    }
+
+    let same = |expected: &Type, found: &Type| {
+        substituted(expected, substitutions, aliases)
+            == substituted(found, &found_substitutions, aliases)
+    };
+
+    let same_params = expected
+        .params
+        .iter()
+        .zip(&found.params)
+        .all(|(expected, found)| same(&expected.r#type, &found.r#type));
+
+    let same_return_type = match (&expected.return_type, &found.return_type) {
+        (Some(expected), Some(found)) => same(expected, found),
+        (expected, found) => expected.is_none() && found.is_none(),
+    };
+
+    same_params && same_return_type
+}
+
+/// Signature of the method with substituted types, for example:
+/// `fun next(self Range) u64?`.
+fn signature(
+    method: &TraitMethod,
+    substitutions: &HashMap<&str, &Type>,
+    aliases: &TypeAliases,
+) -> String {
+    let r#type = |r#type: &Type| substituted(r#type, substitutions, aliases);
+
+    let mut signature = format!("fun {}", method.name.value);
+
+    if !method.generic_annotations.is_empty() {
+        signature.push_str(&format!(
+            "<{}>",
+            method
+                .generic_annotations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    signature.push_str(&format!(
+        "({})",
+        method
+            .params
+            .iter()
+            .map(|param| format!("{} {}", param.name.value, r#type(&param.r#type)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ));
+
+    if let Some(return_type) = &method.return_type {
+        signature.push_str(&format!(" {}", r#type(return_type)));
+    }
+
+    signature
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    fn errors(source: &str) -> Vec<(String, String)> {
+        test_utils::errors(source, |ast| check_implementations(ast))
+    }
+
+    const PRELUDE: &str = "trait Eq {}\ntrait Iterator Eq {\n    type Item;\n    \
+                           fun next(self Self) Item?;\n    fun count(self Self) u64 {}\n}\n";
+
+    #[test]
+    fn complete_implementation_test() {
+        assert_eq!(
+            errors(&format!(
+                "{PRELUDE}impl Eq for Range {{}}\nimpl Iterator for Range {{\n    \
+                 type Item = u64;\n    fun next(self Range) u64? {{}}\n}}"
+            )),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn implementation_errors_test() {
+        assert_eq!(
+            errors(&format!(
+                "{PRELUDE}impl Iterator for Range {{\n    type Item = u64;\n    \
+                 fun next(self Self) i32? {{}}\n    fun reset(self Self) {{}}\n}}\n\
+                 impl Iterator for Range {{}}\nimpl Eq for Range {{}}"
+            )),
+            vec![
+                (
+                    "E021".to_owned(),
+                    "method `next` has incompatible signature with trait `Iterator`".to_owned()
+                ),
+                (
+                    "E020".to_owned(),
+                    "method `reset` is not a member of trait `Iterator`".to_owned()
+                ),
+                (
+                    "E019".to_owned(),
+                    "not all items of trait `Iterator` are implemented".to_owned()
+                ),
+                (
+                    "E023".to_owned(),
+                    "conflicting implementations of trait `Iterator` for type `Range`".to_owned()
+                )
+            ]
+        );
+        assert_eq!(
+            errors("trait Eq {}\ntrait Ord Eq {}\nimpl Ord for i32 {}"),
+            vec![(
+                "E022".to_owned(),
+                "type `i32` doesn't implement trait `Eq`, which is required by `Ord`".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn renamed_signature_test() {
+        let source = "trait Mapper<T> {\n    fun map<U, V>(self Self, value T, f U) V;\n}\n\
+                      impl Mapper<i32> for Range {\n    \
+                      fun map<A, B>(self Range, x i32, g A) B {}\n}";
+        assert_eq!(errors(source), vec![]);
+
+        // Generic parameters are matched by position, not by name.
+        let source = "trait Mapper<T> {\n    fun map<U, V>(self Self, value T, f U) V;\n}\n\
+                      impl Mapper<i32> for Range {\n    \
+                      fun map<V, U>(self Range, value i32, f U) V {}\n}";
+        assert_eq!(
+            errors(source),
+            vec![(
+                "E021".to_owned(),
+                "method `map` has incompatible signature with trait `Mapper`".to_owned()
+            )]
+        );
+    }
+}
@@ -0,0 +1,398 @@
+//! `types.rs` - resolves type aliases.
+//!
+//! Alias is replaced with its value, where generic parameters of the alias
+//! are substituted with the given arguments. Aliases can refer to other
+//! aliases, but not to themselves (directly or through other aliases).
+//! Newtypes are distinct types, so they are never replaced.
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use ry_ast::{
+    location::{Span, WithSpan},
+    visit::*,
+    *,
+};
+
+use crate::error::AnalyzerError;
+
+/// Error of alias resolution.
+#[derive(Debug, PartialEq)]
+pub enum ResolveError {
+    /// Names of aliases in the cycle, in order of expansion.
+    Cycle(Vec<String>),
+    /// Alias is used with wrong number of generic arguments.
+    Arity {
+        span: Span,
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// Type aliases declared in the program, together with names of newtypes.
+#[derive(Debug, Default, Clone)]
+pub struct TypeAliases {
+    aliases: HashMap<String, TypeAlias>,
+    newtypes: HashSet<String>,
+}
+
+impl TypeAliases {
+    /// Collects aliases of the program. If alias is declared several times,
+    /// the first declaration is used (others are reported by
+    /// [`crate::names`]).
+    pub fn new(ast: &ProgramUnit) -> Self {
+        let newtypes = ast
+            .top_level_statements
+            .iter()
+            .filter_map(|(_, statement)| match statement {
+                TopLevelStatement::Newtype(newtype) => Some(newtype.name.value.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        ast.top_level_statements
+            .iter()
+            .filter_map(|(_, statement)| match statement {
+                TopLevelStatement::TypeAlias(alias) => Some(alias.clone()),
+                _ => None,
+            })
+            .collect::<Self>()
+            .with_newtypes(newtypes)
+    }
+
+    /// Adds names of newtypes, see [`TypeAliases::is_newtype`].
+    pub fn with_newtypes(mut self, newtypes: impl IntoIterator<Item = String>) -> Self {
+        self.newtypes.extend(newtypes);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TypeAlias> {
+        self.aliases.get(name)
+    }
+
+    /// Whether `name` is a newtype, so values of other types (including
+    /// the wrapped one) can't be used as its values.
+    pub fn is_newtype(&self, name: &str) -> bool {
+        self.newtypes.contains(name)
+    }
+
+    /// Replaces all aliases in the type with their values.
+    pub fn resolve(&self, r#type: &Type) -> Result<Type, ResolveError> {
+        self.expand(r#type, &mut vec![], &[])
+    }
+
+    /// Expands aliases in the type. `stack` contains aliases, which are
+    /// being expanded, `params` are generic parameters of the innermost one
+    /// (they are substituted later).
+    fn expand(
+        &self,
+        r#type: &Type,
+        stack: &mut Vec<String>,
+        params: &[String],
+    ) -> Result<Type, ResolveError> {
+        let raw = match &*r#type.value {
+            RawType::Array(inner, size) => {
+                RawType::Array(self.expand(inner, stack, params)?, size.clone())
+            }
+            RawType::Pointer(inner) => RawType::Pointer(self.expand(inner, stack, params)?),
+            RawType::Option(inner) => RawType::Option(self.expand(inner, stack, params)?),
+            RawType::Generic(_) => return Ok(r#type.clone()),
+            RawType::Primary(name, arguments) => {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| self.expand(argument, stack, params))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                match self.aliases.get(&name.value) {
+                    Some(alias) if !params.contains(&name.value) => {
+                        return self.expand_alias(alias, arguments, &r#type.span, stack)
+                    }
+                    _ => RawType::Primary(name.clone(), arguments),
+                }
+            }
+        };
+
+        Ok(WithSpan::new(Box::new(raw), r#type.span.clone()))
+    }
+
+    fn expand_alias(
+        &self,
+        alias: &TypeAlias,
+        arguments: Vec<Type>,
+        span: &Span,
+        stack: &mut Vec<String>,
+    ) -> Result<Type, ResolveError> {
+        let name = &alias.name.value;
+
+        if arguments.len() != alias.generic_annotations.len() {
+            return Err(ResolveError::Arity {
+                span: span.clone(),
+                name: name.clone(),
+                expected: alias.generic_annotations.len(),
+                found: arguments.len(),
+            });
+        }
+
+        if let Some(start) = stack.iter().position(|n| n == name) {
+            return Err(ResolveError::Cycle(stack[start..].to_vec()));
+        }
+
+        let params = alias
+            .generic_annotations
+            .iter()
+            .map(|annotation| annotation.name.value.clone())
+            .collect::<Vec<_>>();
+
+        stack.push(name.clone());
+        let value = self.expand(&alias.value, stack, &params);
+        stack.pop();
+
+        let substitutions = params.into_iter().zip(arguments).collect();
+        let value = substitute(&value?, &substitutions);
+
+        Ok(WithSpan::new(value.value, span.clone()))
+    }
+
+    /// Describes the type for diagnostics: "`Byte` (alias of `u8`)" or just
+    /// "`u8`" for non-alias types.
+    pub fn describe(&self, r#type: &Type) -> String {
+        match self.resolve(r#type) {
+            Ok(resolved) if resolved.value != r#type.value => {
+                format!("`{}` (alias of `{}`)", r#type.value, resolved.value)
+            }
+            _ => format!("`{}`", r#type.value),
+        }
+    }
+}
+
+impl FromIterator<TypeAlias> for TypeAliases {
+    /// Collects aliases in order of declaration, the first one of aliases
+    /// with the same name is used (others are reported by [`crate::names`]).
+    fn from_iter<I: IntoIterator<Item = TypeAlias>>(iter: I) -> Self {
+        let mut aliases = HashMap::new();
+
+        for alias in iter {
+            aliases.entry(alias.name.value.clone()).or_insert(alias);
+        }
+
+        Self {
+            aliases,
+            newtypes: HashSet::new(),
+        }
+    }
+}
+
+/// Replaces generic parameters (primary types without arguments) with
+/// the corresponding types.
+pub(crate) fn substitute<K, V>(r#type: &Type, substitutions: &HashMap<K, V>) -> Type
+where
+    K: Borrow<str> + Eq + Hash,
+    V: Borrow<Type>,
+{
+    let raw = match &*r#type.value {
+        RawType::Array(inner, size) => {
+            RawType::Array(substitute(inner, substitutions), size.clone())
+        }
+        RawType::Pointer(inner) => RawType::Pointer(substitute(inner, substitutions)),
+        RawType::Option(inner) => RawType::Option(substitute(inner, substitutions)),
+        RawType::Generic(_) => return r#type.clone(),
+        RawType::Primary(name, arguments) if arguments.is_empty() => {
+            return match substitutions.get(name.value.as_str()) {
+                Some(argument) => argument.borrow().clone(),
+                None => r#type.clone(),
+            }
+        }
+        RawType::Primary(name, arguments) => RawType::Primary(
+            name.clone(),
+            arguments
+                .iter()
+                .map(|argument| substitute(argument, substitutions))
+                .collect(),
+        ),
+    };
+
+    WithSpan::new(Box::new(raw), r#type.span.clone())
+}
+
+/// Checks that type aliases are not cyclic and are used with the right
+/// number of generic arguments. Returns errors found, sorted by location.
+pub fn check_type_aliases(ast: &ProgramUnit) -> Vec<AnalyzerError> {
+    let mut checker = AliasChecker {
+        aliases: TypeAliases::new(ast),
+        errors: vec![],
+    };
+    checker.visit_program_unit(ast);
+
+    checker.errors.sort_by_key(|error| error.span().range.start);
+    checker.errors
+}
+
+struct AliasChecker {
+    aliases: TypeAliases,
+    errors: Vec<AnalyzerError>,
+}
+
+impl AliasChecker {
+    /// Reports wrong number of arguments, if it is located inside of
+    /// the type (errors inside of other aliases are reported at their
+    /// declarations).
+    fn report_arity(&mut self, error: ResolveError, r#type: &Type) {
+        if let ResolveError::Arity {
+            span,
+            name,
+            expected,
+            found,
+        } = error
+        {
+            if span.range.start >= r#type.span.range.start
+                && span.range.end <= r#type.span.range.end
+            {
+                self.errors
+                    .push(AnalyzerError::TypeAliasArity(span, name, expected, found));
+            }
+        }
+    }
+
+    /// Visits array sizes, but not nested types, which are already checked.
+    fn visit_array_sizes(&mut self, r#type: &Type) {
+        match &*r#type.value {
+            RawType::Array(inner, size) => {
+                self.visit_array_sizes(inner);
+
+                if let Some(size) = size {
+                    self.visit_expression(size);
+                }
+            }
+            RawType::Pointer(inner) | RawType::Option(inner) => self.visit_array_sizes(inner),
+            RawType::Primary(_, arguments) => {
+                for argument in arguments {
+                    self.visit_array_sizes(argument);
+                }
+            }
+            RawType::Generic(_) => {}
+        }
+    }
+}
+
+impl Visitor for AliasChecker {
+    fn visit_type_alias(&mut self, alias: &TypeAlias) {
+        for annotation in &alias.generic_annotations {
+            self.visit_generic_annotation(annotation);
+        }
+
+        let name = &alias.name.value;
+
+        // Only the first declaration is used.
+        if !self
+            .aliases
+            .get(name)
+            .is_some_and(|first| first.name.span == alias.name.span)
+        {
+            return self.visit_type(&alias.value);
+        }
+
+        let params = alias
+            .generic_annotations
+            .iter()
+            .map(|annotation| annotation.name.value.clone())
+            .collect::<Vec<_>>();
+
+        match self
+            .aliases
+            .expand(&alias.value, &mut vec![name.clone()], &params)
+        {
+            Ok(_) => {}
+            // Cycle is reported once, at the alias declared first.
+            Err(ResolveError::Cycle(cycle)) => {
+                let first = cycle
+                    .iter()
+                    .filter_map(|name| self.aliases.get(name))
+                    .map(|alias| &alias.name.span)
+                    .min_by_key(|span| span.range.start);
+
+                if cycle[0] == *name && first == Some(&alias.name.span) {
+                    self.errors.push(AnalyzerError::TypeAliasCycle(
+                        alias.name.span.clone(),
+                        cycle,
+                    ));
+                }
+            }
+            Err(error) => self.report_arity(error, &alias.value),
+        }
+
+        self.visit_array_sizes(&alias.value);
+    }
+
+    fn visit_type(&mut self, r#type: &Type) {
+        if let Err(error) = self.aliases.resolve(r#type) {
+            self.report_arity(error, r#type);
+        }
+
+        self.visit_array_sizes(r#type);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{self, parse};
+
+    fn resolve(source: &str, r#type: &str) -> String {
+        let ast = parse(source);
+        let TopLevelStatement::VarDecl(var) =
+            &parse(&format!("var x {type} = 0;")).top_level_statements[0].1
+        else {
+            unreachable!()
+        };
+
+        TypeAliases::new(&ast)
+            .resolve(var.r#type.as_ref().unwrap())
+            .unwrap()
+            .value
+            .to_string()
+    }
+
+    #[test]
+    fn resolve_test() {
+        let source = "type Byte = u8;\ntype Pair<T> = Map<T, T>;\ntype Rows<T> = [Pair<T>?];";
+
+        assert_eq!(resolve(source, "Byte"), "u8");
+        assert_eq!(resolve(source, "*Rows<Byte>"), "*[Map<u8, u8>?]");
+        assert_eq!(
+            resolve(source, "Pair<Pair<i32> >"),
+            "Map<Map<i32, i32>, Map<i32, i32>>"
+        );
+        assert_eq!(resolve(source, "List<Byte>"), "List<u8>");
+    }
+
+    fn errors(source: &str) -> Vec<(String, String)> {
+        test_utils::errors(source, |ast| check_type_aliases(ast))
+    }
+
+    #[test]
+    fn errors_test() {
+        assert_eq!(
+            errors("type A = B;\ntype B = [C];\ntype C = *B;\ntype D<T> = T;\ntype E<T> = D<T>;"),
+            vec![(
+                "E016".to_owned(),
+                "cycle detected when expanding type alias `B`".to_owned()
+            )]
+        );
+        assert_eq!(
+            errors("type A<T> = [T];\ntype B = A;\nfun f(a A<i32, i32>, b A<B>) {}"),
+            vec![
+                (
+                    "E017".to_owned(),
+                    "type alias `A` takes 1 generic argument, but 0 were given".to_owned()
+                ),
+                (
+                    "E017".to_owned(),
+                    "type alias `A` takes 1 generic argument, but 2 were given".to_owned()
+                )
+            ]
+        );
+    }
+}
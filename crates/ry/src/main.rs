@@ -1,10 +1,31 @@
-use clap::{arg, Command};
+mod repl;
+
+use clap::{arg, ArgGroup, Command};
 use codespan_reporting::files::SimpleFiles;
-use ry_ast::token::RawToken;
+use ry_ast::{
+    location::LineIndex,
+    sexp::to_sexp,
+    token::{RawToken, Token},
+};
+use ry_ast_to_graphviz::{
+    graph::{call_graph, import_graph},
+    GraphvizTranslatorState,
+};
+use ry_doky::document;
+use ry_driver::{check, emit_errors, load_program, modules};
+use ry_interpreter::compiler::compile;
 use ry_lexer::Lexer;
-use ry_parser::Parser;
-use ry_report::{Reporter, ReporterState};
-use std::{fs, process::exit};
+use ry_manifest::{find_root, Manifest, ModuleRoots, MANIFEST_FILE};
+use ry_parser::{error::ParserError, Parser};
+use ry_report::{apply_suggestions, registry, Reporter, ReporterState};
+use ry_static_analyzer::fold_constants;
+use serde_json::json;
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+    process::exit,
+};
 
 fn cli() -> Command {
     Command::new("ry")
@@ -15,22 +36,169 @@ fn cli() -> Command {
             Command::new("lex")
                 .about("Convert the source code into list of tokens")
                 .arg(arg!(<PATH> "source file path"))
+                .arg(arg!(--"with-trivia" "include comments and whitespaces"))
+                .arg(
+                    arg!(--format <FORMAT> "output format of the tokens")
+                        .value_parser(["text", "json"])
+                        .default_value("text"),
+                )
                 .arg_required_else_help(true),
         )
         .subcommand(
             Command::new("parse")
                 .about("Convert the source code into AST and print it")
                 .arg(arg!(<PATH> "source file path"))
+                .arg(
+                    arg!(--format <FORMAT> "output format of the AST")
+                        .value_parser(["debug", "json", "sexp"])
+                        .default_value("debug"),
+                )
+                .arg(arg!(--fold "evaluate constant expressions before printing"))
+                .arg_required_else_help(true),
+        )
+        .subcommand(
+            Command::new("check")
+                .about("Parse the source code and report errors of static analysis")
+                .arg(arg!(<PATH> "source file path"))
+                .arg_required_else_help(true),
+        )
+        .subcommand(
+            Command::new("disasm")
+                .about("Compile the program into bytecode and print its listing")
+                .arg(arg!(<PATH> "source file path"))
                 .arg_required_else_help(true),
         )
         .subcommand(
             Command::new("graphviz")
                 .about("Parse source code and print AST in graphviz format")
                 .arg(arg!(<PATH> "source file path"))
+                .arg(
+                    arg!(--spans <KIND> "label nodes with their spans")
+                        .value_parser(["offsets", "lines"]),
+                )
+                .arg_required_else_help(true),
+        )
+        .subcommand(
+            Command::new("doc")
+                .about("Render documentation of the source file in Markdown")
+                .arg(arg!(<PATH> "source file path"))
+                .arg_required_else_help(true),
+        )
+        .subcommand(
+            Command::new("graph")
+                .about("Print call graph or import graph of the program")
+                .arg(arg!(<PATH> "source file path"))
+                .arg(arg!(--calls "graph of calls between functions of the file"))
+                .arg(arg!(--imports "graph of imports between files, starting from the given one"))
+                .group(
+                    ArgGroup::new("kind")
+                        .args(["calls", "imports"])
+                        .required(true),
+                )
+                .arg(
+                    arg!(--format <FORMAT> "output format of the graph")
+                        .value_parser(["dot", "json"])
+                        .default_value("dot"),
+                )
                 .arg_required_else_help(true),
         )
+        .subcommand(
+            Command::new("fix")
+                .about("Apply machine-applicable suggestions of diagnostics to the source file")
+                .arg(arg!(<PATH> "source file path"))
+                .arg_required_else_help(true),
+        )
+        .subcommand(Command::new("lsp").about("Start language server (communicates through stdio)"))
+        .subcommand(Command::new("repl").about(
+            "Start interactive session: evaluate statements and keep declarations between inputs",
+        ))
+        .subcommand(
+            Command::new("explain")
+                .about("Print extended explanation of the error code")
+                .arg(arg!(<CODE> "error code, for example: E001"))
+                .arg_required_else_help(true),
+        )
+}
+
+/// Formats token for `ry lex`: its location, kind and payload.
+fn token_to_text(token: &Token, start: (usize, usize), end: (usize, usize)) -> String {
+    format!(
+        "{}:{}..{}:{} {}{}",
+        start.0,
+        start.1,
+        end.0,
+        end.1,
+        token.value,
+        token
+            .value
+            .payload()
+            .map(|payload| format!(" {payload}"))
+            .unwrap_or_default()
+    )
 }
 
+/// Converts token into JSON object for `ry lex --format=json`.
+fn token_to_json(
+    source: &str,
+    token: &Token,
+    start: (usize, usize),
+    end: (usize, usize),
+) -> serde_json::Value {
+    // Unit variants are serialized as `"Kind"`, others as `{"Kind": value}`.
+    let (kind, value) = match serde_json::to_value(&token.value).unwrap() {
+        serde_json::Value::Object(object) => object.into_iter().next().unwrap(),
+        kind => (kind.as_str().unwrap().to_owned(), serde_json::Value::Null),
+    };
+
+    json!({
+        "kind": kind,
+        "value": value,
+        "text": &source[token.span.range.clone()],
+        "span": [token.span.range.start, token.span.range.end],
+        "start": {"line": start.0, "column": start.1},
+        "end": {"line": end.0, "column": end.1},
+    })
+}
+
+/// Reads the manifest of the project, that the file belongs to, to find
+/// roots of its modules (files outside of projects have no roots). Errors in
+/// the manifest are reported and terminate the process.
+fn module_roots(path: &Path, reporter: &ReporterState) -> ModuleRoots {
+    let Some(root) = find_root(path) else {
+        return ModuleRoots::default();
+    };
+
+    let manifest_path = root.join(MANIFEST_FILE);
+
+    let Ok(source) = fs::read_to_string(&manifest_path) else {
+        reporter.emit_global_error(&format!(
+            "cannot read project manifest `{}`",
+            manifest_path.display()
+        ));
+        exit(1);
+    };
+
+    match Manifest::load(root, &source) {
+        Ok(manifest) => ModuleRoots::new(root, &manifest),
+        Err(errors) => {
+            let name = manifest_path.display().to_string();
+
+            let mut files = SimpleFiles::new();
+            let file_id = files.add(name.as_str(), source.as_str());
+
+            for e in errors {
+                e.emit_diagnostic(reporter, &files, file_id);
+            }
+
+            reporter.emit_global_error("cannot load project manifest due to the previous errors");
+            exit(1);
+        }
+    }
+}
+
+/// Upper bound for `ry fix` iterations, in case suggestions don't converge.
+const MAX_FIX_ITERATIONS: usize = 100;
+
 fn main() {
     let reporter = ReporterState::default();
 
@@ -44,24 +212,60 @@ fn main() {
 
             match fs::read_to_string(filepath) {
                 Ok(contents) => {
+                    let file_id = files.add(filepath, &contents);
+                    let with_trivia = sub_matches.get_flag("with-trivia");
+                    let json = sub_matches.get_one::<String>("format").unwrap() == "json";
+
                     let mut lexer = Lexer::new(&contents);
 
+                    if with_trivia {
+                        lexer = lexer.with_trivia();
+                    }
+
+                    let line_index = LineIndex::new(&contents);
+
+                    let mut json_tokens = vec![];
                     let mut token_index = 0;
+                    let mut has_errors = false;
 
                     loop {
-                        let token = lexer.next().unwrap();
+                        let token = if with_trivia {
+                            lexer.next()
+                        } else {
+                            lexer.next_no_comments()
+                        }
+                        .unwrap();
 
                         if token.value.is(&RawToken::EndOfFile) {
                             break;
                         }
 
-                        println!(
-                            "{token_index}: [{}]@{}..{}",
-                            token.value, token.span.range.start, token.span.range.end,
-                        );
+                        let start = line_index.line_column(&contents, token.span.range.start);
+                        let end = line_index.line_column(&contents, token.span.range.end);
+
+                        if json {
+                            json_tokens.push(token_to_json(&contents, &token, start, end));
+                        } else {
+                            println!("{token_index}: {}", token_to_text(&token, start, end));
+                        }
+
+                        if let RawToken::Invalid(e) = token.value {
+                            ParserError::ErrorToken((e, token.span).into())
+                                .emit_diagnostic(&reporter, &files, file_id);
+
+                            has_errors = true;
+                        }
 
                         token_index += 1;
                     }
+
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&json_tokens).unwrap());
+                    }
+
+                    if has_errors {
+                        exit(1);
+                    }
                 }
                 Err(_) => {
                     reporter.emit_global_error("cannot read given file");
@@ -80,8 +284,31 @@ fn main() {
                     let ast = parser.parse();
 
                     match ast {
-                        Ok(program_unit) => {
-                            println!("{:?}", program_unit);
+                        Ok(mut program_unit) => {
+                            if sub_matches.get_flag("fold") {
+                                let errors = fold_constants(&mut program_unit);
+
+                                if !errors.is_empty() {
+                                    for e in errors {
+                                        e.emit_diagnostic(&reporter, &files, file_id);
+                                    }
+
+                                    reporter.emit_global_error(
+                                        "cannot output AST due to the previous errors",
+                                    );
+
+                                    exit(1);
+                                }
+                            }
+
+                            match sub_matches.get_one::<String>("format").unwrap().as_str() {
+                                "json" => println!(
+                                    "{}",
+                                    serde_json::to_string_pretty(&program_unit).unwrap()
+                                ),
+                                "sexp" => println!("{}", to_sexp(&program_unit).unwrap()),
+                                _ => println!("{:?}", program_unit),
+                            }
                         }
                         Err(e) => {
                             e.emit_diagnostic(&reporter, &files, file_id);
@@ -99,6 +326,57 @@ fn main() {
                 }
             }
         }
+        Some(("check", sub_matches)) => {
+            let filepath = sub_matches.get_one::<String>("PATH").unwrap();
+
+            let roots = module_roots(Path::new(filepath), &reporter);
+
+            let Ok(mut program) = load_program(Path::new(filepath), &roots) else {
+                reporter.emit_global_error("cannot read given file");
+                exit(1);
+            };
+
+            let errors = check(&mut program);
+            let has_errors = emit_errors(&program, &errors, &reporter);
+
+            if has_errors {
+                reporter.emit_global_error(&format!(
+                    "could not check `{filepath}` due to the previous errors"
+                ));
+                exit(1);
+            }
+        }
+        Some(("disasm", sub_matches)) => {
+            let filepath = sub_matches.get_one::<String>("PATH").unwrap();
+
+            let roots = module_roots(Path::new(filepath), &reporter);
+
+            let Ok(mut program) = load_program(Path::new(filepath), &roots) else {
+                reporter.emit_global_error("cannot read given file");
+                exit(1);
+            };
+
+            let errors = check(&mut program);
+            let has_errors = emit_errors(&program, &errors, &reporter);
+
+            let Some(modules) = modules(&program).filter(|_| !has_errors) else {
+                reporter.emit_global_error(&format!(
+                    "could not compile `{filepath}` due to the previous errors"
+                ));
+                exit(1);
+            };
+
+            let compiled = compile(&modules);
+            let listings = compiled
+                .iter()
+                .zip(&program)
+                .map(|(module, file)| {
+                    format!("; {}\n\n{}", file.name, module.disassemble(&file.contents))
+                })
+                .collect::<Vec<_>>();
+
+            print!("{}", listings.join("\n"));
+        }
         Some(("graphviz", sub_matches)) => {
             let filepath = sub_matches.get_one::<String>("PATH").unwrap();
             match fs::read_to_string(filepath) {
@@ -110,8 +388,22 @@ fn main() {
 
                     match ast {
                         Ok(program_unit) => {
-                            let mut translator = ry_ast_to_graphviz::GraphvizTranslatorState::new();
-                            translator.ast_to_graphviz(&program_unit);
+                            let translator = GraphvizTranslatorState::new();
+
+                            let mut translator =
+                                match sub_matches.get_one::<String>("spans").map(String::as_str) {
+                                    Some("offsets") => translator.with_span_labels(),
+                                    Some("lines") => translator.with_line_labels(&contents),
+                                    _ => translator,
+                                };
+
+                            if translator
+                                .ast_to_graphviz(&program_unit, &mut io::stdout().lock())
+                                .is_err()
+                            {
+                                reporter.emit_global_error("cannot write graph");
+                                exit(1);
+                            }
                         }
                         Err(e) => {
                             e.emit_diagnostic(&reporter, &files, file_id);
@@ -129,6 +421,179 @@ fn main() {
                 }
             }
         }
+        Some(("doc", sub_matches)) => {
+            let filepath = sub_matches.get_one::<String>("PATH").unwrap();
+
+            match fs::read_to_string(filepath) {
+                Ok(contents) => {
+                    let file_id = files.add(filepath, &contents);
+
+                    match Parser::new(&contents).parse() {
+                        Ok(program_unit) => print!("{}", document(filepath, &program_unit)),
+                        Err(e) => {
+                            e.emit_diagnostic(&reporter, &files, file_id);
+
+                            reporter.emit_global_error(
+                                "cannot output documentation due to the previous errors",
+                            );
+
+                            exit(1);
+                        }
+                    }
+                }
+                Err(_) => {
+                    reporter.emit_global_error("cannot read given file");
+                    exit(1);
+                }
+            }
+        }
+        Some(("graph", sub_matches)) => {
+            let filepath = sub_matches.get_one::<String>("PATH").unwrap();
+            let mut has_errors = false;
+
+            let (name, graph) = if sub_matches.get_flag("calls") {
+                match fs::read_to_string(filepath) {
+                    Ok(contents) => {
+                        let file_id = files.add(filepath, &contents);
+
+                        match Parser::new(&contents).parse() {
+                            Ok(program_unit) => ("calls", call_graph(&program_unit)),
+                            Err(e) => {
+                                e.emit_diagnostic(&reporter, &files, file_id);
+
+                                reporter.emit_global_error(
+                                    "cannot output call graph due to the previous errors",
+                                );
+
+                                exit(1);
+                            }
+                        }
+                    }
+                    Err(_) => {
+                        reporter.emit_global_error("cannot read given file");
+                        exit(1);
+                    }
+                }
+            } else {
+                let graph = import_graph(Path::new(filepath), |path| {
+                    let Ok(contents) = fs::read_to_string(path) else {
+                        reporter.emit_global_error(&format!(
+                            "cannot read imported file `{}`",
+                            path.display()
+                        ));
+                        has_errors = true;
+                        return None;
+                    };
+
+                    match Parser::new(&contents).parse() {
+                        Ok(program_unit) => Some(program_unit),
+                        Err(e) => {
+                            let path = path.display().to_string();
+
+                            let mut files = SimpleFiles::new();
+                            let file_id = files.add(path.as_str(), contents.as_str());
+
+                            e.emit_diagnostic(&reporter, &files, file_id);
+                            has_errors = true;
+                            None
+                        }
+                    }
+                });
+
+                ("imports", graph)
+            };
+
+            let written = match sub_matches.get_one::<String>("format").unwrap().as_str() {
+                "json" => serde_json::to_writer_pretty(io::stdout().lock(), &graph)
+                    .map_err(io::Error::from)
+                    .and_then(|_| writeln!(io::stdout())),
+                _ => graph.to_dot(name, &mut io::stdout().lock()),
+            };
+
+            if written.is_err() {
+                reporter.emit_global_error("cannot write graph");
+                exit(1);
+            }
+
+            if has_errors {
+                exit(1);
+            }
+        }
+        Some(("fix", sub_matches)) => {
+            let filepath = sub_matches.get_one::<String>("PATH").unwrap();
+
+            match fs::read_to_string(filepath) {
+                Ok(mut contents) => {
+                    let mut fixed = 0;
+
+                    let remaining_error = loop {
+                        let mut parser = Parser::new(&contents);
+
+                        let error = match parser.parse() {
+                            Ok(_) => break None,
+                            Err(e) => e,
+                        };
+
+                        let suggestions = error.build_suggestions();
+
+                        if suggestions.is_empty() || fixed == MAX_FIX_ITERATIONS {
+                            break Some(error);
+                        }
+
+                        contents = apply_suggestions(&contents, &suggestions);
+                        fixed += 1;
+                    };
+
+                    if fixed > 0 && fs::write(filepath, &contents).is_err() {
+                        reporter.emit_global_error("cannot write to given file");
+                        exit(1);
+                    }
+
+                    println!("fixed {fixed} problem(s) in {filepath}");
+
+                    if let Some(e) = remaining_error {
+                        let file_id = files.add(filepath, &contents);
+                        e.emit_diagnostic(&reporter, &files, file_id);
+
+                        reporter.emit_global_error("cannot fix the error automatically");
+
+                        exit(1);
+                    }
+                }
+                Err(_) => {
+                    reporter.emit_global_error("cannot read given file");
+                    exit(1);
+                }
+            }
+        }
+        Some(("explain", sub_matches)) => {
+            let code = sub_matches.get_one::<String>("CODE").unwrap();
+
+            match registry::find(code) {
+                Some(error_code) => print!("{}", error_code.render_explanation()),
+                None => {
+                    reporter.emit_global_error(&format!("unknown error code `{code}`"));
+                    exit(1);
+                }
+            }
+        }
+        Some(("repl", _)) => {
+            let roots = match std::env::current_dir() {
+                Ok(directory) => module_roots(&directory, &reporter),
+                Err(_) => ModuleRoots::default(),
+            };
+
+            if let Err(e) = repl::start(roots, &reporter) {
+                reporter.emit_global_error(&format!("cannot read input: {e}"));
+                exit(1);
+            }
+        }
+        Some(("lsp", _)) => {
+            if let Err(e) = ry_lsp::start() {
+                reporter.emit_global_error(&format!("language server failed: {e}"));
+                exit(1);
+            }
+        }
         _ => {}
     }
 }
@@ -0,0 +1,534 @@
+//! `repl.rs` - interactive session, see `ry repl`.
+//!
+//! Input is either declarations (functions, types, constants, global
+//! variables and imports) or statements. Accepted declarations are kept
+//! for the rest of the session: every one becomes a module, which later
+//! inputs import, so that newer declarations shadow older ones.
+//! Statements are executed right away, the value of the last expression
+//! without semicolon is printed. Global variables and local variables
+//! declared by statements keep their values between inputs.
+//!
+//! Input continues on the next line, while it has unclosed braces,
+//! brackets or parentheses. Lines starting with `:` are commands:
+//! `:type`, `:ast`, `:tokens`, `:help` and `:quit`.
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    mem,
+};
+
+use codespan_reporting::files::SimpleFiles;
+use ry_ast::{
+    location::{LineIndex, Span},
+    sexp::to_sexp,
+    token::RawToken,
+    FunctionDecl, FunctionDef, ProgramUnit, Statement, TopLevelStatement,
+};
+use ry_driver::{check, emit_errors, load_imports, modules, SourceFile};
+use ry_interpreter::{error::RuntimeError, value::Value, with_program_stack, Interpreter};
+use ry_lexer::Lexer;
+use ry_manifest::ModuleRoots;
+use ry_parser::{error::ParserError, Parser};
+use ry_report::{Reporter, ReporterState};
+use ry_static_analyzer::error::AnalyzerError;
+
+use crate::token_to_text;
+
+/// Name of the function, that holds statements of the input during
+/// analysis. It is not an identifier, so programs can't call it.
+const STATEMENTS_FUNCTION: &str = "<repl>";
+
+const HELP: &str = "\
+Enter declarations (`fun`, `struct`, `var`, `import`, ...) to keep them
+in the session, or statements to execute them. Commands:
+  :type <expr>    show the type of the value of the expression
+  :ast <expr>     show the syntax tree of the expression
+  :tokens <expr>  show tokens of the expression
+  :help           show this message
+  :quit           exit the session (or press Ctrl+D)";
+
+pub enum ReplError {
+    /// Parse and analysis errors of the program, see [`emit_errors`].
+    Compile(Vec<SourceFile>, Vec<(usize, AnalyzerError)>),
+
+    /// Runtime error in the file with the index.
+    Runtime(Vec<SourceFile>, usize, RuntimeError),
+
+    /// Command [`String`] doesn't exist.
+    UnknownCommand(String),
+}
+
+impl ReplError {
+    pub fn emit(&self, reporter: &ReporterState) {
+        match self {
+            Self::Compile(program, errors) => {
+                emit_errors(program, errors, reporter);
+            }
+            Self::Runtime(program, file, error) => {
+                let mut files = SimpleFiles::new();
+                let file_ids = program
+                    .iter()
+                    .map(|file| files.add(file.name.as_str(), file.contents.as_str()))
+                    .collect::<Vec<_>>();
+
+                error.emit_diagnostic(reporter, &files, file_ids[*file]);
+            }
+            Self::UnknownCommand(command) => reporter.emit_global_error(&format!(
+                "unknown command `{command}`, type `:help` for the list of commands"
+            )),
+        }
+    }
+}
+
+/// Whether the input has unclosed braces, brackets or parentheses. Invalid
+/// input is considered complete, so that the parser reports the error.
+pub fn is_incomplete(input: &str) -> bool {
+    let mut lexer = Lexer::new(input);
+    let mut depth = 0;
+
+    loop {
+        match lexer.next_no_comments().unwrap().value {
+            RawToken::OpenBrace | RawToken::OpenBracket | RawToken::OpenParent => depth += 1,
+            RawToken::CloseBrace | RawToken::CloseBracket | RawToken::CloseParent => depth -= 1,
+            RawToken::Invalid(_) => return false,
+            RawToken::EndOfFile => return depth > 0,
+            _ => {}
+        }
+    }
+}
+
+/// Whether the input is declarations, judging by its first token. `var`
+/// starts a global variable, unless the input is statements.
+fn is_declaration(input: &str) -> bool {
+    match Lexer::new(input).next_no_comments().unwrap().value {
+        RawToken::Fun
+        | RawToken::Struct
+        | RawToken::Trait
+        | RawToken::Enum
+        | RawToken::Impl
+        | RawToken::Const
+        | RawToken::Type
+        | RawToken::Pub
+        | RawToken::AtSign
+        | RawToken::Import => true,
+        RawToken::Var => Parser::new(input).parse().is_ok(),
+        _ => false,
+    }
+}
+
+/// Wraps statements into the function, so that they are analyzed as
+/// a part of the program.
+fn statements_program(statements: Vec<Statement>) -> ProgramUnit {
+    ProgramUnit {
+        docstring: String::new(),
+        imports: vec![],
+        top_level_statements: vec![(
+            String::new(),
+            TopLevelStatement::FunctionDecl(FunctionDecl {
+                def: FunctionDef {
+                    attributes: vec![],
+                    public: None,
+                    generic_annotations: vec![],
+                    name: (STATEMENTS_FUNCTION.to_owned(), Span::default()).into(),
+                    params: vec![],
+                    return_type: None,
+                    where_clause: vec![],
+                },
+                stmts: statements,
+            }),
+        )],
+    }
+}
+
+/// Names of global variables and constants declared in the file.
+fn declared_globals(file: &SourceFile) -> Vec<String> {
+    let Ok(ast) = &file.ast else {
+        return vec![];
+    };
+
+    ast.top_level_statements
+        .iter()
+        .filter_map(|(_, statement)| match statement {
+            TopLevelStatement::ConstDecl(decl) => Some(decl.name.value.clone()),
+            TopLevelStatement::VarDecl(decl) => Some(decl.name.value.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// State of the interactive session.
+pub struct Session {
+    /// Names and sources of accepted declarations, the newest last.
+    declarations: Vec<(String, String)>,
+    globals: HashMap<String, Value>,
+    /// Local variables declared by statements.
+    variables: HashMap<String, Value>,
+    roots: ModuleRoots,
+    inputs: usize,
+}
+
+impl Session {
+    pub fn new(roots: ModuleRoots) -> Self {
+        Self {
+            declarations: vec![],
+            globals: HashMap::new(),
+            variables: HashMap::new(),
+            roots,
+            inputs: 0,
+        }
+    }
+
+    /// Name of the next input in diagnostics: `<repl:1>`, `<repl:2>`, ...
+    fn next_name(&mut self) -> String {
+        self.inputs += 1;
+        format!("<repl:{}>", self.inputs)
+    }
+
+    /// Program of the input: the input itself (module `0`), accepted
+    /// declarations (the newest first, each importing older ones) and
+    /// files they import.
+    fn program(&self, input: SourceFile) -> Vec<SourceFile> {
+        let count = self.declarations.len();
+        let mut program = vec![SourceFile {
+            imports: (1..=count).collect(),
+            ..input
+        }];
+
+        for (i, (name, contents)) in self.declarations.iter().rev().enumerate() {
+            program.push(SourceFile {
+                name: name.clone(),
+                ast: Parser::new(contents).parse(),
+                contents: contents.clone(),
+                imports: (i + 2..=count).collect(),
+                unresolved_imports: vec![],
+            });
+        }
+
+        load_imports(program, &self.roots)
+    }
+
+    /// Analyzes the program of the input, initializes new globals and
+    /// executes statements of the input (if it has them). Globals and
+    /// variables are updated only if `keep` is set.
+    fn execute(
+        &mut self,
+        input: SourceFile,
+        output: &mut dyn Write,
+        keep: bool,
+    ) -> Result<Value, ReplError> {
+        let mut program = self.program(input);
+        let errors = check(&mut program);
+
+        let Some(modules) = modules(&program).filter(|_| errors.is_empty()) else {
+            return Err(ReplError::Compile(program, errors));
+        };
+
+        // Redeclared globals are initialized again.
+        let mut globals = self.globals.clone();
+
+        for name in declared_globals(&program[0]) {
+            globals.remove(&name);
+        }
+
+        let statements = match &modules[0].ast.top_level_statements[..] {
+            [(_, TopLevelStatement::FunctionDecl(function))]
+                if function.def.name.value == STATEMENTS_FUNCTION =>
+            {
+                &function.stmts[..]
+            }
+            _ => &[],
+        };
+
+        let mut interpreter = Interpreter::new(&modules, output).with_globals(globals);
+
+        let result = interpreter
+            .initialize()
+            .and_then(|_| interpreter.evaluate(0, statements, self.variables.clone()));
+
+        let result = match result {
+            Ok((value, variables)) => {
+                if keep {
+                    self.globals = interpreter.globals().clone();
+                    self.variables = variables;
+                }
+
+                Ok(value)
+            }
+            Err(error) => Err(error),
+        };
+
+        drop(modules);
+        result.map_err(|(module, error)| ReplError::Runtime(program, module, error))
+    }
+
+    /// Evaluates the input. Returns the value of statements, if it is not
+    /// `()`.
+    pub fn eval(
+        &mut self,
+        input: &str,
+        output: &mut dyn Write,
+    ) -> Result<Option<Value>, ReplError> {
+        let name = self.next_name();
+
+        if is_declaration(input) {
+            let file = SourceFile {
+                name: name.clone(),
+                contents: input.to_owned(),
+                ast: Parser::new(input).parse(),
+                imports: vec![],
+                unresolved_imports: vec![],
+            };
+
+            self.execute(file, output, true)?;
+            self.declarations.push((name, input.to_owned()));
+
+            return Ok(None);
+        }
+
+        let file = SourceFile {
+            name,
+            contents: input.to_owned(),
+            ast: Parser::new(input)
+                .parse_statements()
+                .map(statements_program),
+            imports: vec![],
+            unresolved_imports: vec![],
+        };
+
+        match self.execute(file, output, true)? {
+            Value::Unit => Ok(None),
+            value => Ok(Some(value)),
+        }
+    }
+
+    /// Parses the argument of the command as an expression.
+    fn expression(&mut self, input: &str) -> Result<(String, ry_ast::Expression), ReplError> {
+        let name = self.next_name();
+
+        match Parser::new(input).parse_single_expression() {
+            Ok(expression) => Ok((name, expression)),
+            Err(error) => Err(ReplError::Compile(
+                vec![SourceFile {
+                    name,
+                    contents: input.to_owned(),
+                    ast: Err(error),
+                    imports: vec![],
+                    unresolved_imports: vec![],
+                }],
+                vec![],
+            )),
+        }
+    }
+
+    /// Runs the command (line starting with `:`), returns its output.
+    pub fn command(&mut self, line: &str) -> Result<String, ReplError> {
+        let (command, argument) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let argument = argument.trim();
+
+        match command {
+            ":help" => Ok(HELP.to_owned()),
+            // There is no type checker yet, so the expression is evaluated
+            // (with output discarded) to find the type of its value.
+            ":type" => {
+                let (name, expression) = self.expression(argument)?;
+                let file = SourceFile {
+                    name,
+                    contents: argument.to_owned(),
+                    ast: Ok(statements_program(vec![
+                        Statement::ExpressionWithoutSemicolon(expression),
+                    ])),
+                    imports: vec![],
+                    unresolved_imports: vec![],
+                };
+
+                let value = self.execute(file, &mut io::sink(), false)?;
+                Ok(value.type_name())
+            }
+            ":ast" => {
+                let (_, expression) = self.expression(argument)?;
+                Ok(to_sexp(&expression).expect("expressions are always serializable"))
+            }
+            ":tokens" => {
+                let line_index = LineIndex::new(argument);
+                let mut lexer = Lexer::new(argument);
+                let mut tokens = vec![];
+
+                loop {
+                    let token = lexer.next_no_comments().unwrap();
+
+                    match &token.value {
+                        RawToken::EndOfFile => break,
+                        RawToken::Invalid(e) => {
+                            let name = self.next_name();
+                            let error = ParserError::ErrorToken((*e, token.span).into());
+
+                            return Err(ReplError::Compile(
+                                vec![SourceFile {
+                                    name,
+                                    contents: argument.to_owned(),
+                                    ast: Err(error),
+                                    imports: vec![],
+                                    unresolved_imports: vec![],
+                                }],
+                                vec![],
+                            ));
+                        }
+                        _ => {}
+                    }
+
+                    let start = line_index.line_column(argument, token.span.range.start);
+                    let end = line_index.line_column(argument, token.span.range.end);
+
+                    tokens.push(format!(
+                        "{}: {}",
+                        tokens.len(),
+                        token_to_text(&token, start, end)
+                    ));
+                }
+
+                Ok(tokens.join("\n"))
+            }
+            _ => Err(ReplError::UnknownCommand(command.to_owned())),
+        }
+    }
+}
+
+/// Reads inputs from stdin and evaluates them until `:quit` or the end of
+/// input. Errors are reported and don't stop the session.
+pub fn start(roots: ModuleRoots, reporter: &ReporterState) -> io::Result<()> {
+    // Interpreter uses native stack for calls of the program.
+    with_program_stack(|| session(roots, reporter))
+}
+
+fn session(roots: ModuleRoots, reporter: &ReporterState) -> io::Result<()> {
+    let mut session = Session::new(roots);
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout();
+    let mut input = String::new();
+
+    println!("Ry REPL, type `:help` for help and `:quit` to exit.");
+
+    loop {
+        print!("{}", if input.is_empty() { ">> " } else { ".. " });
+        stdout.flush()?;
+
+        if stdin.read_line(&mut input)? == 0 {
+            println!();
+            return Ok(());
+        }
+
+        if input.trim().is_empty() {
+            input.clear();
+            continue;
+        }
+
+        if is_incomplete(&input) {
+            continue;
+        }
+
+        let line = mem::take(&mut input);
+        let line = line.trim();
+
+        let result = match line {
+            ":quit" | ":q" => return Ok(()),
+            _ if line.starts_with(':') => session.command(line).map(Some),
+            _ => session
+                .eval(line, &mut stdout)
+                .map(|value| value.map(|value| value.quoted())),
+        };
+
+        stdout.flush()?;
+
+        match result {
+            Ok(Some(output)) => println!("{output}"),
+            Ok(None) => {}
+            Err(error) => error.emit(reporter),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(session: &mut Session, input: &str) -> Option<String> {
+        match session.eval(input, &mut io::sink()) {
+            Ok(value) => value.map(|value| value.quoted()),
+            Err(_) => panic!("cannot evaluate `{input}`"),
+        }
+    }
+
+    fn command(session: &mut Session, line: &str) -> String {
+        match session.command(line) {
+            Ok(output) => output,
+            Err(_) => panic!("cannot run `{line}`"),
+        }
+    }
+
+    #[test]
+    fn session_test() {
+        let mut session = Session::new(ModuleRoots::default());
+
+        assert_eq!(eval(&mut session, "1 + 2"), Some("3".to_owned()));
+        assert_eq!(eval(&mut session, "var a = [1, 2];"), None);
+        assert_eq!(eval(&mut session, "len(a)"), Some("2".to_owned()));
+
+        assert_eq!(eval(&mut session, "var calls = 0;"), None);
+        assert_eq!(
+            eval(
+                &mut session,
+                "fun greet(name string) string {\n  calls += 1;\n  \"hello, \" + name\n}"
+            ),
+            None
+        );
+        assert_eq!(
+            eval(&mut session, "greet(\"ry\")"),
+            Some("\"hello, ry\"".to_owned())
+        );
+        assert_eq!(
+            eval(&mut session, "greet(\"ry\"); calls"),
+            Some("2".to_owned())
+        );
+
+        // Newer declarations shadow older ones, globals are kept.
+        assert_eq!(
+            eval(&mut session, "fun greet(name string) string { name }"),
+            None
+        );
+        assert_eq!(
+            eval(&mut session, "greet(\"ry\")"),
+            Some("\"ry\"".to_owned())
+        );
+        assert_eq!(eval(&mut session, "calls"), Some("2".to_owned()));
+
+        assert!(matches!(
+            session.eval("b + 1", &mut io::sink()),
+            Err(ReplError::Runtime(_, 0, RuntimeError::UndefinedName(..)))
+        ));
+        assert!(matches!(
+            session.eval("fun f( {}", &mut io::sink()),
+            Err(ReplError::Compile(..))
+        ));
+
+        assert_eq!(command(&mut session, ":type calls + 1"), "integer");
+        assert_eq!(command(&mut session, ":type a"), "list");
+        assert_eq!(
+            command(&mut session, ":tokens a + 1"),
+            "0: 1:1..1:2 identifier a\n1: 1:3..1:4 `+`\n2: 1:5..1:6 integer literal 1"
+        );
+        assert!(session.command(":ast 1 +").is_err());
+        assert!(matches!(
+            session.command(":run"),
+            Err(ReplError::UnknownCommand(command)) if command == ":run"
+        ));
+    }
+
+    #[test]
+    fn incomplete_test() {
+        assert!(is_incomplete("fun f() {\n  [1,"));
+        assert!(!is_incomplete("fun f() {}"));
+        assert!(!is_incomplete("\"unterminated {"));
+        assert!(!is_declaration("var a = 1; a"));
+        assert!(is_declaration("// doc\npub fun f() {}"));
+    }
+}
@@ -0,0 +1,103 @@
+//! Checks that ASTs of all examples survive serialization round trip.
+use std::{fs, path::Path};
+
+use ry_ast::{sexp::to_sexp, ProgramUnit};
+use ry_parser::Parser;
+
+/// Examples, that are expected to be rejected by the parser: they
+/// demonstrate errors or use syntax, that is not supported yet.
+const INVALID_EXAMPLES: &[&str] = &[
+    "generics.ry",
+    "import-after-top-levelstatement.ry",
+    "unnecessary-visibility-qualifier.ry",
+];
+
+/// Parses all examples, except for [`INVALID_EXAMPLES`].
+fn parsed_examples() -> Vec<(String, ProgramUnit)> {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples");
+    let mut programs = vec![];
+    let mut total = 0;
+
+    for entry in fs::read_dir(examples).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let source = fs::read_to_string(&path).unwrap();
+        total += 1;
+
+        if INVALID_EXAMPLES.contains(&name.as_str()) {
+            assert!(
+                Parser::new(&source).parse().is_err(),
+                "{name} is parsed, remove it from the invalid examples"
+            );
+            continue;
+        }
+
+        match Parser::new(&source).parse() {
+            Ok(program_unit) => programs.push((name, program_unit)),
+            Err(error) => panic!("cannot parse {name}: {error:?}"),
+        }
+    }
+
+    // Every invalid example must exist, otherwise fewer files are checked.
+    assert_eq!(
+        programs.len(),
+        total - INVALID_EXAMPLES.len(),
+        "some of the invalid examples are missing"
+    );
+
+    programs
+}
+
+#[test]
+fn json_round_trip() {
+    for (path, program_unit) in parsed_examples() {
+        let json = serde_json::to_string(&program_unit).unwrap();
+        let deserialized: ProgramUnit = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(
+            deserialized, program_unit,
+            "{path} changed after round trip"
+        );
+    }
+}
+
+#[test]
+fn sexp_is_produced() {
+    let program_unit = Parser::new("fun main() {\n    println(\"hi\");\n}\n")
+        .parse()
+        .unwrap();
+
+    assert_eq!(
+        to_sexp(&program_unit).unwrap(),
+        r#"(ProgramUnit
+  :docstring ""
+  :imports ()
+  :top_level_statements ((""
+                           (FunctionDecl
+                             (FunctionDecl
+                               :def (FunctionDef
+                                      :attributes ()
+                                      :public nil
+                                      :generic_annotations ()
+                                      :name "main"@4..8
+                                      :params ()
+                                      :return_type nil
+                                      :where_clause ())
+                               :stmts ((Expression
+                                         (Call@17..30
+                                           ()
+                                           (StaticName@17..24 "println")
+                                           ((String@25..29 "hi"))))))))))"#
+    );
+
+    for (path, program_unit) in parsed_examples() {
+        let sexp = to_sexp(&program_unit).unwrap();
+
+        assert!(sexp.starts_with("(ProgramUnit"), "{path}: {sexp}");
+        assert_eq!(
+            sexp.matches('(').count(),
+            sexp.matches(')').count(),
+            "{path}: unbalanced parentheses"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Checks that error code registry is consistent with the toolchain:
+//! every code emitted anywhere is registered and examples of every
+//! registered code behave as documented.
+use std::{fs, path::Path};
+
+use ry_driver::{check, load_imports, SourceFile};
+use ry_manifest::ModuleRoots;
+use ry_parser::Parser;
+use ry_report::{registry::REGISTRY, Reporter};
+
+/// Files, that examples can import: they are empty, but exist.
+const IMPORTABLE: &[&str] = &["std/io.ry", "std/fs.ry"];
+
+/// Runs the source through the toolchain and returns code of the first
+/// emitted error.
+fn emitted_code(source: &str) -> Option<String> {
+    let directory = tempfile::tempdir().unwrap();
+
+    for file in IMPORTABLE {
+        let path = directory.path().join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "").unwrap();
+    }
+
+    let example = SourceFile {
+        name: directory.path().join("example.ry").display().to_string(),
+        contents: source.to_owned(),
+        ast: Parser::new(source).parse(),
+        imports: vec![],
+        unresolved_imports: vec![],
+    };
+    let mut program = load_imports(vec![example], &ModuleRoots::default());
+
+    if let Err(e) = &program[0].ast {
+        return e.build_diagnostic(0).code;
+    }
+
+    check(&mut program)
+        .first()
+        .and_then(|(_, e)| e.build_diagnostic(0).code)
+}
+
+#[test]
+fn erroneous_examples_produce_their_codes() {
+    for error_code in REGISTRY {
+        assert_eq!(
+            emitted_code(error_code.erroneous_example).as_deref(),
+            Some(error_code.code),
+            "erroneous example of {} doesn't produce it",
+            error_code.code
+        );
+    }
+}
+
+#[test]
+fn corrected_examples_compile() {
+    for error_code in REGISTRY {
+        assert_eq!(
+            emitted_code(error_code.corrected_example),
+            None,
+            "corrected example of {} still produces an error",
+            error_code.code
+        );
+    }
+}
+
+/// Finds all `EXXX` words in the file.
+fn codes_mentioned_in(source: &str) -> Vec<String> {
+    let bytes = source.as_bytes();
+    let mut codes = vec![];
+
+    for (i, window) in bytes.windows(4).enumerate() {
+        let word_start = i == 0 || !bytes[i - 1].is_ascii_alphanumeric();
+        let word_end = bytes.get(i + 4).is_none_or(|c| !c.is_ascii_alphanumeric());
+
+        if word_start && word_end && window[0] == b'E' && window[1..].iter().all(u8::is_ascii_digit)
+        {
+            codes.push(String::from_utf8(window.to_vec()).unwrap());
+        }
+    }
+
+    codes
+}
+
+fn visit_sources(directory: &Path, f: &mut dyn FnMut(&Path, String)) {
+    for entry in fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+
+        if path.is_dir() {
+            if path.file_name().unwrap() != "target" {
+                visit_sources(&path, f);
+            }
+        } else if path.extension().is_some_and(|e| e == "rs") {
+            f(&path, fs::read_to_string(&path).unwrap());
+        }
+    }
+}
+
+#[test]
+fn emitted_codes_are_registered() {
+    let crates = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+
+    visit_sources(crates, &mut |path, source| {
+        for code in codes_mentioned_in(&source) {
+            assert!(
+                REGISTRY.iter().any(|error_code| error_code.code == code),
+                "{code} used in {} is not registered",
+                path.display()
+            );
+        }
+    });
+}
@@ -0,0 +1,43 @@
+//! Checks output of `ry lex` on files with invalid tokens.
+use std::{fs, process::Command};
+
+/// Runs `ry lex` on the file with given contents and returns its output.
+fn lex(name: &str, contents: &str, format: &str) -> String {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join(format!("{name}.ry"));
+    fs::write(&path, contents).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ry"))
+        .arg("lex")
+        .arg(&path)
+        .args(["--format", format])
+        .output()
+        .unwrap();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("panicked"), "ry lex panicked: {stderr}");
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn non_ascii_invalid_tokens() {
+    for (name, contents) in [("char", "var a = §;\n"), ("escape", "\"\\д\";\n")] {
+        let text = lex(name, contents, "text");
+        assert!(text.contains("invalid token"), "{text}");
+
+        let json = lex(name, contents, "json");
+        let tokens: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let invalid = tokens
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|token| token["kind"] == "Invalid")
+            .unwrap();
+
+        assert!(
+            contents.contains(invalid["text"].as_str().unwrap()),
+            "{invalid}"
+        );
+    }
+}